@@ -1,8 +1,10 @@
-use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
 use mg::primitives::{Cube, Sphere};
-use mg::transforms::advanced::Mirror;
+use mg::transforms::advanced::{Matrix, Mirror};
 use mg::transforms::basic::{Rotate, Scale, Translate};
-use mg::{Model, Transform};
+use mg::transforms::deform::Twist;
+use mg::{CompactMesh, Model, Transform};
+use nalgebra::{Matrix4, Vector3};
 
 fn create_test_cube() -> Model {
     Cube::new().size(1.0).center(0.0, 0.0, 0.0).build()
@@ -12,6 +14,11 @@ fn create_test_sphere() -> Model {
     Sphere::new().radius(1.0).segments(32).rings(16).build()
 }
 
+fn create_large_sphere() -> Model {
+    // ~131k vertices, ~262k triangles
+    Sphere::new().radius(1.0).segments(512).rings(256).build()
+}
+
 fn bench_scale_transform(c: &mut Criterion) {
     let mut group = c.benchmark_group("Scale Transform");
 
@@ -145,12 +152,130 @@ fn bench_transform_chain(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_mesh_storage(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Mesh Storage");
+    group.sample_size(20);
+
+    let model = create_large_sphere();
+    let compact = CompactMesh::from(&model.mesh);
+    let matrix = Matrix4::new_rotation(Vector3::new(0.0, 0.5, 0.0))
+        * Matrix4::new_nonuniform_scaling(&Vector3::new(1.0, 2.0, 3.0));
+
+    // Applying the same matrix through each storage layout
+    group.bench_function("matrix_large_sphere_mesh", |b| {
+        b.iter_batched_ref(
+            || model.clone(),
+            |model| black_box(Matrix::new(matrix).apply(model)),
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.bench_function("matrix_large_sphere_compact", |b| {
+        b.iter_batched_ref(
+            || compact.clone(),
+            |compact| compact.transform(black_box(&matrix)),
+            BatchSize::LargeInput,
+        )
+    });
+
+    // Recomputing normals walks every face
+    group.bench_function("normals_large_sphere_mesh", |b| {
+        b.iter_batched_ref(
+            || model.clone(),
+            |model| model.mesh.compute_normals(),
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.bench_function("normals_large_sphere_compact", |b| {
+        b.iter_batched_ref(
+            || compact.clone(),
+            |compact| compact.compute_normals(),
+            BatchSize::LargeInput,
+        )
+    });
+
+    // Copying the whole mesh, which is dominated by allocation and memory traffic
+    group.bench_function("clone_large_sphere_mesh", |b| {
+        b.iter(|| black_box(model.mesh.clone()))
+    });
+
+    group.bench_function("clone_large_sphere_compact", |b| {
+        b.iter(|| black_box(compact.clone()))
+    });
+
+    // Cost of the adapter in each direction
+    group.bench_function("convert_mesh_to_compact", |b| {
+        b.iter(|| black_box(CompactMesh::from(&model.mesh)))
+    });
+
+    group.bench_function("convert_compact_to_mesh", |b| {
+        b.iter(|| black_box(mg::Mesh::from(&compact)))
+    });
+
+    group.finish();
+}
+
+// Name of the current execution mode, so runs with and without `--features parallel`
+// are recorded side by side (e.g. "rotate_large_sphere/serial" vs ".../parallel")
+const MODE: &str = if cfg!(feature = "parallel") {
+    "parallel"
+} else {
+    "serial"
+};
+
+fn bench_parallel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Large Sphere");
+    group.sample_size(20);
+
+    let model = create_large_sphere();
+
+    group.bench_function(format!("rotate_large_sphere/{}", MODE), |b| {
+        b.iter_batched_ref(
+            || model.clone(),
+            |model| black_box(Rotate::around_y(45.0).apply(model)),
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.bench_function(format!("twist_large_sphere/{}", MODE), |b| {
+        b.iter_batched_ref(
+            || model.clone(),
+            |model| black_box(Twist::around_y(90.0, 0.0, 0.0).apply(model)),
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.bench_function(format!("normals_large_sphere/{}", MODE), |b| {
+        b.iter_batched_ref(
+            || model.clone(),
+            |model| model.mesh.compute_normals(),
+            BatchSize::LargeInput,
+        )
+    });
+
+    let export_dir = std::env::temp_dir();
+    group.bench_function(format!("export_obj_large_sphere/{}", MODE), |b| {
+        let path = export_dir.join("bench_large_sphere.obj");
+        b.iter(|| black_box(model.export_obj(&path)))
+    });
+
+    group.bench_function(format!("export_stl_large_sphere/{}", MODE), |b| {
+        let path = export_dir.join("bench_large_sphere.stl");
+        b.iter(|| black_box(model.export_stl(&path)))
+    });
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_scale_transform,
     bench_rotate_transform,
     bench_translate_transform,
     bench_mirror_transform,
-    bench_transform_chain
+    bench_transform_chain,
+    bench_mesh_storage,
+    bench_parallel
 );
 criterion_main!(benches);
@@ -1,9 +1,9 @@
 //! Example demonstrating advanced transforms: Matrix, Mirror, and Quaternion.
 
+use mg::consts::PI;
 use mg::primitives::Cube;
 use mg::transforms::advanced::{Matrix, Mirror, Quaternion};
 use nalgebra::{Matrix4, UnitQuaternion, Vector3};
-use std::f32::consts::PI;
 
 fn main() -> mg::Result<()> {
     println!("Creating models with advanced transformations...");
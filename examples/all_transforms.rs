@@ -1,19 +1,20 @@
 //! Example demonstrating transforms from all categories working together.
 
+use mg::consts::PI;
 use mg::primitives::{Cube, Cylinder, Sphere};
 use mg::transforms::advanced::{Mirror, Quaternion};
 use mg::transforms::basic::{Rotate, Scale};
 use mg::transforms::deform::{Bend, Taper, Twist};
 use mg::transforms::projection::{Cylindrical, Perspective};
+use mg::Real;
 use nalgebra::{Point3, Vector3};
-use std::f32::consts::PI;
 use std::fs;
 
 fn main() -> mg::Result<()> {
     println!("Creating complex models using transforms from all categories...");
 
     // Create output directory if it doesn't exist
-    fs::create_dir_all("output").map_err(|e| mg::Error::IoError(e))?;
+    fs::create_dir_all("output").map_err(mg::Error::IoError)?;
 
     // Model 1: Twisted and Bent Tower
     create_twisted_tower()?;
@@ -48,13 +49,13 @@ fn create_twisted_tower() -> mg::Result<()> {
 
     // Add some cubes along its length for decoration
     for i in 0..5 {
-        let height = -2.0 + i as f32;
+        let height = -2.0 + i as Real;
         let size = 0.2;
         let radius = if i % 2 == 0 { 0.5 } else { 0.4 };
 
         // Create 4 small cubes at this height
         for j in 0..4 {
-            let angle = j as f32 * PI / 2.0;
+            let angle = j as Real * PI / 2.0;
             let mut cube = Cube::new()
                 .size(size)
                 .center(radius * angle.cos(), radius * angle.sin(), height)
@@ -352,7 +353,7 @@ fn create_space_station() -> mg::Result<()> {
 
     // Create struts connecting the rings to the central hub
     for i in 0..8 {
-        let angle = i as f32 * PI / 4.0;
+        let angle = i as Real * PI / 4.0;
 
         // Horizontal ring struts
         let mut strut_h = Cylinder::new()
@@ -1,9 +1,10 @@
 //! Example demonstrating projection transforms: Perspective, Orthographic, and Cylindrical.
 
+use mg::consts::PI;
 use mg::primitives::{Cube, Sphere};
 use mg::transforms::basic::Translate;
 use mg::transforms::projection::{Cylindrical, Orthographic, Perspective};
-use mg::{Face, Model, Vertex};
+use mg::{Face, Model, Real, Vertex};
 use nalgebra::{Point3, Vector3};
 
 fn main() -> mg::Result<()> {
@@ -101,11 +102,11 @@ fn main() -> mg::Result<()> {
     println!("Exported cylindrical grid projection: output/projection_cylindrical_grid.obj");
 
     // Create a sphere by projecting a grid onto a cylinder, then bending it
-    let mut model = create_grid(3.14159 * 2.0, 3.14159, 36, 18);
+    let mut model = create_grid(PI * 2.0, PI, 36, 18);
 
     // Transform y coordinates to range from -PI/2 to PI/2
     for vertex in &mut model.mesh.vertices {
-        vertex.position.y = vertex.position.y - 3.14159 / 2.0;
+        vertex.position.y -= PI / 2.0;
     }
 
     // Project onto a cylinder first
@@ -128,12 +129,12 @@ fn main() -> mg::Result<()> {
 }
 
 /// Create a grid model with specified width, height and subdivisions
-fn create_grid(width: f32, height: f32, columns: usize, rows: usize) -> Model {
+fn create_grid(width: Real, height: Real, columns: usize, rows: usize) -> Model {
     let mut model = Model::new("Grid");
 
     // Calculate spacing
-    let dx = width / columns as f32;
-    let dy = height / rows as f32;
+    let dx = width / columns as Real;
+    let dy = height / rows as Real;
 
     // Calculate starting position (centered at origin)
     let start_x = -width / 2.0;
@@ -143,8 +144,8 @@ fn create_grid(width: f32, height: f32, columns: usize, rows: usize) -> Model {
     let mut vertices = Vec::new();
     for i in 0..=rows {
         for j in 0..=columns {
-            let x = start_x + j as f32 * dx;
-            let y = start_y + i as f32 * dy;
+            let x = start_x + j as Real * dx;
+            let y = start_y + i as Real * dy;
 
             // UV coordinates
             let u = j as f32 / columns as f32;
@@ -1,5 +1,5 @@
-use mg::primitives::{Cube, Cylinder, Sphere};
-use mg::{Model, Rotate, Scale, Translate};
+use mg::primitives::{Capsule, Cone, Cube, Cylinder, Gear, Plane, Sphere, Spring, Thread, Torus};
+use mg::{Model, Real, Rotate, Scale, Translate};
 use std::path::PathBuf;
 use std::process;
 use std::str::FromStr;
@@ -17,6 +17,13 @@ fn main() {
         "cube" => create_cube(&args[2..]),
         "sphere" => create_sphere(&args[2..]),
         "cylinder" => create_cylinder(&args[2..]),
+        "cone" => create_cone(&args[2..]),
+        "torus" => create_torus(&args[2..]),
+        "capsule" => create_capsule(&args[2..]),
+        "plane" => create_plane(&args[2..]),
+        "gear" => create_gear(&args[2..]),
+        "thread" => create_thread(&args[2..]),
+        "spring" => create_spring(&args[2..]),
         "help" | "--help" | "-h" => print_usage(),
         _ => {
             eprintln!("Unknown shape: {}", args[1]);
@@ -34,6 +41,13 @@ fn print_usage() {
     println!("  cube      Generate a cube");
     println!("  sphere    Generate a sphere");
     println!("  cylinder  Generate a cylinder");
+    println!("  cone      Generate a cone or frustum");
+    println!("  torus     Generate a torus");
+    println!("  capsule   Generate a capsule");
+    println!("  plane     Generate a subdivided plane");
+    println!("  gear      Generate an involute spur gear");
+    println!("  thread    Generate an ISO metric screw thread");
+    println!("  spring    Generate a helical spring");
     println!();
     println!("Options for cube:");
     println!("  --size SIZE              Set cube size (default: 1.0)");
@@ -52,6 +66,64 @@ fn print_usage() {
     println!("  --center X,Y,Z           Set center position (default: 0,0,0)");
     println!("  --no-caps                Remove end caps");
     println!();
+    println!("Options for cone:");
+    println!("  --radius RADIUS          Set base radius (default: 1.0)");
+    println!("  --top-radius RADIUS      Set top radius for a frustum (default: 0.0)");
+    println!("  --height HEIGHT          Set height (default: 2.0)");
+    println!("  --segments SEGMENTS      Set number of segments (default: 32)");
+    println!("  --center X,Y,Z           Set center position (default: 0,0,0)");
+    println!("  --no-caps                Remove end caps");
+    println!();
+    println!("Options for torus:");
+    println!("  --major-radius RADIUS    Set ring radius (default: 1.0)");
+    println!("  --minor-radius RADIUS    Set tube radius (default: 0.25)");
+    println!("  --segments SEGMENTS      Set number of segments around the ring (default: 32)");
+    println!("  --rings RINGS            Set number of rings around the tube (default: 16)");
+    println!("  --center X,Y,Z           Set center position (default: 0,0,0)");
+    println!();
+    println!("Options for capsule:");
+    println!("  --radius RADIUS          Set radius (default: 0.5)");
+    println!("  --height HEIGHT          Set cylindrical section height (default: 1.0)");
+    println!("  --segments SEGMENTS      Set number of segments (default: 32)");
+    println!("  --rings RINGS            Set number of rings per hemisphere (default: 8)");
+    println!("  --center X,Y,Z           Set center position (default: 0,0,0)");
+    println!();
+    println!("Options for plane:");
+    println!("  --width WIDTH            Set size along X (default: 1.0)");
+    println!("  --depth DEPTH            Set size along Z (default: 1.0)");
+    println!("  --subdivisions X,Z       Set number of grid cells (default: 1,1)");
+    println!("  --center X,Y,Z           Set center position (default: 0,0,0)");
+    println!();
+    println!("Options for gear:");
+    println!("  --module MODULE          Set tooth size (default: 0.1)");
+    println!("  --teeth TEETH            Set number of teeth (default: 20)");
+    println!("  --pressure-angle DEGREES Set pressure angle (default: 20)");
+    println!("  --width WIDTH            Set face width along Y (default: 0.5)");
+    println!("  --bore DIAMETER          Set bore diameter, 0 for none (default: 0)");
+    println!("  --segments SEGMENTS      Set number of segments per flank (default: 8)");
+    println!("  --center X,Y,Z           Set center position (default: 0,0,0)");
+    println!();
+    println!("Options for thread:");
+    println!("  --metric DIAMETER        Use the ISO coarse pitch for an M size (e.g., 6)");
+    println!("  --diameter DIAMETER      Set major diameter (default: 1.0)");
+    println!("  --pitch PITCH            Set pitch (default: 0.15)");
+    println!("  --length LENGTH          Set length (default: 2.0)");
+    println!("  --internal               Cut the thread into a sleeve, as in a nut");
+    println!("  --outer-diameter DIAM    Set sleeve diameter of internal threads");
+    println!("  --left-handed            Wind the thread the other way");
+    println!("  --segments SEGMENTS      Set number of segments per turn (default: 32)");
+    println!("  --center X,Y,Z           Set center position (default: 0,0,0)");
+    println!();
+    println!("Options for spring:");
+    println!("  --coil-diameter DIAM     Set coil diameter (default: 1.0)");
+    println!("  --wire-diameter DIAM     Set wire diameter (default: 0.1)");
+    println!("  --pitch PITCH            Set rise per turn (default: 0.25)");
+    println!("  --turns TURNS            Set number of turns (default: 5)");
+    println!("  --closed-ends            Close the end turns down to the wire diameter");
+    println!("  --segments SEGMENTS      Set number of segments per turn (default: 32)");
+    println!("  --wire-segments SEGMENTS Set number of segments around the wire (default: 12)");
+    println!("  --center X,Y,Z           Set center position (default: 0,0,0)");
+    println!();
     println!("Common options:");
     println!("  --scale X,Y,Z            Apply scaling (default: 1,1,1)");
     println!("  --rotate AXIS,DEGREES    Apply rotation (e.g., y,45)");
@@ -61,15 +133,13 @@ fn print_usage() {
     println!("  .obj     Wavefront OBJ format");
     println!("  .stl     STL format");
     println!("  .gltf    glTF format");
+    println!("  .ply     PLY format");
 }
 
 fn create_cube(args: &[String]) {
     let mut size = 1.0;
     let mut center = (0.0, 0.0, 0.0);
-    let mut scale = None;
-    let mut rotate = None;
-    let mut translate = None;
-    let mut output_file = None;
+    let mut common = CommonOptions::default();
 
     let mut i = 0;
     while i < args.len() {
@@ -90,79 +160,147 @@ fn create_cube(args: &[String]) {
                     i += 1;
                 }
             }
-            "--scale" => {
+            _ => i = common.parse(args, i),
+        }
+    }
+
+    // Create a cube with the specified parameters
+    let cube = Cube::new()
+        .size(size)
+        .center(center.0, center.1, center.2)
+        .build();
+
+    common.finish(cube);
+}
+
+fn create_sphere(args: &[String]) {
+    let mut radius = 1.0;
+    let mut segments = 32;
+    let mut rings = 16;
+    let mut center = (0.0, 0.0, 0.0);
+    let mut common = CommonOptions::default();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--radius" => {
                 if i + 1 < args.len() {
-                    scale = Some(parse_vector3(&args[i + 1]).unwrap_or((1.0, 1.0, 1.0)));
+                    radius = args[i + 1].parse().unwrap_or(1.0);
                     i += 2;
                 } else {
                     i += 1;
                 }
             }
-            "--rotate" => {
+            "--segments" => {
                 if i + 1 < args.len() {
-                    rotate = parse_rotation(&args[i + 1]);
+                    segments = args[i + 1].parse().unwrap_or(32);
                     i += 2;
                 } else {
                     i += 1;
                 }
             }
-            "--translate" => {
+            "--rings" => {
                 if i + 1 < args.len() {
-                    translate = Some(parse_vector3(&args[i + 1]).unwrap_or((0.0, 0.0, 0.0)));
+                    rings = args[i + 1].parse().unwrap_or(16);
                     i += 2;
                 } else {
                     i += 1;
                 }
             }
-            _ => {
-                output_file = Some(args[i].clone());
-                i += 1;
+            "--center" => {
+                if i + 1 < args.len() {
+                    center = parse_vector3(&args[i + 1]).unwrap_or((0.0, 0.0, 0.0));
+                    i += 2;
+                } else {
+                    i += 1;
+                }
             }
+            _ => i = common.parse(args, i),
         }
     }
 
-    // Create a cube with the specified parameters
-    let mut cube = Cube::new()
-        .size(size)
+    // Create a sphere with the specified parameters
+    let sphere = Sphere::new()
+        .radius(radius)
+        .segments(segments)
+        .rings(rings)
         .center(center.0, center.1, center.2)
         .build();
 
-    // Apply transformations if specified
-    if let Some(s) = scale {
-        cube.apply(Scale::new(s.0, s.1, s.2));
-    }
+    common.finish(sphere);
+}
 
-    if let Some((axis, angle)) = rotate {
-        match axis.as_str() {
-            "x" => cube.apply(Rotate::around_x(angle)),
-            "y" => cube.apply(Rotate::around_y(angle)),
-            "z" => cube.apply(Rotate::around_z(angle)),
-            _ => &mut cube,
-        };
-    }
+fn create_cylinder(args: &[String]) {
+    let mut radius = 1.0;
+    let mut height = 2.0;
+    let mut segments = 32;
+    let mut center = (0.0, 0.0, 0.0);
+    let mut caps = true;
+    let mut common = CommonOptions::default();
 
-    if let Some(t) = translate {
-        cube.apply(Translate::new(t.0, t.1, t.2));
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--radius" => {
+                if i + 1 < args.len() {
+                    radius = args[i + 1].parse().unwrap_or(1.0);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--height" => {
+                if i + 1 < args.len() {
+                    height = args[i + 1].parse().unwrap_or(2.0);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--segments" => {
+                if i + 1 < args.len() {
+                    segments = args[i + 1].parse().unwrap_or(32);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--center" => {
+                if i + 1 < args.len() {
+                    center = parse_vector3(&args[i + 1]).unwrap_or((0.0, 0.0, 0.0));
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--no-caps" => {
+                caps = false;
+                i += 1;
+            }
+            _ => i = common.parse(args, i),
+        }
     }
 
-    // Export the model to the specified file
-    if let Some(file) = output_file {
-        export_model(&cube, &file);
-    } else {
-        eprintln!("No output file specified");
-        process::exit(1);
-    }
+    // Create a cylinder with the specified parameters
+    let cylinder = Cylinder::new()
+        .radius(radius)
+        .height(height)
+        .segments(segments)
+        .center(center.0, center.1, center.2)
+        .caps(caps)
+        .build();
+
+    common.finish(cylinder);
 }
 
-fn create_sphere(args: &[String]) {
+fn create_cone(args: &[String]) {
     let mut radius = 1.0;
+    let mut top_radius = 0.0;
+    let mut height = 2.0;
     let mut segments = 32;
-    let mut rings = 16;
     let mut center = (0.0, 0.0, 0.0);
-    let mut scale = None;
-    let mut rotate = None;
-    let mut translate = None;
-    let mut output_file = None;
+    let mut caps = true;
+    let mut common = CommonOptions::default();
 
     let mut i = 0;
     while i < args.len() {
@@ -175,6 +313,86 @@ fn create_sphere(args: &[String]) {
                     i += 1;
                 }
             }
+            "--top-radius" => {
+                if i + 1 < args.len() {
+                    top_radius = args[i + 1].parse().unwrap_or(0.0);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--height" => {
+                if i + 1 < args.len() {
+                    height = args[i + 1].parse().unwrap_or(2.0);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--segments" => {
+                if i + 1 < args.len() {
+                    segments = args[i + 1].parse().unwrap_or(32);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--center" => {
+                if i + 1 < args.len() {
+                    center = parse_vector3(&args[i + 1]).unwrap_or((0.0, 0.0, 0.0));
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--no-caps" => {
+                caps = false;
+                i += 1;
+            }
+            _ => i = common.parse(args, i),
+        }
+    }
+
+    // Create a cone with the specified parameters
+    let cone = Cone::new()
+        .radius(radius)
+        .top_radius(top_radius)
+        .height(height)
+        .segments(segments)
+        .center(center.0, center.1, center.2)
+        .caps(caps)
+        .build();
+
+    common.finish(cone);
+}
+
+fn create_torus(args: &[String]) {
+    let mut major_radius = 1.0;
+    let mut minor_radius = 0.25;
+    let mut segments = 32;
+    let mut rings = 16;
+    let mut center = (0.0, 0.0, 0.0);
+    let mut common = CommonOptions::default();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--major-radius" => {
+                if i + 1 < args.len() {
+                    major_radius = args[i + 1].parse().unwrap_or(1.0);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--minor-radius" => {
+                if i + 1 < args.len() {
+                    minor_radius = args[i + 1].parse().unwrap_or(0.25);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
             "--segments" => {
                 if i + 1 < args.len() {
                     segments = args[i + 1].parse().unwrap_or(32);
@@ -199,102 +417,296 @@ fn create_sphere(args: &[String]) {
                     i += 1;
                 }
             }
-            "--scale" => {
+            _ => i = common.parse(args, i),
+        }
+    }
+
+    // Create a torus with the specified parameters
+    let torus = Torus::new()
+        .major_radius(major_radius)
+        .minor_radius(minor_radius)
+        .segments(segments)
+        .rings(rings)
+        .center(center.0, center.1, center.2)
+        .build();
+
+    common.finish(torus);
+}
+
+fn create_capsule(args: &[String]) {
+    let mut radius = 0.5;
+    let mut height = 1.0;
+    let mut segments = 32;
+    let mut rings = 8;
+    let mut center = (0.0, 0.0, 0.0);
+    let mut common = CommonOptions::default();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--radius" => {
                 if i + 1 < args.len() {
-                    scale = Some(parse_vector3(&args[i + 1]).unwrap_or((1.0, 1.0, 1.0)));
+                    radius = args[i + 1].parse().unwrap_or(0.5);
                     i += 2;
                 } else {
                     i += 1;
                 }
             }
-            "--rotate" => {
+            "--height" => {
                 if i + 1 < args.len() {
-                    rotate = parse_rotation(&args[i + 1]);
+                    height = args[i + 1].parse().unwrap_or(1.0);
                     i += 2;
                 } else {
                     i += 1;
                 }
             }
-            "--translate" => {
+            "--segments" => {
                 if i + 1 < args.len() {
-                    translate = Some(parse_vector3(&args[i + 1]).unwrap_or((0.0, 0.0, 0.0)));
+                    segments = args[i + 1].parse().unwrap_or(32);
                     i += 2;
                 } else {
                     i += 1;
                 }
             }
-            _ => {
-                output_file = Some(args[i].clone());
-                i += 1;
+            "--rings" => {
+                if i + 1 < args.len() {
+                    rings = args[i + 1].parse().unwrap_or(8);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--center" => {
+                if i + 1 < args.len() {
+                    center = parse_vector3(&args[i + 1]).unwrap_or((0.0, 0.0, 0.0));
+                    i += 2;
+                } else {
+                    i += 1;
+                }
             }
+            _ => i = common.parse(args, i),
         }
     }
 
-    // Create a sphere with the specified parameters
-    let mut sphere = Sphere::new()
+    // Create a capsule with the specified parameters
+    let capsule = Capsule::new()
         .radius(radius)
+        .height(height)
         .segments(segments)
         .rings(rings)
         .center(center.0, center.1, center.2)
         .build();
 
-    // Apply transformations if specified
-    if let Some(s) = scale {
-        sphere.apply(Scale::new(s.0, s.1, s.2));
-    }
+    common.finish(capsule);
+}
 
-    if let Some((axis, angle)) = rotate {
-        match axis.as_str() {
-            "x" => sphere.apply(Rotate::around_x(angle)),
-            "y" => sphere.apply(Rotate::around_y(angle)),
-            "z" => sphere.apply(Rotate::around_z(angle)),
-            _ => &mut sphere,
-        };
-    }
+fn create_plane(args: &[String]) {
+    let mut width = 1.0;
+    let mut depth = 1.0;
+    let mut subdivisions = (1, 1);
+    let mut center = (0.0, 0.0, 0.0);
+    let mut common = CommonOptions::default();
 
-    if let Some(t) = translate {
-        sphere.apply(Translate::new(t.0, t.1, t.2));
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--width" => {
+                if i + 1 < args.len() {
+                    width = args[i + 1].parse().unwrap_or(1.0);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--depth" => {
+                if i + 1 < args.len() {
+                    depth = args[i + 1].parse().unwrap_or(1.0);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--subdivisions" => {
+                if i + 1 < args.len() {
+                    subdivisions = parse_pair(&args[i + 1]).unwrap_or((1, 1));
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--center" => {
+                if i + 1 < args.len() {
+                    center = parse_vector3(&args[i + 1]).unwrap_or((0.0, 0.0, 0.0));
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            _ => i = common.parse(args, i),
+        }
     }
 
-    // Export the model to the specified file
-    if let Some(file) = output_file {
-        export_model(&sphere, &file);
-    } else {
-        eprintln!("No output file specified");
-        process::exit(1);
+    // Create a plane with the specified parameters
+    let plane = Plane::new()
+        .width(width)
+        .depth(depth)
+        .subdivisions(subdivisions.0, subdivisions.1)
+        .center(center.0, center.1, center.2)
+        .build();
+
+    common.finish(plane);
+}
+
+fn create_gear(args: &[String]) {
+    let mut module = 0.1;
+    let mut teeth = 20;
+    let mut pressure_angle = 20.0;
+    let mut width = 0.5;
+    let mut bore = 0.0;
+    let mut segments = 8;
+    let mut center = (0.0, 0.0, 0.0);
+    let mut common = CommonOptions::default();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--module" => {
+                if i + 1 < args.len() {
+                    module = args[i + 1].parse().unwrap_or(0.1);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--teeth" => {
+                if i + 1 < args.len() {
+                    teeth = args[i + 1].parse().unwrap_or(20);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--pressure-angle" => {
+                if i + 1 < args.len() {
+                    pressure_angle = args[i + 1].parse().unwrap_or(20.0);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--width" => {
+                if i + 1 < args.len() {
+                    width = args[i + 1].parse().unwrap_or(0.5);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--bore" => {
+                if i + 1 < args.len() {
+                    bore = args[i + 1].parse().unwrap_or(0.0);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--segments" => {
+                if i + 1 < args.len() {
+                    segments = args[i + 1].parse().unwrap_or(8);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--center" => {
+                if i + 1 < args.len() {
+                    center = parse_vector3(&args[i + 1]).unwrap_or((0.0, 0.0, 0.0));
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            _ => i = common.parse(args, i),
+        }
     }
+
+    // Create a gear with the specified parameters
+    let gear = Gear::new()
+        .module(module)
+        .teeth(teeth)
+        .pressure_angle(pressure_angle)
+        .width(width)
+        .bore(bore)
+        .segments(segments)
+        .center(center.0, center.1, center.2)
+        .build();
+
+    common.finish(gear);
 }
 
-fn create_cylinder(args: &[String]) {
-    let mut radius = 1.0;
-    let mut height = 2.0;
+fn create_thread(args: &[String]) {
+    let mut metric: Option<Real> = None;
+    let mut diameter: Option<Real> = None;
+    let mut pitch: Option<Real> = None;
+    let mut length = 2.0;
+    let mut internal = false;
+    let mut outer_diameter: Option<Real> = None;
+    let mut left_handed = false;
     let mut segments = 32;
     let mut center = (0.0, 0.0, 0.0);
-    let mut caps = true;
-    let mut scale = None;
-    let mut rotate = None;
-    let mut translate = None;
-    let mut output_file = None;
+    let mut common = CommonOptions::default();
 
     let mut i = 0;
     while i < args.len() {
         match args[i].as_str() {
-            "--radius" => {
+            "--metric" => {
                 if i + 1 < args.len() {
-                    radius = args[i + 1].parse().unwrap_or(1.0);
+                    metric = args[i + 1].parse().ok();
                     i += 2;
                 } else {
                     i += 1;
                 }
             }
-            "--height" => {
+            "--diameter" => {
                 if i + 1 < args.len() {
-                    height = args[i + 1].parse().unwrap_or(2.0);
+                    diameter = args[i + 1].parse().ok();
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--pitch" => {
+                if i + 1 < args.len() {
+                    pitch = args[i + 1].parse().ok();
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--length" => {
+                if i + 1 < args.len() {
+                    length = args[i + 1].parse().unwrap_or(2.0);
                     i += 2;
                 } else {
                     i += 1;
                 }
             }
+            "--internal" => {
+                internal = true;
+                i += 1;
+            }
+            "--outer-diameter" => {
+                if i + 1 < args.len() {
+                    outer_diameter = args[i + 1].parse().ok();
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--left-handed" => {
+                left_handed = true;
+                i += 1;
+            }
             "--segments" => {
                 if i + 1 < args.len() {
                     segments = args[i + 1].parse().unwrap_or(32);
@@ -311,74 +723,201 @@ fn create_cylinder(args: &[String]) {
                     i += 1;
                 }
             }
-            "--no-caps" => {
-                caps = false;
-                i += 1;
+            _ => i = common.parse(args, i),
+        }
+    }
+
+    // Start from the standard pitch of a metric size, then apply any overrides
+    let mut thread = match metric {
+        Some(size) => Thread::metric(size),
+        None => Thread::new(),
+    };
+    if let Some(diameter) = diameter {
+        thread = thread.diameter(diameter);
+    }
+    if let Some(pitch) = pitch {
+        thread = thread.pitch(pitch);
+    }
+    if let Some(outer_diameter) = outer_diameter {
+        thread = thread.outer_diameter(outer_diameter);
+    }
+    let thread = thread
+        .length(length)
+        .internal(internal)
+        .left_handed(left_handed)
+        .segments(segments)
+        .center(center.0, center.1, center.2)
+        .build();
+
+    common.finish(thread);
+}
+
+fn create_spring(args: &[String]) {
+    let mut coil_diameter = 1.0;
+    let mut wire_diameter = 0.1;
+    let mut pitch = 0.25;
+    let mut turns = 5.0;
+    let mut closed_ends = false;
+    let mut segments = 32;
+    let mut wire_segments = 12;
+    let mut center = (0.0, 0.0, 0.0);
+    let mut common = CommonOptions::default();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--coil-diameter" => {
+                if i + 1 < args.len() {
+                    coil_diameter = args[i + 1].parse().unwrap_or(1.0);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
             }
-            "--scale" => {
+            "--wire-diameter" => {
                 if i + 1 < args.len() {
-                    scale = Some(parse_vector3(&args[i + 1]).unwrap_or((1.0, 1.0, 1.0)));
+                    wire_diameter = args[i + 1].parse().unwrap_or(0.1);
                     i += 2;
                 } else {
                     i += 1;
                 }
             }
-            "--rotate" => {
+            "--pitch" => {
                 if i + 1 < args.len() {
-                    rotate = parse_rotation(&args[i + 1]);
+                    pitch = args[i + 1].parse().unwrap_or(0.25);
                     i += 2;
                 } else {
                     i += 1;
                 }
             }
-            "--translate" => {
+            "--turns" => {
                 if i + 1 < args.len() {
-                    translate = Some(parse_vector3(&args[i + 1]).unwrap_or((0.0, 0.0, 0.0)));
+                    turns = args[i + 1].parse().unwrap_or(5.0);
                     i += 2;
                 } else {
                     i += 1;
                 }
             }
-            _ => {
-                output_file = Some(args[i].clone());
+            "--closed-ends" => {
+                closed_ends = true;
                 i += 1;
             }
+            "--segments" => {
+                if i + 1 < args.len() {
+                    segments = args[i + 1].parse().unwrap_or(32);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--wire-segments" => {
+                if i + 1 < args.len() {
+                    wire_segments = args[i + 1].parse().unwrap_or(12);
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            "--center" => {
+                if i + 1 < args.len() {
+                    center = parse_vector3(&args[i + 1]).unwrap_or((0.0, 0.0, 0.0));
+                    i += 2;
+                } else {
+                    i += 1;
+                }
+            }
+            _ => i = common.parse(args, i),
         }
     }
 
-    // Create a cylinder with the specified parameters
-    let mut cylinder = Cylinder::new()
-        .radius(radius)
-        .height(height)
+    // Create a spring with the specified parameters
+    let spring = Spring::new()
+        .coil_diameter(coil_diameter)
+        .wire_diameter(wire_diameter)
+        .pitch(pitch)
+        .turns(turns)
+        .closed_ends(closed_ends)
         .segments(segments)
+        .wire_segments(wire_segments)
         .center(center.0, center.1, center.2)
-        .caps(caps)
         .build();
 
-    // Apply transformations if specified
-    if let Some(s) = scale {
-        cylinder.apply(Scale::new(s.0, s.1, s.2));
-    }
+    common.finish(spring);
+}
 
-    if let Some((axis, angle)) = rotate {
-        match axis.as_str() {
-            "x" => cylinder.apply(Rotate::around_x(angle)),
-            "y" => cylinder.apply(Rotate::around_y(angle)),
-            "z" => cylinder.apply(Rotate::around_z(angle)),
-            _ => &mut cylinder,
-        };
-    }
+/// Transform and output options accepted by every shape.
+#[derive(Default)]
+struct CommonOptions {
+    scale: Option<(Real, Real, Real)>,
+    rotate: Option<(String, Real)>,
+    translate: Option<(Real, Real, Real)>,
+    output_file: Option<String>,
+}
 
-    if let Some(t) = translate {
-        cylinder.apply(Translate::new(t.0, t.1, t.2));
+impl CommonOptions {
+    /// Parse the option at `args[i]`, returning the index of the next unparsed argument.
+    ///
+    /// Anything that is not a common option is taken as the output file.
+    fn parse(&mut self, args: &[String], i: usize) -> usize {
+        match args[i].as_str() {
+            "--scale" => {
+                if i + 1 < args.len() {
+                    self.scale = Some(parse_vector3(&args[i + 1]).unwrap_or((1.0, 1.0, 1.0)));
+                    i + 2
+                } else {
+                    i + 1
+                }
+            }
+            "--rotate" => {
+                if i + 1 < args.len() {
+                    self.rotate = parse_rotation(&args[i + 1]);
+                    i + 2
+                } else {
+                    i + 1
+                }
+            }
+            "--translate" => {
+                if i + 1 < args.len() {
+                    self.translate = Some(parse_vector3(&args[i + 1]).unwrap_or((0.0, 0.0, 0.0)));
+                    i + 2
+                } else {
+                    i + 1
+                }
+            }
+            _ => {
+                self.output_file = Some(args[i].clone());
+                i + 1
+            }
+        }
     }
 
-    // Export the model to the specified file
-    if let Some(file) = output_file {
-        export_model(&cylinder, &file);
-    } else {
-        eprintln!("No output file specified");
-        process::exit(1);
+    /// Apply the requested transformations and export the model.
+    fn finish(self, mut model: Model) {
+        // Apply transformations if specified
+        if let Some(s) = self.scale {
+            model.apply(Scale::new(s.0, s.1, s.2));
+        }
+
+        if let Some((axis, angle)) = self.rotate {
+            match axis.as_str() {
+                "x" => model.apply(Rotate::around_x(angle)),
+                "y" => model.apply(Rotate::around_y(angle)),
+                "z" => model.apply(Rotate::around_z(angle)),
+                _ => &mut model,
+            };
+        }
+
+        if let Some(t) = self.translate {
+            model.apply(Translate::new(t.0, t.1, t.2));
+        }
+
+        // Export the model to the specified file
+        if let Some(file) = self.output_file {
+            export_model(&model, &file);
+        } else {
+            eprintln!("No output file specified");
+            process::exit(1);
+        }
     }
 }
 
@@ -407,28 +946,47 @@ fn export_model(model: &Model, file: &str) {
             }
             println!("Model exported to {}", file);
         }
+        Some("ply") => {
+            if let Err(e) = model.export_ply(&path) {
+                eprintln!("Error exporting to PLY: {}", e);
+                process::exit(1);
+            }
+            println!("Model exported to {}", file);
+        }
         _ => {
             eprintln!("Unsupported file format: {}", file);
-            eprintln!("Supported formats: .obj, .stl, .gltf");
+            eprintln!("Supported formats: .obj, .stl, .gltf, .ply");
             process::exit(1);
         }
     }
 }
 
-fn parse_vector3(s: &str) -> Option<(f32, f32, f32)> {
+fn parse_vector3(s: &str) -> Option<(Real, Real, Real)> {
     let parts: Vec<&str> = s.split(',').collect();
     if parts.len() != 3 {
         return None;
     }
 
-    let x = parts[0].parse::<f32>().ok()?;
-    let y = parts[1].parse::<f32>().ok()?;
-    let z = parts[2].parse::<f32>().ok()?;
+    let x = parts[0].parse::<Real>().ok()?;
+    let y = parts[1].parse::<Real>().ok()?;
+    let z = parts[2].parse::<Real>().ok()?;
 
     Some((x, y, z))
 }
 
-fn parse_rotation(s: &str) -> Option<(String, f32)> {
+fn parse_pair(s: &str) -> Option<(usize, usize)> {
+    let parts: Vec<&str> = s.split(',').collect();
+    if parts.len() != 2 {
+        return None;
+    }
+
+    let a = parts[0].parse::<usize>().ok()?;
+    let b = parts[1].parse::<usize>().ok()?;
+
+    Some((a, b))
+}
+
+fn parse_rotation(s: &str) -> Option<(String, Real)> {
     let parts: Vec<&str> = s.split(',').collect();
     if parts.len() != 2 {
         return None;
@@ -439,7 +997,7 @@ fn parse_rotation(s: &str) -> Option<(String, f32)> {
         return None;
     }
 
-    let angle = parts[1].parse::<f32>().ok()?;
+    let angle = parts[1].parse::<Real>().ok()?;
 
     Some((axis, angle))
 }
@@ -0,0 +1,323 @@
+//! Bounding volumes of meshes: axis-aligned boxes, spheres and oriented boxes.
+//!
+//! Each comes from the matching [`Mesh`] method, [`Mesh::aabb`],
+//! [`Mesh::bounding_sphere`] and [`Mesh::oriented_bounding_box`], which give `None`
+//! for a mesh without vertices.
+
+use crate::noise::SplitMix64;
+use crate::{Mesh, Real};
+use nalgebra::{Matrix3, Point3, Vector3};
+
+/// An axis-aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    /// Corner with the smallest coordinates.
+    pub min: Point3<Real>,
+    /// Corner with the largest coordinates.
+    pub max: Point3<Real>,
+}
+
+impl Aabb {
+    /// The smallest box containing all the points, or `None` if there are none.
+    pub fn from_points<'a>(points: impl IntoIterator<Item = &'a Point3<Real>>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = *points.next()?;
+        Some(points.fold(Self::new(first, first), |aabb, p| Self {
+            min: aabb.min.inf(p),
+            max: aabb.max.sup(p),
+        }))
+    }
+
+    /// Create a box from its minimum and maximum corners.
+    pub fn new(min: Point3<Real>, max: Point3<Real>) -> Self {
+        Self { min, max }
+    }
+
+    /// The middle of the box.
+    pub fn center(&self) -> Point3<Real> {
+        nalgebra::center(&self.min, &self.max)
+    }
+
+    /// The length of the box along each axis.
+    pub fn size(&self) -> Vector3<Real> {
+        self.max - self.min
+    }
+
+    /// The volume of the box.
+    pub fn volume(&self) -> Real {
+        self.size().product()
+    }
+
+    /// Whether a point lies inside or on the box.
+    pub fn contains(&self, point: &Point3<Real>) -> bool {
+        (0..3).all(|axis| point[axis] >= self.min[axis] && point[axis] <= self.max[axis])
+    }
+
+    /// Whether two boxes touch or overlap.
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        (0..3).all(|axis| self.min[axis] <= other.max[axis] && other.min[axis] <= self.max[axis])
+    }
+
+    /// The distance from a point to the box, zero inside it.
+    pub fn distance(&self, point: &Point3<Real>) -> Real {
+        (self.min - point)
+            .sup(&(point - self.max))
+            .sup(&Vector3::zeros())
+            .norm()
+    }
+}
+
+/// A sphere enclosing a set of points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingSphere {
+    /// Center of the sphere.
+    pub center: Point3<Real>,
+    /// Radius of the sphere.
+    pub radius: Real,
+}
+
+impl BoundingSphere {
+    /// Whether a point lies inside or on the sphere.
+    pub fn contains(&self, point: &Point3<Real>) -> bool {
+        (point - self.center).norm() <= self.radius
+    }
+}
+
+/// A box free to turn, given by its center, three perpendicular unit axes and its
+/// half size along each.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrientedBox {
+    /// Middle of the box.
+    pub center: Point3<Real>,
+    /// The box's axes, as the columns of a rotation matrix.
+    pub axes: Matrix3<Real>,
+    /// Half the box's length along each of its axes.
+    pub half_extents: Vector3<Real>,
+}
+
+impl OrientedBox {
+    /// The volume of the box.
+    pub fn volume(&self) -> Real {
+        8.0 * self.half_extents.product()
+    }
+
+    /// The eight corners of the box, with bit 0, 1 and 2 of the index choosing the
+    /// positive side of the first, second and third axis.
+    pub fn corners(&self) -> [Point3<Real>; 8] {
+        std::array::from_fn(|k| {
+            let sign = |bit: usize| if k >> bit & 1 == 1 { 1.0 } else { -1.0 };
+            self.center
+                + self.axes
+                    * self
+                        .half_extents
+                        .component_mul(&Vector3::new(sign(0), sign(1), sign(2)))
+        })
+    }
+
+    /// Whether a point lies inside or on the box.
+    pub fn contains(&self, point: &Point3<Real>) -> bool {
+        let local = self.axes.transpose() * (point - self.center);
+        (0..3).all(|axis| local[axis].abs() <= self.half_extents[axis])
+    }
+}
+
+impl Mesh {
+    /// The axis-aligned bounding box of the vertices, or `None` without vertices.
+    pub fn aabb(&self) -> Option<Aabb> {
+        Aabb::from_points(self.vertices.iter().map(|v| &v.position))
+    }
+
+    /// The smallest sphere enclosing the vertices, or `None` without vertices.
+    ///
+    /// Found by Welzl's algorithm in its move-to-front form, on the vertices in a
+    /// fixed shuffled order so the result does not depend on luck. The radius is
+    /// stretched to the farthest vertex, so rounding never leaves one outside.
+    pub fn bounding_sphere(&self) -> Option<BoundingSphere> {
+        let mut points: Vec<Point3<Real>> = self.vertices.iter().map(|v| v.position).collect();
+        let aabb = Aabb::from_points(&points)?;
+        let epsilon =
+            64.0 * Real::EPSILON * (aabb.size().norm() + aabb.center().coords.abs().max());
+
+        let mut random = SplitMix64::new(0x5eed);
+        for i in (1..points.len()).rev() {
+            points.swap(i, (random.next_u64() % (i as u64 + 1)) as usize);
+        }
+        let count = points.len();
+        let mut sphere = move_to_front(&mut points, count, &mut Vec::new(), epsilon);
+        sphere.radius = points
+            .iter()
+            .map(|p| (p - sphere.center).norm())
+            .fold(sphere.radius, Real::max);
+        Some(sphere)
+    }
+
+    /// A tight box around the vertices turned to fit them, or `None` without
+    /// vertices.
+    ///
+    /// The axes are the principal axes of the vertices' convex hull surface, which
+    /// suit elongated and slanted shapes. When that box is no smaller than the
+    /// axis-aligned one, as for shapes without a clear main direction, the
+    /// axis-aligned box is returned instead.
+    pub fn oriented_bounding_box(&self) -> Option<OrientedBox> {
+        let aabb = self.aabb()?;
+        let axis_aligned = OrientedBox {
+            center: aabb.center(),
+            axes: Matrix3::identity(),
+            half_extents: aabb.size() / 2.0,
+        };
+
+        let points: Vec<Point3<Real>> = self.vertices.iter().map(|v| v.position).collect();
+        let Some(triangles) = crate::hull::quickhull(&points) else {
+            return Some(axis_aligned);
+        };
+
+        // Covariance of the hull surface, weighting each triangle by its area
+        let origin = aabb.center();
+        let mut area = 0.0;
+        let mut mean = Vector3::zeros();
+        let mut second = Matrix3::zeros();
+        for &[a, b, c] in &triangles {
+            let [a, b, c] = [a, b, c].map(|i| points[i] - origin);
+            let weight = (b - a).cross(&(c - a)).norm() / 2.0;
+            area += weight;
+            mean += (a + b + c) * (weight / 3.0);
+            second += (a * a.transpose()
+                + b * b.transpose()
+                + c * c.transpose()
+                + (a + b + c) * (a + b + c).transpose())
+                * (weight / 12.0);
+        }
+        let mean = mean / area;
+        let covariance = second / area - mean * mean.transpose();
+
+        let mut axes = covariance.symmetric_eigen().eigenvectors;
+        if axes.determinant() < 0.0 {
+            axes.set_column(2, &-axes.column(2));
+        }
+        let local: Vec<Point3<Real>> = triangles
+            .iter()
+            .flatten()
+            .map(|&i| Point3::from(axes.transpose() * (points[i] - origin)))
+            .collect();
+        let bounds = Aabb::from_points(&local)?;
+        let oriented = OrientedBox {
+            center: origin + axes * bounds.center().coords,
+            axes,
+            half_extents: bounds.size() / 2.0,
+        };
+
+        if oriented.volume() < axis_aligned.volume() {
+            Some(oriented)
+        } else {
+            Some(axis_aligned)
+        }
+    }
+}
+
+/// The smallest sphere enclosing the first `count` points with the `boundary`
+/// points on its surface.
+///
+/// Each point found outside the current sphere goes onto the boundary for a
+/// smaller problem and then to the front of the list, where later passes test it
+/// first. The boundary never holds more than four points, so the recursion stays
+/// shallow.
+fn move_to_front(
+    points: &mut [Point3<Real>],
+    count: usize,
+    boundary: &mut Vec<Point3<Real>>,
+    epsilon: Real,
+) -> BoundingSphere {
+    let mut sphere = sphere_through(boundary);
+    if boundary.len() == 4 {
+        return sphere;
+    }
+    for i in 0..count {
+        let point = points[i];
+        if (point - sphere.center).norm() > sphere.radius + epsilon {
+            boundary.push(point);
+            sphere = move_to_front(points, i, boundary, epsilon);
+            boundary.pop();
+            points[..=i].rotate_right(1);
+        }
+    }
+    sphere
+}
+
+/// The smallest sphere with up to four points on its surface.
+///
+/// Points that are collinear or coplanar have no sphere through all of them, so
+/// the smallest sphere through some and enclosing the rest is used.
+fn sphere_through(points: &[Point3<Real>]) -> BoundingSphere {
+    match *points {
+        [] => BoundingSphere {
+            center: Point3::origin(),
+            radius: -1.0,
+        },
+        [a] => BoundingSphere {
+            center: a,
+            radius: 0.0,
+        },
+        [a, b] => BoundingSphere {
+            center: nalgebra::center(&a, &b),
+            radius: (b - a).norm() / 2.0,
+        },
+        [a, b, c] => {
+            // The circumcenter in the triangle's plane
+            let (u, v) = (b - a, c - a);
+            let normal = u.cross(&v);
+            let denominator = 2.0 * normal.norm_squared();
+            if denominator <= Real::EPSILON * u.norm_squared() * v.norm_squared() {
+                return widest_pair(points);
+            }
+            let offset = (normal.cross(&u) * v.norm_squared()
+                + v.cross(&normal) * u.norm_squared())
+                / denominator;
+            BoundingSphere {
+                center: a + offset,
+                radius: offset.norm(),
+            }
+        }
+        [a, b, c, d] => {
+            let (u, v, w) = (b - a, c - a, d - a);
+            let determinant = 2.0 * u.dot(&v.cross(&w));
+            let scale = u.norm() * v.norm() * w.norm();
+            if determinant.abs() <= Real::EPSILON * scale {
+                // Coplanar: the smallest circle-sphere through three that holds the fourth
+                return [[a, b, c], [a, b, d], [a, c, d], [b, c, d]]
+                    .into_iter()
+                    .map(|triple| sphere_through(&triple))
+                    .filter(|s| {
+                        let slack = s.radius * 1e-4;
+                        points
+                            .iter()
+                            .all(|p| (p - s.center).norm() <= s.radius + slack)
+                    })
+                    .min_by(|s, t| s.radius.total_cmp(&t.radius))
+                    .unwrap_or_else(|| widest_pair(points));
+            }
+            let offset = (v.cross(&w) * u.norm_squared()
+                + w.cross(&u) * v.norm_squared()
+                + u.cross(&v) * w.norm_squared())
+                / determinant;
+            BoundingSphere {
+                center: a + offset,
+                radius: offset.norm(),
+            }
+        }
+        _ => unreachable!("A sphere is fixed by at most four points"),
+    }
+}
+
+/// The sphere on the two points farthest apart as its diameter.
+fn widest_pair(points: &[Point3<Real>]) -> BoundingSphere {
+    let mut widest = sphere_through(&points[..1]);
+    for (i, a) in points.iter().enumerate() {
+        for b in &points[i + 1..] {
+            let sphere = sphere_through(&[*a, *b]);
+            if sphere.radius > widest.radius {
+                widest = sphere;
+            }
+        }
+    }
+    widest
+}
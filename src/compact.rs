@@ -0,0 +1,359 @@
+//! Compact struct-of-arrays mesh storage for large meshes.
+//!
+//! [`Mesh`] stores one [`Vertex`] struct per vertex and a heap-allocated index list
+//! per [`Face`], which is convenient for building geometry but costly for models with
+//! millions of triangles. [`CompactMesh`] keeps positions, normals and texture
+//! coordinates in contiguous buffers and all faces in one flat `u32` index buffer.
+//! The other per-vertex channels and the materials come along unchanged.
+//!
+//! Conversions in both directions let the existing transforms and exporters be used
+//! unchanged, while the native methods operate directly on the buffers.
+
+use crate::parallel;
+use crate::types::Material;
+use crate::{Face, Mesh, Model, Real, Result, Transform, Vertex, VertexAttribute};
+use nalgebra::{Matrix4, Point3, Vector3, Vector4};
+use std::collections::{BTreeMap, HashMap};
+
+/// A mesh stored as contiguous attribute buffers and a flat index buffer.
+///
+/// Faces are triangles unless polygon offsets are present: for a triangle-only mesh
+/// `face_offsets` is empty and face `i` is `indices[3 * i..3 * i + 3]`. Otherwise
+/// face `i` is `indices[face_offsets[i]..face_offsets[i + 1]]`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CompactMesh {
+    /// Vertex positions
+    pub positions: Vec<Point3<Real>>,
+    /// Vertex normals, one per position
+    pub normals: Vec<Vector3<Real>>,
+    /// Texture coordinates; empty if the mesh has none, otherwise one per position
+    pub tex_coords: Vec<[f32; 2]>,
+    /// Vertex indices of all faces
+    pub indices: Vec<u32>,
+    /// Start offset of each face in `indices` followed by the end offset of the last face
+    ///
+    /// Empty for triangle-only meshes.
+    pub face_offsets: Vec<u32>,
+    /// Tangents as in [`Mesh::tangents`]; empty or one per position
+    pub tangents: Vec<Vector4<Real>>,
+    /// RGBA colors as in [`Mesh::colors`]; empty or one per position
+    pub colors: Vec<[f32; 4]>,
+    /// Additional texture coordinate sets as in [`Mesh::tex_coord_sets`], each with
+    /// one entry per position
+    pub tex_coord_sets: Vec<Vec<[f32; 2]>>,
+    /// User-named per-vertex attributes as in [`Mesh::attributes`]
+    pub attributes: BTreeMap<String, VertexAttribute>,
+    /// Material properties
+    pub materials: HashMap<String, Material>,
+    /// Material of each face; empty if no face has one
+    pub face_materials: Vec<Option<String>>,
+}
+
+impl CompactMesh {
+    /// Create a new empty triangle mesh.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an empty mesh with room for the given number of vertices and triangles.
+    pub fn with_capacity(vertices: usize, triangles: usize) -> Self {
+        Self {
+            positions: Vec::with_capacity(vertices),
+            normals: Vec::with_capacity(vertices),
+            tex_coords: Vec::new(),
+            indices: Vec::with_capacity(triangles * 3),
+            ..Self::default()
+        }
+    }
+
+    /// Number of vertices in the mesh.
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Number of faces in the mesh.
+    pub fn face_count(&self) -> usize {
+        if self.is_triangles() {
+            self.indices.len() / 3
+        } else {
+            self.face_offsets.len() - 1
+        }
+    }
+
+    /// Returns true if every face is a triangle and no offsets are stored.
+    pub fn is_triangles(&self) -> bool {
+        self.face_offsets.is_empty()
+    }
+
+    /// Get the vertex indices of a face.
+    pub fn face(&self, index: usize) -> &[u32] {
+        if self.is_triangles() {
+            &self.indices[index * 3..index * 3 + 3]
+        } else {
+            let start = self.face_offsets[index] as usize;
+            let end = self.face_offsets[index + 1] as usize;
+            &self.indices[start..end]
+        }
+    }
+
+    /// Iterate over the vertex indices of all faces.
+    pub fn faces(&self) -> impl Iterator<Item = &[u32]> + '_ {
+        (0..self.face_count()).map(move |i| self.face(i))
+    }
+
+    /// Add a vertex to the mesh.
+    ///
+    /// Returns the index of the added vertex. Other per-vertex channels get default
+    /// values, except tangents, which are cleared as [`Mesh::add_vertex`] does.
+    pub fn add_vertex(
+        &mut self,
+        position: Point3<Real>,
+        normal: Vector3<Real>,
+        tex_coords: Option<(f32, f32)>,
+    ) -> u32 {
+        let index = self.positions.len() as u32;
+
+        // Texture coordinates are all-or-nothing, so back-fill when the first set arrives
+        if let Some((u, v)) = tex_coords {
+            self.tex_coords.resize(self.positions.len(), [0.0, 0.0]);
+            self.tex_coords.push([u, v]);
+        } else if !self.tex_coords.is_empty() {
+            self.tex_coords.push([0.0, 0.0]);
+        }
+
+        self.positions.push(position);
+        self.normals.push(normal);
+        self.tangents.clear();
+        if !self.colors.is_empty() {
+            self.colors.push([1.0, 1.0, 1.0, 1.0]);
+        }
+        for set in &mut self.tex_coord_sets {
+            set.push([0.0, 0.0]);
+        }
+        for attribute in self.attributes.values_mut() {
+            attribute.push_default();
+        }
+        index
+    }
+
+    /// Add a triangle face to the mesh.
+    pub fn add_triangle(&mut self, v1: u32, v2: u32, v3: u32) {
+        self.indices.extend_from_slice(&[v1, v2, v3]);
+        if !self.is_triangles() {
+            self.face_offsets.push(self.indices.len() as u32);
+        }
+        if !self.face_materials.is_empty() {
+            self.face_materials.push(None);
+        }
+    }
+
+    /// Add a face with any number of vertices.
+    ///
+    /// Adding a non-triangular face switches the mesh from the triangle-only layout
+    /// to stored face offsets.
+    pub fn add_face(&mut self, indices: &[u32]) {
+        if indices.len() != 3 && self.is_triangles() {
+            self.face_offsets = (0..=self.indices.len() as u32).step_by(3).collect();
+        }
+
+        self.indices.extend_from_slice(indices);
+        if !self.is_triangles() {
+            self.face_offsets.push(self.indices.len() as u32);
+        }
+        if !self.face_materials.is_empty() {
+            self.face_materials.push(None);
+        }
+    }
+
+    /// Compute surface normals for vertices by averaging face normals.
+    ///
+    /// This produces the same normals as [`Mesh::compute_normals`].
+    pub fn compute_normals(&mut self) {
+        let face_normals = parallel::map_indices(self.face_count(), |i| {
+            let face = self.face(i);
+            if face.len() < 3 {
+                return None;
+            }
+
+            let v0 = self.positions[face[0] as usize];
+            let v1 = self.positions[face[1] as usize];
+            let v2 = self.positions[face[2] as usize];
+            Some((v1 - v0).cross(&(v2 - v0)).normalize())
+        });
+
+        // Accumulate in face order so the result does not depend on threading
+        let mut normals = vec![Vector3::zeros(); self.positions.len()];
+        for (face, normal) in self.faces().zip(face_normals) {
+            if let Some(normal) = normal {
+                for &idx in face {
+                    normals[idx as usize] += normal;
+                }
+            }
+        }
+
+        parallel::for_each_mut(&mut normals, |normal| {
+            if normal.magnitude() > 0.0 {
+                *normal = normal.normalize();
+            } else {
+                *normal = Vector3::new(0.0, 1.0, 0.0);
+            }
+        });
+
+        self.normals = normals;
+    }
+
+    /// Apply an affine transformation matrix directly to the position and normal buffers.
+    ///
+    /// Normals are transformed with the inverse transpose of the matrix and tangents
+    /// with the matrix itself, like [`Matrix`](crate::Matrix) does for a [`Model`].
+    pub fn transform(&mut self, matrix: &Matrix4<Real>) {
+        let linear = matrix.fixed_view::<3, 3>(0, 0).into_owned();
+        let normal_matrix = linear
+            .try_inverse()
+            .unwrap_or_else(nalgebra::Matrix3::identity)
+            .transpose();
+
+        parallel::for_each_mut(&mut self.positions, |position| {
+            *position = matrix.transform_point(position);
+        });
+
+        parallel::for_each_mut(&mut self.normals, |normal| {
+            let transformed = normal_matrix * *normal;
+            if transformed.magnitude() > 0.0 {
+                *normal = transformed.normalize();
+            }
+        });
+
+        let handedness_flip = linear.determinant() < 0.0;
+        parallel::for_each_mut(&mut self.tangents, |tangent| {
+            let transformed = linear * tangent.xyz();
+            if transformed.magnitude() > 0.0 {
+                let transformed = transformed.normalize();
+                tangent.x = transformed.x;
+                tangent.y = transformed.y;
+                tangent.z = transformed.z;
+            }
+            if handedness_flip {
+                tangent.w = -tangent.w;
+            }
+        });
+    }
+
+    /// Translate all positions by the given offset.
+    pub fn translate(&mut self, x: Real, y: Real, z: Real) {
+        let offset = Vector3::new(x, y, z);
+        parallel::for_each_mut(&mut self.positions, |position| {
+            *position += offset;
+        });
+    }
+
+    /// Apply any [`Transform`] by converting to a [`Model`] and back.
+    ///
+    /// This keeps every existing transform usable on compact meshes, at the cost of
+    /// the round trip; prefer the native methods for hot paths.
+    pub fn apply<T: Transform>(&mut self, transform: T) -> Result<()> {
+        let mut model = self.to_model("CompactMesh");
+        transform.apply(&mut model)?;
+        *self = CompactMesh::from(&model.mesh);
+        Ok(())
+    }
+
+    /// Convert to a [`Model`] so it can be used with the exporters and plugins.
+    pub fn to_model(&self, name: impl Into<String>) -> Model {
+        let mut model = Model::new(name);
+        model.mesh = Mesh::from(self);
+        model
+    }
+}
+
+impl From<&Mesh> for CompactMesh {
+    /// Convert a [`Mesh`], keeping every per-vertex channel, the faces and materials.
+    fn from(mesh: &Mesh) -> Self {
+        let triangles_only = mesh.faces.iter().all(|f| f.indices.len() == 3);
+        let has_tex_coords = mesh.vertices.iter().any(|v| v.tex_coords.is_some());
+
+        let mut compact = CompactMesh {
+            positions: mesh.vertices.iter().map(|v| v.position).collect(),
+            normals: mesh.vertices.iter().map(|v| v.normal).collect(),
+            tex_coords: Vec::new(),
+            indices: Vec::with_capacity(mesh.faces.iter().map(|f| f.indices.len()).sum()),
+            face_offsets: Vec::new(),
+            tangents: mesh.tangents.clone(),
+            colors: mesh.colors.clone(),
+            tex_coord_sets: mesh
+                .tex_coord_sets
+                .iter()
+                .map(|set| set.iter().map(|&(u, v)| [u, v]).collect())
+                .collect(),
+            attributes: mesh.attributes.clone(),
+            materials: mesh.materials.clone(),
+            face_materials: Vec::new(),
+        };
+
+        if mesh.face_materials.iter().any(Option::is_some) {
+            compact.face_materials = mesh.face_materials.clone();
+        }
+
+        if has_tex_coords {
+            compact.tex_coords = mesh
+                .vertices
+                .iter()
+                .map(|v| {
+                    let (u, v) = v.tex_coords.unwrap_or((0.0, 0.0));
+                    [u, v]
+                })
+                .collect();
+        }
+
+        if !triangles_only {
+            compact.face_offsets.reserve(mesh.faces.len() + 1);
+            compact.face_offsets.push(0);
+        }
+
+        for face in &mesh.faces {
+            compact
+                .indices
+                .extend(face.indices.iter().map(|&idx| idx as u32));
+            if !triangles_only {
+                compact.face_offsets.push(compact.indices.len() as u32);
+            }
+        }
+
+        compact
+    }
+}
+
+impl From<&CompactMesh> for Mesh {
+    fn from(compact: &CompactMesh) -> Self {
+        let mut mesh = Mesh::new();
+        mesh.vertices.reserve(compact.vertex_count());
+        mesh.faces.reserve(compact.face_count());
+
+        for (i, position) in compact.positions.iter().enumerate() {
+            mesh.add_vertex(Vertex::new(
+                *position,
+                compact.normals[i],
+                compact.tex_coords.get(i).map(|uv| (uv[0], uv[1])),
+            ));
+        }
+
+        for (i, face) in compact.faces().enumerate() {
+            mesh.add_face(
+                Face::new(face.iter().map(|&idx| idx as usize).collect()),
+                compact.face_materials.get(i).cloned().flatten(),
+            );
+        }
+
+        // Set after the vertices, since adding them clears the tangents
+        mesh.tangents = compact.tangents.clone();
+        mesh.colors = compact.colors.clone();
+        mesh.tex_coord_sets = compact
+            .tex_coord_sets
+            .iter()
+            .map(|set| set.iter().map(|uv| (uv[0], uv[1])).collect())
+            .collect();
+        mesh.attributes = compact.attributes.clone();
+        mesh.materials = compact.materials.clone();
+        mesh
+    }
+}
@@ -0,0 +1,338 @@
+//! Boolean operations on polygons.
+
+use crate::generators::distinct_points;
+use crate::triangulate::signed_area;
+use crate::Real;
+use nalgebra::Point2;
+use std::collections::HashMap;
+
+/// Which region [`boolean`] keeps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BooleanOp {
+    /// Everything inside either shape.
+    Union,
+    /// Only what is inside both shapes.
+    Intersection,
+    /// What is inside the first shape but not the second.
+    Difference,
+    /// What is inside exactly one of the shapes.
+    Xor,
+}
+
+/// Where an edge lies relative to the other shape.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Outside,
+    Inside,
+    /// On an edge of the other shape running the same way.
+    Same,
+    /// On an edge of the other shape running the opposite way.
+    Opposite,
+}
+
+/// Combine two shapes, each a set of closed contours.
+///
+/// A point is inside a shape when it is inside an odd number of its contours, so
+/// holes are contours inside outlines, wound either way. Contours should not cross
+/// themselves or each other within a shape, though the two shapes may overlap in
+/// any way, including along shared edges.
+///
+/// Returns contours with outlines counter-clockwise and holes clockwise, the way
+/// [`Extrude`](crate::generators::Extrude) takes them.
+pub fn boolean(
+    subject: &[Vec<(Real, Real)>],
+    clip: &[Vec<(Real, Real)>],
+    op: BooleanOp,
+) -> Vec<Vec<(Real, Real)>> {
+    let a = prepare(subject);
+    let b = prepare(clip);
+    let (mut min, mut max) = (
+        Point2::new(Real::INFINITY, Real::INFINITY),
+        Point2::new(Real::NEG_INFINITY, Real::NEG_INFINITY),
+    );
+    for p in a.iter().chain(&b).flatten() {
+        min = min.inf(p);
+        max = max.sup(p);
+    }
+    if a.is_empty() && b.is_empty() {
+        return Vec::new();
+    }
+    let epsilon = (max - min).amax() * Real::EPSILON * 256.0;
+
+    let (edges_a, edges_b) = split(&a, &b, epsilon);
+    let sides_a = classify(&edges_a, &edges_b, &b);
+    let sides_b = classify(&edges_b, &edges_a, &a);
+
+    // Whether to keep an edge of the first or second shape, and whether to reverse it
+    let select = |side: Side, first: bool| -> Option<bool> {
+        match (op, side, first) {
+            (BooleanOp::Union, Side::Outside, _) => Some(false),
+            (BooleanOp::Union, Side::Same, true) => Some(false),
+            (BooleanOp::Intersection, Side::Inside, _) => Some(false),
+            (BooleanOp::Intersection, Side::Same, true) => Some(false),
+            (BooleanOp::Difference, Side::Outside | Side::Opposite, true) => Some(false),
+            (BooleanOp::Difference, Side::Inside, false) => Some(true),
+            (BooleanOp::Xor, Side::Outside, _) => Some(false),
+            (BooleanOp::Xor, Side::Inside, _) => Some(true),
+            _ => None,
+        }
+    };
+    let mut kept = Vec::new();
+    for (edges, sides, first) in [(&edges_a, &sides_a, true), (&edges_b, &sides_b, false)] {
+        for (&(start, end), &side) in edges.iter().zip(sides) {
+            match select(side, first) {
+                Some(false) => kept.push((start, end)),
+                Some(true) => kept.push((end, start)),
+                None => {}
+            }
+        }
+    }
+
+    link(&kept, epsilon)
+        .into_iter()
+        .map(|contour| contour.iter().map(|p| (p.x, p.y)).collect())
+        .collect()
+}
+
+/// Clean up contours and wind them by depth: outlines counter-clockwise, holes
+/// clockwise.
+fn prepare(contours: &[Vec<(Real, Real)>]) -> Vec<Vec<Point2<Real>>> {
+    let mut contours: Vec<Vec<Point2<Real>>> = contours
+        .iter()
+        .map(|contour| distinct_points(contour))
+        .filter(|contour| contour.len() >= 3 && signed_area(contour) != 0.0)
+        .collect();
+    for i in 0..contours.len() {
+        let depth = (0..contours.len())
+            .filter(|&j| j != i && inside(contours[i][0], &contours[j]))
+            .count();
+        if (signed_area(&contours[i]) > 0.0) != (depth % 2 == 0) {
+            contours[i].reverse();
+        }
+    }
+    contours
+}
+
+/// Whether `point` is inside a closed contour, by counting crossings of a ray.
+fn inside(point: Point2<Real>, contour: &[Point2<Real>]) -> bool {
+    let mut result = false;
+    let mut previous = contour[contour.len() - 1];
+    for &current in contour {
+        if (current.y > point.y) != (previous.y > point.y) {
+            let x = previous.x
+                + (point.y - previous.y) * (current.x - previous.x) / (current.y - previous.y);
+            if point.x < x {
+                result = !result;
+            }
+        }
+        previous = current;
+    }
+    result
+}
+
+/// Edges of both shapes, split wherever they cross or touch the other shape.
+///
+/// Every split point is shared exactly by the pieces on both sides, so edges that
+/// overlap become pieces with identical ends.
+#[allow(clippy::type_complexity)]
+fn split(
+    a: &[Vec<Point2<Real>>],
+    b: &[Vec<Point2<Real>>],
+    epsilon: Real,
+) -> (
+    Vec<(Point2<Real>, Point2<Real>)>,
+    Vec<(Point2<Real>, Point2<Real>)>,
+) {
+    let edges = |contours: &[Vec<Point2<Real>>]| -> Vec<(Point2<Real>, Point2<Real>)> {
+        contours
+            .iter()
+            .flat_map(|c| (0..c.len()).map(move |k| (c[k], c[(k + 1) % c.len()])))
+            .collect()
+    };
+    let (edges_a, edges_b) = (edges(a), edges(b));
+    let mut cuts_a: Vec<Vec<(Real, Point2<Real>)>> = vec![Vec::new(); edges_a.len()];
+    let mut cuts_b: Vec<Vec<(Real, Point2<Real>)>> = vec![Vec::new(); edges_b.len()];
+
+    // The parameter along an edge of a point strictly inside it, if it lies on it
+    let on_edge = |p: Point2<Real>, (start, end): (Point2<Real>, Point2<Real>)| {
+        let direction = end - start;
+        let length = direction.norm();
+        let t = (p - start).dot(&direction) / (length * length);
+        let interior = t * length > epsilon && (1.0 - t) * length > epsilon;
+        let distance = direction.perp(&(p - start)).abs() / length;
+        (interior && distance <= epsilon).then_some(t)
+    };
+
+    for (i, &ea) in edges_a.iter().enumerate() {
+        for (j, &eb) in edges_b.iter().enumerate() {
+            if ea.0.x.min(ea.1.x) > eb.0.x.max(eb.1.x) + epsilon
+                || eb.0.x.min(eb.1.x) > ea.0.x.max(ea.1.x) + epsilon
+                || ea.0.y.min(ea.1.y) > eb.0.y.max(eb.1.y) + epsilon
+                || eb.0.y.min(eb.1.y) > ea.0.y.max(ea.1.y) + epsilon
+            {
+                continue;
+            }
+            let mut touched = false;
+            for p in [eb.0, eb.1] {
+                if let Some(t) = on_edge(p, ea) {
+                    cuts_a[i].push((t, p));
+                    touched = true;
+                }
+            }
+            for p in [ea.0, ea.1] {
+                if let Some(u) = on_edge(p, eb) {
+                    cuts_b[j].push((u, p));
+                    touched = true;
+                }
+            }
+            if touched {
+                continue;
+            }
+
+            // A crossing strictly inside both edges
+            let (r, s) = (ea.1 - ea.0, eb.1 - eb.0);
+            let denominator = r.perp(&s);
+            if denominator.abs() <= epsilon * (r.norm() + s.norm()) {
+                continue;
+            }
+            let offset = eb.0 - ea.0;
+            let t = offset.perp(&s) / denominator;
+            let u = offset.perp(&r) / denominator;
+            let interior =
+                |t: Real, length: Real| t * length > epsilon && (1.0 - t) * length > epsilon;
+            if interior(t, r.norm()) && interior(u, s.norm()) {
+                let p = ea.0 + r * t;
+                cuts_a[i].push((t, p));
+                cuts_b[j].push((u, p));
+            }
+        }
+    }
+
+    let pieces = |edges: &[(Point2<Real>, Point2<Real>)],
+                  cuts: &mut [Vec<(Real, Point2<Real>)>]| {
+        let mut pieces = Vec::with_capacity(edges.len());
+        for (&(start, end), cuts) in edges.iter().zip(cuts.iter_mut()) {
+            cuts.sort_by(|x, y| x.0.total_cmp(&y.0));
+            let mut previous = start;
+            for &(_, p) in cuts.iter() {
+                if p != previous {
+                    pieces.push((previous, p));
+                    previous = p;
+                }
+            }
+            if previous != end {
+                pieces.push((previous, end));
+            }
+        }
+        pieces
+    };
+    (pieces(&edges_a, &mut cuts_a), pieces(&edges_b, &mut cuts_b))
+}
+
+/// Exact key for a point, treating both zeros alike.
+#[allow(clippy::unnecessary_cast)] // Only a no-op with the `f64` feature
+fn key(p: Point2<Real>) -> [u64; 2] {
+    [(p.x + 0.0).to_bits() as u64, (p.y + 0.0).to_bits() as u64]
+}
+
+/// Where each edge lies relative to the other shape.
+fn classify(
+    edges: &[(Point2<Real>, Point2<Real>)],
+    others: &[(Point2<Real>, Point2<Real>)],
+    other: &[Vec<Point2<Real>>],
+) -> Vec<Side> {
+    let mut directions = HashMap::new();
+    for &(start, end) in others {
+        directions.insert((key(start), key(end)), Side::Same);
+        directions.insert((key(end), key(start)), Side::Opposite);
+    }
+    edges
+        .iter()
+        .map(|&(start, end)| {
+            if let Some(&side) = directions.get(&(key(start), key(end))) {
+                return side;
+            }
+            let middle = Point2::from((start.coords + end.coords) / 2.0);
+            let count = other.iter().filter(|c| inside(middle, c)).count();
+            if count % 2 == 1 {
+                Side::Inside
+            } else {
+                Side::Outside
+            }
+        })
+        .collect()
+}
+
+/// Join edges end to start into closed contours.
+///
+/// Where several edges leave a point, the sharpest turn to the left is taken, so
+/// shapes touching at a corner stay separate contours. Points in the middle of
+/// straight runs are dropped.
+fn link(edges: &[(Point2<Real>, Point2<Real>)], epsilon: Real) -> Vec<Vec<Point2<Real>>> {
+    let mut leaving: HashMap<[u64; 2], Vec<usize>> = HashMap::new();
+    for (i, &(start, _)) in edges.iter().enumerate() {
+        leaving.entry(key(start)).or_default().push(i);
+    }
+    let mut used = vec![false; edges.len()];
+    let mut contours = Vec::new();
+
+    for first in 0..edges.len() {
+        if used[first] {
+            continue;
+        }
+        used[first] = true;
+        let mut contour = vec![edges[first].0];
+        let mut current = first;
+        let closed = loop {
+            let (start, end) = edges[current];
+            if key(end) == key(edges[first].0) {
+                break true;
+            }
+            let incoming = end - start;
+            let next = leaving.get(&key(end)).and_then(|candidates| {
+                candidates
+                    .iter()
+                    .copied()
+                    .filter(|&k| !used[k])
+                    .max_by(|&x, &y| {
+                        let turn = |k: usize| {
+                            let outgoing = edges[k].1 - edges[k].0;
+                            incoming.perp(&outgoing).atan2(incoming.dot(&outgoing))
+                        };
+                        turn(x).total_cmp(&turn(y))
+                    })
+            });
+            match next {
+                Some(k) => {
+                    used[k] = true;
+                    contour.push(end);
+                    current = k;
+                }
+                None => break false,
+            }
+        };
+        if !closed {
+            continue;
+        }
+
+        // Drop points where the contour runs straight on
+        let mut k = 0;
+        while contour.len() >= 3 && k < contour.len() {
+            let n = contour.len();
+            let (a, b, c) = (contour[(k + n - 1) % n], contour[k], contour[(k + 1) % n]);
+            let (before, after) = (b - a, c - b);
+            if before.perp(&after).abs() <= epsilon * (before + after).norm()
+                && before.dot(&after) > 0.0
+            {
+                contour.remove(k);
+                k = k.saturating_sub(1);
+            } else {
+                k += 1;
+            }
+        }
+        if contour.len() >= 3 && signed_area(&contour) != 0.0 {
+            contours.push(contour);
+        }
+    }
+    contours
+}
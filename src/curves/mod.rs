@@ -0,0 +1,436 @@
+//! 2D curves and outlines for building profiles.
+//!
+//! A [`Path`] chains lines, circular and elliptical arcs and quadratic and cubic
+//! Bezier curves, and can be made from Catmull-Rom splines or B-splines through a set
+//! of points, or parsed from SVG path data with [`parse_svg_path`]. Flattened paths
+//! are lists of `(x, y)` pairs, like the outlines the generators take, and can be
+//! resampled by arc length, offset, and combined with polygon booleans.
+
+mod boolean;
+mod offset;
+mod svg;
+
+pub use boolean::{boolean, BooleanOp};
+pub use offset::{offset, Join};
+pub use svg::parse_svg_path;
+
+use crate::Real;
+use nalgebra::{Point2, Vector2};
+
+/// A piece of a path, running from the end of the previous piece.
+#[derive(Debug, Clone)]
+enum Segment {
+    Line(Point2<Real>),
+    Quadratic(Point2<Real>, Point2<Real>),
+    Cubic(Point2<Real>, Point2<Real>, Point2<Real>),
+    /// The points `center + axes[0] * cos(t) + axes[1] * sin(t)` for `t` from
+    /// `start` to `start + sweep`, ending exactly at `end`.
+    Arc {
+        center: Point2<Real>,
+        axes: [Vector2<Real>; 2],
+        start: Real,
+        sweep: Real,
+        end: Point2<Real>,
+    },
+}
+
+impl Segment {
+    fn end(&self) -> Point2<Real> {
+        match *self {
+            Segment::Line(end)
+            | Segment::Quadratic(_, end)
+            | Segment::Cubic(_, _, end)
+            | Segment::Arc { end, .. } => end,
+        }
+    }
+}
+
+/// Builder for a 2D path of lines, arcs and Bezier curves.
+///
+/// Paths start at a point and each segment runs on from the end of the last one.
+/// Arcs sweeping the positive way turn counter-clockwise with Y up, which is
+/// clockwise on screen in SVG's Y-down coordinates.
+#[derive(Debug, Clone)]
+pub struct Path {
+    start: Point2<Real>,
+    segments: Vec<Segment>,
+    closed: bool,
+}
+
+impl Path {
+    /// Start a new path at the given point.
+    pub fn new(x: Real, y: Real) -> Self {
+        Self {
+            start: Point2::new(x, y),
+            segments: Vec::new(),
+            closed: false,
+        }
+    }
+
+    /// Create a closed circle, starting on its right.
+    pub fn circle(cx: Real, cy: Real, radius: Real) -> Self {
+        assert!(radius > 0.0, "Path circle radius must be positive");
+        let start = Point2::new(cx + radius, cy);
+        Self {
+            start,
+            segments: vec![Segment::Arc {
+                center: Point2::new(cx, cy),
+                axes: [Vector2::new(radius, 0.0), Vector2::new(0.0, radius)],
+                start: 0.0,
+                sweep: 2.0 * crate::consts::PI,
+                end: start,
+            }],
+            closed: true,
+        }
+    }
+
+    /// Create a smooth path through every point as a uniform Catmull-Rom spline.
+    pub fn catmull_rom(points: &[(Real, Real)], closed: bool) -> Self {
+        let points: Vec<Point2<Real>> = points.iter().map(|&(x, y)| Point2::new(x, y)).collect();
+        assert!(
+            points.len() >= 2,
+            "Catmull-Rom path needs at least 2 points"
+        );
+        let count = points.len() as isize;
+        // Open curves repeat their end points so the spline reaches them
+        let point = |i: isize| {
+            if closed {
+                points[i.rem_euclid(count) as usize]
+            } else {
+                points[i.clamp(0, count - 1) as usize]
+            }
+        };
+
+        let mut path = Self::new(points[0].x, points[0].y);
+        let spans = if closed { count } else { count - 1 };
+        for span in 0..spans {
+            let (p0, p1, p2, p3) = (
+                point(span - 1),
+                point(span),
+                point(span + 1),
+                point(span + 2),
+            );
+            path.segments.push(Segment::Cubic(
+                p1 + (p2 - p0) / 6.0,
+                p2 - (p3 - p1) / 6.0,
+                p2,
+            ));
+        }
+        path.closed = closed;
+        path
+    }
+
+    /// Create a uniform cubic B-spline path from its control points.
+    ///
+    /// The curve follows the control points without passing through them; open
+    /// curves still start and end on their first and last points.
+    pub fn bspline(control_points: &[(Real, Real)], closed: bool) -> Self {
+        let mut points: Vec<Point2<Real>> = control_points
+            .iter()
+            .map(|&(x, y)| Point2::new(x, y))
+            .collect();
+        assert!(
+            points.len() >= if closed { 3 } else { 2 },
+            "B-spline path needs at least 2 points, or 3 when closed"
+        );
+        if closed {
+            points.extend_from_within(..3);
+        } else {
+            let (first, last) = (points[0], points[points.len() - 1]);
+            points.splice(0..0, [first, first]);
+            points.extend([last, last]);
+        }
+
+        // Each span is the Bezier curve matching four consecutive control points
+        let on_curve = |a: Point2<Real>, b: Point2<Real>, c: Point2<Real>| {
+            Point2::from((a.coords + b.coords * 4.0 + c.coords) / 6.0)
+        };
+        let mut path = {
+            let start = on_curve(points[0], points[1], points[2]);
+            Self::new(start.x, start.y)
+        };
+        for span in points.windows(4) {
+            let (p1, p2) = (span[1].coords, span[2].coords);
+            path.segments.push(Segment::Cubic(
+                Point2::from((p1 * 2.0 + p2) / 3.0),
+                Point2::from((p1 + p2 * 2.0) / 3.0),
+                on_curve(span[1], span[2], span[3]),
+            ));
+        }
+        path.closed = closed;
+        path
+    }
+
+    /// Add a straight line to the given point.
+    pub fn line_to(self, x: Real, y: Real) -> Self {
+        self.push(Segment::Line(Point2::new(x, y)))
+    }
+
+    /// Add a quadratic Bezier curve with one control point.
+    pub fn quadratic_to(self, cx: Real, cy: Real, x: Real, y: Real) -> Self {
+        self.push(Segment::Quadratic(Point2::new(cx, cy), Point2::new(x, y)))
+    }
+
+    /// Add a cubic Bezier curve with two control points.
+    pub fn cubic_to(self, x1: Real, y1: Real, x2: Real, y2: Real, x: Real, y: Real) -> Self {
+        self.push(Segment::Cubic(
+            Point2::new(x1, y1),
+            Point2::new(x2, y2),
+            Point2::new(x, y),
+        ))
+    }
+
+    /// Add a circular arc of the given radius to the given point.
+    ///
+    /// Of the arcs that fit, `large_arc` picks the one sweeping more than half a turn
+    /// and `counter_clockwise` the direction.
+    pub fn arc_to(
+        self,
+        radius: Real,
+        large_arc: bool,
+        counter_clockwise: bool,
+        x: Real,
+        y: Real,
+    ) -> Self {
+        self.elliptical_arc_to(radius, radius, 0.0, large_arc, counter_clockwise, x, y)
+    }
+
+    /// Add an elliptical arc to the given point, as in SVG's `A` command.
+    ///
+    /// The ellipse has radii `rx` and `ry` with its X axis turned by `rotation`
+    /// degrees. Radii too small to reach the end point are scaled up until they do,
+    /// and a zero radius draws a straight line.
+    #[allow(clippy::too_many_arguments)]
+    pub fn elliptical_arc_to(
+        self,
+        rx: Real,
+        ry: Real,
+        rotation: Real,
+        large_arc: bool,
+        sweep: bool,
+        x: Real,
+        y: Real,
+    ) -> Self {
+        let (from, to) = (self.end_point(), Point2::new(x, y));
+        if from == to {
+            return self;
+        }
+        let (mut rx, mut ry) = (rx.abs(), ry.abs());
+        if rx == 0.0 || ry == 0.0 {
+            return self.line_to(x, y);
+        }
+
+        // The endpoint to center conversion from the SVG specification
+        let (sin, cos) = rotation.to_radians().sin_cos();
+        let half = (from - to) / 2.0;
+        let x1 = cos * half.x + sin * half.y;
+        let y1 = cos * half.y - sin * half.x;
+        let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+        if lambda > 1.0 {
+            rx *= lambda.sqrt();
+            ry *= lambda.sqrt();
+        }
+        let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+        let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+        let mut coefficient = (numerator / denominator).max(0.0).sqrt();
+        if large_arc == sweep {
+            coefficient = -coefficient;
+        }
+        let (cx, cy) = (coefficient * rx * y1 / ry, -coefficient * ry * x1 / rx);
+        let center =
+            Point2::new(cos * cx - sin * cy, sin * cx + cos * cy) + (from.coords + to.coords) / 2.0;
+
+        let u = Vector2::new((x1 - cx) / rx, (y1 - cy) / ry);
+        let v = Vector2::new((-x1 - cx) / rx, (-y1 - cy) / ry);
+        let start = u.y.atan2(u.x);
+        let mut angle = u.perp(&v).atan2(u.dot(&v));
+        if sweep && angle < 0.0 {
+            angle += 2.0 * crate::consts::PI;
+        } else if !sweep && angle > 0.0 {
+            angle -= 2.0 * crate::consts::PI;
+        }
+
+        self.push(Segment::Arc {
+            center,
+            axes: [Vector2::new(cos, sin) * rx, Vector2::new(-sin, cos) * ry],
+            start,
+            sweep: angle,
+            end: to,
+        })
+    }
+
+    /// Close the path back to its start.
+    pub fn close(mut self) -> Self {
+        self.closed = true;
+        self
+    }
+
+    /// Whether the path is closed.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Whether the path has no segments.
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// The point the path starts from.
+    pub fn start(&self) -> (Real, Real) {
+        (self.start.x, self.start.y)
+    }
+
+    /// The point the last segment ends at.
+    pub fn end(&self) -> (Real, Real) {
+        let end = self.end_point();
+        (end.x, end.y)
+    }
+
+    /// Move the whole path.
+    pub fn translate(self, dx: Real, dy: Real) -> Self {
+        let offset = Vector2::new(dx, dy);
+        self.map(|p| p + offset, |v| v)
+    }
+
+    /// Scale the path about the origin; `scale(1.0, -1.0)` flips SVG paths so Y
+    /// points up.
+    pub fn scale(self, sx: Real, sy: Real) -> Self {
+        let scale = |v: Vector2<Real>| Vector2::new(v.x * sx, v.y * sy);
+        self.map(|p| Point2::from(scale(p.coords)), scale)
+    }
+
+    /// Approximate the path by straight lines straying at most `tolerance` from it.
+    ///
+    /// Returns the corners in order. Closed paths do not repeat their first point at
+    /// the end.
+    pub fn flatten(&self, tolerance: Real) -> Vec<(Real, Real)> {
+        assert!(tolerance > 0.0, "Path flatten tolerance must be positive");
+        let mut points = vec![self.start];
+        for segment in &self.segments {
+            let from = points[points.len() - 1];
+            match *segment {
+                Segment::Line(end) => points.push(end),
+                Segment::Quadratic(c, end) => {
+                    flatten_bezier(&[from, c, end], tolerance, &mut points)
+                }
+                Segment::Cubic(c1, c2, end) => {
+                    flatten_bezier(&[from, c1, c2, end], tolerance, &mut points)
+                }
+                Segment::Arc {
+                    center,
+                    axes,
+                    start,
+                    sweep,
+                    end,
+                } => {
+                    let radius = axes[0].norm().max(axes[1].norm());
+                    let step = (2.0 * (1.0 - tolerance / radius).max(-1.0).acos())
+                        .min(crate::consts::FRAC_PI_2);
+                    let count = (sweep.abs() / step).ceil().max(1.0) as usize;
+                    for i in 1..count {
+                        let t = start + sweep * i as Real / count as Real;
+                        points.push(center + axes[0] * t.cos() + axes[1] * t.sin());
+                    }
+                    points.push(end);
+                }
+            }
+        }
+        points.dedup();
+        if self.closed && points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        points.iter().map(|p| (p.x, p.y)).collect()
+    }
+
+    fn end_point(&self) -> Point2<Real> {
+        self.segments.last().map_or(self.start, Segment::end)
+    }
+
+    fn push(mut self, segment: Segment) -> Self {
+        assert!(!self.closed, "Cannot add segments to a closed Path");
+        self.segments.push(segment);
+        self
+    }
+
+    /// Apply an affine map given by how it moves points and direction vectors.
+    fn map(
+        mut self,
+        point: impl Fn(Point2<Real>) -> Point2<Real>,
+        vector: impl Fn(Vector2<Real>) -> Vector2<Real>,
+    ) -> Self {
+        self.start = point(self.start);
+        for segment in &mut self.segments {
+            *segment = match *segment {
+                Segment::Line(end) => Segment::Line(point(end)),
+                Segment::Quadratic(c, end) => Segment::Quadratic(point(c), point(end)),
+                Segment::Cubic(c1, c2, end) => Segment::Cubic(point(c1), point(c2), point(end)),
+                Segment::Arc {
+                    center,
+                    axes,
+                    start,
+                    sweep,
+                    end,
+                } => Segment::Arc {
+                    center: point(center),
+                    axes: axes.map(&vector),
+                    start,
+                    sweep,
+                    end: point(end),
+                },
+            };
+        }
+        self
+    }
+}
+
+/// Append points along a Bezier curve after its first control point.
+///
+/// The number of lines comes from the bound on the curve's second derivative, so
+/// the chord error stays within `tolerance`.
+fn flatten_bezier(control: &[Point2<Real>], tolerance: Real, points: &mut Vec<Point2<Real>>) {
+    let degree = control.len() - 1;
+    let bend = control
+        .windows(3)
+        .map(|w| (w[2].coords - w[1].coords * 2.0 + w[0].coords).norm())
+        .fold(0.0, Real::max);
+    let bound = (degree * (degree - 1)) as Real * bend;
+    let count = (bound / (8.0 * tolerance)).sqrt().ceil().max(1.0) as usize;
+    for i in 1..=count {
+        let t = i as Real / count as Real;
+        // De Casteljau's algorithm
+        let mut level = control.to_vec();
+        while level.len() > 1 {
+            for k in 0..level.len() - 1 {
+                level[k] = level[k] + (level[k + 1] - level[k]) * t;
+            }
+            level.pop();
+        }
+        points.push(level[0]);
+    }
+}
+
+/// Resample a polyline to `count` points evenly spaced along its length.
+///
+/// Open polylines keep their end points; closed ones start at the first point and
+/// space the last point one step short of it.
+pub fn resample(points: &[(Real, Real)], count: usize, closed: bool) -> Vec<(Real, Real)> {
+    assert!(!points.is_empty(), "Cannot resample an empty polyline");
+    assert!(count >= 2, "Resampling needs at least 2 points");
+    let points: Vec<Point2<Real>> = points.iter().map(|&(x, y)| Point2::new(x, y)).collect();
+    crate::generators::resample(&points, count, closed)
+        .iter()
+        .map(|p| (p.x, p.y))
+        .collect()
+}
+
+/// Length of a polyline, including the closing edge when `closed`.
+pub fn length(points: &[(Real, Real)], closed: bool) -> Real {
+    let mut total: Real = points
+        .windows(2)
+        .map(|w| (w[1].0 - w[0].0).hypot(w[1].1 - w[0].1))
+        .sum();
+    if let (true, Some(first), Some(last)) = (closed, points.first(), points.last()) {
+        total += (first.0 - last.0).hypot(first.1 - last.1);
+    }
+    total
+}
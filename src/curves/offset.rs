@@ -0,0 +1,187 @@
+//! Offsetting closed outlines outwards or inwards.
+
+use crate::generators::distinct_points;
+use crate::triangulate::signed_area;
+use crate::Real;
+use nalgebra::{Point2, Vector2};
+
+/// How [`offset`] fills the gap where an outline turns away from the offset side.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Join {
+    /// Extend both edges to a sharp corner, cutting it off like `Bevel` where the
+    /// corner would reach further than the limit times the distance.
+    Miter(Real),
+    /// Round the corner with an arc, split at least every 10 degrees.
+    Round,
+    /// Cut the corner off straight.
+    Bevel,
+}
+
+/// Offset a closed outline by `distance`, growing it when positive and shrinking it
+/// when negative whichever way it winds.
+///
+/// Corners facing the offset are joined by `join`; the others meet where the
+/// offset edges cross. Where the offset crosses itself it is split into separate
+/// contours, wound the same way as the outline, and the loops that turn inside out
+/// are dropped: shrinking a narrow waist leaves two contours, and shrinking by half
+/// the width or more leaves none. Pockets that grow shut are filled.
+pub fn offset(outline: &[(Real, Real)], distance: Real, join: Join) -> Vec<Vec<(Real, Real)>> {
+    if let Join::Miter(limit) = join {
+        assert!(limit >= 1.0, "Miter limit must be at least 1");
+    }
+    let mut points = distinct_points(outline);
+    assert!(
+        points.len() >= 3,
+        "Offset outline must have at least 3 distinct points"
+    );
+    let clockwise = signed_area(&points) < 0.0;
+    if clockwise {
+        points.reverse();
+    }
+
+    // Counter-clockwise, the right-hand normal of each edge points outwards
+    let n = points.len();
+    let normal = |k: usize| {
+        let edge = (points[(k + 1) % n] - points[k]).normalize();
+        Vector2::new(edge.y, -edge.x)
+    };
+    let mut result: Vec<Point2<Real>> = Vec::with_capacity(n * 2);
+    for (k, &corner) in points.iter().enumerate() {
+        let (before, after) = (normal((k + n - 1) % n), normal(k));
+        let cos = before.dot(&after);
+        let turn = before.perp(&after);
+        if cos > 1.0 - 1e-6 {
+            result.push(corner + before * distance);
+            continue;
+        }
+        if cos < -1.0 + 1e-6 {
+            // The outline doubles back on itself
+            result.push(corner + before * distance);
+            result.push(corner + after * distance);
+            continue;
+        }
+
+        let miter = corner + (before + after) * (distance / (1.0 + cos));
+        if turn * distance <= 0.0 {
+            // The offset edges cross before reaching the corner
+            result.push(miter);
+            continue;
+        }
+        match join {
+            Join::Miter(limit) if (2.0 / (1.0 + cos)).sqrt() <= limit => result.push(miter),
+            Join::Miter(_) | Join::Bevel => {
+                result.push(corner + before * distance);
+                result.push(corner + after * distance);
+            }
+            Join::Round => {
+                let angle = turn.atan2(cos);
+                let steps = (angle.abs() / (10.0 as Real).to_radians()).ceil() as usize;
+                for i in 0..=steps {
+                    let (sin, cos) = (angle * i as Real / steps as Real).sin_cos();
+                    let direction = Vector2::new(
+                        before.x * cos - before.y * sin,
+                        before.x * sin + before.y * cos,
+                    );
+                    result.push(corner + direction * distance);
+                }
+            }
+        }
+    }
+
+    // Keep the loops that run the outline's way and stay the full distance from it
+    let mut contours = Vec::new();
+    for mut contour in split_loops(result) {
+        let reaches = contour.iter().any(|p| {
+            (0..n)
+                .map(|k| segment_distance(p, &points[k], &points[(k + 1) % n]))
+                .fold(Real::INFINITY, Real::min)
+                >= distance.abs() * (1.0 - 1e-3)
+        });
+        if contour.len() < 3 || signed_area(&contour) <= 0.0 || !reaches {
+            continue;
+        }
+        if clockwise {
+            contour.reverse();
+        }
+        contours.push(contour.iter().map(|p| (p.x, p.y)).collect());
+    }
+    contours
+}
+
+/// Split a closed polygon into loops at the points where it crosses itself.
+fn split_loops(polygon: Vec<Point2<Real>>) -> Vec<Vec<Point2<Real>>> {
+    let mut pending = vec![polygon];
+    let mut loops = Vec::new();
+    'next: while let Some(polygon) = pending.pop() {
+        let n = polygon.len();
+        for i in 0..n {
+            // Edges next to each other only meet at their shared corner
+            for j in i + 2..n {
+                if i == 0 && j == n - 1 {
+                    continue;
+                }
+                let (a, b) = (polygon[i], polygon[(i + 1) % n]);
+                let (c, d) = (polygon[j], polygon[(j + 1) % n]);
+                if let Some(crossing) = crossing(a, b, c, d) {
+                    let mut inner = vec![crossing];
+                    inner.extend_from_slice(&polygon[i + 1..=j]);
+                    let mut outer = polygon[..=i].to_vec();
+                    outer.push(crossing);
+                    outer.extend_from_slice(&polygon[j + 1..]);
+                    pending.push(inner);
+                    pending.push(outer);
+                    continue 'next;
+                }
+            }
+        }
+        loops.push(polygon);
+    }
+
+    // Splitting where the polygon touches itself repeats the point touched
+    for polygon in &mut loops {
+        polygon.dedup();
+        while polygon.len() > 1 && polygon.first() == polygon.last() {
+            polygon.pop();
+        }
+    }
+    loops
+}
+
+/// The point where segments `ab` and `cd` meet, if they do, snapped to an end of
+/// either that it touches.
+fn crossing(
+    a: Point2<Real>,
+    b: Point2<Real>,
+    c: Point2<Real>,
+    d: Point2<Real>,
+) -> Option<Point2<Real>> {
+    let (r, s) = (b - a, d - c);
+    let denominator = r.perp(&s);
+    if denominator.abs() <= Real::EPSILON * r.norm() * s.norm() {
+        return None;
+    }
+    let t = (c - a).perp(&s) / denominator;
+    let u = (c - a).perp(&r) / denominator;
+    let snap = 1e-6;
+    if !(-snap..=1.0 + snap).contains(&t) || !(-snap..=1.0 + snap).contains(&u) {
+        return None;
+    }
+    Some(if t.abs() <= snap {
+        a
+    } else if (t - 1.0).abs() <= snap {
+        b
+    } else if u.abs() <= snap {
+        c
+    } else if (u - 1.0).abs() <= snap {
+        d
+    } else {
+        a + r * t
+    })
+}
+
+/// Distance from `p` to the segment `ab`.
+fn segment_distance(p: &Point2<Real>, a: &Point2<Real>, b: &Point2<Real>) -> Real {
+    let edge = b - a;
+    let t = ((p - a).dot(&edge) / edge.norm_squared()).clamp(0.0, 1.0);
+    (p - (a + edge * t)).norm()
+}
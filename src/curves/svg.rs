@@ -0,0 +1,230 @@
+//! Parsing of SVG path data, the `d` attribute of `<path>` elements.
+
+use super::Path;
+use crate::{Error, Real, Result};
+use nalgebra::{Point2, Vector2};
+
+/// Parse SVG path data into one path per subpath.
+///
+/// Every command is supported, absolute and relative, including implicit repeats
+/// and the shorthand curves that reflect the previous control point. Coordinates
+/// are taken as they are, with Y pointing down; flip them with
+/// [`Path::scale`]`(1.0, -1.0)` for Y up. Subpaths with nothing drawn after their
+/// moveto are dropped.
+pub fn parse_svg_path(data: &str) -> Result<Vec<Path>> {
+    let mut lexer = Lexer {
+        bytes: data.as_bytes(),
+        position: 0,
+    };
+    let mut paths = Vec::new();
+    let mut path: Option<Path> = None;
+    let mut current = Point2::origin();
+    let mut start = Point2::origin();
+    // The control point a following `S` or `T` reflects, and whether it was cubic
+    let mut control: Option<(Point2<Real>, bool)> = None;
+    let mut command: Option<u8> = None;
+
+    while lexer.skip_separators() {
+        let letter = match lexer.peek() {
+            Some(c) if c.is_ascii_alphabetic() => {
+                lexer.position += 1;
+                c
+            }
+            _ => match command {
+                // Coordinates after a moveto are implicit linetos
+                Some(b'M') => b'L',
+                Some(b'm') => b'l',
+                Some(c) if !c.eq_ignore_ascii_case(&b'z') => c,
+                _ => return Err(lexer.error("expected a command")),
+            },
+        };
+        if command.is_none() && !letter.eq_ignore_ascii_case(&b'm') {
+            return Err(lexer.error("path data must start with a moveto"));
+        }
+        command = Some(letter);
+
+        let relative = letter.is_ascii_lowercase();
+        let origin = if relative {
+            current.coords
+        } else {
+            Vector2::zeros()
+        };
+        let point = |lexer: &mut Lexer| -> Result<Point2<Real>> {
+            Ok(Point2::new(lexer.number()?, lexer.number()?) + origin)
+        };
+
+        let upper = letter.to_ascii_uppercase();
+        if upper == b'M' {
+            current = point(&mut lexer)?;
+            start = current;
+            paths.extend(path.take().filter(|p| !p.is_empty()));
+            path = Some(Path::new(current.x, current.y));
+            control = None;
+            continue;
+        }
+        if upper == b'Z' {
+            paths.extend(path.take().filter(|p| !p.is_empty()).map(Path::close));
+            current = start;
+            control = None;
+            continue;
+        }
+
+        // Drawing straight after a closepath starts a new subpath where it began
+        let mut building = path
+            .take()
+            .unwrap_or_else(|| Path::new(current.x, current.y));
+        let mut next_control = None;
+        let end = match upper {
+            b'L' => {
+                let end = point(&mut lexer)?;
+                building = building.line_to(end.x, end.y);
+                end
+            }
+            b'H' => {
+                let end = Point2::new(lexer.number()? + origin.x, current.y);
+                building = building.line_to(end.x, end.y);
+                end
+            }
+            b'V' => {
+                let end = Point2::new(current.x, lexer.number()? + origin.y);
+                building = building.line_to(end.x, end.y);
+                end
+            }
+            b'C' | b'S' => {
+                let first = if upper == b'C' {
+                    point(&mut lexer)?
+                } else {
+                    reflect(control, current, true)
+                };
+                let second = point(&mut lexer)?;
+                let end = point(&mut lexer)?;
+                building = building.cubic_to(first.x, first.y, second.x, second.y, end.x, end.y);
+                next_control = Some((second, true));
+                end
+            }
+            b'Q' | b'T' => {
+                let c = if upper == b'Q' {
+                    point(&mut lexer)?
+                } else {
+                    reflect(control, current, false)
+                };
+                let end = point(&mut lexer)?;
+                building = building.quadratic_to(c.x, c.y, end.x, end.y);
+                next_control = Some((c, false));
+                end
+            }
+            b'A' => {
+                let (rx, ry, rotation) = (lexer.number()?, lexer.number()?, lexer.number()?);
+                let (large_arc, sweep) = (lexer.flag()?, lexer.flag()?);
+                let end = point(&mut lexer)?;
+                building =
+                    building.elliptical_arc_to(rx, ry, rotation, large_arc, sweep, end.x, end.y);
+                end
+            }
+            _ => {
+                return Err(lexer.error(&format!("unknown command '{}'", letter as char)));
+            }
+        };
+        path = Some(building);
+        current = end;
+        control = next_control;
+    }
+
+    paths.extend(path.filter(|p| !p.is_empty()));
+    Ok(paths)
+}
+
+/// The first control point of a shorthand curve: the previous control point of the
+/// same kind mirrored through the current point, or the current point itself.
+fn reflect(
+    control: Option<(Point2<Real>, bool)>,
+    current: Point2<Real>,
+    cubic: bool,
+) -> Point2<Real> {
+    match control {
+        Some((c, kind)) if kind == cubic => current + (current - c),
+        _ => current,
+    }
+}
+
+/// Reads numbers and flags from path data.
+struct Lexer<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Lexer<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.position).copied()
+    }
+
+    /// Skip whitespace and commas, returning whether anything is left.
+    fn skip_separators(&mut self) -> bool {
+        while matches!(self.peek(), Some(c) if c.is_ascii_whitespace() || c == b',') {
+            self.position += 1;
+        }
+        self.position < self.bytes.len()
+    }
+
+    fn error(&self, message: &str) -> Error {
+        Error::ImportError(format!(
+            "Invalid SVG path data at byte {}: {}",
+            self.position, message
+        ))
+    }
+
+    /// Read a number, which may run straight on from the previous one as in
+    /// `1.5.5` or `1-2`.
+    fn number(&mut self) -> Result<Real> {
+        self.skip_separators();
+        let begin = self.position;
+        if matches!(self.peek(), Some(b'+' | b'-')) {
+            self.position += 1;
+        }
+        let digits = |lexer: &mut Self| {
+            let from = lexer.position;
+            while matches!(lexer.peek(), Some(c) if c.is_ascii_digit()) {
+                lexer.position += 1;
+            }
+            lexer.position > from
+        };
+        let mut any = digits(self);
+        if self.peek() == Some(b'.') {
+            self.position += 1;
+            any |= digits(self);
+        }
+        if !any {
+            self.position = begin;
+            return Err(self.error("expected a number"));
+        }
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            let mantissa_end = self.position;
+            self.position += 1;
+            if matches!(self.peek(), Some(b'+' | b'-')) {
+                self.position += 1;
+            }
+            if !digits(self) {
+                self.position = mantissa_end;
+            }
+        }
+        let text = std::str::from_utf8(&self.bytes[begin..self.position]).unwrap();
+        text.parse()
+            .map_err(|_| self.error(&format!("bad number '{}'", text)))
+    }
+
+    /// Read an arc flag, a single `0` or `1` that needs no separator after it.
+    fn flag(&mut self) -> Result<bool> {
+        self.skip_separators();
+        match self.peek() {
+            Some(b'0') => {
+                self.position += 1;
+                Ok(false)
+            }
+            Some(b'1') => {
+                self.position += 1;
+                Ok(true)
+            }
+            _ => Err(self.error("expected an arc flag")),
+        }
+    }
+}
@@ -0,0 +1,508 @@
+//! Approximate convex decomposition of meshes into colliders.
+//!
+//! Physics engines collide convex shapes quickly but concave ones slowly or not at
+//! all, so concave models are usually given a set of convex hulls that together
+//! cover them. [`ConvexDecomposition`] finds such a set in the manner of V-HACD:
+//! the mesh is voxelized, and the part whose hull encloses the most empty space is
+//! cut in two by the axis-aligned plane that leaves the least, until every hull is
+//! close enough to its part. The hulls that together waste the least space are then
+//! merged until they fit the hull budget.
+//!
+//! The hulls come out as models named `collider_0`, `collider_1` and so on, ready
+//! for [`export_gltf_nodes`](crate::exporters::gltf::export_gltf_nodes) or
+//! [`export_obj_groups`](crate::exporters::obj::export_obj_groups), which write
+//! each one as a separate node or group.
+
+use crate::bounds::Aabb;
+use crate::hull;
+use crate::parallel;
+use crate::triangulate::triangulate;
+use crate::voxel::VoxelGrid;
+use crate::{Error, Mesh, Model, Real, Result};
+use nalgebra::{Point2, Point3, Vector3};
+
+/// Weight of the volume difference between the two sides of a cut in its cost.
+///
+/// Cuts that leave much the same concavity are common, as when the first cut of a
+/// ring leaves two ring halves with the hole between them. Preferring even cuts
+/// among those, as V-HACD does, keeps the parts from being whittled down in slices.
+const BALANCE: Real = 0.05;
+
+/// Builder that splits a mesh into convex hulls.
+///
+/// The concavity tolerance is how much empty space a hull may enclose beyond its
+/// part of the mesh, as a fraction of the mesh's volume. The resolution is the
+/// number of voxels along the longest side of the mesh's bounding box; finer grids
+/// place cuts more precisely and measure volumes better, at some cost in time.
+#[derive(Debug, Clone)]
+pub struct ConvexDecomposition {
+    concavity: Real,
+    max_hulls: usize,
+    resolution: usize,
+}
+
+impl Default for ConvexDecomposition {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConvexDecomposition {
+    /// Create a decomposition with a concavity tolerance of 0.02, at most 16 hulls and
+    /// 32 voxels along the longest side.
+    pub fn new() -> Self {
+        Self {
+            concavity: 0.02,
+            max_hulls: 16,
+            resolution: 32,
+        }
+    }
+
+    /// Set the empty space a hull may enclose, as a fraction of the mesh's volume.
+    pub fn concavity(mut self, concavity: Real) -> Self {
+        assert!(concavity >= 0.0, "Concavity tolerance must not be negative");
+        self.concavity = concavity;
+        self
+    }
+
+    /// Set the largest number of hulls to produce.
+    pub fn max_hulls(mut self, max_hulls: usize) -> Self {
+        assert!(max_hulls >= 1, "Convex decomposition needs at least 1 hull");
+        self.max_hulls = max_hulls;
+        self
+    }
+
+    /// Set the number of voxels along the longest side of the mesh's bounding box.
+    pub fn resolution(mut self, resolution: usize) -> Self {
+        assert!(
+            resolution >= 1,
+            "Convex decomposition needs at least 1 voxel"
+        );
+        self.resolution = resolution;
+        self
+    }
+
+    /// Split `mesh` into convex hulls, named `collider_0` onwards.
+    ///
+    /// Hull vertices come from the mesh surface and the cuts between parts, so the
+    /// hulls hug the mesh rather than its voxels. The mesh should be closed, or its
+    /// inside and the volumes measuring concavity come out wrong.
+    pub fn decompose(&self, mesh: &Mesh) -> Result<Vec<Model>> {
+        let polygons: Vec<Vec<Point3<Real>>> = mesh
+            .faces
+            .iter()
+            .filter(|face| face.indices.len() >= 3)
+            .flat_map(|face| {
+                let polygon: Vec<Point3<Real>> = face
+                    .indices
+                    .iter()
+                    .map(|&i| mesh.vertices[i].position)
+                    .collect();
+                triangles(&polygon)
+            })
+            .collect();
+        let invalid =
+            || Error::InvalidModelData("Convex decomposition needs a mesh with volume".to_string());
+        let Aabb { min, max } = Aabb::from_points(polygons.iter().flatten()).ok_or_else(invalid)?;
+        let extent = (max - min).max();
+        if extent <= 0.0 {
+            return Err(invalid());
+        }
+
+        let voxel_size = extent / self.resolution as Real;
+        let grid = VoxelGrid::from_mesh(mesh, voxel_size, 1);
+        let voxels: Vec<[usize; 3]> = grid.voxels().into_iter().map(|(v, _)| v).collect();
+        let space = Space {
+            grid,
+            min,
+            voxel_size,
+        };
+
+        if hull::quickhull(&polygons.concat()).is_none() {
+            return Err(invalid());
+        }
+        let whole = Part::new(&space, polygons, voxels, Vec::new());
+        let total = whole.volume;
+        let concavity = |volume: Real, hull_volume: Real| (hull_volume - volume).max(0.0) / total;
+
+        // Cut the worst part again and again, up to twice the hull budget, since some
+        // shapes like rings only get better after several cuts. Parts that no plane
+        // can cut are left as they are.
+        let mut parts = vec![(whole, true)];
+        while parts.len() < 2 * self.max_hulls {
+            let worst = parts
+                .iter()
+                .enumerate()
+                .filter(|(_, (part, splittable))| {
+                    *splittable && concavity(part.volume, part.hull.volume) > self.concavity
+                })
+                .max_by(|(_, (a, _)), (_, (b, _))| {
+                    let a = concavity(a.volume, a.hull.volume);
+                    a.total_cmp(&concavity(b.volume, b.hull.volume))
+                })
+                .map(|(i, _)| i);
+            let Some(worst) = worst else {
+                break;
+            };
+
+            let part = &parts[worst].0;
+            let cuts = part.cuts();
+            let halves = parallel::map_indices(cuts.len(), |i| {
+                let (axis, index) = cuts[i];
+                let (below, above) = part.split(&space, axis, index);
+                let balance = (below.volume - above.volume).abs() / total;
+                let cost = concavity(below.volume, below.hull.volume)
+                    + concavity(above.volume, above.hull.volume)
+                    + BALANCE * balance;
+                (cost, (below, above))
+            });
+            match halves.into_iter().min_by(|(a, _), (b, _)| a.total_cmp(b)) {
+                Some((_, (below, above))) => {
+                    parts[worst] = (below, true);
+                    parts.push((above, true));
+                }
+                None => parts[worst].1 = false,
+            }
+        }
+
+        // Then merge the pair of hulls that adds the least empty space, while there
+        // are too many hulls or the merged hull is still within the tolerance
+        let mut clusters: Vec<Option<Cluster>> = parts
+            .iter()
+            .enumerate()
+            .map(|(i, (part, _))| {
+                Some(Cluster {
+                    parts: vec![i],
+                    volume: part.volume,
+                    hull: part.hull.clone(),
+                })
+            })
+            .collect();
+        let merge = |a: &Cluster, b: &Cluster| Cluster {
+            parts: [a.parts.as_slice(), &b.parts].concat(),
+            volume: a.volume + b.volume,
+            hull: VoxelHull::new([a.hull.points.as_slice(), &b.hull.points].concat()),
+        };
+        let pairs: Vec<(usize, usize)> = (0..clusters.len())
+            .flat_map(|j| (0..j).map(move |i| (i, j)))
+            .collect();
+        let mut merges: Vec<(usize, usize, Cluster)> = parallel::map_indices(pairs.len(), |k| {
+            let (i, j) = pairs[k];
+            let merged = merge(clusters[i].as_ref().unwrap(), clusters[j].as_ref().unwrap());
+            (i, j, merged)
+        });
+        let mut count = clusters.len();
+        while count > 1 {
+            let added = |&(i, j, ref merged): &(usize, usize, Cluster)| {
+                let (a, b) = (clusters[i].as_ref().unwrap(), clusters[j].as_ref().unwrap());
+                merged.hull.volume - a.hull.volume - b.hull.volume
+            };
+            let best = (0..merges.len())
+                .filter(|&k| {
+                    let merged = &merges[k].2;
+                    count > self.max_hulls
+                        || concavity(merged.volume, merged.hull.volume) <= self.concavity
+                })
+                .min_by(|&a, &b| added(&merges[a]).total_cmp(&added(&merges[b])));
+            let Some(best) = best else {
+                break;
+            };
+
+            let (i, j, merged) = merges.swap_remove(best);
+            clusters[i] = None;
+            clusters[j] = None;
+            merges.retain(|&(a, b, _)| a != i && a != j && b != i && b != j);
+            let alive: Vec<usize> = (0..clusters.len())
+                .filter(|&k| clusters[k].is_some())
+                .collect();
+            let new = clusters.len();
+            merges.extend(parallel::map_indices(alive.len(), |k| {
+                (
+                    alive[k],
+                    new,
+                    merge(clusters[alive[k]].as_ref().unwrap(), &merged),
+                )
+            }));
+            clusters.push(Some(merged));
+            count -= 1;
+        }
+
+        Ok(clusters
+            .iter()
+            .flatten()
+            .filter_map(|cluster| {
+                let points: Vec<Point3<Real>> = cluster
+                    .parts
+                    .iter()
+                    .flat_map(|&i| parts[i].0.points())
+                    .collect();
+                hull::hull_model("collider", &points)
+            })
+            .enumerate()
+            .map(|(i, mut model)| {
+                model.name = format!("collider_{}", i);
+                model
+            })
+            .collect())
+    }
+}
+
+/// The convex hull of some voxel corners, kept as its vertices and volume.
+#[derive(Clone)]
+struct VoxelHull {
+    points: Vec<Point3<Real>>,
+    volume: Real,
+}
+
+impl VoxelHull {
+    fn new(points: Vec<Point3<Real>>) -> Self {
+        match hull::quickhull(&points) {
+            Some(triangles) => {
+                let mut vertices: Vec<usize> = triangles.iter().flatten().copied().collect();
+                vertices.sort_unstable();
+                vertices.dedup();
+                Self {
+                    volume: hull::volume(&points, &triangles),
+                    points: vertices.into_iter().map(|i| points[i]).collect(),
+                }
+            }
+            None => Self {
+                points,
+                volume: 0.0,
+            },
+        }
+    }
+}
+
+/// Parts merged into one hull.
+struct Cluster {
+    parts: Vec<usize>,
+    volume: Real,
+    hull: VoxelHull,
+}
+
+/// The voxelized mesh and the placement of its voxels.
+struct Space {
+    grid: VoxelGrid,
+    min: Point3<Real>,
+    voxel_size: Real,
+}
+
+impl Space {
+    fn center(&self, voxel: [usize; 3]) -> Point3<Real> {
+        self.min + Vector3::from(voxel.map(|i| i as Real + 0.5)) * self.voxel_size
+    }
+
+    /// The lowest corner of a voxel, or of the grid of voxel corners.
+    fn corner(&self, corner: [usize; 3]) -> Point3<Real> {
+        self.min + Vector3::from(corner.map(|i| i as Real)) * self.voxel_size
+    }
+
+    /// Whether a voxel and its six neighbours are all filled, so it lies inside the
+    /// mesh rather than on its surface.
+    fn is_interior(&self, [x, y, z]: [usize; 3]) -> bool {
+        let filled = |x: usize, y: usize, z: usize| self.grid.get(x, y, z) != 0;
+        x > 0
+            && y > 0
+            && z > 0
+            && filled(x - 1, y, z)
+            && filled(x + 1, y, z)
+            && filled(x, y - 1, z)
+            && filled(x, y + 1, z)
+            && filled(x, y, z - 1)
+            && filled(x, y, z + 1)
+    }
+}
+
+/// A piece of the mesh between cutting planes, with its hull.
+struct Part {
+    /// The mesh faces clipped to the piece.
+    polygons: Vec<Vec<Point3<Real>>>,
+    /// The voxels of the piece.
+    voxels: Vec<[usize; 3]>,
+    /// Points inside the mesh on the cuts bounding the piece.
+    seam: Vec<Point3<Real>>,
+    /// Volume of the voxels.
+    volume: Real,
+    /// Hull of the voxels.
+    hull: VoxelHull,
+}
+
+impl Part {
+    /// A piece with its volumes measured on the voxels, so a piece that is a convex
+    /// block of voxels has no concavity at all.
+    fn new(
+        space: &Space,
+        polygons: Vec<Vec<Point3<Real>>>,
+        voxels: Vec<[usize; 3]>,
+        seam: Vec<Point3<Real>>,
+    ) -> Self {
+        // Voxels on the outside of the piece, whose corners span the hull
+        let (mut low, mut high) = ([usize::MAX; 3], [0; 3]);
+        for voxel in &voxels {
+            for axis in 0..3 {
+                low[axis] = low[axis].min(voxel[axis]);
+                high[axis] = high[axis].max(voxel[axis]);
+            }
+        }
+        let mut corners: Vec<[usize; 3]> = voxels
+            .iter()
+            .filter(|&&v| {
+                !space.is_interior(v) || (0..3).any(|a| v[a] == low[a] || v[a] == high[a])
+            })
+            .flat_map(|&[x, y, z]| {
+                (0..8).map(move |k| [x + (k & 1), y + (k >> 1 & 1), z + (k >> 2)])
+            })
+            .collect();
+        corners.sort_unstable();
+        corners.dedup();
+        let corners: Vec<Point3<Real>> = corners.into_iter().map(|c| space.corner(c)).collect();
+
+        Self {
+            volume: voxels.len() as Real * space.voxel_size.powi(3),
+            polygons,
+            voxels,
+            seam,
+            hull: VoxelHull::new(corners),
+        }
+    }
+
+    /// The points the piece's collider is the hull of, without repeats.
+    fn points(&self) -> Vec<Point3<Real>> {
+        let mut points: Vec<Point3<Real>> = self
+            .polygons
+            .iter()
+            .flatten()
+            .chain(&self.seam)
+            .copied()
+            .collect();
+        points.sort_by(|a, b| {
+            (a.x.total_cmp(&b.x))
+                .then(a.y.total_cmp(&b.y))
+                .then(a.z.total_cmp(&b.z))
+        });
+        points.dedup();
+        points
+    }
+
+    /// Candidate cuts as an axis and the index of the first voxel above the plane,
+    /// up to eight evenly spaced along each axis.
+    fn cuts(&self) -> Vec<(usize, usize)> {
+        let mut cuts = Vec::new();
+        for axis in 0..3 {
+            let indices = self.voxels.iter().map(|v| v[axis]);
+            let (Some(low), Some(high)) = (indices.clone().min(), indices.max()) else {
+                continue;
+            };
+            let step = ((high - low) / 8).max(1);
+            cuts.extend((low + step..=high).step_by(step).map(|index| (axis, index)));
+        }
+        cuts
+    }
+
+    /// The pieces below and above the plane between voxel `index - 1` and `index`
+    /// along `axis`.
+    fn split(&self, space: &Space, axis: usize, index: usize) -> (Part, Part) {
+        let position = space.min[axis] + index as Real * space.voxel_size;
+
+        let (mut below, mut above) = (Vec::new(), Vec::new());
+        for polygon in &self.polygons {
+            let (low, high) = clip(polygon, axis, position);
+            if !low.is_empty() {
+                below.push(low);
+            }
+            if !high.is_empty() {
+                above.push(high);
+            }
+        }
+
+        let (voxels_below, voxels_above): (Vec<[usize; 3]>, Vec<[usize; 3]>) =
+            self.voxels.iter().partition(|v| v[axis] < index);
+        let (mut seam_below, mut seam_above): (Vec<Point3<Real>>, Vec<Point3<Real>>) =
+            self.seam.iter().partition(|p| p[axis] < position);
+
+        // Inside voxels next to the cut put the cross-section into both hulls
+        for &voxel in &self.voxels {
+            if (voxel[axis] + 1 == index || voxel[axis] == index) && space.is_interior(voxel) {
+                let mut point = space.center(voxel);
+                point[axis] = position;
+                if voxel[axis] < index {
+                    seam_below.push(point);
+                } else {
+                    seam_above.push(point);
+                }
+            }
+        }
+
+        (
+            Part::new(space, below, voxels_below, seam_below),
+            Part::new(space, above, voxels_above, seam_above),
+        )
+    }
+}
+
+/// The parts of a polygon below and above the plane at `position` along `axis`.
+///
+/// The polygon must be convex. One lying in the plane goes only to the side it faces
+/// away from, where the inside of the mesh is.
+fn clip(
+    polygon: &[Point3<Real>],
+    axis: usize,
+    position: Real,
+) -> (Vec<Point3<Real>>, Vec<Point3<Real>>) {
+    if polygon.iter().all(|p| p[axis] == position) {
+        let normal: Real = (1..polygon.len() - 1)
+            .map(|k| (polygon[k] - polygon[0]).cross(&(polygon[k + 1] - polygon[0]))[axis])
+            .sum();
+        return if normal > 0.0 {
+            (polygon.to_vec(), Vec::new())
+        } else {
+            (Vec::new(), polygon.to_vec())
+        };
+    }
+
+    // A polygon only touching the plane is not on its other side
+    let (mut below, mut above) = (Vec::new(), Vec::new());
+    if polygon.iter().all(|p| p[axis] <= position) {
+        return (polygon.to_vec(), above);
+    }
+    if polygon.iter().all(|p| p[axis] >= position) {
+        return (below, polygon.to_vec());
+    }
+    for (i, &a) in polygon.iter().enumerate() {
+        let b = polygon[(i + 1) % polygon.len()];
+        let (da, db) = (a[axis] - position, b[axis] - position);
+        if da <= 0.0 {
+            below.push(a);
+        }
+        if da >= 0.0 {
+            above.push(a);
+        }
+        if (da < 0.0 && db > 0.0) || (da > 0.0 && db < 0.0) {
+            let mut crossing = a + (b - a) * (da / (da - db));
+            crossing[axis] = position;
+            below.push(crossing);
+            above.push(crossing);
+        }
+    }
+    (below, above)
+}
+
+/// A face split into triangles, which stay convex however they are clipped.
+fn triangles(polygon: &[Point3<Real>]) -> Vec<Vec<Point3<Real>>> {
+    if polygon.len() == 3 {
+        return vec![polygon.to_vec()];
+    }
+
+    // Triangulate in the coordinate plane the face is most nearly parallel to
+    let normal: Vector3<Real> = (1..polygon.len() - 1)
+        .map(|k| (polygon[k] - polygon[0]).cross(&(polygon[k + 1] - polygon[0])))
+        .sum();
+    let drop = normal.iamax();
+    let (u, v) = ((drop + 1) % 3, (drop + 2) % 3);
+    let projected: Vec<Point2<Real>> = polygon.iter().map(|p| Point2::new(p[u], p[v])).collect();
+    triangulate(&projected)
+        .into_iter()
+        .map(|triangle| triangle.iter().map(|&i| polygon[i]).collect())
+        .collect()
+}
@@ -1,6 +1,8 @@
 //! glTF file format exporter.
 
-use crate::{Model, Result};
+use crate::parallel;
+use crate::{Error, Model, Result};
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
@@ -23,15 +25,42 @@ pub fn export_gltf<P: AsRef<Path>>(model: &Model, path: P) -> Result<()> {
     let bin_path = path_with_ext.with_extension("bin");
     let bin_filename = bin_path.file_name().unwrap().to_string_lossy().to_string();
 
+    // Collect the optional vertex attributes once for the JSON and binary parts
+    let optional = optional_attributes(model)?;
+
     // Export the binary buffer
-    export_binary_buffer(model, &bin_path)?;
+    export_binary_buffer(model, &optional, &bin_path)?;
 
     // Create the JSON file (.gltf)
     let json_file = File::create(path_with_ext)?;
     let mut json_writer = BufWriter::new(json_file);
 
     // Get buffer size
-    let buffer_size = calculate_buffer_size(model);
+    let buffer_size = calculate_buffer_size(model, &optional);
+    let (min, max) = position_bounds(model);
+
+    // Optional vertex attributes follow the fixed position/normal/index accessors
+    let mut attributes = String::new();
+    let mut extra_accessors = String::new();
+    let mut extra_buffer_views = String::new();
+    let mut offset = attribute_data_offset(model);
+
+    for (i, attribute) in optional.iter().enumerate() {
+        let index = i + 3;
+        let byte_length = attribute.values.len() * 4;
+
+        attributes.push_str(&format!(
+            ",\n            \"{}\": {}",
+            attribute.semantic, index
+        ));
+        extra_accessors.push_str(&accessor_json(
+            index,
+            model.mesh.vertices.len(),
+            attribute.kind,
+        ));
+        extra_buffer_views.push_str(&buffer_view_json(offset, byte_length));
+        offset += byte_length;
+    }
 
     // Write glTF JSON structure
     write!(
@@ -59,7 +88,7 @@ pub fn export_gltf<P: AsRef<Path>>(model: &Model, path: P) -> Result<()> {
         {{
           "attributes": {{
             "POSITION": 0,
-            "NORMAL": 1{}"
+            "NORMAL": 1{}
           }},
           "indices": 2,
           "mode": 4
@@ -84,7 +113,7 @@ pub fn export_gltf<P: AsRef<Path>>(model: &Model, path: P) -> Result<()> {
     }},
     {{
       "bufferView": 2,
-      "componentType": 5123,
+      "componentType": {},
       "count": {},
       "type": "SCALAR"
     }}{}
@@ -118,41 +147,26 @@ pub fn export_gltf<P: AsRef<Path>>(model: &Model, path: P) -> Result<()> {
 }}"#,
         // Node name
         model.name,
-        // Add TEXCOORD_0 to attributes if model has texture coordinates
-        if model.mesh.vertices.iter().any(|v| v.tex_coords.is_some()) {
-            ",\n            \"TEXCOORD_0\": 3"
-        } else {
-            ""
-        },
+        // Optional vertex attributes
+        attributes,
         // Position accessor
         model.mesh.vertices.len(),
         // Min bounds
-        calculate_min_x(model),
-        calculate_min_y(model),
-        calculate_min_z(model),
+        min[0],
+        min[1],
+        min[2],
         // Max bounds
-        calculate_max_x(model),
-        calculate_max_y(model),
-        calculate_max_z(model),
+        max[0],
+        max[1],
+        max[2],
         // Normal accessor count
         model.mesh.vertices.len(),
+        // Index component type
+        index_component_type(model),
         // Index accessor count
         count_indices(model),
-        // Add TEXCOORD_0 accessor if needed
-        if model.mesh.vertices.iter().any(|v| v.tex_coords.is_some()) {
-            format!(
-                r#",
-    {{
-      "bufferView": 3,
-      "componentType": 5126,
-      "count": {},
-      "type": "VEC2"
-    }}"#,
-                model.mesh.vertices.len()
-            )
-        } else {
-            String::new()
-        },
+        // Optional attribute accessors
+        extra_accessors,
         // Position buffer view
         model.mesh.vertices.len() * 12, // 3 floats * 4 bytes
         // Normal buffer view
@@ -160,23 +174,9 @@ pub fn export_gltf<P: AsRef<Path>>(model: &Model, path: P) -> Result<()> {
         model.mesh.vertices.len() * 12, // 3 floats * 4 bytes
         // Index buffer view
         model.mesh.vertices.len() * 24, // offset
-        count_indices(model) * 2,       // 1 unsigned short * 2 bytes
-        // Add TEXCOORD_0 buffer view if needed
-        if model.mesh.vertices.iter().any(|v| v.tex_coords.is_some()) {
-            format!(
-                r#",
-    {{
-      "buffer": 0,
-      "byteOffset": {},
-      "byteLength": {},
-      "target": 34962
-    }}"#,
-                model.mesh.vertices.len() * 24 + count_indices(model) * 2, // offset
-                model.mesh.vertices.len() * 8
-            ) // 2 floats * 4 bytes
-        } else {
-            String::new()
-        },
+        count_indices(model) * index_size(model),
+        // Optional attribute buffer views
+        extra_buffer_views,
         // Buffer URI
         bin_filename,
         // Buffer size
@@ -186,19 +186,212 @@ pub fn export_gltf<P: AsRef<Path>>(model: &Model, path: P) -> Result<()> {
     Ok(())
 }
 
-/// Calculate the total buffer size for the model.
-fn calculate_buffer_size(model: &Model) -> usize {
-    let position_size = model.mesh.vertices.len() * 12; // 3 floats * 4 bytes
-    let normal_size = model.mesh.vertices.len() * 12; // 3 floats * 4 bytes
-    let index_size = count_indices(model) * 2; // 1 unsigned short * 2 bytes
+/// A float vertex attribute written after the index data.
+struct AttributeData {
+    /// glTF attribute semantic (e.g. `TEXCOORD_0`, `COLOR_0`, `_TEMPERATURE`)
+    semantic: String,
+    /// glTF accessor type (e.g. `VEC2`)
+    kind: &'static str,
+    /// Flattened component values for all vertices
+    values: Vec<f32>,
+}
+
+/// Collect the optional vertex attributes present on the model, in export order.
+///
+/// Fails if a texture coordinate set or named attribute does not have one value per
+/// vertex, or if two named attributes map to the same glTF semantic, which would
+/// write a duplicate key into the JSON.
+fn optional_attributes(model: &Model) -> Result<Vec<AttributeData>> {
+    let mesh = &model.mesh;
+    let mut attributes = Vec::new();
+
+    if has_tex_coords(model) {
+        attributes.push(AttributeData {
+            semantic: "TEXCOORD_0".to_string(),
+            kind: "VEC2",
+            values: mesh
+                .vertices
+                .iter()
+                .flat_map(|v| {
+                    let (u, v) = v.tex_coords.unwrap_or((0.0, 0.0));
+                    [u, v]
+                })
+                .collect(),
+        });
+    }
+
+    if mesh.has_tangents() {
+        attributes.push(AttributeData {
+            semantic: "TANGENT".to_string(),
+            kind: "VEC4",
+            values: mesh
+                .tangents
+                .iter()
+                .flat_map(|t| {
+                    let t = t.cast::<f32>();
+                    [t.x, t.y, t.z, t.w]
+                })
+                .collect(),
+        });
+    }
+
+    for (i, set) in mesh.tex_coord_sets.iter().enumerate() {
+        if set.len() != mesh.vertices.len() {
+            return Err(Error::ExportError(format!(
+                "Texture coordinate set {} has {} entries but the mesh has {} vertices",
+                i + 1,
+                set.len(),
+                mesh.vertices.len()
+            )));
+        }
+        attributes.push(AttributeData {
+            semantic: format!("TEXCOORD_{}", i + 1),
+            kind: "VEC2",
+            values: set.iter().flat_map(|&(u, v)| [u, v]).collect(),
+        });
+    }
+
+    if mesh.has_colors() {
+        attributes.push(AttributeData {
+            semantic: "COLOR_0".to_string(),
+            kind: "VEC4",
+            values: mesh.colors.iter().flatten().copied().collect(),
+        });
+    }
+
+    let mut semantics: HashMap<String, &str> = HashMap::new();
+    for (name, attribute) in &mesh.attributes {
+        if attribute.len() != mesh.vertices.len() {
+            return Err(Error::ExportError(format!(
+                "Attribute '{}' has {} values but the mesh has {} vertices",
+                name,
+                attribute.len(),
+                mesh.vertices.len()
+            )));
+        }
+        let semantic = custom_semantic(name);
+        if let Some(other) = semantics.insert(semantic.clone(), name) {
+            return Err(Error::ExportError(format!(
+                "Attributes '{}' and '{}' both export as glTF attribute '{}'",
+                other, name, semantic
+            )));
+        }
+        attributes.push(AttributeData {
+            semantic,
+            kind: match attribute.components() {
+                1 => "SCALAR",
+                2 => "VEC2",
+                3 => "VEC3",
+                _ => "VEC4",
+            },
+            values: (0..attribute.len())
+                .flat_map(|i| attribute.value(i).iter().copied())
+                .collect(),
+        });
+    }
+
+    Ok(attributes)
+}
+
+/// Build an application-specific glTF attribute name, e.g. "temperature" -> "_TEMPERATURE".
+fn custom_semantic(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_uppercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("_{}", sanitized)
+}
+
+/// Byte offset of the first optional attribute block in the binary buffer.
+///
+/// Positions, normals and indices come first; the index block is padded to a
+/// 4-byte boundary so the float attributes after it stay aligned.
+fn attribute_data_offset(model: &Model) -> usize {
+    align4(model.mesh.vertices.len() * 24 + count_indices(model) * index_size(model))
+}
+
+/// Check whether the indices need 32 bits.
+///
+/// glTF reserves the largest value of the index type, so 16-bit indices can only
+/// address 65535 vertices.
+fn has_wide_indices(model: &Model) -> bool {
+    model.mesh.vertices.len() > u16::MAX as usize
+}
+
+/// Size in bytes of one index.
+fn index_size(model: &Model) -> usize {
+    if has_wide_indices(model) {
+        4
+    } else {
+        2
+    }
+}
 
-    let texcoord_size = if model.mesh.vertices.iter().any(|v| v.tex_coords.is_some()) {
-        model.mesh.vertices.len() * 8 // 2 floats * 4 bytes
+/// glTF component type of the indices: unsigned int or unsigned short.
+fn index_component_type(model: &Model) -> u32 {
+    if has_wide_indices(model) {
+        5125
     } else {
-        0
-    };
+        5123
+    }
+}
+
+/// Round a byte offset up to the next multiple of four.
+fn align4(offset: usize) -> usize {
+    (offset + 3) & !3
+}
+
+/// Check whether the model should export a TEXCOORD_0 attribute.
+///
+/// Extra texture coordinate sets are numbered from `TEXCOORD_1`, and glTF needs the
+/// numbering to start at 0, so they bring in a zero base set when there is none.
+fn has_tex_coords(model: &Model) -> bool {
+    !model.mesh.tex_coord_sets.is_empty()
+        || model.mesh.vertices.iter().any(|v| v.tex_coords.is_some())
+}
+
+/// Format a float accessor entry for an optional vertex attribute.
+fn accessor_json(buffer_view: usize, count: usize, kind: &str) -> String {
+    format!(
+        r#",
+    {{
+      "bufferView": {},
+      "componentType": 5126,
+      "count": {},
+      "type": "{}"
+    }}"#,
+        buffer_view, count, kind
+    )
+}
+
+/// Format a vertex-attribute buffer view entry.
+fn buffer_view_json(offset: usize, length: usize) -> String {
+    format!(
+        r#",
+    {{
+      "buffer": 0,
+      "byteOffset": {},
+      "byteLength": {},
+      "target": 34962
+    }}"#,
+        offset, length
+    )
+}
+
+/// Calculate the total buffer size for the model.
+fn calculate_buffer_size(model: &Model, optional: &[AttributeData]) -> usize {
+    let attribute_size: usize = optional
+        .iter()
+        .map(|attribute| attribute.values.len() * 4)
+        .sum();
 
-    position_size + normal_size + index_size + texcoord_size
+    attribute_data_offset(model) + attribute_size
 }
 
 /// Count total indices in the model.
@@ -223,123 +416,254 @@ fn count_indices(model: &Model) -> usize {
     count
 }
 
-/// Find the minimum X coordinate in the model.
-fn calculate_min_x(model: &Model) -> f32 {
-    model
-        .mesh
-        .vertices
-        .iter()
-        .map(|v| v.position.x)
-        .fold(f32::INFINITY, f32::min)
-}
-
-/// Find the minimum Y coordinate in the model.
-fn calculate_min_y(model: &Model) -> f32 {
-    model
-        .mesh
-        .vertices
-        .iter()
-        .map(|v| v.position.y)
-        .fold(f32::INFINITY, f32::min)
-}
-
-/// Find the minimum Z coordinate in the model.
-fn calculate_min_z(model: &Model) -> f32 {
-    model
-        .mesh
-        .vertices
-        .iter()
-        .map(|v| v.position.z)
-        .fold(f32::INFINITY, f32::min)
-}
-
-/// Find the maximum X coordinate in the model.
-fn calculate_max_x(model: &Model) -> f32 {
-    model
-        .mesh
-        .vertices
-        .iter()
-        .map(|v| v.position.x)
-        .fold(f32::NEG_INFINITY, f32::max)
-}
-
-/// Find the maximum Y coordinate in the model.
-fn calculate_max_y(model: &Model) -> f32 {
-    model
-        .mesh
-        .vertices
-        .iter()
-        .map(|v| v.position.y)
-        .fold(f32::NEG_INFINITY, f32::max)
-}
-
-/// Find the maximum Z coordinate in the model.
-fn calculate_max_z(model: &Model) -> f32 {
-    model
-        .mesh
-        .vertices
-        .iter()
-        .map(|v| v.position.z)
-        .fold(f32::NEG_INFINITY, f32::max)
+/// The smallest and largest vertex coordinates, for the position accessor.
+fn position_bounds(model: &Model) -> ([f32; 3], [f32; 3]) {
+    model.mesh.aabb().map_or(([0.0; 3], [0.0; 3]), |aabb| {
+        let (min, max) = (aabb.min.cast::<f32>(), aabb.max.cast::<f32>());
+        ([min.x, min.y, min.z], [max.x, max.y, max.z])
+    })
 }
 
 /// Export the binary buffer for glTF.
-fn export_binary_buffer<P: AsRef<Path>>(model: &Model, path: P) -> Result<()> {
+fn export_binary_buffer<P: AsRef<Path>>(
+    model: &Model,
+    optional: &[AttributeData],
+    path: P,
+) -> Result<()> {
     let file = File::create(path)?;
     let mut writer = BufWriter::new(file);
 
     // Write vertex positions
-    for vertex in &model.mesh.vertices {
-        writer.write_all(&vertex.position.x.to_le_bytes())?;
-        writer.write_all(&vertex.position.y.to_le_bytes())?;
-        writer.write_all(&vertex.position.z.to_le_bytes())?;
-    }
+    parallel::write_chunks(&mut writer, &model.mesh.vertices, |out, _, vertex| {
+        // glTF only supports single-precision vertex data
+        let position = vertex.position.cast::<f32>();
+        out.write_all(&position.x.to_le_bytes())?;
+        out.write_all(&position.y.to_le_bytes())?;
+        out.write_all(&position.z.to_le_bytes())
+    })?;
 
     // Write vertex normals
-    for vertex in &model.mesh.vertices {
-        writer.write_all(&vertex.normal.x.to_le_bytes())?;
-        writer.write_all(&vertex.normal.y.to_le_bytes())?;
-        writer.write_all(&vertex.normal.z.to_le_bytes())?;
-    }
+    parallel::write_chunks(&mut writer, &model.mesh.vertices, |out, _, vertex| {
+        let normal = vertex.normal.cast::<f32>();
+        out.write_all(&normal.x.to_le_bytes())?;
+        out.write_all(&normal.y.to_le_bytes())?;
+        out.write_all(&normal.z.to_le_bytes())
+    })?;
 
-    // Write indices
-    let mut indices = Vec::new();
+    // Write indices, as 16 bits where they fit
+    let indices = fan_indices(model);
+    if has_wide_indices(model) {
+        for idx in &indices {
+            writer.write_all(&idx.to_le_bytes())?;
+        }
+    } else {
+        for &idx in &indices {
+            writer.write_all(&(idx as u16).to_le_bytes())?;
+        }
 
-    for face in &model.mesh.faces {
-        match face.indices.len().cmp(&3) {
-            std::cmp::Ordering::Less => {
-                continue;
-            }
-            std::cmp::Ordering::Equal => {
-                // Simple triangle
-                for &idx in &face.indices {
-                    indices.push(idx as u16);
-                }
-            }
-            std::cmp::Ordering::Greater => {
-                // Triangulate the face
-                let v0 = face.indices[0];
-                for i in 1..face.indices.len() - 1 {
-                    indices.push(v0 as u16);
-                    indices.push(face.indices[i] as u16);
-                    indices.push(face.indices[i + 1] as u16);
-                }
-            }
+        // Pad the index block so the following float data is 4-byte aligned
+        if indices.len() % 2 == 1 {
+            writer.write_all(&[0, 0])?;
         }
     }
 
-    for idx in indices {
-        writer.write_all(&idx.to_le_bytes())?;
+    // Write the optional attributes (texture coordinates, tangents, colors, ...)
+    for attribute in optional {
+        parallel::write_chunks(&mut writer, &attribute.values, |out, _, value| {
+            out.write_all(&value.to_le_bytes())
+        })?;
     }
 
-    // Write texture coordinates if any
-    if model.mesh.vertices.iter().any(|v| v.tex_coords.is_some()) {
-        for vertex in &model.mesh.vertices {
-            let (u, v) = vertex.tex_coords.unwrap_or((0.0, 0.0));
-            writer.write_all(&u.to_le_bytes())?;
-            writer.write_all(&v.to_le_bytes())?;
-        }
+    Ok(())
+}
+
+/// Export several models to one glTF file, each as a separate node and mesh named
+/// after the model.
+///
+/// Only positions, normals and triangle indices are written, which suits collision
+/// hulls and other helper geometry. Indices are 32-bit, so models may have any
+/// number of vertices.
+pub fn export_gltf_nodes<P: AsRef<Path>>(models: &[Model], path: P) -> Result<()> {
+    let path = path.as_ref();
+
+    let mut path_with_ext = PathBuf::from(path);
+    if path_with_ext.extension().is_none_or(|ext| ext != "gltf") {
+        path_with_ext.set_extension("gltf");
+    }
+    let bin_path = path_with_ext.with_extension("bin");
+    let bin_filename = bin_path.file_name().unwrap().to_string_lossy().to_string();
+
+    // Each model's positions, normals and indices follow the previous model's
+    let mut buffer = Vec::new();
+    let mut nodes = Vec::new();
+    let mut meshes = Vec::new();
+    let mut accessors = Vec::new();
+    let mut buffer_views = Vec::new();
+    for (i, model) in models.iter().enumerate() {
+        let indices = fan_indices(model);
+        let count = model.mesh.vertices.len();
+        let (min, max) = position_bounds(model);
+
+        let mut view = |data: &[u8], target: u32| {
+            buffer_views.push(format!(
+                r#"    {{
+      "buffer": 0,
+      "byteOffset": {},
+      "byteLength": {},
+      "target": {}
+    }}"#,
+                buffer.len(),
+                data.len(),
+                target
+            ));
+            buffer.extend_from_slice(data);
+        };
+        let positions: Vec<u8> = model
+            .mesh
+            .vertices
+            .iter()
+            .flat_map(|v| {
+                let p = v.position.cast::<f32>();
+                [p.x, p.y, p.z]
+            })
+            .flat_map(f32::to_le_bytes)
+            .collect();
+        let normals: Vec<u8> = model
+            .mesh
+            .vertices
+            .iter()
+            .flat_map(|v| {
+                let n = v.normal.cast::<f32>();
+                [n.x, n.y, n.z]
+            })
+            .flat_map(f32::to_le_bytes)
+            .collect();
+        let index_data: Vec<u8> = indices.iter().flat_map(|i| i.to_le_bytes()).collect();
+        view(&positions, 34962);
+        view(&normals, 34962);
+        view(&index_data, 34963);
+
+        accessors.push(format!(
+            r#"    {{
+      "bufferView": {},
+      "componentType": 5126,
+      "count": {},
+      "type": "VEC3",
+      "min": [{}, {}, {}],
+      "max": [{}, {}, {}]
+    }}"#,
+            3 * i,
+            count,
+            min[0],
+            min[1],
+            min[2],
+            max[0],
+            max[1],
+            max[2]
+        ));
+        accessors.push(format!(
+            r#"    {{
+      "bufferView": {},
+      "componentType": 5126,
+      "count": {},
+      "type": "VEC3"
+    }}"#,
+            3 * i + 1,
+            count
+        ));
+        accessors.push(format!(
+            r#"    {{
+      "bufferView": {},
+      "componentType": 5125,
+      "count": {},
+      "type": "SCALAR"
+    }}"#,
+            3 * i + 2,
+            indices.len()
+        ));
+        meshes.push(format!(
+            r#"    {{
+      "primitives": [
+        {{
+          "attributes": {{
+            "POSITION": {},
+            "NORMAL": {}
+          }},
+          "indices": {},
+          "mode": 4
+        }}
+      ]
+    }}"#,
+            3 * i,
+            3 * i + 1,
+            3 * i + 2
+        ));
+        nodes.push(format!(
+            r#"    {{
+      "mesh": {},
+      "name": "{}"
+    }}"#,
+            i, model.name
+        ));
     }
+    std::fs::write(&bin_path, &buffer)?;
+
+    let node_indices: Vec<String> = (0..models.len()).map(|i| i.to_string()).collect();
+    let json_file = File::create(path_with_ext)?;
+    let mut json_writer = BufWriter::new(json_file);
+    write!(
+        json_writer,
+        r#"{{
+  "asset": {{
+    "version": "2.0",
+    "generator": "model-generator"
+  }},
+  "scene": 0,
+  "scenes": [
+    {{
+      "nodes": [{}]
+    }}
+  ],
+  "nodes": [
+{}
+  ],
+  "meshes": [
+{}
+  ],
+  "accessors": [
+{}
+  ],
+  "bufferViews": [
+{}
+  ],
+  "buffers": [
+    {{
+      "uri": "{}",
+      "byteLength": {}
+    }}
+  ]
+}}"#,
+        node_indices.join(", "),
+        nodes.join(",\n"),
+        meshes.join(",\n"),
+        accessors.join(",\n"),
+        buffer_views.join(",\n"),
+        bin_filename,
+        buffer.len()
+    )?;
 
     Ok(())
 }
+
+/// Triangle indices of the model's faces, splitting polygons into fans.
+fn fan_indices(model: &Model) -> Vec<u32> {
+    let mut indices = Vec::new();
+    for face in &model.mesh.faces {
+        for i in 1..face.indices.len().saturating_sub(1) {
+            indices.push(face.indices[0] as u32);
+            indices.push(face.indices[i] as u32);
+            indices.push(face.indices[i + 1] as u32);
+        }
+    }
+    indices
+}
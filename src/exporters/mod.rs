@@ -2,4 +2,5 @@
 
 pub mod gltf;
 pub mod obj;
+pub mod ply;
 pub mod stl;
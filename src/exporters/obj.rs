@@ -1,5 +1,6 @@
 //! OBJ file format exporter.
 
+use crate::parallel;
 use crate::{Model, Result};
 use std::fs::File;
 use std::io::{BufWriter, Write};
@@ -33,62 +34,77 @@ pub fn export_obj<P: AsRef<Path>>(model: &Model, path: P) -> Result<()> {
         export_mtl(model, path.as_ref().with_file_name(&mtl_filename))?;
     }
 
-    // Write vertex data
-    for vertex in &model.mesh.vertices {
-        writeln!(
-            writer,
-            "v {} {} {}",
-            vertex.position.x, vertex.position.y, vertex.position.z
-        )?;
-    }
+    // Write vertex data, appending RGB colors (a widely supported OBJ extension) if present
+    let has_colors = model.mesh.has_colors();
+    parallel::write_chunks(&mut writer, &model.mesh.vertices, |out, i, vertex| {
+        if has_colors {
+            let color = model.mesh.colors[i];
+            writeln!(
+                out,
+                "v {} {} {} {} {} {}",
+                vertex.position.x,
+                vertex.position.y,
+                vertex.position.z,
+                color[0],
+                color[1],
+                color[2]
+            )
+        } else {
+            writeln!(
+                out,
+                "v {} {} {}",
+                vertex.position.x, vertex.position.y, vertex.position.z
+            )
+        }
+    })?;
 
     // Write texture coordinates if any vertices have them
     let has_tex_coords = model.mesh.vertices.iter().any(|v| v.tex_coords.is_some());
     if has_tex_coords {
-        for vertex in &model.mesh.vertices {
+        parallel::write_chunks(&mut writer, &model.mesh.vertices, |out, _, vertex| {
             let (u, v) = vertex.tex_coords.unwrap_or((0.0, 0.0));
-            writeln!(writer, "vt {} {}", u, v)?;
-        }
+            writeln!(out, "vt {} {}", u, v)
+        })?;
     }
 
     // Write normals
-    for vertex in &model.mesh.vertices {
+    parallel::write_chunks(&mut writer, &model.mesh.vertices, |out, _, vertex| {
         writeln!(
-            writer,
+            out,
             "vn {} {} {}",
             vertex.normal.x, vertex.normal.y, vertex.normal.z
-        )?;
-    }
+        )
+    })?;
 
-    // Group faces by material
-    let mut current_material: Option<String> = None;
+    // Group faces by material, switching whenever a face's material differs from the previous face's
+    let material_of = |face_idx: usize| model.mesh.face_materials.get(face_idx).cloned().flatten();
 
-    for (face_idx, face) in model.mesh.faces.iter().enumerate() {
+    parallel::write_chunks(&mut writer, &model.mesh.faces, |out, face_idx, face| {
         // Check if we need to switch material
-        let face_material = model.mesh.face_materials.get(face_idx).cloned().flatten();
-        if face_material != current_material {
+        let face_material = material_of(face_idx);
+        let previous_material = face_idx.checked_sub(1).and_then(material_of);
+        if face_material != previous_material {
             if let Some(mat_name) = &face_material {
-                writeln!(writer, "usemtl {}", mat_name)?;
+                writeln!(out, "usemtl {}", mat_name)?;
             }
-            current_material = face_material;
         }
 
         // Write face with vertex/texcoord/normal indices
-        write!(writer, "f")?;
+        write!(out, "f")?;
         for &vertex_idx in &face.indices {
             // OBJ is 1-indexed
             let v_idx = vertex_idx + 1;
 
             if has_tex_coords {
                 // Format: v/vt/vn
-                write!(writer, " {}/{}/{}", v_idx, v_idx, v_idx)?;
+                write!(out, " {}/{}/{}", v_idx, v_idx, v_idx)?;
             } else {
                 // Format: v//vn
-                write!(writer, " {}//{}", v_idx, v_idx)?;
+                write!(out, " {}//{}", v_idx, v_idx)?;
             }
         }
-        writeln!(writer)?;
-    }
+        writeln!(out)
+    })?;
 
     Ok(())
 }
@@ -156,3 +172,46 @@ fn export_mtl<P: AsRef<Path>>(model: &Model, path: P) -> Result<()> {
 
     Ok(())
 }
+
+/// Export several models to one OBJ file, each as a group named after the model.
+///
+/// Only positions, normals and faces are written, which suits collision hulls and
+/// other helper geometry; materials and texture coordinates are left out.
+pub fn export_obj_groups<P: AsRef<Path>>(models: &[Model], path: P) -> Result<()> {
+    let file = File::create(path.as_ref())?;
+    let mut writer = BufWriter::new(file);
+
+    writeln!(writer, "# OBJ file generated by model-generator")?;
+    writeln!(writer, "# Groups: {}", models.len())?;
+
+    // OBJ indices count from 1 across the whole file
+    let mut first = 1;
+    for model in models {
+        writeln!(writer)?;
+        writeln!(writer, "g {}", model.name)?;
+        for vertex in &model.mesh.vertices {
+            writeln!(
+                writer,
+                "v {} {} {}",
+                vertex.position.x, vertex.position.y, vertex.position.z
+            )?;
+        }
+        for vertex in &model.mesh.vertices {
+            writeln!(
+                writer,
+                "vn {} {} {}",
+                vertex.normal.x, vertex.normal.y, vertex.normal.z
+            )?;
+        }
+        for face in &model.mesh.faces {
+            write!(writer, "f")?;
+            for &vertex_idx in &face.indices {
+                write!(writer, " {}//{}", vertex_idx + first, vertex_idx + first)?;
+            }
+            writeln!(writer)?;
+        }
+        first += model.mesh.vertices.len();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,169 @@
+//! PLY file format exporter.
+
+use crate::parallel;
+use crate::{Error, Model, Result};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Export a model to PLY format.
+///
+/// This exports the model as an ASCII PLY (Polygon File Format) file. Besides
+/// positions and normals, PLY can carry arbitrary per-vertex properties, so texture
+/// coordinates, vertex colors and the mesh's named attributes are all written out.
+/// It is read by MeshLab, CloudCompare, Blender and most point cloud tools.
+pub fn export_ply<P: AsRef<Path>>(model: &Model, path: P) -> Result<()> {
+    let mesh = &model.mesh;
+
+    let has_tex_coords = mesh.vertices.iter().any(|v| v.tex_coords.is_some());
+    let has_colors = mesh.has_colors();
+    let tex_coord_sets: Vec<_> = mesh
+        .tex_coord_sets
+        .iter()
+        .filter(|set| set.len() == mesh.vertices.len())
+        .collect();
+    let attributes: Vec<_> = mesh
+        .attributes
+        .iter()
+        .filter(|(_, attribute)| attribute.len() == mesh.vertices.len())
+        .collect();
+    let face_count = mesh.faces.iter().filter(|f| f.indices.len() >= 3).count();
+
+    // Vertex properties as (type, name), in the order values are written
+    let mut properties: Vec<(&str, String)> = ["x", "y", "z", "nx", "ny", "nz"]
+        .iter()
+        .map(|name| (GEOMETRY_TYPE, name.to_string()))
+        .collect();
+    if has_tex_coords {
+        properties.push(("float", "s".to_string()));
+        properties.push(("float", "t".to_string()));
+    }
+    for set_idx in 1..=tex_coord_sets.len() {
+        properties.push(("float", format!("s{}", set_idx)));
+        properties.push(("float", format!("t{}", set_idx)));
+    }
+    if has_colors {
+        for name in ["red", "green", "blue", "alpha"] {
+            properties.push(("uchar", name.to_string()));
+        }
+    }
+
+    // Attribute names must not clash with the properties above or with each other
+    let mut taken: HashSet<String> = properties.iter().map(|(_, name)| name.clone()).collect();
+    for (name, attribute) in &attributes {
+        let base = property_name(name);
+        let names = if attribute.components() == 1 {
+            vec![base]
+        } else {
+            ["x", "y", "z", "w"][..attribute.components()]
+                .iter()
+                .map(|suffix| format!("{}_{}", base, suffix))
+                .collect()
+        };
+        for property in names {
+            if !taken.insert(property.clone()) {
+                return Err(Error::ExportError(format!(
+                    "Attribute '{}' exports as PLY property '{}', which is already in use",
+                    name, property
+                )));
+            }
+            properties.push(("float", property));
+        }
+    }
+
+    let file = File::create(path)?;
+    let mut writer = BufWriter::new(file);
+
+    // Write header
+    writeln!(writer, "ply")?;
+    writeln!(writer, "format ascii 1.0")?;
+    writeln!(writer, "comment PLY file generated by model-generator")?;
+    writeln!(writer, "comment Model name: {}", model.name)?;
+    writeln!(writer, "element vertex {}", mesh.vertices.len())?;
+    for (kind, name) in &properties {
+        writeln!(writer, "property {} {}", kind, name)?;
+    }
+
+    writeln!(writer, "element face {}", face_count)?;
+    writeln!(writer, "property list uchar int vertex_indices")?;
+    writeln!(writer, "end_header")?;
+
+    // Write vertex data, one line per vertex
+    parallel::write_chunks(&mut writer, &mesh.vertices, |out, i, vertex| {
+        write!(
+            out,
+            "{} {} {} {} {} {}",
+            vertex.position.x,
+            vertex.position.y,
+            vertex.position.z,
+            vertex.normal.x,
+            vertex.normal.y,
+            vertex.normal.z
+        )?;
+
+        if has_tex_coords {
+            let (u, v) = vertex.tex_coords.unwrap_or((0.0, 0.0));
+            write!(out, " {} {}", u, v)?;
+        }
+
+        for set in &tex_coord_sets {
+            let (u, v) = set[i];
+            write!(out, " {} {}", u, v)?;
+        }
+
+        if has_colors {
+            for channel in mesh.colors[i] {
+                write!(out, " {}", color_to_byte(channel))?;
+            }
+        }
+
+        for (_, attribute) in &attributes {
+            for value in attribute.value(i) {
+                write!(out, " {}", value)?;
+            }
+        }
+
+        writeln!(out)
+    })?;
+
+    // Write faces; PLY supports arbitrary polygons so no triangulation is needed
+    parallel::write_chunks(&mut writer, &mesh.faces, |out, _, face| {
+        if face.indices.len() < 3 {
+            return Ok(());
+        }
+
+        write!(out, "{}", face.indices.len())?;
+        for &idx in &face.indices {
+            write!(out, " {}", idx)?;
+        }
+        writeln!(out)
+    })?;
+
+    Ok(())
+}
+
+/// PLY type of positions and normals; written at full precision with the `f64` feature.
+const GEOMETRY_TYPE: &str = if cfg!(feature = "f64") {
+    "double"
+} else {
+    "float"
+};
+
+/// Convert a color channel in [0, 1] to an 8-bit value.
+fn color_to_byte(channel: f32) -> u8 {
+    (channel.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+/// Make an attribute name safe to use as a PLY property name.
+fn property_name(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
@@ -1,6 +1,7 @@
 //! STL file format exporter.
 
-use crate::{Model, Result};
+use crate::parallel;
+use crate::{Model, Real, Result};
 use nalgebra::Vector3;
 use std::fs::File;
 use std::io::{BufWriter, Write};
@@ -25,15 +26,14 @@ fn export_ascii_stl<P: AsRef<Path>>(model: &Model, path: P) -> Result<()> {
     writeln!(writer, "solid {}", model.name)?;
 
     // Write each face as a triangle or triangulate non-triangular faces
-    for face in &model.mesh.faces {
+    parallel::write_chunks(&mut writer, &model.mesh.faces, |out, _, face| {
         match face.indices.len().cmp(&3) {
             std::cmp::Ordering::Less => {
                 // Skip degenerate faces
-                continue;
             }
             std::cmp::Ordering::Equal => {
                 // Simple triangle case
-                write_stl_triangle(&mut writer, model, &face.indices)?;
+                write_stl_triangle(out, model, &face.indices)?;
             }
             std::cmp::Ordering::Greater => {
                 // Triangulate the face using a simple fan triangulation
@@ -42,11 +42,12 @@ fn export_ascii_stl<P: AsRef<Path>>(model: &Model, path: P) -> Result<()> {
                 for i in 1..face.indices.len() - 1 {
                     let v1 = face.indices[i];
                     let v2 = face.indices[i + 1];
-                    write_stl_triangle(&mut writer, model, &[v0, v1, v2])?;
+                    write_stl_triangle(out, model, &[v0, v1, v2])?;
                 }
             }
         }
-    }
+        Ok(())
+    })?;
 
     // Write footer
     writeln!(writer, "endsolid {}", model.name)?;
@@ -55,14 +56,21 @@ fn export_ascii_stl<P: AsRef<Path>>(model: &Model, path: P) -> Result<()> {
 }
 
 /// Write a single triangle to the STL file.
-fn write_stl_triangle<W: Write>(writer: &mut W, model: &Model, indices: &[usize]) -> Result<()> {
+fn write_stl_triangle<W: Write>(
+    writer: &mut W,
+    model: &Model,
+    indices: &[usize],
+) -> std::io::Result<()> {
     // Get the vertices
     let v0 = model.mesh.vertices[indices[0]].position;
     let v1 = model.mesh.vertices[indices[1]].position;
     let v2 = model.mesh.vertices[indices[2]].position;
 
     // Calculate face normal if needed (STL requires normals)
-    let normal = calculate_normal(v0, v1, v2);
+    let normal = calculate_normal(v0, v1, v2).cast::<f32>();
+
+    // STL is a single-precision format
+    let (v0, v1, v2) = (v0.cast::<f32>(), v1.cast::<f32>(), v2.cast::<f32>());
 
     // Write the triangle
     writeln!(
@@ -82,10 +90,10 @@ fn write_stl_triangle<W: Write>(writer: &mut W, model: &Model, indices: &[usize]
 
 /// Calculate the normal for a triangle.
 fn calculate_normal(
-    v0: nalgebra::Point3<f32>,
-    v1: nalgebra::Point3<f32>,
-    v2: nalgebra::Point3<f32>,
-) -> Vector3<f32> {
+    v0: nalgebra::Point3<Real>,
+    v1: nalgebra::Point3<Real>,
+    v2: nalgebra::Point3<Real>,
+) -> Vector3<Real> {
     let edge1 = v1 - v0;
     let edge2 = v2 - v0;
     let normal = edge1.cross(&edge2);
@@ -172,7 +180,10 @@ fn write_binary_triangle<W: Write>(writer: &mut W, model: &Model, indices: &[usi
     let v2 = model.mesh.vertices[indices[2]].position;
 
     // Calculate face normal
-    let normal = calculate_normal(v0, v1, v2);
+    let normal = calculate_normal(v0, v1, v2).cast::<f32>();
+
+    // STL is a single-precision format
+    let (v0, v1, v2) = (v0.cast::<f32>(), v1.cast::<f32>(), v2.cast::<f32>());
 
     // Write normal (12 bytes)
     writer.write_all(&normal.x.to_le_bytes())?;
@@ -0,0 +1,205 @@
+//! Sets of Bezier patches, such as the Utah teapot.
+
+use super::nurbs::Patch;
+use crate::{Error, Model, Real, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Highest patch degree accepted from BPT data.
+const MAX_BPT_DEGREE: usize = 64;
+
+/// Builder that tessellates a set of Bezier patches into one model.
+///
+/// Each patch is given as rows of a grid of control points, each row running along u
+/// and the rows following each other along v; a 4 by 4 grid makes the usual bicubic
+/// patch. Patches face along the cross product of their u and v derivatives.
+///
+/// Tessellation is adaptive like [`NurbsSurface`](super::NurbsSurface), per patch.
+/// Patches that share an edge, with the same control points in either direction,
+/// are split the same way along it so the mesh has no cracks between them.
+pub struct BezierPatches {
+    patches: Vec<Vec<Vec<(Real, Real, Real)>>>,
+    tolerance: Option<Real>,
+    max_segments: usize,
+}
+
+impl BezierPatches {
+    /// Create an empty set of patches.
+    pub fn new() -> Self {
+        Self {
+            patches: Vec::new(),
+            tolerance: None,
+            max_segments: 32,
+        }
+    }
+
+    /// Load patches from a file in the BPT text format.
+    pub fn from_bpt<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_bpt_data(&std::fs::read_to_string(path)?)
+    }
+
+    /// Parse patches in the BPT text format: the number of patches, then for each
+    /// its degrees along u and v followed by its control points as `x y z`, row by
+    /// row.
+    pub fn from_bpt_data(text: &str) -> Result<Self> {
+        let invalid = |message: &str| Error::ImportError(format!("Invalid BPT data: {}", message));
+        let mut tokens = text.split_whitespace();
+        let mut next = |what: &str| {
+            tokens
+                .next()
+                .ok_or_else(|| invalid(&format!("missing {}", what)))
+        };
+        let count: usize = next("patch count")?
+            .parse()
+            .map_err(|_| invalid("bad patch count"))?;
+
+        let mut patches = Self::new();
+        for _ in 0..count {
+            let mut degree = [0usize; 2];
+            for d in &mut degree {
+                *d = next("degree")?.parse().map_err(|_| invalid("bad degree"))?;
+                if *d == 0 || *d > MAX_BPT_DEGREE {
+                    return Err(invalid(&format!(
+                        "degrees must be between 1 and {}",
+                        MAX_BPT_DEGREE
+                    )));
+                }
+            }
+            let mut control_points = Vec::with_capacity(degree[1] + 1);
+            for _ in 0..=degree[1] {
+                let mut row = Vec::with_capacity(degree[0] + 1);
+                for _ in 0..=degree[0] {
+                    let mut coordinate = || -> Result<Real> {
+                        next("control point")?
+                            .parse()
+                            .map_err(|_| invalid("bad coordinate"))
+                    };
+                    row.push((coordinate()?, coordinate()?, coordinate()?));
+                }
+                control_points.push(row);
+            }
+            patches = patches.patch(control_points);
+        }
+        Ok(patches)
+    }
+
+    /// Add a patch from its grid of control points.
+    pub fn patch(mut self, control_points: Vec<Vec<(Real, Real, Real)>>) -> Self {
+        assert!(
+            control_points.len() >= 2 && control_points[0].len() >= 2,
+            "Bezier patch needs at least 2 control points in each direction"
+        );
+        assert!(
+            control_points
+                .iter()
+                .all(|row| row.len() == control_points[0].len()),
+            "Bezier patch control points must form a rectangular grid"
+        );
+        self.patches.push(control_points);
+        self
+    }
+
+    /// Set the largest distance the mesh may stray from the patches.
+    ///
+    /// Defaults to 1/1000 of the diagonal of the largest patch's control points.
+    pub fn tolerance(mut self, tolerance: Real) -> Self {
+        assert!(tolerance > 0.0, "BezierPatches tolerance must be positive");
+        self.tolerance = Some(tolerance);
+        self
+    }
+
+    /// Set the most segments a patch is split into in each direction.
+    pub fn max_segments(mut self, segments: usize) -> Self {
+        assert!(
+            segments >= 1,
+            "BezierPatches must allow at least 1 segment per patch"
+        );
+        self.max_segments = segments;
+        self
+    }
+
+    /// Number of patches in the set.
+    pub fn len(&self) -> usize {
+        self.patches.len()
+    }
+
+    /// Whether the set has no patches.
+    pub fn is_empty(&self) -> bool {
+        self.patches.is_empty()
+    }
+
+    /// Build the model with every patch.
+    pub fn build(self) -> Model {
+        let patches: Vec<Patch> = self
+            .patches
+            .iter()
+            .map(|rows| Patch::bezier(rows))
+            .collect();
+        let diagonal = patches.iter().map(Patch::diagonal).fold(0.0, Real::max);
+        let tolerance = self.tolerance.unwrap_or(diagonal / 1000.0);
+
+        // Slot 2k holds the segments of patch k along u, 2k + 1 along v. Slots joined
+        // by a shared edge end up with the largest count among them.
+        let mut slots: Vec<usize> = patches
+            .iter()
+            .flat_map(|patch| {
+                let (u, v) = patch.segments(tolerance, self.max_segments);
+                [u[0], v[0]]
+            })
+            .collect();
+        let mut parent: Vec<usize> = (0..slots.len()).collect();
+        let mut edges: HashMap<Vec<[i64; 3]>, usize> = HashMap::new();
+        let scale = 1e6 / diagonal.max(Real::MIN_POSITIVE);
+        for (k, grid) in self.patches.iter().enumerate() {
+            let (last_u, last_v) = (grid[0].len() - 1, grid.len() - 1);
+            // The first and last rows run along u, the first and last columns along v
+            let boundaries = [
+                (2 * k, grid[0].clone()),
+                (2 * k, grid[last_v].clone()),
+                (2 * k + 1, grid.iter().map(|row| row[0]).collect()),
+                (2 * k + 1, grid.iter().map(|row| row[last_u]).collect()),
+            ];
+            for (slot, points) in boundaries {
+                let mut key: Vec<[i64; 3]> = points
+                    .iter()
+                    .map(|&(x, y, z)| [x, y, z].map(|c| (c * scale).round() as i64))
+                    .collect();
+                // Edges where every point coincides join nothing
+                if key.iter().all(|p| *p == key[0]) {
+                    continue;
+                }
+                let reversed: Vec<_> = key.iter().rev().copied().collect();
+                key = key.min(reversed);
+                let other = *edges.entry(key).or_insert(slot);
+                let (a, b) = (find(&mut parent, slot), find(&mut parent, other));
+                if a != b {
+                    parent[a] = b;
+                    slots[b] = slots[b].max(slots[a]);
+                }
+            }
+        }
+
+        let mut model = Model::new("BezierPatches");
+        for (k, patch) in patches.iter().enumerate() {
+            let u = slots[find(&mut parent, 2 * k)];
+            let v = slots[find(&mut parent, 2 * k + 1)];
+            patch.tessellate(&mut model, &[u], &[v]);
+        }
+        model
+    }
+}
+
+impl Default for BezierPatches {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The root of `slot` in a union-find forest, halving paths on the way.
+fn find(parent: &mut [usize], mut slot: usize) -> usize {
+    while parent[slot] != slot {
+        parent[slot] = parent[parent[slot]];
+        slot = parent[slot];
+    }
+    slot
+}
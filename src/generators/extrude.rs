@@ -0,0 +1,191 @@
+//! Linear extrusion of 2D polygons with holes.
+
+use super::{add_cap, distinct_points, loop_columns, rotate, stitch_rows};
+use crate::triangulate::{signed_area, triangulate_with_holes};
+use crate::types::to_f32;
+use crate::{Model, Real, Vertex};
+use nalgebra::{Point2, Point3, Vector2, Vector3};
+
+/// Builder that extrudes a 2D outline with holes along the Z axis.
+///
+/// The outline and holes are given as `(x, y)` points in the XY plane, wound either
+/// way, and the solid runs from `z = 0` up to the extrusion depth. Holes must lie
+/// inside the outline without touching it or each other.
+///
+/// The top can be twisted and scaled about the origin of the outline's coordinates;
+/// use more slices to follow a twist smoothly. Outline corners sharper than the crease
+/// angle get split normals on the walls.
+pub struct Extrude {
+    outline: Vec<(Real, Real)>,
+    holes: Vec<Vec<(Real, Real)>>,
+    depth: Real,
+    twist: Real,
+    scale: Real,
+    slices: usize,
+    caps: bool,
+    crease_angle: Real,
+}
+
+impl Extrude {
+    /// Create a new extrude builder for the given outline.
+    pub fn new(outline: Vec<(Real, Real)>) -> Self {
+        Self {
+            outline,
+            holes: Vec::new(),
+            depth: 1.0,
+            twist: 0.0,
+            scale: 1.0,
+            slices: 1,
+            caps: true,
+            crease_angle: 30.0,
+        }
+    }
+
+    /// Cut a hole through the extrusion.
+    pub fn hole(mut self, hole: Vec<(Real, Real)>) -> Self {
+        self.holes.push(hole);
+        self
+    }
+
+    /// Set the extrusion depth along +Z.
+    pub fn depth(mut self, depth: Real) -> Self {
+        assert!(depth > 0.0, "Extrude depth must be positive");
+        self.depth = depth;
+        self
+    }
+
+    /// Set the rotation of the top relative to the bottom in degrees, counter-clockwise
+    /// about the Z axis.
+    pub fn twist(mut self, degrees: Real) -> Self {
+        self.twist = degrees;
+        self
+    }
+
+    /// Set the scale of the top relative to the bottom; zero tapers to a point.
+    pub fn scale(mut self, scale: Real) -> Self {
+        assert!(scale >= 0.0, "Extrude scale must not be negative");
+        self.scale = scale;
+        self
+    }
+
+    /// Set the number of slices along the depth.
+    pub fn slices(mut self, slices: usize) -> Self {
+        assert!(slices >= 1, "Extrude must have at least 1 slice");
+        self.slices = slices;
+        self
+    }
+
+    /// Set whether to close the bottom and top with caps.
+    pub fn caps(mut self, caps: bool) -> Self {
+        self.caps = caps;
+        self
+    }
+
+    /// Set the outline angle in degrees above which corners get split normals.
+    pub fn crease_angle(mut self, degrees: Real) -> Self {
+        assert!(degrees >= 0.0, "Extrude crease angle must not be negative");
+        self.crease_angle = degrees;
+        self
+    }
+
+    /// Build the extruded model.
+    pub fn build(self) -> Model {
+        let mut model = Model::new("Extrude");
+
+        let mut outline = distinct_points(&self.outline);
+        assert!(
+            outline.len() >= 3,
+            "Extrude outline must have at least three distinct points"
+        );
+        if signed_area(&outline) < 0.0 {
+            outline.reverse();
+        }
+
+        // Holes run clockwise so their walls face into the hole
+        let holes: Vec<Vec<Point2<Real>>> = self
+            .holes
+            .iter()
+            .map(|hole| {
+                let mut hole = distinct_points(hole);
+                assert!(
+                    hole.len() >= 3,
+                    "Extrude hole must have at least three distinct points"
+                );
+                if signed_area(&hole) > 0.0 {
+                    hole.reverse();
+                }
+                hole
+            })
+            .collect();
+
+        for points in std::iter::once(&outline).chain(&holes) {
+            self.add_wall(&mut model, points);
+        }
+
+        if self.caps {
+            let triangles = triangulate_with_holes(&outline, &holes);
+            let points: Vec<Point2<Real>> = outline
+                .iter()
+                .chain(holes.iter().flatten())
+                .copied()
+                .collect();
+            for top in [false, true] {
+                if top && self.scale == 0.0 {
+                    continue;
+                }
+                let (cos, sin, scale, z) = self.placement(if top { 1.0 } else { 0.0 });
+                let positions: Vec<Point3<Real>> = points
+                    .iter()
+                    .map(|p| {
+                        let position = rotate(p.coords * scale, cos, sin);
+                        Point3::new(position.x, position.y, z)
+                    })
+                    .collect();
+                let normal = if top { Vector3::z() } else { -Vector3::z() };
+                add_cap(&mut model, &points, &positions, &triangles, normal, !top);
+            }
+        }
+
+        model
+    }
+
+    /// Rotation (as cosine and sine), scale and height at `t` in [0, 1] along the depth.
+    fn placement(&self, t: Real) -> (Real, Real, Real, Real) {
+        let (sin, cos) = (self.twist.to_radians() * t).sin_cos();
+        (cos, sin, 1.0 + (self.scale - 1.0) * t, self.depth * t)
+    }
+
+    /// Add the side wall of one closed loop.
+    fn add_wall(&self, model: &mut Model, points: &[Point2<Real>]) {
+        let columns = loop_columns(points, self.crease_angle);
+
+        let first = model.mesh.vertices.len();
+        let twist_rate = self.twist.to_radians();
+        for slice in 0..=self.slices {
+            let t = slice as Real / self.slices as Real;
+            let (cos, sin, scale, z) = self.placement(t);
+
+            for &(point, tangent, u) in &columns {
+                let position = rotate(point.coords * scale, cos, sin);
+
+                // The wall normal is the cross product of the tangent along the loop and
+                // the direction a point travels as the outline grows, twists and rises
+                let radial = point.coords * (self.scale - 1.0);
+                let swirl = Vector2::new(-point.y, point.x) * (scale * twist_rate);
+                let travel = rotate(radial + swirl, cos, sin);
+                let tangent = rotate(tangent, cos, sin);
+                let normal = Vector3::new(tangent.x, tangent.y, 0.0)
+                    .cross(&Vector3::new(travel.x, travel.y, self.depth))
+                    .normalize();
+
+                model.mesh.add_vertex(Vertex::new(
+                    Point3::new(position.x, position.y, z),
+                    normal,
+                    Some((u, to_f32(t))),
+                ));
+            }
+        }
+
+        stitch_rows(model, first, self.slices + 1, columns.len());
+    }
+}
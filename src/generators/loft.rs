@@ -0,0 +1,307 @@
+//! Skinning a surface through a sequence of cross-sections.
+
+use super::{add_cap, catmull_rom, resample, stitch_rows};
+use crate::triangulate::triangulate;
+use crate::types::to_f32;
+use crate::{Model, Real, Vertex};
+use nalgebra::{Point2, Point3, Vector3};
+
+/// Builder that skins a surface through a sequence of closed cross-sections, such as
+/// the stations of a hull.
+///
+/// Sections may have different numbers of points; any section that does not already
+/// have the loft's resolution is resampled evenly along its length, starting from its
+/// first point. Sections are then wound consistently and rotated to line up with
+/// their neighbours. A section of a single point closes the surface in a tip.
+///
+/// Between sections the surface is ruled by default, or follows a Catmull-Rom spline
+/// through the sections. Corners sharper than the crease angle in any section get
+/// split normals along the whole loft.
+pub struct Loft {
+    sections: Vec<Vec<Point3<Real>>>,
+    resolution: Option<usize>,
+    spline_samples: usize,
+    caps: bool,
+    crease_angle: Real,
+}
+
+impl Loft {
+    /// Create a new loft builder with no sections.
+    pub fn new() -> Self {
+        Self {
+            sections: Vec::new(),
+            resolution: None,
+            spline_samples: 1,
+            caps: true,
+            crease_angle: 30.0,
+        }
+    }
+
+    /// Add a closed 3D cross-section.
+    pub fn section(mut self, points: Vec<(Real, Real, Real)>) -> Self {
+        assert!(!points.is_empty(), "Loft section must not be empty");
+        self.sections.push(
+            points
+                .into_iter()
+                .map(|(x, y, z)| Point3::new(x, y, z))
+                .collect(),
+        );
+        self
+    }
+
+    /// Add a closed 2D cross-section of `(x, y)` points in the plane at height `z`.
+    pub fn station(mut self, z: Real, points: Vec<(Real, Real)>) -> Self {
+        assert!(!points.is_empty(), "Loft section must not be empty");
+        self.sections.push(
+            points
+                .into_iter()
+                .map(|(x, y)| Point3::new(x, y, z))
+                .collect(),
+        );
+        self
+    }
+
+    /// Set the number of points around every section.
+    ///
+    /// Defaults to the largest number of points in any section.
+    pub fn resolution(mut self, resolution: usize) -> Self {
+        assert!(resolution >= 3, "Loft resolution must be at least 3 points");
+        self.resolution = Some(resolution);
+        self
+    }
+
+    /// Interpolate between sections with a Catmull-Rom spline, sampled `samples`
+    /// times per span. A value of 1 keeps ruled surfaces between sections.
+    pub fn spline(mut self, samples: usize) -> Self {
+        assert!(
+            samples >= 1,
+            "Loft spline must have at least 1 sample per span"
+        );
+        self.spline_samples = samples;
+        self
+    }
+
+    /// Set whether to close the first and last sections with caps.
+    pub fn caps(mut self, caps: bool) -> Self {
+        self.caps = caps;
+        self
+    }
+
+    /// Set the section angle in degrees above which corners get split normals.
+    pub fn crease_angle(mut self, degrees: Real) -> Self {
+        assert!(degrees >= 0.0, "Loft crease angle must not be negative");
+        self.crease_angle = degrees;
+        self
+    }
+
+    /// Build the lofted model.
+    pub fn build(self) -> Model {
+        let mut model = Model::new("Loft");
+        assert!(
+            self.sections.len() >= 2,
+            "Loft must have at least two sections"
+        );
+
+        let mut sections: Vec<Vec<Point3<Real>>> = self
+            .sections
+            .iter()
+            .map(|section| {
+                let mut section = section.clone();
+                section.dedup();
+                if section.len() > 1 && section.first() == section.last() {
+                    section.pop();
+                }
+                section
+            })
+            .collect();
+        let resolution = self
+            .resolution
+            .unwrap_or_else(|| sections.iter().map(Vec::len).max().unwrap())
+            .max(3);
+        for section in &mut sections {
+            if section.len() != resolution {
+                *section = resample(section, resolution, true);
+            }
+        }
+
+        // Wind every section counter-clockwise around the direction of the loft,
+        // so the surface faces outwards
+        let centroids: Vec<Point3<Real>> = sections.iter().map(|s| centroid(s)).collect();
+        let last = sections.len() - 1;
+        for (i, section) in sections.iter_mut().enumerate() {
+            let direction = centroids[(i + 1).min(last)] - centroids[i.saturating_sub(1)];
+            if area_normal(section).dot(&direction) < 0.0 {
+                section.reverse();
+                section.rotate_right(1);
+            }
+        }
+
+        // Rotate each section to the starting point that best matches the previous one
+        for i in 1..sections.len() {
+            let previous = &sections[i - 1];
+            let shift = (0..resolution)
+                .map(|shift| {
+                    let distance: Real = (0..resolution)
+                        .map(|k| {
+                            (sections[i][(k + shift) % resolution] - previous[k]).norm_squared()
+                        })
+                        .sum();
+                    (shift, distance)
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map_or(0, |(shift, _)| shift);
+            sections[i].rotate_left(shift);
+        }
+
+        // Interpolate rows between the sections, one point of the sections at a time
+        let columns: Vec<Vec<Point3<Real>>> = (0..resolution)
+            .map(|k| {
+                let points: Vec<Point3<Real>> = sections.iter().map(|s| s[k]).collect();
+                catmull_rom(&points, self.spline_samples, false)
+            })
+            .collect();
+        let rows: Vec<Vec<Point3<Real>>> = (0..columns[0].len())
+            .map(|r| columns.iter().map(|column| column[r]).collect())
+            .collect();
+
+        self.add_surface(&mut model, &rows);
+
+        if self.caps {
+            for (row, start) in [(&rows[0], true), (&rows[rows.len() - 1], false)] {
+                let normal = area_normal(row);
+                if normal.norm() < 1e-9 {
+                    continue;
+                }
+                let normal = normal.normalize();
+
+                // Triangulate in the plane the section is closest to
+                let center = centroid(row);
+                let tangent = (row[0] - center - normal * normal.dot(&(row[0] - center)))
+                    .try_normalize(1e-9)
+                    .unwrap_or_else(|| normal.cross(&Vector3::x()).normalize());
+                let bitangent = normal.cross(&tangent);
+                let points: Vec<Point2<Real>> = row
+                    .iter()
+                    .map(|p| Point2::new((p - center).dot(&tangent), (p - center).dot(&bitangent)))
+                    .collect();
+
+                let triangles = triangulate(&points);
+                let cap_normal = if start { -normal } else { normal };
+                add_cap(&mut model, &points, row, &triangles, cap_normal, start);
+            }
+        }
+
+        model
+    }
+
+    /// Add the skin through all rows, with creases where any row has a sharp corner.
+    fn add_surface(&self, model: &mut Model, rows: &[Vec<Point3<Real>>]) {
+        let resolution = rows[0].len();
+        let direction = |row: &[Point3<Real>], k: usize| {
+            (row[(k + 1) % resolution] - row[k % resolution]).try_normalize(1e-9)
+        };
+
+        // Columns as (point index, use incoming side, use outgoing side); the first
+        // point is repeated at the end so texture coordinates do not wrap
+        let crease_cos = self.crease_angle.to_radians().cos();
+        let sharp = |k: usize| {
+            rows.iter().any(|row| {
+                let incoming = direction(row, k + resolution - 1);
+                let outgoing = direction(row, k);
+                matches!((incoming, outgoing), (Some(a), Some(b)) if a.dot(&b) < crease_cos)
+            })
+        };
+        let mut columns = Vec::with_capacity(resolution + 1);
+        for k in 0..=resolution {
+            if !sharp(k % resolution) {
+                columns.push((k, true, true));
+            } else {
+                if k > 0 {
+                    columns.push((k, true, false));
+                }
+                if k < resolution {
+                    columns.push((k, false, true));
+                }
+            }
+        }
+
+        // Texture `v` follows the distance between row centroids
+        let centers: Vec<Point3<Real>> = rows.iter().map(|row| centroid(row)).collect();
+        let mut lengths = vec![0.0];
+        for r in 1..rows.len() {
+            lengths.push(lengths[r - 1] + (centers[r] - centers[r - 1]).norm());
+        }
+        let total = lengths[rows.len() - 1];
+
+        let first = model.mesh.vertices.len();
+        let last = rows.len() - 1;
+        for (r, row) in rows.iter().enumerate() {
+            let mut perimeter = vec![0.0];
+            for k in 0..resolution {
+                perimeter.push(perimeter[k] + (row[(k + 1) % resolution] - row[k]).norm());
+            }
+            let v = if total > 0.0 { lengths[r] / total } else { 0.0 };
+
+            for &(k, incoming, outgoing) in &columns {
+                let position = row[k % resolution];
+                let travel = rows[(r + 1).min(last)][k % resolution]
+                    - rows[r.saturating_sub(1)][k % resolution];
+
+                let mut tangent = Vector3::zeros();
+                if incoming {
+                    tangent += direction(row, k + resolution - 1).unwrap_or_else(Vector3::zeros);
+                }
+                if outgoing {
+                    tangent += direction(row, k).unwrap_or_else(Vector3::zeros);
+                }
+
+                // A row collapsed to a tip faces along the loft
+                let normal = tangent
+                    .cross(&travel)
+                    .try_normalize(1e-9)
+                    .unwrap_or_else(|| {
+                        let along = (centers[last] - centers[0]).normalize();
+                        if r == 0 {
+                            -along
+                        } else {
+                            along
+                        }
+                    });
+
+                let u = if perimeter[resolution] > 0.0 {
+                    perimeter[k] / perimeter[resolution]
+                } else {
+                    k as Real / resolution as Real
+                };
+                model
+                    .mesh
+                    .add_vertex(Vertex::new(position, normal, Some((to_f32(u), to_f32(v)))));
+            }
+        }
+
+        stitch_rows(model, first, rows.len(), columns.len());
+    }
+}
+
+impl Default for Loft {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Average of a set of points.
+fn centroid(points: &[Point3<Real>]) -> Point3<Real> {
+    let sum = points
+        .iter()
+        .fold(Vector3::zeros(), |sum, p| sum + p.coords);
+    Point3::from(sum / points.len() as Real)
+}
+
+/// Normal of a closed curve scaled by its enclosed area (Newell's method).
+fn area_normal(points: &[Point3<Real>]) -> Vector3<Real> {
+    let n = points.len();
+    (0..n)
+        .map(|k| points[k].coords.cross(&points[(k + 1) % n].coords))
+        .fold(Vector3::zeros(), |sum, v| sum + v)
+        / 2.0
+}
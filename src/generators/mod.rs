@@ -0,0 +1,235 @@
+//! Generators that build models from profiles, paths and functions.
+//!
+//! Where [`primitives`](crate::primitives) produce fixed shapes from a few
+//! parameters, generators take user-supplied geometry such as a 2D profile.
+
+mod bezier;
+mod extrude;
+mod loft;
+mod nurbs;
+mod parametric;
+mod revolve;
+mod sweep;
+
+pub use bezier::BezierPatches;
+pub use extrude::Extrude;
+pub use loft::Loft;
+pub use nurbs::NurbsSurface;
+pub use parametric::ParametricSurface;
+pub use revolve::Revolve;
+pub use sweep::Sweep;
+
+pub(crate) use revolve::{revolve_disc, revolve_upright, ProfilePoint, FULL_TURN};
+
+use crate::types::to_f32;
+use crate::{Face, Model, Real, Vertex};
+use nalgebra::{Point, Point2, Point3, Vector2, Vector3};
+
+/// Connect `rows` consecutive rows of `row_len` vertices, starting at vertex `first`,
+/// with two triangles per quad.
+///
+/// Triangles are counter-clockwise when seen with rows running left to right and
+/// later rows above earlier ones. Triangles that collapse, such as at a pole or
+/// between two rows at the same position, are skipped.
+pub(crate) fn stitch_rows(model: &mut Model, first: usize, rows: usize, row_len: usize) {
+    for row in 0..rows.saturating_sub(1) {
+        for i in 0..row_len.saturating_sub(1) {
+            let a = first + row * row_len + i;
+            let b = a + 1;
+            let c = b + row_len;
+            let d = a + row_len;
+
+            for [v1, v2, v3] in [[a, b, c], [a, c, d]] {
+                let vertices = &model.mesh.vertices;
+                let (p1, p2, p3) = (
+                    vertices[v1].position,
+                    vertices[v2].position,
+                    vertices[v3].position,
+                );
+                if p1 != p2 && p2 != p3 && p3 != p1 {
+                    model.mesh.add_face(Face::triangle(v1, v2, v3), None);
+                }
+            }
+        }
+    }
+}
+
+/// Sample a uniform Catmull-Rom spline through `points`, `samples` times per span.
+pub(crate) fn catmull_rom<const D: usize>(
+    points: &[Point<Real, D>],
+    samples: usize,
+    closed: bool,
+) -> Vec<Point<Real, D>> {
+    let count = points.len();
+    if count < 3 || samples < 2 {
+        return points.to_vec();
+    }
+
+    // Open curves repeat their end points so the spline reaches them
+    let point = |i: isize| -> Point<Real, D> {
+        if closed {
+            points[i.rem_euclid(count as isize) as usize]
+        } else {
+            points[i.clamp(0, count as isize - 1) as usize]
+        }
+    };
+
+    let spans = if closed { count } else { count - 1 };
+    let mut sampled = Vec::with_capacity(spans * samples + 1);
+    for span in 0..spans as isize {
+        let (p0, p1, p2, p3) = (
+            point(span - 1).coords,
+            point(span).coords,
+            point(span + 1).coords,
+            point(span + 2).coords,
+        );
+        for j in 0..samples {
+            let t = j as Real / samples as Real;
+            let (t2, t3) = (t * t, t * t * t);
+            let position = (p1 * 2.0
+                + (p2 - p0) * t
+                + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+                + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+                * 0.5;
+            sampled.push(Point::from(position));
+        }
+    }
+    if !closed {
+        sampled.push(points[count - 1]);
+    }
+    sampled
+}
+
+/// Resample a curve to `count` points evenly spaced along its length.
+///
+/// Open curves keep both end points; closed curves start at the first point and
+/// stop one spacing short of it.
+pub(crate) fn resample<const D: usize>(
+    points: &[Point<Real, D>],
+    count: usize,
+    closed: bool,
+) -> Vec<Point<Real, D>> {
+    let n = points.len();
+    let spans = if closed { n } else { n - 1 };
+    let mut lengths = vec![0.0];
+    for k in 0..spans {
+        lengths.push(lengths[k] + (points[(k + 1) % n] - points[k]).norm());
+    }
+    let total = lengths[spans];
+    if total == 0.0 || count < 2 {
+        return vec![points[0]; count];
+    }
+
+    let intervals = if closed { count } else { count - 1 };
+    let mut span = 0;
+    (0..count)
+        .map(|i| {
+            let target = total * i as Real / intervals as Real;
+            while span + 1 < spans && lengths[span + 1] < target {
+                span += 1;
+            }
+            let length = lengths[span + 1] - lengths[span];
+            let t = if length > 0.0 {
+                ((target - lengths[span]) / length).min(1.0)
+            } else {
+                0.0
+            };
+            points[span] + (points[(span + 1) % n] - points[span]) * t
+        })
+        .collect()
+}
+
+/// Convert a loop of `(x, y)` pairs to points, dropping repeats and a closing point.
+pub(crate) fn distinct_points(points: &[(Real, Real)]) -> Vec<Point2<Real>> {
+    let mut points: Vec<Point2<Real>> = points.iter().map(|&(x, y)| Point2::new(x, y)).collect();
+    points.dedup();
+    if points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    points
+}
+
+/// Columns of vertices around a closed loop as `(point, tangent, u)`.
+///
+/// Texture `u` follows the length of the loop, and the first point is repeated at
+/// the end so it does not wrap. Corners sharper than `crease_angle` degrees get one
+/// column for each side, so the surface keeps a hard edge there.
+pub(crate) fn loop_columns(
+    points: &[Point2<Real>],
+    crease_angle: Real,
+) -> Vec<(Point2<Real>, Vector2<Real>, f32)> {
+    let count = points.len();
+    let direction = |k: usize| (points[(k + 1) % count] - points[k]).normalize();
+
+    let mut lengths = vec![0.0];
+    for k in 0..count {
+        lengths.push(lengths[k] + (points[(k + 1) % count] - points[k]).norm());
+    }
+
+    let crease_cos = crease_angle.to_radians().cos();
+    let mut columns = Vec::with_capacity(count + 1);
+    for k in 0..=count {
+        let point = points[k % count];
+        let u = to_f32(lengths[k] / lengths[count]);
+        let incoming = direction((k + count - 1) % count);
+        let outgoing = direction(k % count);
+
+        if incoming.dot(&outgoing) >= crease_cos {
+            columns.push((point, (incoming + outgoing).normalize(), u));
+        } else {
+            if k > 0 {
+                columns.push((point, incoming, u));
+            }
+            if k < count {
+                columns.push((point, outgoing, u));
+            }
+        }
+    }
+    columns
+}
+
+/// Add a flat cap from triangulated 2D points placed at `positions`.
+///
+/// Texture coordinates map the bounding box of the 2D points to the unit square.
+/// `flip` reverses the winding of the triangles.
+pub(crate) fn add_cap(
+    model: &mut Model,
+    points: &[Point2<Real>],
+    positions: &[Point3<Real>],
+    triangles: &[[usize; 3]],
+    normal: Vector3<Real>,
+    flip: bool,
+) {
+    let (min, max) = points.iter().fold(
+        (
+            Point2::new(Real::INFINITY, Real::INFINITY),
+            Point2::new(Real::NEG_INFINITY, Real::NEG_INFINITY),
+        ),
+        |(min, max), p| (min.inf(p), max.sup(p)),
+    );
+    let extent = (max - min).map(|c| if c > 0.0 { c } else { 1.0 });
+
+    let first = model.mesh.vertices.len();
+    for (p, &position) in points.iter().zip(positions) {
+        let uv = (p - min).component_div(&extent);
+        model.mesh.add_vertex(Vertex::new(
+            position,
+            normal,
+            Some((to_f32(uv.x), to_f32(uv.y))),
+        ));
+    }
+
+    for &[a, b, c] in triangles {
+        let face = if flip {
+            Face::triangle(first + a, first + c, first + b)
+        } else {
+            Face::triangle(first + a, first + b, first + c)
+        };
+        model.mesh.add_face(face, None);
+    }
+}
+
+/// Rotate a 2D vector by the angle given by its cosine and sine.
+pub(crate) fn rotate(v: Vector2<Real>, cos: Real, sin: Real) -> Vector2<Real> {
+    Vector2::new(v.x * cos - v.y * sin, v.x * sin + v.y * cos)
+}
@@ -0,0 +1,444 @@
+//! NURBS surfaces and the tessellation shared with Bezier patches.
+
+use super::stitch_rows;
+use crate::types::to_f32;
+use crate::{Model, Real, Vertex};
+use nalgebra::{Point3, Vector3};
+
+/// Builder that tessellates a non-uniform rational B-spline (NURBS) surface.
+///
+/// Control points are given as rows of a grid: each row runs along u, and the rows
+/// follow each other along v. Without explicit knots the surface is clamped and uniform, so it
+/// passes through the corners of the grid; without weights it is polynomial. Faces
+/// are wound so the surface faces along the cross product of its u and v
+/// derivatives, and normals are exact.
+///
+/// Tessellation is adaptive: every knot span is split into just enough segments that,
+/// judged by the bend of its control polygon, the surface strays no further than the
+/// tolerance from the mesh. All rows share the same split, so the mesh has no cracks.
+pub struct NurbsSurface {
+    control_points: Vec<Vec<(Real, Real, Real)>>,
+    weights: Option<Vec<Vec<Real>>>,
+    degree: (usize, usize),
+    knots: Option<(Vec<Real>, Vec<Real>)>,
+    tolerance: Option<Real>,
+    max_segments: usize,
+}
+
+impl NurbsSurface {
+    /// Create a new NURBS surface builder of the given degrees along u and v.
+    pub fn new(
+        control_points: Vec<Vec<(Real, Real, Real)>>,
+        degree_u: usize,
+        degree_v: usize,
+    ) -> Self {
+        assert!(
+            degree_u >= 1 && degree_v >= 1,
+            "NurbsSurface degrees must be at least 1"
+        );
+        assert!(
+            control_points.len() > degree_v,
+            "NurbsSurface needs more control points along v than its degree"
+        );
+        let columns = control_points[0].len();
+        assert!(
+            control_points.iter().all(|row| row.len() == columns),
+            "NurbsSurface control points must form a rectangular grid"
+        );
+        assert!(
+            columns > degree_u,
+            "NurbsSurface needs more control points along u than its degree"
+        );
+        Self {
+            control_points,
+            weights: None,
+            degree: (degree_u, degree_v),
+            knots: None,
+            tolerance: None,
+            max_segments: 64,
+        }
+    }
+
+    /// Set the weight of every control point, in the same grid layout.
+    pub fn weights(mut self, weights: Vec<Vec<Real>>) -> Self {
+        assert!(
+            weights.len() == self.control_points.len()
+                && weights
+                    .iter()
+                    .zip(&self.control_points)
+                    .all(|(w, p)| w.len() == p.len()),
+            "NurbsSurface weights must match the control point grid"
+        );
+        assert!(
+            weights.iter().flatten().all(|&w| w > 0.0),
+            "NurbsSurface weights must be positive"
+        );
+        self.weights = Some(weights);
+        self
+    }
+
+    /// Set the knot vectors along u and v.
+    ///
+    /// Each needs as many knots as control points along its direction plus the
+    /// degree plus one, in non-decreasing order.
+    pub fn knots(mut self, knots_u: Vec<Real>, knots_v: Vec<Real>) -> Self {
+        let (count_u, count_v) = (self.control_points[0].len(), self.control_points.len());
+        assert!(
+            knots_u.len() == count_u + self.degree.0 + 1
+                && knots_v.len() == count_v + self.degree.1 + 1,
+            "NurbsSurface needs control points plus degree plus one knots in each direction"
+        );
+        assert!(
+            knots_u.windows(2).all(|k| k[0] <= k[1]) && knots_v.windows(2).all(|k| k[0] <= k[1]),
+            "NurbsSurface knots must not decrease"
+        );
+        assert!(
+            knots_u[self.degree.0] < knots_u[count_u] && knots_v[self.degree.1] < knots_v[count_v],
+            "NurbsSurface knots must span a non-empty domain"
+        );
+        self.knots = Some((knots_u, knots_v));
+        self
+    }
+
+    /// Set the largest distance the mesh may stray from the surface.
+    ///
+    /// Defaults to 1/1000 of the diagonal of the control points' bounding box.
+    pub fn tolerance(mut self, tolerance: Real) -> Self {
+        assert!(tolerance > 0.0, "NurbsSurface tolerance must be positive");
+        self.tolerance = Some(tolerance);
+        self
+    }
+
+    /// Set the most segments a single knot span is split into.
+    pub fn max_segments(mut self, segments: usize) -> Self {
+        assert!(
+            segments >= 1,
+            "NurbsSurface must allow at least 1 segment per span"
+        );
+        self.max_segments = segments;
+        self
+    }
+
+    /// Build the surface model.
+    pub fn build(self) -> Model {
+        let (count_u, count_v) = (self.control_points[0].len(), self.control_points.len());
+        let knots = self.knots.unwrap_or_else(|| {
+            (
+                clamped_knots(count_u, self.degree.0),
+                clamped_knots(count_v, self.degree.1),
+            )
+        });
+        let weights = self
+            .weights
+            .unwrap_or_else(|| vec![vec![1.0; count_u]; count_v]);
+        let patch = Patch {
+            points: transpose(&self.control_points, |&(x, y, z)| Point3::new(x, y, z)),
+            weights: transpose(&weights, |&w| w),
+            degree: self.degree,
+            knots,
+        };
+
+        let tolerance = self.tolerance.unwrap_or_else(|| patch.diagonal() / 1000.0);
+        let (segments_u, segments_v) = patch.segments(tolerance, self.max_segments);
+        let mut model = Model::new("NurbsSurface");
+        patch.tessellate(&mut model, &segments_u, &segments_v);
+        model
+    }
+}
+
+/// Uniform knots that clamp the surface to its end control points.
+fn clamped_knots(count: usize, degree: usize) -> Vec<Real> {
+    let inner = count - degree;
+    (0..count + degree + 1)
+        .map(|k| (k.saturating_sub(degree).min(inner)) as Real / inner as Real)
+        .collect()
+}
+
+/// Turn rows of a grid along v into columns indexed `[u][v]`.
+fn transpose<T, U>(rows: &[Vec<T>], convert: impl Fn(&T) -> U) -> Vec<Vec<U>> {
+    (0..rows[0].len())
+        .map(|i| rows.iter().map(|row| convert(&row[i])).collect())
+        .collect()
+}
+
+/// A rational B-spline surface ready for evaluation, with its control points and
+/// weights indexed `[u][v]`.
+pub(crate) struct Patch {
+    points: Vec<Vec<Point3<Real>>>,
+    weights: Vec<Vec<Real>>,
+    degree: (usize, usize),
+    knots: (Vec<Real>, Vec<Real>),
+}
+
+impl Patch {
+    /// A Bezier patch from rows of control points along u: a single span with
+    /// every weight 1.
+    pub(crate) fn bezier(rows: &[Vec<(Real, Real, Real)>]) -> Self {
+        let (count_u, count_v) = (rows[0].len(), rows.len());
+        Self {
+            points: transpose(rows, |&(x, y, z)| Point3::new(x, y, z)),
+            weights: vec![vec![1.0; count_v]; count_u],
+            degree: (count_u - 1, count_v - 1),
+            knots: (
+                clamped_knots(count_u, count_u - 1),
+                clamped_knots(count_v, count_v - 1),
+            ),
+        }
+    }
+
+    /// Length of the diagonal of the control points' bounding box.
+    pub(crate) fn diagonal(&self) -> Real {
+        let first = self.points[0][0];
+        let (min, max) = self
+            .points
+            .iter()
+            .flatten()
+            .fold((first, first), |(min, max), p| (min.inf(p), max.sup(p)));
+        (max - min).norm()
+    }
+
+    /// Segments needed in each non-empty knot span along u and along v.
+    ///
+    /// A polynomial piece of degree `p` whose control polygon bends by at most `M`
+    /// (the largest second difference) stays within `p (p - 1) M / (8 n^2)` of its
+    /// `n`-segment chord polygon. Weights are ignored in the estimate.
+    pub(crate) fn segments(
+        &self,
+        tolerance: Real,
+        max_segments: usize,
+    ) -> (Vec<usize>, Vec<usize>) {
+        let (count_u, count_v) = (self.points.len(), self.points[0].len());
+        let needed = |degree: usize, bend: Real| {
+            let n = ((degree * (degree - 1)) as Real * bend / (8.0 * tolerance)).sqrt();
+            (n.ceil() as usize).clamp(1, max_segments)
+        };
+        let second = |a: Point3<Real>, b: Point3<Real>, c: Point3<Real>| {
+            (a.coords - b.coords * 2.0 + c.coords).norm()
+        };
+
+        let (p, q) = self.degree;
+        let segments_u = spans(&self.knots.0, p, count_u)
+            .map(|span| {
+                let bend = (span - p..span.saturating_sub(1))
+                    .flat_map(|i| {
+                        (0..count_v).map(move |j| {
+                            second(
+                                self.points[i][j],
+                                self.points[i + 1][j],
+                                self.points[i + 2][j],
+                            )
+                        })
+                    })
+                    .fold(0.0, Real::max);
+                needed(p, bend)
+            })
+            .collect();
+        let segments_v = spans(&self.knots.1, q, count_v)
+            .map(|span| {
+                let bend = (span - q..span.saturating_sub(1))
+                    .flat_map(|j| {
+                        (0..count_u).map(move |i| {
+                            second(
+                                self.points[i][j],
+                                self.points[i][j + 1],
+                                self.points[i][j + 2],
+                            )
+                        })
+                    })
+                    .fold(0.0, Real::max);
+                needed(q, bend)
+            })
+            .collect();
+        (segments_u, segments_v)
+    }
+
+    /// Tessellate the surface with the given number of segments in each non-empty
+    /// knot span along u and v.
+    pub(crate) fn tessellate(&self, model: &mut Model, segments_u: &[usize], segments_v: &[usize]) {
+        let (count_u, count_v) = (self.points.len(), self.points[0].len());
+        let us = samples(&self.knots.0, self.degree.0, count_u, segments_u);
+        let vs = samples(&self.knots.1, self.degree.1, count_v, segments_v);
+        let row_len = us.len();
+
+        let mut positions = Vec::with_capacity(us.len() * vs.len());
+        let mut normals = Vec::with_capacity(us.len() * vs.len());
+        for &v in &vs {
+            for &u in &us {
+                let (position, along_u, along_v) = self.evaluate(u, v);
+                positions.push(position);
+                normals.push(self.normal(u, v, along_u, along_v));
+            }
+        }
+
+        // Weld samples that only differ by rounding, such as along a degenerate edge
+        // where all control points coincide, so the faces between them are skipped
+        let tolerance = self.diagonal() * 1e-6;
+        for index in 0..positions.len() {
+            for neighbor in [index.checked_sub(1), index.checked_sub(row_len)] {
+                let Some(neighbor) = neighbor else { continue };
+                if (neighbor + 1 == index && index % row_len == 0)
+                    || (positions[index] - positions[neighbor]).norm() > tolerance
+                {
+                    continue;
+                }
+                positions[index] = positions[neighbor];
+            }
+        }
+
+        let (u0, u1) = (us[0], us[us.len() - 1]);
+        let (v0, v1) = (vs[0], vs[vs.len() - 1]);
+        let first = model.mesh.vertices.len();
+        for (j, &v) in vs.iter().enumerate() {
+            for (i, &u) in us.iter().enumerate() {
+                let index = j * row_len + i;
+                model.mesh.add_vertex(Vertex::new(
+                    positions[index],
+                    normals[index],
+                    Some((to_f32((u - u0) / (u1 - u0)), to_f32((v - v0) / (v1 - v0)))),
+                ));
+            }
+        }
+        stitch_rows(model, first, vs.len(), row_len);
+    }
+
+    /// Position and first derivatives along u and v at `(u, v)`.
+    fn evaluate(&self, u: Real, v: Real) -> (Point3<Real>, Vector3<Real>, Vector3<Real>) {
+        let (p, q) = self.degree;
+        let span_u = find_span(&self.knots.0, p, self.points.len(), u);
+        let span_v = find_span(&self.knots.1, q, self.points[0].len(), v);
+        let (basis_u, derivative_u) = basis(&self.knots.0, p, span_u, u);
+        let (basis_v, derivative_v) = basis(&self.knots.1, q, span_v, v);
+
+        // Homogeneous sums of the point and its derivatives, with their weights
+        let mut point = (Vector3::zeros(), 0.0);
+        let mut along_u = (Vector3::zeros(), 0.0);
+        let mut along_v = (Vector3::zeros(), 0.0);
+        for k in 0..=p {
+            for l in 0..=q {
+                let (i, j) = (span_u - p + k, span_v - q + l);
+                let w = self.weights[i][j];
+                let wp = self.points[i][j].coords * w;
+                let b = basis_u[k] * basis_v[l];
+                let bu = derivative_u[k] * basis_v[l];
+                let bv = basis_u[k] * derivative_v[l];
+                point = (point.0 + wp * b, point.1 + w * b);
+                along_u = (along_u.0 + wp * bu, along_u.1 + w * bu);
+                along_v = (along_v.0 + wp * bv, along_v.1 + w * bv);
+            }
+        }
+
+        let position = point.0 / point.1;
+        (
+            Point3::from(position),
+            (along_u.0 - position * along_u.1) / point.1,
+            (along_v.0 - position * along_v.1) / point.1,
+        )
+    }
+
+    /// Unit normal from the derivatives at `(u, v)`.
+    ///
+    /// Where the derivatives vanish or are parallel, such as at a degenerate corner,
+    /// the normal is taken a little way towards the middle of the domain instead.
+    fn normal(
+        &self,
+        u: Real,
+        v: Real,
+        along_u: Vector3<Real>,
+        along_v: Vector3<Real>,
+    ) -> Vector3<Real> {
+        let (p, q) = self.degree;
+        let (count_u, count_v) = (self.points.len(), self.points[0].len());
+        let (u0, u1) = (self.knots.0[p], self.knots.0[count_u]);
+        let (v0, v1) = (self.knots.1[q], self.knots.1[count_v]);
+        let (u_mid, v_mid) = ((u0 + u1) / 2.0, (v0 + v1) / 2.0);
+
+        let (mut along_u, mut along_v) = (along_u, along_v);
+        for nudge in [1e-4, 1e-3, 1e-2, 1e-1] {
+            let (length_u, length_v) = (along_u.norm(), along_v.norm());
+            let normal = along_u.cross(&along_v);
+            if length_u.min(length_v) > length_u.max(length_v) * 1e-4
+                && normal.norm() > length_u * length_v * 1e-4
+            {
+                return normal.normalize();
+            }
+            let u = u + (u_mid - u).signum() * (u1 - u0) * nudge;
+            let v = v + (v_mid - v).signum() * (v1 - v0) * nudge;
+            (_, along_u, along_v) = self.evaluate(u, v);
+        }
+        Vector3::z()
+    }
+}
+
+/// The index of every non-empty knot span of the domain.
+fn spans(knots: &[Real], degree: usize, count: usize) -> impl Iterator<Item = usize> + '_ {
+    (degree..count).filter(move |&span| knots[span] < knots[span + 1])
+}
+
+/// Parameters at which to sample, splitting each non-empty span evenly.
+fn samples(knots: &[Real], degree: usize, count: usize, segments: &[usize]) -> Vec<Real> {
+    let mut values = vec![knots[degree]];
+    for (span, &n) in spans(knots, degree, count).zip(segments) {
+        let (start, end) = (knots[span], knots[span + 1]);
+        values.extend((1..=n).map(|k| start + (end - start) * k as Real / n as Real));
+    }
+    values
+}
+
+/// The knot span containing `t`, with the end of the domain in the last span.
+fn find_span(knots: &[Real], degree: usize, count: usize, t: Real) -> usize {
+    if t >= knots[count] {
+        return spans(knots, degree, count).last().unwrap_or(count - 1);
+    }
+    let (mut low, mut high) = (degree, count);
+    while high - low > 1 {
+        let middle = (low + high) / 2;
+        if t < knots[middle] {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+    low
+}
+
+/// The `degree + 1` basis functions that are non-zero in `span`, and their first
+/// derivatives, at `t` (Cox-de Boor recursion).
+fn basis(knots: &[Real], degree: usize, span: usize, t: Real) -> (Vec<Real>, Vec<Real>) {
+    let mut values = vec![1.0];
+    let mut lower = Vec::new();
+    for d in 1..=degree {
+        lower = values.clone();
+        values = vec![0.0; d + 1];
+        let mut saved = 0.0;
+        for r in 0..d {
+            let right = knots[span + r + 1] - t;
+            let left = t - knots[span + r + 1 - d];
+            let denominator = right + left;
+            let temp = if denominator > 0.0 {
+                lower[r] / denominator
+            } else {
+                0.0
+            };
+            values[r] = saved + right * temp;
+            saved = left * temp;
+        }
+        values[d] = saved;
+    }
+
+    // Each derivative combines the two basis functions of one degree lower that
+    // overlap it
+    let mut derivatives = vec![0.0; degree + 1];
+    if degree > 0 {
+        let p = degree as Real;
+        for (k, derivative) in derivatives.iter_mut().enumerate() {
+            let i = span + k - degree;
+            if k > 0 && knots[i + degree] > knots[i] {
+                *derivative += p * lower[k - 1] / (knots[i + degree] - knots[i]);
+            }
+            if k < degree && knots[i + degree + 1] > knots[i + 1] {
+                *derivative -= p * lower[k] / (knots[i + degree + 1] - knots[i + 1]);
+            }
+        }
+    }
+    (values, derivatives)
+}
@@ -0,0 +1,221 @@
+//! Surfaces defined by a parametric function.
+
+use super::stitch_rows;
+use crate::{Model, Real, Vertex};
+use nalgebra::{Point3, Vector3};
+
+/// Builder that tessellates a surface given as a function of `(u, v)`.
+///
+/// The function is sampled on a regular grid over the u and v ranges. Faces are
+/// wound so the surface faces along the cross product of its u and v derivatives,
+/// which is also the direction of the normals; reverse one of the ranges to turn it
+/// around. Normals come from finite differences of the function itself, so it must
+/// be defined slightly beyond the ends of its ranges.
+///
+/// Wrapping a direction marks the surface as closed along it: samples on the seam
+/// are welded to the start of the range wherever the two coincide up to rounding.
+pub struct ParametricSurface<F>
+where
+    F: Fn(Real, Real) -> Point3<Real>,
+{
+    function: F,
+    u_range: (Real, Real),
+    v_range: (Real, Real),
+    u_segments: usize,
+    v_segments: usize,
+    wrap_u: bool,
+    wrap_v: bool,
+}
+
+impl<F> ParametricSurface<F>
+where
+    F: Fn(Real, Real) -> Point3<Real>,
+{
+    /// Create a new parametric surface builder for `function`.
+    pub fn new(function: F) -> Self {
+        Self {
+            function,
+            u_range: (0.0, 1.0),
+            v_range: (0.0, 1.0),
+            u_segments: 32,
+            v_segments: 32,
+            wrap_u: false,
+            wrap_v: false,
+        }
+    }
+
+    /// Set the range of the u parameter.
+    pub fn u_range(mut self, start: Real, end: Real) -> Self {
+        assert!(start != end, "ParametricSurface u range must not be empty");
+        self.u_range = (start, end);
+        self
+    }
+
+    /// Set the range of the v parameter.
+    pub fn v_range(mut self, start: Real, end: Real) -> Self {
+        assert!(start != end, "ParametricSurface v range must not be empty");
+        self.v_range = (start, end);
+        self
+    }
+
+    /// Set the number of segments along u and v.
+    pub fn resolution(mut self, u_segments: usize, v_segments: usize) -> Self {
+        assert!(
+            u_segments >= 1 && v_segments >= 1,
+            "ParametricSurface must have at least 1 segment in each direction"
+        );
+        self.u_segments = u_segments;
+        self.v_segments = v_segments;
+        self
+    }
+
+    /// Set whether the surface is closed along u.
+    pub fn wrap_u(mut self, wrap: bool) -> Self {
+        self.wrap_u = wrap;
+        self
+    }
+
+    /// Set whether the surface is closed along v.
+    pub fn wrap_v(mut self, wrap: bool) -> Self {
+        self.wrap_v = wrap;
+        self
+    }
+
+    /// Build the surface model.
+    pub fn build(self) -> Model {
+        let mut model = Model::new("ParametricSurface");
+
+        let (u0, u1) = self.u_range;
+        let (v0, v1) = self.v_range;
+        let u_step = (u1 - u0) / self.u_segments as Real;
+        let v_step = (v1 - v0) / self.v_segments as Real;
+        let row_len = self.u_segments + 1;
+
+        let mut positions = Vec::with_capacity(row_len * (self.v_segments + 1));
+        for j in 0..=self.v_segments {
+            for i in 0..=self.u_segments {
+                positions.push((self.function)(
+                    u0 + u_step * i as Real,
+                    v0 + v_step * j as Real,
+                ));
+            }
+        }
+
+        // Weld samples that only differ by rounding, so the faces between them collapse
+        // and are skipped: rows or columns that shrink to a point, such as the poles of
+        // a sphere, and the seams of wrapped directions. Rounding is judged against the
+        // spacing of neighbouring samples, so it does not grow with the distance from
+        // the origin
+        let (columns, rows) = (row_len, self.v_segments + 1);
+        let row_lines: Vec<Vec<usize>> = (0..rows)
+            .map(|j| (0..columns).map(|i| j * row_len + i).collect())
+            .collect();
+        let column_lines: Vec<Vec<usize>> = (0..columns)
+            .map(|i| (0..rows).map(|j| j * row_len + i).collect())
+            .collect();
+        weld_collapsed(&mut positions, &row_lines);
+        weld_collapsed(&mut positions, &column_lines);
+        if self.wrap_u {
+            weld_seam(&mut positions, &column_lines);
+        }
+        if self.wrap_v {
+            weld_seam(&mut positions, &row_lines);
+        }
+
+        let first = model.mesh.vertices.len();
+        for j in 0..=self.v_segments {
+            for i in 0..=self.u_segments {
+                let (u, v) = (u0 + u_step * i as Real, v0 + v_step * j as Real);
+                model.mesh.add_vertex(Vertex::new(
+                    positions[j * row_len + i],
+                    self.normal(u, v, u_step, v_step),
+                    Some((
+                        i as f32 / self.u_segments as f32,
+                        j as f32 / self.v_segments as f32,
+                    )),
+                ));
+            }
+        }
+
+        stitch_rows(&mut model, first, self.v_segments + 1, row_len);
+        model
+    }
+
+    /// Normal at `(u, v)` from central differences of the function.
+    ///
+    /// Where the derivatives vanish or are parallel, such as at the pole of a sphere,
+    /// the normal is taken a little way towards the middle of the ranges instead.
+    fn normal(&self, u: Real, v: Real, u_step: Real, v_step: Real) -> Vector3<Real> {
+        let f = &self.function;
+        let (du, dv) = (u_step * 0.01, v_step * 0.01);
+        let (u_mid, v_mid) = (
+            (self.u_range.0 + self.u_range.1) / 2.0,
+            (self.v_range.0 + self.v_range.1) / 2.0,
+        );
+
+        for nudge in [0.0, 0.01, 0.1, 0.5] {
+            let u = u + (u_mid - u).signum() * u_step.abs() * nudge;
+            let v = v + (v_mid - v).signum() * v_step.abs() * nudge;
+            let along_u = f(u + du, v) - f(u - du, v);
+            let along_v = f(u, v + dv) - f(u, v - dv);
+            let (length_u, length_v) = (along_u.norm(), along_v.norm());
+            let normal = along_u.cross(&along_v);
+            if length_u.min(length_v) > length_u.max(length_v) * 1e-3
+                && normal.norm() > length_u * length_v * 1e-3
+            {
+                return normal.normalize();
+            }
+        }
+        Vector3::y()
+    }
+}
+
+/// Distance below which samples are taken to coincide, relative to the spacing of
+/// their neighbours.
+const WELD_TOLERANCE: Real = 1e-3;
+
+/// Largest distance between corresponding samples of two lines.
+fn line_gap(positions: &[Point3<Real>], a: &[usize], b: &[usize]) -> Real {
+    a.iter()
+        .zip(b)
+        .map(|(&x, &y)| (positions[x] - positions[y]).norm())
+        .fold(0.0, Real::max)
+}
+
+/// Weld every line of samples that shrinks to a point onto its first sample.
+fn weld_collapsed(positions: &mut [Point3<Real>], lines: &[Vec<usize>]) {
+    let collapsed: Vec<bool> = (0..lines.len())
+        .map(|k| {
+            let line = &lines[k];
+            let extent = line
+                .iter()
+                .map(|&index| (positions[index] - positions[line[0]]).norm())
+                .fold(0.0, Real::max);
+            let spacing = [k.checked_sub(1), Some(k + 1)]
+                .into_iter()
+                .flatten()
+                .filter_map(|other| lines.get(other))
+                .map(|other| line_gap(positions, line, other))
+                .fold(0.0, Real::max);
+            extent <= spacing * WELD_TOLERANCE
+        })
+        .collect();
+
+    for (line, _) in lines.iter().zip(collapsed).filter(|(_, c)| *c) {
+        for &index in line {
+            positions[index] = positions[line[0]];
+        }
+    }
+}
+
+/// Weld the samples of the last line onto those of the first where they coincide.
+fn weld_seam(positions: &mut [Point3<Real>], lines: &[Vec<usize>]) {
+    let (first, last) = (&lines[0], &lines[lines.len() - 1]);
+    let before = &lines[lines.len() - 2];
+    for k in 0..last.len() {
+        let spacing = (positions[last[k]] - positions[before[k]]).norm();
+        if (positions[last[k]] - positions[first[k]]).norm() <= spacing * WELD_TOLERANCE {
+            positions[last[k]] = positions[first[k]];
+        }
+    }
+}
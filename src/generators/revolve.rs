@@ -0,0 +1,412 @@
+//! Surfaces of revolution (lathe).
+
+use super::{add_cap, catmull_rom, stitch_rows};
+use crate::triangulate::{signed_area, triangulate};
+use crate::types::to_f32;
+use crate::{Face, Model, Real, Vertex};
+use nalgebra::{Point2, Point3, Vector2, Vector3};
+
+/// Builder that spins a 2D profile around an axis, like a lathe.
+///
+/// The profile is a list of `(radius, height)` points: the distance from the axis
+/// and the position along it. The surface faces to the right of the direction of
+/// travel, so a profile that walks up the outside of a vase from its foot to its lip
+/// gets outward normals. Closed profiles are oriented automatically.
+///
+/// Profile corners sharper than the crease angle get split normals, so turned parts
+/// keep hard edges while smooth curves are shaded smoothly.
+pub struct Revolve {
+    profile: Vec<(Real, Real)>,
+    center: (Real, Real, Real),
+    axis: Vector3<Real>,
+    angle: Real,
+    segments: usize,
+    caps: bool,
+    closed: bool,
+    spline_samples: usize,
+    crease_angle: Real,
+}
+
+impl Revolve {
+    /// Create a new revolve builder for the given `(radius, height)` profile.
+    pub fn new(profile: Vec<(Real, Real)>) -> Self {
+        Self {
+            profile,
+            center: (0.0, 0.0, 0.0),
+            axis: Vector3::y(),
+            angle: 360.0,
+            segments: 32,
+            caps: true,
+            closed: false,
+            spline_samples: 1,
+            crease_angle: 30.0,
+        }
+    }
+
+    /// Set a point on the axis of revolution; profile heights are measured from it.
+    pub fn center(mut self, x: Real, y: Real, z: Real) -> Self {
+        self.center = (x, y, z);
+        self
+    }
+
+    /// Set the direction of the axis of revolution.
+    pub fn axis(mut self, x: Real, y: Real, z: Real) -> Self {
+        let axis = Vector3::new(x, y, z);
+        assert!(axis.norm() > 0.0, "Revolve axis must not be zero");
+        self.axis = axis.normalize();
+        self
+    }
+
+    /// Set the sweep angle in degrees (360 for a full turn).
+    pub fn angle(mut self, degrees: Real) -> Self {
+        assert!(
+            degrees > 0.0 && degrees <= 360.0,
+            "Revolve angle must be in (0, 360] degrees"
+        );
+        self.angle = degrees;
+        self
+    }
+
+    /// Set the number of segments around the sweep.
+    pub fn segments(mut self, segments: usize) -> Self {
+        assert!(segments >= 3, "Revolve must have at least 3 segments");
+        self.segments = segments;
+        self
+    }
+
+    /// Set whether to close the open ends of the profile and the sides of a partial sweep.
+    pub fn caps(mut self, caps: bool) -> Self {
+        self.caps = caps;
+        self
+    }
+
+    /// Set whether the profile is a closed loop (e.g. a ring cross-section).
+    pub fn closed(mut self, closed: bool) -> Self {
+        self.closed = closed;
+        self
+    }
+
+    /// Treat the profile as control points of a Catmull-Rom spline through them,
+    /// sampled `samples` times per span. A value of 1 keeps the polyline.
+    pub fn spline(mut self, samples: usize) -> Self {
+        assert!(
+            samples >= 1,
+            "Revolve spline must have at least 1 sample per span"
+        );
+        self.spline_samples = samples;
+        self
+    }
+
+    /// Set the profile angle in degrees above which corners get split normals.
+    pub fn crease_angle(mut self, degrees: Real) -> Self {
+        assert!(degrees >= 0.0, "Revolve crease angle must not be negative");
+        self.crease_angle = degrees;
+        self
+    }
+
+    /// Build the revolved model.
+    pub fn build(self) -> Model {
+        let mut model = Model::new("Revolve");
+
+        let mut points: Vec<Point2<Real>> = self
+            .profile
+            .iter()
+            .map(|&(radius, height)| Point2::new(radius, height))
+            .collect();
+        if self.spline_samples > 1 {
+            points = catmull_rom(&points, self.spline_samples, self.closed);
+        }
+        points.dedup();
+        if self.closed && points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+
+        assert!(
+            points.len() >= if self.closed { 3 } else { 2 },
+            "Revolve profile must have at least two distinct points"
+        );
+        assert!(
+            points.iter().all(|p| p.x >= 0.0),
+            "Revolve profile must not cross the axis"
+        );
+
+        if self.closed && signed_area(&points) < 0.0 {
+            points.reverse();
+        }
+
+        let count = points.len();
+        let spans = if self.closed { count } else { count - 1 };
+        let span_normal = |k: usize| {
+            let direction = (points[(k + 1) % count] - points[k]).normalize();
+            Vector2::new(direction.y, -direction.x)
+        };
+
+        // Texture `v` follows the arc length of the profile
+        let mut arc_lengths = vec![0.0];
+        for k in 0..spans {
+            let length = (points[(k + 1) % count] - points[k]).norm();
+            arc_lengths.push(arc_lengths[k] + length);
+        }
+        let total_length = arc_lengths[spans];
+
+        let crease_cos = self.crease_angle.to_radians().cos();
+        let mut rows = Vec::with_capacity(spans + 1);
+        for k in 0..=spans {
+            let point = points[k % count];
+            let v = to_f32(arc_lengths[k] / total_length);
+            let incoming = (k > 0 || self.closed).then(|| span_normal((k + spans - 1) % spans));
+            let outgoing = (k < spans || self.closed).then(|| span_normal(k % spans));
+
+            match (incoming, outgoing) {
+                (Some(a), Some(b)) if a.dot(&b) >= crease_cos => {
+                    let normal = (a + b).normalize();
+                    rows.push(ProfilePoint::new(point.x, point.y, (normal.x, normal.y), v));
+                }
+                _ => {
+                    // A closed profile starts with its outgoing span and ends with its incoming one
+                    if let Some(a) = incoming.filter(|_| !(self.closed && k == 0)) {
+                        rows.push(ProfilePoint::new(point.x, point.y, (a.x, a.y), v));
+                    }
+                    if let Some(b) = outgoing.filter(|_| !(self.closed && k == spans)) {
+                        rows.push(ProfilePoint::new(point.x, point.y, (b.x, b.y), v));
+                    }
+                }
+            }
+        }
+
+        let (cx, cy, cz) = self.center;
+        let frame = RevolveFrame::new(Point3::new(cx, cy, cz), self.axis);
+        let sweep = self.angle.to_radians();
+        let full_turn = self.angle >= 360.0;
+        revolve_profile(&mut model, &frame, sweep, self.segments, &rows);
+
+        if self.caps && !self.closed {
+            let (first, last) = (points[0], points[count - 1]);
+            if first.x > 0.0 {
+                revolve_disc(&mut model, &frame, sweep, self.segments, first, true);
+            }
+            if last.x > 0.0 {
+                revolve_disc(&mut model, &frame, sweep, self.segments, last, false);
+            }
+        }
+
+        if self.caps && !full_turn {
+            // The cut faces are the profile closed off along the axis
+            let mut polygon = points.clone();
+            if !self.closed {
+                for end in [points[count - 1], points[0]] {
+                    let on_axis = Point2::new(0.0, end.y);
+                    if end.x > 0.0 && polygon.last() != Some(&on_axis) {
+                        polygon.push(on_axis);
+                    }
+                }
+            }
+            add_side_cap(&mut model, &frame, &polygon, 0.0, true);
+            add_side_cap(&mut model, &frame, &polygon, sweep, false);
+        }
+
+        model
+    }
+}
+
+/// Add one flat face of a partial sweep, in the profile plane at `angle`.
+fn add_side_cap(
+    model: &mut Model,
+    frame: &RevolveFrame,
+    polygon: &[Point2<Real>],
+    angle: Real,
+    start: bool,
+) {
+    let (sin, cos) = angle.sin_cos();
+
+    // For a counter-clockwise profile the start face looks back against the sweep
+    // and the end face along it
+    let backward = frame.direction(1.0, 0.0, cos, sin).cross(&frame.axis);
+    let normal = if start == (signed_area(polygon) > 0.0) {
+        backward
+    } else {
+        -backward
+    };
+
+    let triangles = triangulate(polygon);
+    let positions: Vec<Point3<Real>> = polygon
+        .iter()
+        .map(|p| frame.point(p.x, p.y, cos, sin))
+        .collect();
+    add_cap(model, polygon, &positions, &triangles, normal, !start);
+}
+
+/// Angle of a full turn, in radians.
+pub(crate) const FULL_TURN: Real = 2.0 * crate::consts::PI;
+
+/// Axis and orientation of a surface of revolution.
+pub(crate) struct RevolveFrame {
+    origin: Point3<Real>,
+    axis: Vector3<Real>,
+    reference: Vector3<Real>,
+    binormal: Vector3<Real>,
+}
+
+impl RevolveFrame {
+    /// Create a frame around `axis` (a unit vector) through `origin`.
+    ///
+    /// Angle zero lies along X (or Z for axes close to X), and angles increase
+    /// counter-clockwise when looking down the axis.
+    pub(crate) fn new(origin: Point3<Real>, axis: Vector3<Real>) -> Self {
+        let candidate = if axis.x.abs() < 0.9 {
+            Vector3::x()
+        } else {
+            Vector3::z()
+        };
+        let reference = (candidate - axis * axis.dot(&candidate)).normalize();
+        Self {
+            origin,
+            axis,
+            reference,
+            binormal: axis.cross(&reference),
+        }
+    }
+
+    /// Point at `radius` from the axis and `height` along it, at the angle given
+    /// by its cosine and sine.
+    fn point(&self, radius: Real, height: Real, cos: Real, sin: Real) -> Point3<Real> {
+        self.origin + self.direction(radius, height, cos, sin)
+    }
+
+    /// Vector with `radial` and `axial` components at the angle given by its cosine and sine.
+    fn direction(&self, radial: Real, axial: Real, cos: Real, sin: Real) -> Vector3<Real> {
+        self.reference * (radial * cos) + self.binormal * (radial * sin) + self.axis * axial
+    }
+}
+
+/// A point on the profile of a surface of revolution.
+pub(crate) struct ProfilePoint {
+    /// Distance from the axis
+    radius: Real,
+    /// Position along the axis, relative to the frame origin
+    height: Real,
+    /// Outward normal in the profile plane as (radial, axial) components
+    normal: (Real, Real),
+    /// Texture `v` coordinate of this profile row
+    v: f32,
+}
+
+impl ProfilePoint {
+    pub(crate) fn new(radius: Real, height: Real, normal: (Real, Real), v: f32) -> Self {
+        Self {
+            radius,
+            height,
+            normal,
+            v,
+        }
+    }
+}
+
+/// Angle of column `i` of `segments` over a sweep, as its cosine and sine.
+///
+/// The last column of a full turn reuses the exact values of the first so the seam
+/// closes without gaps.
+fn column_angle(i: usize, segments: usize, sweep: Real) -> (Real, Real) {
+    let angle = if i == segments {
+        if sweep >= FULL_TURN {
+            0.0
+        } else {
+            sweep
+        }
+    } else {
+        sweep * i as Real / segments as Real
+    };
+    let (sin, cos) = angle.sin_cos();
+    (cos, sin)
+}
+
+/// Sweep profile rows through `sweep` radians around a frame's axis.
+///
+/// Each row gets `segments + 1` vertices, so texture coordinates do not wrap at the
+/// seam of a full turn. Rows run so that the surface faces to the right of the
+/// profile's direction of travel.
+pub(crate) fn revolve_profile(
+    model: &mut Model,
+    frame: &RevolveFrame,
+    sweep: Real,
+    segments: usize,
+    profile: &[ProfilePoint],
+) {
+    let first = model.mesh.vertices.len();
+    let row_len = segments + 1;
+
+    for point in profile {
+        for i in 0..=segments {
+            let (cos, sin) = column_angle(i, segments, sweep);
+            let (radial, axial) = point.normal;
+
+            model.mesh.add_vertex(Vertex::new(
+                frame.point(point.radius, point.height, cos, sin),
+                frame.direction(radial, axial, cos, sin),
+                Some((i as f32 / segments as f32, point.v)),
+            ));
+        }
+    }
+
+    stitch_rows(model, first, profile.len(), row_len);
+}
+
+/// Revolve profile rows a full turn around the vertical axis through `center`, as
+/// the round primitives are built, returning the frame for adding caps.
+pub(crate) fn revolve_upright(
+    model: &mut Model,
+    center: (Real, Real, Real),
+    segments: usize,
+    profile: &[ProfilePoint],
+) -> RevolveFrame {
+    let (cx, cy, cz) = center;
+    let frame = RevolveFrame::new(Point3::new(cx, cy, cz), Vector3::y());
+    revolve_profile(model, &frame, FULL_TURN, segments, profile);
+    frame
+}
+
+/// Close the end of a revolved profile at `point` with a flat disc (or sector).
+///
+/// `start` selects the winding for the first row of the profile; the last row uses
+/// the opposite winding so both discs face away from the surface.
+pub(crate) fn revolve_disc(
+    model: &mut Model,
+    frame: &RevolveFrame,
+    sweep: Real,
+    segments: usize,
+    point: Point2<Real>,
+    start: bool,
+) {
+    let full_turn = sweep >= FULL_TURN;
+
+    // Matching the winding of the surface makes the first disc face down the axis
+    let normal = if start { -frame.axis } else { frame.axis };
+
+    let center = model.mesh.vertices.len();
+    model.mesh.add_vertex(Vertex::new(
+        frame.point(0.0, point.y, 1.0, 0.0),
+        normal,
+        Some((0.5, 0.5)),
+    ));
+
+    let ring_len = if full_turn { segments } else { segments + 1 };
+    for i in 0..ring_len {
+        let (cos, sin) = column_angle(i, segments, sweep);
+        model.mesh.add_vertex(Vertex::new(
+            frame.point(point.x, point.y, cos, sin),
+            normal,
+            Some((to_f32(0.5 + 0.5 * cos), to_f32(0.5 + 0.5 * sin))),
+        ));
+    }
+
+    for i in 0..segments {
+        let current = center + 1 + i;
+        let next = center + 1 + (i + 1) % ring_len;
+        let face = if start {
+            Face::triangle(center, next, current)
+        } else {
+            Face::triangle(center, current, next)
+        };
+        model.mesh.add_face(face, None);
+    }
+}
@@ -0,0 +1,370 @@
+//! Sweeping a 2D cross-section along a 3D path.
+
+use super::{add_cap, catmull_rom, distinct_points, loop_columns, rotate, stitch_rows};
+use crate::triangulate::{signed_area, triangulate_with_holes};
+use crate::types::to_f32;
+use crate::{Model, Real, Vertex};
+use nalgebra::{Point2, Point3, Vector2, Vector3};
+
+/// Builder that moves a 2D cross-section along a 3D path, for pipes, rails and handles.
+///
+/// The cross-section is a closed `(x, y)` outline, optionally with holes, wound either
+/// way. It is carried along the path on rotation-minimizing frames, so it does not
+/// spin around the path on its own. A closed path spreads any residual rotation
+/// evenly so the ends meet; it cannot scale, and its twist must be a whole number of
+/// turns.
+///
+/// Sharp corners in the path are mitered so the cross-section keeps its size through
+/// them; use a spline path to round them instead. A path may not turn straight back
+/// on itself.
+pub struct Sweep {
+    profile: Vec<(Real, Real)>,
+    holes: Vec<Vec<(Real, Real)>>,
+    path: Vec<(Real, Real, Real)>,
+    closed: bool,
+    spline_samples: usize,
+    scale: Real,
+    twist: Real,
+    caps: bool,
+    crease_angle: Real,
+}
+
+impl Sweep {
+    /// Create a new sweep builder moving `profile` along the points of `path`.
+    pub fn new(profile: Vec<(Real, Real)>, path: Vec<(Real, Real, Real)>) -> Self {
+        Self {
+            profile,
+            holes: Vec::new(),
+            path,
+            closed: false,
+            spline_samples: 1,
+            scale: 1.0,
+            twist: 0.0,
+            caps: true,
+            crease_angle: 30.0,
+        }
+    }
+
+    /// Cut a hole through the cross-section, e.g. the bore of a pipe.
+    pub fn hole(mut self, hole: Vec<(Real, Real)>) -> Self {
+        self.holes.push(hole);
+        self
+    }
+
+    /// Set whether the path is a closed loop.
+    pub fn closed(mut self, closed: bool) -> Self {
+        self.closed = closed;
+        self
+    }
+
+    /// Treat the path as control points of a Catmull-Rom spline through them,
+    /// sampled `samples` times per span. A value of 1 keeps the polyline.
+    pub fn spline(mut self, samples: usize) -> Self {
+        assert!(
+            samples >= 1,
+            "Sweep spline must have at least 1 sample per span"
+        );
+        self.spline_samples = samples;
+        self
+    }
+
+    /// Set the scale of the cross-section at the end of the path relative to the
+    /// start; zero tapers to a point.
+    pub fn scale(mut self, scale: Real) -> Self {
+        assert!(scale >= 0.0, "Sweep scale must not be negative");
+        self.scale = scale;
+        self
+    }
+
+    /// Set the rotation of the cross-section at the end of the path relative to the
+    /// start, in degrees.
+    pub fn twist(mut self, degrees: Real) -> Self {
+        self.twist = degrees;
+        self
+    }
+
+    /// Set whether to close the ends of an open path with caps.
+    pub fn caps(mut self, caps: bool) -> Self {
+        self.caps = caps;
+        self
+    }
+
+    /// Set the cross-section angle in degrees above which corners get split normals.
+    pub fn crease_angle(mut self, degrees: Real) -> Self {
+        assert!(degrees >= 0.0, "Sweep crease angle must not be negative");
+        self.crease_angle = degrees;
+        self
+    }
+
+    /// Build the swept model.
+    pub fn build(self) -> Model {
+        let mut model = Model::new("Sweep");
+
+        let mut outline = distinct_points(&self.profile);
+        assert!(
+            outline.len() >= 3,
+            "Sweep profile must have at least three distinct points"
+        );
+        if signed_area(&outline) < 0.0 {
+            outline.reverse();
+        }
+
+        // Holes run clockwise so their walls face into the hole
+        let holes: Vec<Vec<Point2<Real>>> = self
+            .holes
+            .iter()
+            .map(|hole| {
+                let mut hole = distinct_points(hole);
+                assert!(
+                    hole.len() >= 3,
+                    "Sweep hole must have at least three distinct points"
+                );
+                if signed_area(&hole) > 0.0 {
+                    hole.reverse();
+                }
+                hole
+            })
+            .collect();
+
+        let mut path: Vec<Point3<Real>> = self
+            .path
+            .iter()
+            .map(|&(x, y, z)| Point3::new(x, y, z))
+            .collect();
+        if self.spline_samples > 1 {
+            path = catmull_rom(&path, self.spline_samples, self.closed);
+        }
+        path.dedup();
+        if self.closed && path.len() > 1 && path.first() == path.last() {
+            path.pop();
+        }
+        if self.closed {
+            assert!(
+                path.len() >= 3,
+                "Sweep along a closed path must have at least three distinct points"
+            );
+            assert!(
+                self.scale == 1.0,
+                "Sweep along a closed path must not scale"
+            );
+            let turns = self.twist / 360.0;
+            assert!(
+                (turns - turns.round()).abs() < 1e-6,
+                "Sweep along a closed path must twist by whole turns"
+            );
+        } else {
+            assert!(
+                path.len() >= 2,
+                "Sweep path must have at least two distinct points"
+            );
+        }
+
+        let rings = self.rings(&path);
+        for points in std::iter::once(&outline).chain(&holes) {
+            self.add_wall(&mut model, &rings, points);
+        }
+
+        if self.caps && !self.closed {
+            let triangles = triangulate_with_holes(&outline, &holes);
+            let points: Vec<Point2<Real>> = outline
+                .iter()
+                .chain(holes.iter().flatten())
+                .copied()
+                .collect();
+
+            let (start, end) = (&rings[0], &rings[rings.len() - 1]);
+            let positions: Vec<Point3<Real>> = points.iter().map(|&p| start.place(p)).collect();
+            add_cap(
+                &mut model,
+                &points,
+                &positions,
+                &triangles,
+                -start.tangent,
+                true,
+            );
+            if self.scale > 0.0 {
+                let positions: Vec<Point3<Real>> = points.iter().map(|&p| end.place(p)).collect();
+                add_cap(
+                    &mut model,
+                    &points,
+                    &positions,
+                    &triangles,
+                    end.tangent,
+                    false,
+                );
+            }
+        }
+
+        model
+    }
+
+    /// Place a ring at every path point, plus a copy of the first at the end of a
+    /// closed path.
+    fn rings(&self, path: &[Point3<Real>]) -> Vec<Ring> {
+        let count = path.len();
+        let ring_count = if self.closed { count + 1 } else { count };
+        let spans = ring_count - 1;
+        let point = |i: usize| path[i % count];
+        let segment = |i: usize| (point(i + 1) - point(i)).normalize();
+
+        let mut lengths = vec![0.0];
+        for i in 0..spans {
+            lengths.push(lengths[i] + (point(i + 1) - point(i)).norm());
+        }
+
+        // Tangents bisect the path's corners, where the cross-section is stretched
+        // across the bend into a miter
+        let mut tangents = Vec::with_capacity(ring_count);
+        let mut miters = Vec::with_capacity(ring_count);
+        for i in 0..ring_count {
+            let incoming = (self.closed || i > 0).then(|| segment((i + spans - 1) % spans));
+            let outgoing = (self.closed || i < spans).then(|| segment(i % spans));
+            match (incoming, outgoing) {
+                (Some(a), Some(b)) => {
+                    assert!(
+                        (a + b).norm() > 1e-6,
+                        "Sweep path must not turn back on itself"
+                    );
+                    let tangent = (a + b).normalize();
+                    let bend = b - a;
+                    let miter = if bend.norm() > 1e-6 {
+                        (bend.normalize(), 1.0 / a.dot(&tangent) - 1.0)
+                    } else {
+                        (Vector3::zeros(), 0.0)
+                    };
+                    tangents.push(tangent);
+                    miters.push(miter);
+                }
+                (Some(a), None) | (None, Some(a)) => {
+                    tangents.push(a);
+                    miters.push((Vector3::zeros(), 0.0));
+                }
+                (None, None) => unreachable!(),
+            }
+        }
+
+        // Rotation-minimizing frames by the double reflection method
+        let first = tangents[0];
+        let reference = if first.x.abs() < 0.9 {
+            Vector3::x()
+        } else {
+            Vector3::z()
+        };
+        let mut normals = vec![(reference - first * first.dot(&reference)).normalize()];
+        for i in 0..spans {
+            let v1 = point(i + 1) - point(i);
+            let c1 = v1.norm_squared();
+            let reflected_normal = normals[i] - v1 * (2.0 / c1 * v1.dot(&normals[i]));
+            let reflected_tangent = tangents[i] - v1 * (2.0 / c1 * v1.dot(&tangents[i]));
+
+            let v2 = tangents[i + 1] - reflected_tangent;
+            let c2 = v2.norm_squared();
+            let normal = if c2 > 1e-12 {
+                reflected_normal - v2 * (2.0 / c2 * v2.dot(&reflected_normal))
+            } else {
+                reflected_normal
+            };
+            let tangent = tangents[i + 1];
+            normals.push((normal - tangent * tangent.dot(&normal)).normalize());
+        }
+
+        // The frame that comes back around a closed path is generally rotated
+        // against the one it started with
+        let closure = if self.closed {
+            let (end, start) = (normals[spans], normals[0]);
+            end.cross(&start).dot(&first).atan2(end.dot(&start))
+        } else {
+            0.0
+        };
+
+        (0..ring_count)
+            .map(|i| {
+                let s = lengths[i] / lengths[spans];
+                let (sin, cos) = ((self.twist.to_radians() + closure) * s).sin_cos();
+                Ring {
+                    origin: point(i),
+                    tangent: tangents[i],
+                    normal: normals[i],
+                    binormal: tangents[i].cross(&normals[i]),
+                    miter: miters[i],
+                    cos,
+                    sin,
+                    scale: 1.0 + (self.scale - 1.0) * s,
+                    v: to_f32(s),
+                }
+            })
+            .collect()
+    }
+
+    /// Add the surface swept by one closed loop of the cross-section.
+    fn add_wall(&self, model: &mut Model, rings: &[Ring], points: &[Point2<Real>]) {
+        let columns = loop_columns(points, self.crease_angle);
+        let mut positions: Vec<Vec<Point3<Real>>> = rings
+            .iter()
+            .map(|ring| columns.iter().map(|&(p, _, _)| ring.place(p)).collect())
+            .collect();
+
+        // The last ring of a closed path only meets the first up to rounding, so it
+        // takes the first's positions to close the seam exactly
+        if self.closed {
+            positions[rings.len() - 1] = positions[0].clone();
+        }
+
+        // Normals cross the loop's tangent with the direction each column travels
+        // between neighbouring rings
+        let last = rings.len() - 1;
+        let first = model.mesh.vertices.len();
+        for (i, ring) in rings.iter().enumerate() {
+            let (prev, next) = if self.closed {
+                (
+                    if i == 0 { last - 1 } else { i - 1 },
+                    if i == last { 1 } else { i + 1 },
+                )
+            } else {
+                (i.saturating_sub(1), (i + 1).min(last))
+            };
+
+            for (j, &(_, tangent, u)) in columns.iter().enumerate() {
+                let travel = positions[next][j] - positions[prev][j];
+                let normal = ring.direction(tangent).cross(&travel).normalize();
+                model
+                    .mesh
+                    .add_vertex(Vertex::new(positions[i][j], normal, Some((u, ring.v))));
+            }
+        }
+
+        stitch_rows(model, first, rings.len(), columns.len());
+    }
+}
+
+/// Placement of the cross-section at one point along the path.
+struct Ring {
+    origin: Point3<Real>,
+    tangent: Vector3<Real>,
+    /// Direction of the cross-section's X axis
+    normal: Vector3<Real>,
+    /// Direction of the cross-section's Y axis
+    binormal: Vector3<Real>,
+    /// Bend direction and extra stretch across it at a corner
+    miter: (Vector3<Real>, Real),
+    cos: Real,
+    sin: Real,
+    scale: Real,
+    /// Texture `v` coordinate, following the length of the path
+    v: f32,
+}
+
+impl Ring {
+    /// Map a direction in the cross-section into space, ignoring scale.
+    fn direction(&self, v: Vector2<Real>) -> Vector3<Real> {
+        let v = rotate(v, self.cos, self.sin);
+        let offset = self.normal * v.x + self.binormal * v.y;
+        let (bend, stretch) = self.miter;
+        offset + bend * (offset.dot(&bend) * stretch)
+    }
+
+    /// Map a cross-section point into space.
+    fn place(&self, p: Point2<Real>) -> Point3<Real> {
+        self.origin + self.direction(p.coords * self.scale)
+    }
+}
@@ -0,0 +1,259 @@
+//! 3D convex hulls by the quickhull algorithm.
+
+use crate::{Face, Model, Real, Vertex};
+use nalgebra::{Point3, Vector3};
+use std::collections::HashMap;
+
+/// A triangle of the hull under construction, with the points still outside it.
+struct HullFace {
+    vertices: [usize; 3],
+    normal: Vector3<Real>,
+    offset: Real,
+    outside: Vec<usize>,
+    alive: bool,
+}
+
+impl HullFace {
+    fn new(vertices: [usize; 3], points: &[Point3<Real>]) -> Self {
+        let [a, b, c] = vertices.map(|i| points[i]);
+        let normal = (b - a).cross(&(c - a));
+        let length = normal.norm();
+        let normal = if length > 0.0 {
+            normal / length
+        } else {
+            Vector3::zeros()
+        };
+        Self {
+            vertices,
+            normal,
+            offset: normal.dot(&a.coords),
+            outside: Vec::new(),
+            alive: true,
+        }
+    }
+
+    fn distance(&self, point: &Point3<Real>) -> Real {
+        self.normal.dot(&point.coords) - self.offset
+    }
+
+    fn edges(&self) -> [(usize, usize); 3] {
+        let [a, b, c] = self.vertices;
+        [(a, b), (b, c), (c, a)]
+    }
+}
+
+/// Triangles of the convex hull of `points`, as indices wound counter-clockwise seen
+/// from outside.
+///
+/// Points within rounding distance of a face count as inside, so coplanar and
+/// repeated points are left out. Returns `None` when the points do not span a
+/// volume.
+pub(crate) fn quickhull(points: &[Point3<Real>]) -> Option<Vec<[usize; 3]>> {
+    if points.len() < 4 {
+        return None;
+    }
+    let scale: Real = (0..3)
+        .map(|axis| points.iter().map(|p| p[axis].abs()).fold(0.0, Real::max))
+        .sum();
+    let epsilon = 3.0 * scale * Real::EPSILON;
+
+    let mut faces = initial_simplex(points, epsilon)?;
+    let simplex: Vec<usize> = faces.iter().flat_map(|f| f.vertices).collect();
+    for (i, point) in points.iter().enumerate() {
+        if simplex.contains(&i) {
+            continue;
+        }
+        if let Some(face) = faces.iter_mut().find(|f| f.distance(point) > epsilon) {
+            face.outside.push(i);
+        }
+    }
+
+    // Each directed edge maps to the face it runs counter-clockwise around
+    let mut edges: HashMap<(usize, usize), usize> = HashMap::new();
+    for (index, face) in faces.iter().enumerate() {
+        for edge in face.edges() {
+            edges.insert(edge, index);
+        }
+    }
+
+    let mut pending: Vec<usize> = (0..faces.len()).collect();
+    while let Some(start) = pending.pop() {
+        if !faces[start].alive || faces[start].outside.is_empty() {
+            continue;
+        }
+        let face = &faces[start];
+        let eye = *face
+            .outside
+            .iter()
+            .max_by(|&&a, &&b| {
+                face.distance(&points[a])
+                    .total_cmp(&face.distance(&points[b]))
+            })
+            .unwrap();
+
+        // The faces the eye sees, found by spreading from the first across edges
+        let mut visible = vec![start];
+        let mut seen = vec![start];
+        let mut k = 0;
+        while k < visible.len() {
+            for (a, b) in faces[visible[k]].edges() {
+                if let Some(&neighbour) = edges.get(&(b, a)) {
+                    if !seen.contains(&neighbour) {
+                        seen.push(neighbour);
+                        if faces[neighbour].distance(&points[eye]) > epsilon {
+                            visible.push(neighbour);
+                        }
+                    }
+                }
+            }
+            k += 1;
+        }
+
+        // Edges between visible and hidden faces form the horizon around the eye
+        let mut horizon = Vec::new();
+        let mut orphans = Vec::new();
+        for &index in &visible {
+            for (a, b) in faces[index].edges() {
+                let hidden = edges
+                    .get(&(b, a))
+                    .is_none_or(|neighbour| !visible.contains(neighbour));
+                if hidden {
+                    horizon.push((a, b));
+                }
+            }
+        }
+        for &index in &visible {
+            for edge in faces[index].edges() {
+                if edges.get(&edge) == Some(&index) {
+                    edges.remove(&edge);
+                }
+            }
+            faces[index].alive = false;
+            orphans.append(&mut faces[index].outside);
+        }
+
+        let first = faces.len();
+        for (a, b) in horizon {
+            let face = HullFace::new([a, b, eye], points);
+            for edge in face.edges() {
+                edges.insert(edge, faces.len());
+            }
+            faces.push(face);
+        }
+        for point in orphans {
+            if point == eye {
+                continue;
+            }
+            if let Some(face) = faces[first..]
+                .iter_mut()
+                .find(|f| f.distance(&points[point]) > epsilon)
+            {
+                face.outside.push(point);
+            }
+        }
+        pending.extend(first..faces.len());
+    }
+
+    Some(
+        faces
+            .iter()
+            .filter(|f| f.alive)
+            .map(|f| f.vertices)
+            .collect(),
+    )
+}
+
+/// The convex hull of `points` as a model of shared-vertex triangles with smooth
+/// normals, or `None` when the points do not span a volume.
+pub(crate) fn hull_model(name: &str, points: &[Point3<Real>]) -> Option<Model> {
+    let triangles = quickhull(points)?;
+    let mut model = Model::new(name);
+    let mut remap = HashMap::new();
+    for triangle in triangles {
+        let indices = triangle.map(|i| {
+            *remap.entry(i).or_insert_with(|| {
+                model
+                    .mesh
+                    .add_vertex(Vertex::with_position(points[i].x, points[i].y, points[i].z))
+            })
+        });
+        model
+            .mesh
+            .add_face(Face::triangle(indices[0], indices[1], indices[2]), None);
+    }
+    model.mesh.compute_normals();
+    Some(model)
+}
+
+/// Volume enclosed by hull triangles from [`quickhull`].
+pub(crate) fn volume(points: &[Point3<Real>], triangles: &[[usize; 3]]) -> Real {
+    triangles
+        .iter()
+        .map(|&[a, b, c]| {
+            let (a, b, c) = (points[a].coords, points[b].coords, points[c].coords);
+            a.dot(&b.cross(&c)) / 6.0
+        })
+        .sum()
+}
+
+/// The four faces of a tetrahedron on points far apart, facing outwards.
+fn initial_simplex(points: &[Point3<Real>], epsilon: Real) -> Option<Vec<HullFace>> {
+    // The two farthest apart of the extreme points along each axis
+    let mut extremes = Vec::with_capacity(6);
+    for axis in 0..3 {
+        let by_axis = |a: &&Point3<Real>, b: &&Point3<Real>| a[axis].total_cmp(&b[axis]);
+        let indexed = || points.iter().enumerate();
+        extremes.push(indexed().min_by(|a, b| by_axis(&a.1, &b.1))?.0);
+        extremes.push(indexed().max_by(|a, b| by_axis(&a.1, &b.1))?.0);
+    }
+    let (mut a, mut b, mut farthest) = (0, 0, 0.0);
+    for &i in &extremes {
+        for &j in &extremes {
+            let distance = (points[i] - points[j]).norm();
+            if distance > farthest {
+                (a, b, farthest) = (i, j, distance);
+            }
+        }
+    }
+    if farthest <= epsilon {
+        return None;
+    }
+
+    let direction = (points[b] - points[a]) / farthest;
+    let from_line = |p: &Point3<Real>| (p - points[a]).cross(&direction).norm();
+    let c = farthest_by(points, from_line);
+    if from_line(&points[c]) <= epsilon {
+        return None;
+    }
+    let normal = (points[b] - points[a])
+        .cross(&(points[c] - points[a]))
+        .normalize();
+    let from_plane = |p: &Point3<Real>| normal.dot(&(p - points[a])).abs();
+    let d = farthest_by(points, from_plane);
+    if from_plane(&points[d]) <= epsilon {
+        return None;
+    }
+
+    let center = Point3::from(
+        (points[a].coords + points[b].coords + points[c].coords + points[d].coords) / 4.0,
+    );
+    Some(
+        [[a, b, c], [a, d, b], [b, d, c], [c, d, a]]
+            .into_iter()
+            .map(|[i, j, k]| {
+                let face = HullFace::new([i, j, k], points);
+                if face.distance(&center) > 0.0 {
+                    HullFace::new([i, k, j], points)
+                } else {
+                    face
+                }
+            })
+            .collect(),
+    )
+}
+
+fn farthest_by(points: &[Point3<Real>], distance: impl Fn(&Point3<Real>) -> Real) -> usize {
+    (0..points.len())
+        .max_by(|&i, &j| distance(&points[i]).total_cmp(&distance(&points[j])))
+        .unwrap_or(0)
+}
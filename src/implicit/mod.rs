@@ -0,0 +1,91 @@
+//! Implicit surfaces defined by signed distance functions.
+//!
+//! A signed distance function (SDF) gives the distance from a point to the surface of
+//! a shape: negative inside, positive outside. Shapes are combined with hard or
+//! smooth boolean operations and repeated across space, then turned into a
+//! [`Model`](crate::Model) by a [`Polygonizer`].
+//!
+//! Any closure taking a point and returning a distance is also an SDF.
+
+mod ops;
+mod polygonize;
+mod shapes;
+
+pub use ops::{Intersection, Repeat, Subtraction, Union};
+pub use polygonize::Polygonizer;
+pub use shapes::{Capsule, Cuboid, Sphere, Torus};
+
+use crate::Real;
+use nalgebra::Point3;
+
+/// A signed distance function.
+pub trait Sdf {
+    /// Signed distance from `point` to the surface; negative inside.
+    fn distance(&self, point: &Point3<Real>) -> Real;
+
+    /// Combine with another shape, keeping the space inside either.
+    fn union<S: Sdf>(self, other: S) -> Union<Self, S>
+    where
+        Self: Sized,
+    {
+        Union::new(self, other, 0.0)
+    }
+
+    /// Union that blends the shapes together over a distance of `smoothness`.
+    fn smooth_union<S: Sdf>(self, other: S, smoothness: Real) -> Union<Self, S>
+    where
+        Self: Sized,
+    {
+        Union::new(self, other, smoothness)
+    }
+
+    /// Cut another shape out of this one.
+    fn subtract<S: Sdf>(self, other: S) -> Subtraction<Self, S>
+    where
+        Self: Sized,
+    {
+        Subtraction::new(self, other, 0.0)
+    }
+
+    /// Subtraction that rounds the cut over a distance of `smoothness`.
+    fn smooth_subtract<S: Sdf>(self, other: S, smoothness: Real) -> Subtraction<Self, S>
+    where
+        Self: Sized,
+    {
+        Subtraction::new(self, other, smoothness)
+    }
+
+    /// Keep only the space inside both shapes.
+    fn intersect<S: Sdf>(self, other: S) -> Intersection<Self, S>
+    where
+        Self: Sized,
+    {
+        Intersection::new(self, other, 0.0)
+    }
+
+    /// Intersection that rounds the shared edges over a distance of `smoothness`.
+    fn smooth_intersect<S: Sdf>(self, other: S, smoothness: Real) -> Intersection<Self, S>
+    where
+        Self: Sized,
+    {
+        Intersection::new(self, other, smoothness)
+    }
+
+    /// Repeat the shape endlessly with the given spacing along each axis; a spacing of
+    /// zero leaves that axis alone.
+    fn repeat(self, x: Real, y: Real, z: Real) -> Repeat<Self>
+    where
+        Self: Sized,
+    {
+        Repeat::new(self, x, y, z)
+    }
+}
+
+impl<F> Sdf for F
+where
+    F: Fn(&Point3<Real>) -> Real,
+{
+    fn distance(&self, point: &Point3<Real>) -> Real {
+        self(point)
+    }
+}
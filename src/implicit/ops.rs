@@ -0,0 +1,152 @@
+//! Boolean operations and domain repetition on signed distance functions.
+
+use super::Sdf;
+use crate::Real;
+use nalgebra::{Point3, Vector3};
+
+/// Polynomial smooth minimum, blending over a distance of `k`.
+fn smooth_min(a: Real, b: Real, k: Real) -> Real {
+    if k <= 0.0 {
+        return a.min(b);
+    }
+    let h = (0.5 + 0.5 * (b - a) / k).clamp(0.0, 1.0);
+    b + (a - b) * h - k * h * (1.0 - h)
+}
+
+/// Polynomial smooth maximum, blending over a distance of `k`.
+fn smooth_max(a: Real, b: Real, k: Real) -> Real {
+    -smooth_min(-a, -b, k)
+}
+
+/// The union of two shapes, optionally blended.
+#[derive(Debug, Clone)]
+pub struct Union<A, B> {
+    a: A,
+    b: B,
+    smoothness: Real,
+}
+
+impl<A: Sdf, B: Sdf> Union<A, B> {
+    /// Create a union blending over `smoothness` (zero for a hard union).
+    pub fn new(a: A, b: B, smoothness: Real) -> Self {
+        assert!(smoothness >= 0.0, "Smoothness must not be negative");
+        Self { a, b, smoothness }
+    }
+}
+
+impl<A: Sdf, B: Sdf> Sdf for Union<A, B> {
+    fn distance(&self, point: &Point3<Real>) -> Real {
+        smooth_min(
+            self.a.distance(point),
+            self.b.distance(point),
+            self.smoothness,
+        )
+    }
+}
+
+/// The first shape with the second cut out of it, optionally rounded.
+#[derive(Debug, Clone)]
+pub struct Subtraction<A, B> {
+    a: A,
+    b: B,
+    smoothness: Real,
+}
+
+impl<A: Sdf, B: Sdf> Subtraction<A, B> {
+    /// Create a subtraction of `b` from `a` rounding over `smoothness` (zero for a hard cut).
+    pub fn new(a: A, b: B, smoothness: Real) -> Self {
+        assert!(smoothness >= 0.0, "Smoothness must not be negative");
+        Self { a, b, smoothness }
+    }
+}
+
+impl<A: Sdf, B: Sdf> Sdf for Subtraction<A, B> {
+    fn distance(&self, point: &Point3<Real>) -> Real {
+        smooth_max(
+            self.a.distance(point),
+            -self.b.distance(point),
+            self.smoothness,
+        )
+    }
+}
+
+/// The intersection of two shapes, optionally rounded.
+#[derive(Debug, Clone)]
+pub struct Intersection<A, B> {
+    a: A,
+    b: B,
+    smoothness: Real,
+}
+
+impl<A: Sdf, B: Sdf> Intersection<A, B> {
+    /// Create an intersection rounding over `smoothness` (zero for a hard intersection).
+    pub fn new(a: A, b: B, smoothness: Real) -> Self {
+        assert!(smoothness >= 0.0, "Smoothness must not be negative");
+        Self { a, b, smoothness }
+    }
+}
+
+impl<A: Sdf, B: Sdf> Sdf for Intersection<A, B> {
+    fn distance(&self, point: &Point3<Real>) -> Real {
+        smooth_max(
+            self.a.distance(point),
+            self.b.distance(point),
+            self.smoothness,
+        )
+    }
+}
+
+/// A shape repeated on a grid.
+///
+/// Copies sit at whole multiples of the spacing, endlessly or for a limited count
+/// along each axis starting at the original. The shape should fit within one cell of
+/// the grid for the distances to stay exact.
+#[derive(Debug, Clone)]
+pub struct Repeat<S> {
+    sdf: S,
+    spacing: Vector3<Real>,
+    count: Option<Vector3<Real>>,
+}
+
+impl<S: Sdf> Repeat<S> {
+    /// Repeat `sdf` with the given spacing along each axis; zero leaves an axis alone.
+    pub fn new(sdf: S, x: Real, y: Real, z: Real) -> Self {
+        assert!(
+            x >= 0.0 && y >= 0.0 && z >= 0.0,
+            "Repeat spacing must not be negative"
+        );
+        Self {
+            sdf,
+            spacing: Vector3::new(x, y, z),
+            count: None,
+        }
+    }
+
+    /// Limit the number of copies along each axis.
+    pub fn count(mut self, x: usize, y: usize, z: usize) -> Self {
+        assert!(
+            x >= 1 && y >= 1 && z >= 1,
+            "Repeat must have at least one copy along each axis"
+        );
+        self.count = Some(Vector3::new(x as Real, y as Real, z as Real));
+        self
+    }
+}
+
+impl<S: Sdf> Sdf for Repeat<S> {
+    fn distance(&self, point: &Point3<Real>) -> Real {
+        let mut local = *point;
+        for axis in 0..3 {
+            let spacing = self.spacing[axis];
+            if spacing <= 0.0 {
+                continue;
+            }
+            let mut cell = (point[axis] / spacing).round();
+            if let Some(count) = self.count {
+                cell = cell.clamp(0.0, count[axis] - 1.0);
+            }
+            local[axis] -= cell * spacing;
+        }
+        self.sdf.distance(&local)
+    }
+}
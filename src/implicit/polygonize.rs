@@ -0,0 +1,452 @@
+//! Turning signed distance functions into triangle meshes.
+
+use super::Sdf;
+use crate::parallel;
+use crate::{Face, Model, Real, Vertex};
+use nalgebra::{Matrix3, Point3, Vector3};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// Fraction of a grid edge within which a crossing is moved onto the grid point.
+const SNAP: Real = 1e-3;
+
+/// Cube edges as pairs of corners; corner `i` is offset by bit 0 along X, bit 1
+/// along Y and bit 2 along Z.
+const EDGES: [(usize, usize); 12] = [
+    (0, 1),
+    (2, 3),
+    (4, 5),
+    (6, 7),
+    (0, 2),
+    (1, 3),
+    (4, 6),
+    (5, 7),
+    (0, 4),
+    (1, 5),
+    (2, 6),
+    (3, 7),
+];
+
+/// Cube faces as their corners in order around the face.
+const FACES: [[usize; 4]; 6] = [
+    [0, 2, 6, 4],
+    [1, 3, 7, 5],
+    [0, 1, 5, 4],
+    [2, 3, 7, 6],
+    [0, 1, 3, 2],
+    [4, 5, 7, 6],
+];
+
+/// Builder that polygonizes a signed distance function over a box.
+///
+/// The function is sampled on a grid of cubic cells covering the bounds, with the
+/// resolution giving the number of cells along the longest side. Marching cubes
+/// places vertices on the cell edges, which rounds off sharp edges and corners; dual
+/// contouring places one vertex inside each cell where the surface's planes meet, so
+/// it keeps them. The bounds should enclose the surface with some margin, or it is
+/// left open where it leaves them.
+pub struct Polygonizer<'a, S: Sdf + Sync + ?Sized> {
+    sdf: &'a S,
+    min: Point3<Real>,
+    max: Point3<Real>,
+    resolution: usize,
+    dual_contouring: bool,
+}
+
+impl<'a, S: Sdf + Sync + ?Sized> Polygonizer<'a, S> {
+    /// Create a new polygonizer for `sdf`.
+    pub fn new(sdf: &'a S) -> Self {
+        Self {
+            sdf,
+            min: Point3::new(-1.0, -1.0, -1.0),
+            max: Point3::new(1.0, 1.0, 1.0),
+            resolution: 32,
+            dual_contouring: false,
+        }
+    }
+
+    /// Set the box to sample, as its minimum and maximum corners.
+    pub fn bounds(mut self, min: (Real, Real, Real), max: (Real, Real, Real)) -> Self {
+        assert!(
+            min.0 < max.0 && min.1 < max.1 && min.2 < max.2,
+            "Polygonizer bounds must have a positive size"
+        );
+        self.min = Point3::new(min.0, min.1, min.2);
+        self.max = Point3::new(max.0, max.1, max.2);
+        self
+    }
+
+    /// Set the number of cells along the longest side of the bounds.
+    pub fn resolution(mut self, resolution: usize) -> Self {
+        assert!(resolution >= 1, "Polygonizer must have at least 1 cell");
+        self.resolution = resolution;
+        self
+    }
+
+    /// Set whether to use dual contouring instead of marching cubes.
+    pub fn dual_contouring(mut self, dual_contouring: bool) -> Self {
+        self.dual_contouring = dual_contouring;
+        self
+    }
+
+    /// Build the polygonized model.
+    pub fn build(self) -> Model {
+        let extent = self.max - self.min;
+        let size = extent.max() / self.resolution as Real;
+        let cells = [0, 1, 2].map(|axis| ((extent[axis] / size).ceil() as usize).max(1));
+        let points = cells.map(|count| count + 1);
+
+        let position = |point: [usize; 3]| {
+            self.min + Vector3::new(point[0] as Real, point[1] as Real, point[2] as Real) * size
+        };
+        let values = parallel::map_indices(points[0] * points[1] * points[2], |i| {
+            let point = [
+                i % points[0],
+                i / points[0] % points[1],
+                i / (points[0] * points[1]),
+            ];
+            self.sdf.distance(&position(point))
+        });
+
+        let grid = Grid {
+            min: self.min,
+            size,
+            cells,
+            points,
+            values,
+        };
+
+        let mut model = Model::new("Implicit");
+        if self.dual_contouring {
+            self.dual_contour(&mut model, &grid);
+        } else {
+            self.march_cubes(&mut model, &grid);
+        }
+        model
+    }
+
+    /// Unit normal at `point` from the gradient of the distance function.
+    fn normal(&self, point: &Point3<Real>, step: Real) -> Vector3<Real> {
+        let gradient = Vector3::from_fn(|axis, _| {
+            let mut offset = Vector3::zeros();
+            offset[axis] = step;
+            self.sdf.distance(&(point + offset)) - self.sdf.distance(&(point - offset))
+        });
+        gradient.try_normalize(0.0).unwrap_or_else(Vector3::y)
+    }
+
+    /// Polygonize with marching cubes, sharing one vertex per crossed grid edge.
+    fn march_cubes(&self, model: &mut Model, grid: &Grid) {
+        let table = case_table();
+        let mut edge_vertices: HashMap<(usize, usize), usize> = HashMap::new();
+
+        for cell in grid.cells() {
+            let case = grid.case(cell);
+            for triangle in &table[case] {
+                let indices = triangle.map(|edge| {
+                    // Crossings right next to a grid point share a vertex there instead of
+                    // leaving slivers around it; those are keyed with axis 3
+                    let (start, axis) = grid.edge(cell, edge);
+                    let mut t = grid.fraction(start, axis);
+                    let key = if t < SNAP {
+                        t = 0.0;
+                        (grid.index(start), 3)
+                    } else if t > 1.0 - SNAP {
+                        t = 1.0;
+                        (grid.index(start) + grid.stride(axis), 3)
+                    } else {
+                        (grid.index(start), axis)
+                    };
+                    *edge_vertices.entry(key).or_insert_with(|| {
+                        let mut position = grid.position(start);
+                        position[axis] += t * grid.size;
+                        model.mesh.add_vertex(Vertex::new(
+                            position,
+                            self.normal(&position, grid.size * 0.01),
+                            None,
+                        ))
+                    })
+                });
+                add_triangle(model, indices);
+            }
+        }
+    }
+
+    /// Polygonize with dual contouring, placing one vertex in each crossed cell and
+    /// joining the four cells around every crossed grid edge with a quad.
+    fn dual_contour(&self, model: &mut Model, grid: &Grid) {
+        let step = grid.size * 0.01;
+        let mut cell_vertices: HashMap<[usize; 3], usize> = HashMap::new();
+
+        for cell in grid.cells() {
+            let case = grid.case(cell);
+            if case == 0 || case == 255 {
+                continue;
+            }
+
+            // Minimize the distance to the tangent planes at the edge crossings (the
+            // quadratic error function), relative to their average for stability
+            let crossings: Vec<(Point3<Real>, Vector3<Real>)> = (0..12)
+                .filter_map(|edge| {
+                    let (a, b) = EDGES[edge];
+                    if (case >> a & 1) == (case >> b & 1) {
+                        return None;
+                    }
+                    let (start, axis) = grid.edge(cell, edge);
+                    let position = grid.crossing(start, axis);
+                    Some((position, self.normal(&position, step)))
+                })
+                .collect();
+            let mass = Point3::from(
+                crossings
+                    .iter()
+                    .fold(Vector3::zeros(), |sum, (p, _)| sum + p.coords)
+                    / crossings.len() as Real,
+            );
+            let mut ata = Matrix3::zeros();
+            let mut atb = Vector3::zeros();
+            for (p, n) in &crossings {
+                ata += n * n.transpose();
+                atb += n * n.dot(&(p - mass));
+            }
+            let svd = ata.svd(true, true);
+            let threshold = svd.singular_values.max() * 0.1;
+            let offset = svd
+                .solve(&atb, threshold)
+                .unwrap_or_else(|_| Vector3::zeros());
+
+            // Keep the vertex inside its cell
+            let low = grid.position(cell);
+            let high = low + Vector3::repeat(grid.size);
+            let position = (mass + offset).sup(&low).inf(&high);
+
+            let index =
+                model
+                    .mesh
+                    .add_vertex(Vertex::new(position, self.normal(&position, step), None));
+            cell_vertices.insert(cell, index);
+        }
+
+        for point in grid.points() {
+            for axis in 0..3 {
+                let (b, c) = ((axis + 1) % 3, (axis + 2) % 3);
+                if point[axis] >= grid.cells[axis] || point[b] == 0 || point[c] == 0 {
+                    continue;
+                }
+                if point[b] >= grid.points[b] - 1 || point[c] >= grid.points[c] - 1 {
+                    continue;
+                }
+                let mut end = point;
+                end[axis] += 1;
+                let inside = grid.value(point) < 0.0;
+                if inside == (grid.value(end) < 0.0) {
+                    continue;
+                }
+
+                // The four cells around the edge, counter-clockwise seen from its end
+                let quad = [(0, 0), (1, 0), (1, 1), (0, 1)].map(|(db, dc)| {
+                    let mut cell = point;
+                    cell[b] -= db;
+                    cell[c] -= dc;
+                    cell_vertices[&cell]
+                });
+                let [q0, q1, q2, q3] = if inside {
+                    quad
+                } else {
+                    [quad[0], quad[3], quad[2], quad[1]]
+                };
+                add_triangle(model, [q0, q1, q2]);
+                add_triangle(model, [q0, q2, q3]);
+            }
+        }
+    }
+}
+
+/// Add a triangle unless two of its corners coincide.
+fn add_triangle(model: &mut Model, [a, b, c]: [usize; 3]) {
+    let vertices = &model.mesh.vertices;
+    let (pa, pb, pc) = (
+        vertices[a].position,
+        vertices[b].position,
+        vertices[c].position,
+    );
+    if pa != pb && pb != pc && pc != pa {
+        model.mesh.add_face(Face::triangle(a, b, c), None);
+    }
+}
+
+/// Samples of a distance function on a regular grid.
+struct Grid {
+    min: Point3<Real>,
+    size: Real,
+    cells: [usize; 3],
+    points: [usize; 3],
+    values: Vec<Real>,
+}
+
+impl Grid {
+    fn index(&self, point: [usize; 3]) -> usize {
+        point[0] + self.points[0] * (point[1] + self.points[1] * point[2])
+    }
+
+    fn value(&self, point: [usize; 3]) -> Real {
+        self.values[self.index(point)]
+    }
+
+    fn position(&self, point: [usize; 3]) -> Point3<Real> {
+        self.min + Vector3::new(point[0] as Real, point[1] as Real, point[2] as Real) * self.size
+    }
+
+    /// Coordinates of every cell, by the grid point at its minimum corner.
+    fn cells(&self) -> impl Iterator<Item = [usize; 3]> {
+        let [nx, ny, nz] = self.cells;
+        (0..nz).flat_map(move |z| (0..ny).flat_map(move |y| (0..nx).map(move |x| [x, y, z])))
+    }
+
+    /// Coordinates of every grid point.
+    fn points(&self) -> impl Iterator<Item = [usize; 3]> {
+        let [nx, ny, nz] = self.points;
+        (0..nz).flat_map(move |z| (0..ny).flat_map(move |y| (0..nx).map(move |x| [x, y, z])))
+    }
+
+    /// Bit mask of the cell's corners that are inside the surface.
+    fn case(&self, cell: [usize; 3]) -> usize {
+        (0..8)
+            .filter(|&corner| self.value(corner_point(cell, corner)) < 0.0)
+            .fold(0, |case, corner| case | 1 << corner)
+    }
+
+    /// A cell edge as the grid point it starts from and the axis it runs along.
+    fn edge(&self, cell: [usize; 3], edge: usize) -> ([usize; 3], usize) {
+        let (a, b) = EDGES[edge];
+        (corner_point(cell, a & b), (a ^ b).trailing_zeros() as usize)
+    }
+
+    /// Distance between neighbouring grid points along `axis` in the sample array.
+    fn stride(&self, axis: usize) -> usize {
+        self.points[..axis].iter().product()
+    }
+
+    /// How far along the grid edge from `start` along `axis` the surface crosses it,
+    /// as a fraction of the edge.
+    fn fraction(&self, start: [usize; 3], axis: usize) -> Real {
+        let (a, b) = (
+            self.value(start),
+            self.values[self.index(start) + self.stride(axis)],
+        );
+        a / (a - b)
+    }
+
+    /// Where the surface crosses the grid edge from `start` along `axis`.
+    fn crossing(&self, start: [usize; 3], axis: usize) -> Point3<Real> {
+        let mut position = self.position(start);
+        position[axis] += self.fraction(start, axis) * self.size;
+        position
+    }
+}
+
+/// Grid point at `corner` of the cell whose minimum corner is `cell`.
+fn corner_point(cell: [usize; 3], corner: usize) -> [usize; 3] {
+    [0, 1, 2].map(|axis| cell[axis] + (corner >> axis & 1))
+}
+
+/// Offset of a cube corner from the cube's minimum corner.
+fn corner_offset(corner: usize) -> Vector3<Real> {
+    Vector3::new(
+        (corner & 1) as Real,
+        (corner >> 1 & 1) as Real,
+        (corner >> 2 & 1) as Real,
+    )
+}
+
+/// Triangles for each of the 256 inside/outside configurations of a cube's corners,
+/// as indices into [`EDGES`].
+fn case_table() -> &'static [Vec<[usize; 3]>] {
+    static TABLE: OnceLock<Vec<Vec<[usize; 3]>>> = OnceLock::new();
+    TABLE.get_or_init(|| (0..256).map(case_triangles).collect())
+}
+
+/// Work out the triangles of one marching cubes configuration.
+///
+/// Rather than a hand-written table, the outline of the surface is traced across the
+/// cube's faces and each closed loop is fanned into triangles. Where a face has two
+/// inside corners diagonally opposite, they are kept apart; the choice depends only
+/// on the face, so neighbouring cubes always agree and the mesh has no cracks.
+fn case_triangles(case: usize) -> Vec<[usize; 3]> {
+    let inside = |corner: usize| case >> corner & 1 == 1;
+    let edge_between = |a: usize, b: usize| {
+        EDGES
+            .iter()
+            .position(|&edge| edge == (a, b) || edge == (b, a))
+            .unwrap()
+    };
+
+    // Link the crossed edges that are joined by the outline on some face
+    let mut links: [Vec<usize>; 12] = Default::default();
+    for face in FACES {
+        let edge = |k: usize| edge_between(face[k], face[(k + 1) % 4]);
+        let crossed: Vec<usize> = (0..4)
+            .filter(|&k| inside(face[k]) != inside(face[(k + 1) % 4]))
+            .collect();
+        let mut pairs = Vec::new();
+        if crossed.len() == 2 {
+            pairs.push((edge(crossed[0]), edge(crossed[1])));
+        } else if crossed.len() == 4 {
+            for k in (0..4).filter(|&k| inside(face[k])) {
+                pairs.push((edge((k + 3) % 4), edge(k)));
+            }
+        }
+        for (a, b) in pairs {
+            links[a].push(b);
+            links[b].push(a);
+        }
+    }
+
+    let mut visited = [false; 12];
+    let mut triangles = Vec::new();
+    for start in 0..12 {
+        if visited[start] || links[start].is_empty() {
+            continue;
+        }
+
+        let mut ring = vec![start];
+        visited[start] = true;
+        let (mut previous, mut current) = (start, links[start][0]);
+        while current != start {
+            visited[current] = true;
+            ring.push(current);
+            let next = if links[current][0] == previous {
+                links[current][1]
+            } else {
+                links[current][0]
+            };
+            previous = current;
+            current = next;
+        }
+
+        // Wind the loop so it faces from the inside corners towards the outside ones
+        let midpoint = |edge: usize| {
+            let (a, b) = EDGES[edge];
+            (corner_offset(a) + corner_offset(b)) / 2.0
+        };
+        let normal = (0..ring.len()).fold(Vector3::zeros(), |sum, k| {
+            sum + midpoint(ring[k]).cross(&midpoint(ring[(k + 1) % ring.len()]))
+        });
+        let outward = ring.iter().fold(Vector3::zeros(), |sum, &edge| {
+            let (a, b) = EDGES[edge];
+            if inside(a) {
+                sum + corner_offset(b) - corner_offset(a)
+            } else {
+                sum + corner_offset(a) - corner_offset(b)
+            }
+        });
+        if normal.dot(&outward) < 0.0 {
+            ring.reverse();
+        }
+
+        for k in 1..ring.len() - 1 {
+            triangles.push([ring[0], ring[k], ring[k + 1]]);
+        }
+    }
+    triangles
+}
@@ -0,0 +1,156 @@
+//! Signed distance functions of basic shapes.
+
+use super::Sdf;
+use crate::Real;
+use nalgebra::{Point3, Vector3};
+
+/// A sphere.
+#[derive(Debug, Clone)]
+pub struct Sphere {
+    radius: Real,
+    center: Point3<Real>,
+}
+
+impl Sphere {
+    /// Create a sphere of the given radius at the origin.
+    pub fn new(radius: Real) -> Self {
+        assert!(radius > 0.0, "Sphere radius must be positive");
+        Self {
+            radius,
+            center: Point3::origin(),
+        }
+    }
+
+    /// Set the center of the sphere.
+    pub fn center(mut self, x: Real, y: Real, z: Real) -> Self {
+        self.center = Point3::new(x, y, z);
+        self
+    }
+}
+
+impl Sdf for Sphere {
+    fn distance(&self, point: &Point3<Real>) -> Real {
+        (point - self.center).norm() - self.radius
+    }
+}
+
+/// An axis-aligned box, optionally with rounded edges.
+#[derive(Debug, Clone)]
+pub struct Cuboid {
+    half_size: Vector3<Real>,
+    center: Point3<Real>,
+    rounding: Real,
+}
+
+impl Cuboid {
+    /// Create a box with the given full width, height and depth at the origin.
+    pub fn new(width: Real, height: Real, depth: Real) -> Self {
+        assert!(
+            width > 0.0 && height > 0.0 && depth > 0.0,
+            "Cuboid dimensions must be positive"
+        );
+        Self {
+            half_size: Vector3::new(width, height, depth) / 2.0,
+            center: Point3::origin(),
+            rounding: 0.0,
+        }
+    }
+
+    /// Set the center of the box.
+    pub fn center(mut self, x: Real, y: Real, z: Real) -> Self {
+        self.center = Point3::new(x, y, z);
+        self
+    }
+
+    /// Round the edges and corners with the given radius, keeping the outer size.
+    pub fn rounding(mut self, radius: Real) -> Self {
+        assert!(
+            radius >= 0.0 && radius <= self.half_size.min(),
+            "Cuboid rounding must be between zero and half the smallest dimension"
+        );
+        self.rounding = radius;
+        self
+    }
+}
+
+impl Sdf for Cuboid {
+    fn distance(&self, point: &Point3<Real>) -> Real {
+        let q = (point - self.center).abs() - self.half_size.add_scalar(-self.rounding);
+        let outside = q.map(|c| c.max(0.0)).norm();
+        let inside = q.max().min(0.0);
+        outside + inside - self.rounding
+    }
+}
+
+/// A torus around the Y axis.
+#[derive(Debug, Clone)]
+pub struct Torus {
+    major_radius: Real,
+    minor_radius: Real,
+    center: Point3<Real>,
+}
+
+impl Torus {
+    /// Create a torus with the given ring and tube radii at the origin.
+    pub fn new(major_radius: Real, minor_radius: Real) -> Self {
+        assert!(
+            major_radius > 0.0 && minor_radius > 0.0,
+            "Torus radii must be positive"
+        );
+        Self {
+            major_radius,
+            minor_radius,
+            center: Point3::origin(),
+        }
+    }
+
+    /// Set the center of the torus.
+    pub fn center(mut self, x: Real, y: Real, z: Real) -> Self {
+        self.center = Point3::new(x, y, z);
+        self
+    }
+}
+
+impl Sdf for Torus {
+    fn distance(&self, point: &Point3<Real>) -> Real {
+        let p = point - self.center;
+        let ring = (p.x * p.x + p.z * p.z).sqrt() - self.major_radius;
+        (ring * ring + p.y * p.y).sqrt() - self.minor_radius
+    }
+}
+
+/// A capsule along the Y axis: a cylinder with hemispherical ends.
+#[derive(Debug, Clone)]
+pub struct Capsule {
+    radius: Real,
+    height: Real,
+    center: Point3<Real>,
+}
+
+impl Capsule {
+    /// Create a capsule at the origin; `height` is the length of the cylindrical part.
+    pub fn new(radius: Real, height: Real) -> Self {
+        assert!(radius > 0.0, "Capsule radius must be positive");
+        assert!(height >= 0.0, "Capsule height must not be negative");
+        Self {
+            radius,
+            height,
+            center: Point3::origin(),
+        }
+    }
+
+    /// Set the center of the capsule.
+    pub fn center(mut self, x: Real, y: Real, z: Real) -> Self {
+        self.center = Point3::new(x, y, z);
+        self
+    }
+}
+
+impl Sdf for Capsule {
+    fn distance(&self, point: &Point3<Real>) -> Real {
+        let mut p = point - self.center;
+        let half_height = self.height / 2.0;
+        p.y -= p.y.clamp(-half_height, half_height);
+        p.norm() - self.radius
+    }
+}
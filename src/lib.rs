@@ -7,14 +7,37 @@ use std::path::Path;
 use thiserror::Error;
 
 // Re-exports for convenience
-pub use types::{Face, Mesh, Vertex};
+pub use compact::CompactMesh;
+pub use types::{Face, MassProperties, Mesh, Real, Vertex, VertexAttribute};
+
+/// Mathematical constants for [`Real`].
+#[cfg(not(feature = "f64"))]
+pub use std::f32::consts;
+/// Mathematical constants for [`Real`].
+#[cfg(feature = "f64")]
+pub use std::f64::consts;
 
 // Module declarations
+pub mod bounds;
+pub mod compact;
+pub mod curves;
+pub mod decompose;
 pub mod exporters;
+pub mod generators;
+pub mod implicit;
+pub mod lsystem;
+pub mod noise;
 pub mod plugin;
 pub mod primitives;
+pub mod spatial;
+pub mod text;
 pub mod transforms;
 pub mod types;
+pub mod voxel;
+
+mod hull;
+mod parallel;
+mod triangulate;
 
 // Re-export common transforms for convenience
 pub use transforms::advanced::{Matrix, Mirror, Quaternion};
@@ -69,6 +92,50 @@ impl Model {
         self
     }
 
+    /// Move the model so the center of its bounding box is at the origin.
+    pub fn center_on_origin(&mut self) -> &mut Self {
+        if let Some(aabb) = self.mesh.aabb() {
+            let center = aabb.center();
+            self.apply(Translate::new(-center.x, -center.y, -center.z));
+        }
+        self
+    }
+
+    /// Scale the model evenly to the largest size that fits a box of the given
+    /// size, and center it on the origin.
+    ///
+    /// Axes along which the model is flat do not limit the scale. A box that is not
+    /// positive along every axis holds nothing, so the model is left unchanged.
+    pub fn fit_into_box(&mut self, x: Real, y: Real, z: Real) -> &mut Self {
+        if !(x > 0.0 && y > 0.0 && z > 0.0) {
+            return self;
+        }
+        let Some(aabb) = self.mesh.aabb() else {
+            return self;
+        };
+        let size = aabb.size();
+        let scale = [x, y, z]
+            .iter()
+            .zip(size.iter())
+            .filter(|(_, &extent)| extent > 0.0)
+            .map(|(&target, &extent)| target / extent)
+            .fold(Real::INFINITY, Real::min);
+        self.center_on_origin();
+        if scale.is_finite() {
+            self.apply(Scale::uniform(scale));
+        }
+        self
+    }
+
+    /// Move the model up or down so its lowest point rests on the ground plane,
+    /// Y = 0.
+    pub fn place_on_ground(&mut self) -> &mut Self {
+        if let Some(aabb) = self.mesh.aabb() {
+            self.apply(Translate::new(0.0, -aabb.min.y, 0.0));
+        }
+        self
+    }
+
     /// Export the model to OBJ format.
     pub fn export_obj<P: AsRef<Path>>(&self, path: P) -> Result<()> {
         exporters::obj::export_obj(self, path)
@@ -79,6 +146,11 @@ impl Model {
         exporters::stl::export_stl(self, path)
     }
 
+    /// Export the model to PLY format.
+    pub fn export_ply<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        exporters::ply::export_ply(self, path)
+    }
+
     /// Export the model to glTF format.
     pub fn export_gltf<P: AsRef<Path>>(&self, path: P) -> Result<()> {
         exporters::gltf::export_gltf(self, path)
@@ -0,0 +1,88 @@
+//! Lindenmayer systems for plants and other branching structures.
+//!
+//! An [`LSystem`] rewrites a string of symbols by its rules a number of times, and a
+//! [`Turtle`] reads the result as drawing commands to build the model.
+
+mod turtle;
+
+pub use turtle::{BranchStyle, Turtle};
+
+use crate::noise::SplitMix64;
+use crate::Real;
+use std::collections::HashMap;
+
+/// Builder for a grammar that rewrites every symbol in parallel at each iteration.
+///
+/// Symbols without a rule are copied unchanged. A symbol with several rules is
+/// stochastic: each time it is rewritten one rule is picked at random, in proportion
+/// to its weight. The same seed always expands to the same string.
+pub struct LSystem {
+    axiom: String,
+    rules: HashMap<char, Vec<(Real, String)>>,
+    seed: u64,
+}
+
+impl LSystem {
+    /// Create a new L-system starting from the given axiom.
+    pub fn new(axiom: impl Into<String>) -> Self {
+        Self {
+            axiom: axiom.into(),
+            rules: HashMap::new(),
+            seed: 0,
+        }
+    }
+
+    /// Add a rule rewriting `symbol` to `replacement`.
+    pub fn rule(self, symbol: char, replacement: impl Into<String>) -> Self {
+        self.weighted_rule(symbol, 1.0, replacement)
+    }
+
+    /// Add a rule rewriting `symbol` to `replacement`, picked in proportion to
+    /// `weight` among the rules for the same symbol.
+    pub fn weighted_rule(
+        mut self,
+        symbol: char,
+        weight: Real,
+        replacement: impl Into<String>,
+    ) -> Self {
+        assert!(weight > 0.0, "L-system rule weight must be positive");
+        self.rules
+            .entry(symbol)
+            .or_default()
+            .push((weight, replacement.into()));
+        self
+    }
+
+    /// Set the seed that picks among stochastic rules.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Rewrite the axiom `iterations` times.
+    pub fn expand(&self, iterations: usize) -> String {
+        let mut rng = SplitMix64::new(self.seed);
+        let mut current = self.axiom.clone();
+        for _ in 0..iterations {
+            let mut next = String::with_capacity(current.len() * 2);
+            for symbol in current.chars() {
+                match self.rules.get(&symbol).map(Vec::as_slice) {
+                    None => next.push(symbol),
+                    Some([(_, replacement)]) => next.push_str(replacement),
+                    Some(rules) => {
+                        let total: Real = rules.iter().map(|(weight, _)| weight).sum();
+                        let mut pick = rng.next_real() * total;
+                        let rule = rules.iter().find(|(weight, _)| {
+                            pick -= weight;
+                            pick < 0.0
+                        });
+                        // Rounding can leave a sliver past the last rule
+                        next.push_str(&rule.unwrap_or(&rules[rules.len() - 1]).1);
+                    }
+                }
+            }
+            current = next;
+        }
+        current
+    }
+}
@@ -0,0 +1,366 @@
+//! The turtle that turns an expanded L-system string into branches and leaves.
+
+use crate::generators::Sweep;
+use crate::noise::SplitMix64;
+use crate::primitives::Cone;
+use crate::types::Material;
+use crate::{Error, Face, Model, Real, Result, Vertex};
+use nalgebra::{Matrix3, Point3, Rotation3, Unit, Vector3};
+
+/// How the [`Turtle`] draws branches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BranchStyle {
+    /// A tapered cylinder for every step, each a closed solid of its own.
+    Cylinders,
+    /// One tube swept along each branch, tapering linearly from its base to its tip.
+    Swept,
+}
+
+/// Builder for the turtle interpreter that draws an L-system string in 3D.
+///
+/// The turtle starts at the origin heading up +Y, with its left along -X and its up
+/// along +Z, and understands the usual symbols:
+///
+/// - `F` draws a branch one step forward, `f` moves forward without drawing
+/// - `+` and `-` turn left and right, `&` and `^` pitch down and up, `\` and `/`
+///   roll left and right, all by the angle
+/// - `|` turns around and `$` rolls back level so left is horizontal
+/// - `[` and `]` save and restore the turtle to start and end a side branch
+/// - `!` shrinks the branch radius by the taper
+/// - `L` places a leaf card along the heading
+///
+/// Other symbols are ignored. Branches tip off at the taper of their last radius.
+/// Bark faces use the `bark` material and leaf faces the `leaf` material.
+pub struct Turtle {
+    step: Real,
+    angle: Real,
+    radius: Real,
+    taper: Real,
+    leaf_size: Real,
+    style: BranchStyle,
+    segments: usize,
+    angle_variation: Real,
+    length_variation: Real,
+    seed: u64,
+}
+
+impl Turtle {
+    /// Create a new turtle with default settings.
+    pub fn new() -> Self {
+        Self {
+            step: 1.0,
+            angle: 25.0,
+            radius: 0.05,
+            taper: 0.7,
+            leaf_size: 0.3,
+            style: BranchStyle::Swept,
+            segments: 8,
+            angle_variation: 0.0,
+            length_variation: 0.0,
+            seed: 0,
+        }
+    }
+
+    /// Set the distance moved by `F` and `f`.
+    pub fn step(mut self, step: Real) -> Self {
+        assert!(step > 0.0, "Turtle step must be positive");
+        self.step = step;
+        self
+    }
+
+    /// Set the angle of each turn, in degrees.
+    pub fn angle(mut self, degrees: Real) -> Self {
+        self.angle = degrees;
+        self
+    }
+
+    /// Set the radius of the trunk.
+    pub fn radius(mut self, radius: Real) -> Self {
+        assert!(radius > 0.0, "Turtle radius must be positive");
+        self.radius = radius;
+        self
+    }
+
+    /// Set the factor `!` and branch tips scale the radius by.
+    pub fn taper(mut self, taper: Real) -> Self {
+        assert!(
+            taper > 0.0 && taper <= 1.0,
+            "Turtle taper must be within (0, 1]"
+        );
+        self.taper = taper;
+        self
+    }
+
+    /// Set the length of leaf cards; they are half as wide.
+    pub fn leaf_size(mut self, size: Real) -> Self {
+        assert!(size > 0.0, "Turtle leaf size must be positive");
+        self.leaf_size = size;
+        self
+    }
+
+    /// Set how branches are drawn.
+    pub fn style(mut self, style: BranchStyle) -> Self {
+        self.style = style;
+        self
+    }
+
+    /// Set the number of segments around each branch.
+    pub fn segments(mut self, segments: usize) -> Self {
+        assert!(segments >= 3, "Turtle must have at least 3 segments");
+        self.segments = segments;
+        self
+    }
+
+    /// Set the largest random change to each turn, in degrees.
+    pub fn angle_variation(mut self, degrees: Real) -> Self {
+        assert!(
+            degrees >= 0.0,
+            "Turtle angle variation must not be negative"
+        );
+        self.angle_variation = degrees;
+        self
+    }
+
+    /// Set the largest random change to each step as a fraction of its length.
+    pub fn length_variation(mut self, fraction: Real) -> Self {
+        assert!(
+            (0.0..1.0).contains(&fraction),
+            "Turtle length variation must be within [0, 1)"
+        );
+        self.length_variation = fraction;
+        self
+    }
+
+    /// Set the seed of the random variation.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Draw the commands into a model.
+    ///
+    /// Fails if a `]` has no matching `[`; branches left open at the end are drawn.
+    pub fn build(&self, commands: &str) -> Result<Model> {
+        let mut model = Model::new("LSystem");
+        let mut rng = SplitMix64::new(self.seed);
+        let mut state = State {
+            position: Point3::origin(),
+            heading: Vector3::y(),
+            left: -Vector3::x(),
+            radius: self.radius,
+        };
+        let mut branch: Vec<(Point3<Real>, Real)> = Vec::new();
+        let mut stack = Vec::new();
+
+        for symbol in commands.chars() {
+            let turn = |rng: &mut SplitMix64, sign: Real| {
+                let variation = self.angle_variation * (2.0 * rng.next_real() - 1.0);
+                sign * (self.angle + variation).to_radians()
+            };
+            match symbol {
+                'F' | 'f' => {
+                    let variation = self.length_variation * (2.0 * rng.next_real() - 1.0);
+                    let length = self.step * (1.0 + variation);
+                    if symbol == 'f' {
+                        self.draw_branch(&mut model, std::mem::take(&mut branch));
+                    } else if branch.is_empty() {
+                        branch.push((state.position, state.radius));
+                    }
+                    state.position += state.heading * length;
+                    if symbol == 'F' {
+                        branch.push((state.position, state.radius));
+                    }
+                }
+                '+' => state.rotate(state.up(), turn(&mut rng, 1.0)),
+                '-' => state.rotate(state.up(), turn(&mut rng, -1.0)),
+                '&' => state.rotate(state.left, turn(&mut rng, 1.0)),
+                '^' => state.rotate(state.left, turn(&mut rng, -1.0)),
+                '\\' => state.rotate(state.heading, turn(&mut rng, 1.0)),
+                '/' => state.rotate(state.heading, turn(&mut rng, -1.0)),
+                '|' => state.rotate(state.up(), crate::consts::PI),
+                '$' => {
+                    let left = Vector3::y().cross(&state.heading);
+                    if left.norm() > 1e-6 {
+                        state.left = left.normalize();
+                    }
+                }
+                '!' => {
+                    state.radius *= self.taper;
+                    if let Some(last) = branch.last_mut() {
+                        last.1 = state.radius;
+                    }
+                }
+                '[' => stack.push((state.clone(), std::mem::take(&mut branch))),
+                ']' => {
+                    let (saved, parent) = stack.pop().ok_or_else(|| {
+                        Error::InvalidModelData("Unmatched ']' in turtle commands".to_string())
+                    })?;
+                    self.draw_branch(&mut model, std::mem::replace(&mut branch, parent));
+                    state = saved;
+                }
+                'L' => self.draw_leaf(&mut model, &state),
+                _ => {}
+            }
+        }
+
+        self.draw_branch(&mut model, branch);
+        for (_, open) in stack {
+            self.draw_branch(&mut model, open);
+        }
+
+        let used = |name: &str| {
+            model
+                .mesh
+                .face_materials
+                .iter()
+                .any(|material| material.as_deref() == Some(name))
+        };
+        for (name, diffuse) in [
+            ("bark", [0.4, 0.26, 0.13, 1.0]),
+            ("leaf", [0.2, 0.55, 0.15, 1.0]),
+        ] {
+            if used(name) {
+                let mut material = Material::new(name);
+                material.diffuse = diffuse;
+                model.mesh.materials.insert(name.to_string(), material);
+            }
+        }
+
+        Ok(model)
+    }
+
+    /// Draw a branch through its points and the radius at each.
+    fn draw_branch(&self, model: &mut Model, mut branch: Vec<(Point3<Real>, Real)>) {
+        branch.dedup_by(|a, b| a.0 == b.0);
+        if branch.len() < 2 {
+            return;
+        }
+        let last = branch.len() - 1;
+        branch[last].1 *= self.taper;
+
+        match self.style {
+            BranchStyle::Cylinders => {
+                for pair in branch.windows(2) {
+                    let ((start, base), (end, top)) = (pair[0], pair[1]);
+                    let axis = end - start;
+                    let cone = Cone::new()
+                        .radius(base)
+                        .top_radius(top)
+                        .height(axis.norm())
+                        .segments(self.segments)
+                        .build();
+                    let frame = frame_along(axis.normalize());
+                    append(model, cone, &frame, start + axis / 2.0, "bark");
+                }
+            }
+            BranchStyle::Swept => {
+                let base = branch[0].1;
+                let circle = (0..self.segments)
+                    .map(|i| {
+                        let t = 2.0 * crate::consts::PI * i as Real / self.segments as Real;
+                        (base * t.cos(), base * t.sin())
+                    })
+                    .collect();
+                let path = branch.iter().map(|(p, _)| (p.x, p.y, p.z)).collect();
+                let tube = Sweep::new(circle, path)
+                    .scale(branch[last].1 / base)
+                    .build();
+                append(model, tube, &Matrix3::identity(), Point3::origin(), "bark");
+            }
+        }
+    }
+
+    /// Draw a two-sided leaf card from the turtle's position along its heading.
+    fn draw_leaf(&self, model: &mut Model, state: &State) {
+        let half_width = state.left * self.leaf_size / 4.0;
+        let length = state.heading * self.leaf_size;
+        let corners = [
+            (state.position - half_width, (0.0, 0.0)),
+            (state.position + half_width, (1.0, 0.0)),
+            (state.position + half_width + length, (1.0, 1.0)),
+            (state.position - half_width + length, (0.0, 1.0)),
+        ];
+        // The front faces up and the back down; a single card would vanish from
+        // behind in renderers that cull back faces
+        for normal in [-state.up(), state.up()] {
+            let first = model.mesh.vertices.len();
+            for &(position, uv) in &corners {
+                model
+                    .mesh
+                    .add_vertex(Vertex::new(position, normal, Some(uv)));
+            }
+            let face = if normal.dot(&state.up()) > 0.0 {
+                Face::quad(first + 3, first + 2, first + 1, first)
+            } else {
+                Face::quad(first, first + 1, first + 2, first + 3)
+            };
+            model.mesh.add_face(face, Some("leaf".to_string()));
+        }
+    }
+}
+
+impl Default for Turtle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The turtle's position, orientation and branch radius.
+#[derive(Clone)]
+struct State {
+    position: Point3<Real>,
+    heading: Vector3<Real>,
+    left: Vector3<Real>,
+    radius: Real,
+}
+
+impl State {
+    fn up(&self) -> Vector3<Real> {
+        self.heading.cross(&self.left)
+    }
+
+    /// Rotate the orientation around `axis`, keeping it orthonormal as rounding
+    /// errors pile up over long strings.
+    fn rotate(&mut self, axis: Vector3<Real>, angle: Real) {
+        let rotation = Rotation3::from_axis_angle(&Unit::new_normalize(axis), angle);
+        self.heading = (rotation * self.heading).normalize();
+        let left = rotation * self.left;
+        self.left = (left - self.heading * self.heading.dot(&left)).normalize();
+    }
+}
+
+/// A rotation taking +Y to `direction`.
+fn frame_along(direction: Vector3<Real>) -> Matrix3<Real> {
+    let helper = if direction.x.abs() < 0.9 {
+        Vector3::x()
+    } else {
+        Vector3::z()
+    };
+    let side = helper.cross(&direction).normalize();
+    Matrix3::from_columns(&[direction.cross(&side), direction, side])
+}
+
+/// Append `part`, rotated by `frame` and moved to `offset`, with every face in
+/// `material`.
+fn append(
+    model: &mut Model,
+    part: Model,
+    frame: &Matrix3<Real>,
+    offset: Point3<Real>,
+    material: &str,
+) {
+    let first = model.mesh.vertices.len();
+    for vertex in part.mesh.vertices {
+        model.mesh.add_vertex(Vertex::new(
+            offset + frame * vertex.position.coords,
+            frame * vertex.normal,
+            vertex.tex_coords,
+        ));
+    }
+    for face in part.mesh.faces {
+        let indices = face.indices.iter().map(|&i| i + first).collect();
+        model
+            .mesh
+            .add_face(Face::new(indices), Some(material.to_string()));
+    }
+}
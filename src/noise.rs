@@ -0,0 +1,223 @@
+//! Seeded gradient noise for procedural terrain and textures.
+
+use crate::Real;
+
+/// The gradient noise function summed by [`Noise`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoiseKind {
+    /// Ken Perlin's improved noise on a square grid.
+    Perlin,
+    /// Simplex noise on a triangular grid, with fewer directional artifacts.
+    Simplex,
+}
+
+/// Fractal Brownian motion (fBm) built from octaves of 2D gradient noise.
+///
+/// Each octave samples the noise at `lacunarity` times the frequency of the one
+/// before and `persistence` times its amplitude. The sum is normalized by the total
+/// amplitude, so samples stay roughly within [-1, 1]. The same seed always produces
+/// the same noise.
+#[derive(Debug, Clone)]
+pub struct Noise {
+    kind: NoiseKind,
+    octaves: usize,
+    frequency: Real,
+    lacunarity: Real,
+    persistence: Real,
+    permutation: Vec<u8>,
+}
+
+impl Noise {
+    /// Create fBm of the given kind of noise with default settings.
+    pub fn new(kind: NoiseKind) -> Self {
+        Self {
+            kind,
+            octaves: 4,
+            frequency: 1.0,
+            lacunarity: 2.0,
+            persistence: 0.5,
+            permutation: permutation(0),
+        }
+    }
+
+    /// Create fBm of Perlin noise with default settings.
+    pub fn perlin() -> Self {
+        Self::new(NoiseKind::Perlin)
+    }
+
+    /// Create fBm of simplex noise with default settings.
+    pub fn simplex() -> Self {
+        Self::new(NoiseKind::Simplex)
+    }
+
+    /// Set the seed that picks the noise pattern.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.permutation = permutation(seed);
+        self
+    }
+
+    /// Set the number of octaves summed.
+    pub fn octaves(mut self, octaves: usize) -> Self {
+        assert!(octaves >= 1, "Noise must have at least 1 octave");
+        self.octaves = octaves;
+        self
+    }
+
+    /// Set the frequency of the first octave, in features per unit.
+    pub fn frequency(mut self, frequency: Real) -> Self {
+        assert!(frequency > 0.0, "Noise frequency must be positive");
+        self.frequency = frequency;
+        self
+    }
+
+    /// Set the frequency multiplier between octaves.
+    pub fn lacunarity(mut self, lacunarity: Real) -> Self {
+        assert!(lacunarity > 0.0, "Noise lacunarity must be positive");
+        self.lacunarity = lacunarity;
+        self
+    }
+
+    /// Set the amplitude multiplier between octaves.
+    pub fn persistence(mut self, persistence: Real) -> Self {
+        assert!(persistence > 0.0, "Noise persistence must be positive");
+        self.persistence = persistence;
+        self
+    }
+
+    /// Sample the noise at `(x, y)`.
+    pub fn sample(&self, x: Real, y: Real) -> Real {
+        let (mut frequency, mut amplitude) = (self.frequency, 1.0);
+        let (mut sum, mut total) = (0.0, 0.0);
+        for octave in 0..self.octaves {
+            // Shift each octave so their lattices do not line up at the origin
+            let shift = octave as Real * 17.31;
+            let (u, v) = (x * frequency + shift, y * frequency - shift);
+            let value = match self.kind {
+                NoiseKind::Perlin => self.perlin_2d(u, v),
+                NoiseKind::Simplex => self.simplex_2d(u, v),
+            };
+            sum += value * amplitude;
+            total += amplitude;
+            frequency *= self.lacunarity;
+            amplitude *= self.persistence;
+        }
+        sum / total
+    }
+
+    /// Hash of a lattice point.
+    fn hash(&self, x: i64, y: i64) -> usize {
+        let p = &self.permutation;
+        p[(p[(x & 255) as usize] as usize + (y & 255) as usize) & 511] as usize
+    }
+
+    /// Improved Perlin noise, within [-1, 1].
+    fn perlin_2d(&self, x: Real, y: Real) -> Real {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (fx, fy) = (x - x0, y - y0);
+        let (ix, iy) = (x0 as i64, y0 as i64);
+
+        let fade = |t: Real| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+        let (u, v) = (fade(fx), fade(fy));
+        let corner = |dx: i64, dy: i64| {
+            gradient(
+                self.hash(ix + dx, iy + dy),
+                fx - dx as Real,
+                fy - dy as Real,
+            )
+        };
+        let lerp = |a: Real, b: Real, t: Real| a + (b - a) * t;
+
+        lerp(
+            lerp(corner(0, 0), corner(1, 0), u),
+            lerp(corner(0, 1), corner(1, 1), u),
+            v,
+        )
+    }
+
+    /// Simplex noise, within about [-1, 1].
+    fn simplex_2d(&self, x: Real, y: Real) -> Real {
+        // Skew into the lattice of equilateral triangles and back
+        let f2 = 0.5 * (Real::sqrt(3.0) - 1.0);
+        let g2 = (3.0 - Real::sqrt(3.0)) / 6.0;
+
+        let s = (x + y) * f2;
+        let (i, j) = ((x + s).floor(), (y + s).floor());
+        let t = (i + j) * g2;
+        let (x0, y0) = (x - (i - t), y - (j - t));
+
+        // The second corner depends on which triangle of the square the point is in
+        let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+        let offsets = [
+            (0, 0, x0, y0),
+            (i1, j1, x0 - i1 as Real + g2, y0 - j1 as Real + g2),
+            (1, 1, x0 - 1.0 + 2.0 * g2, y0 - 1.0 + 2.0 * g2),
+        ];
+
+        let (i, j) = (i as i64, j as i64);
+        let sum: Real = offsets
+            .iter()
+            .map(|&(di, dj, dx, dy)| {
+                let falloff = 0.5 - dx * dx - dy * dy;
+                if falloff <= 0.0 {
+                    0.0
+                } else {
+                    let falloff = falloff * falloff;
+                    falloff * falloff * gradient(self.hash(i + di, j + dj), dx, dy)
+                }
+            })
+            .sum();
+        sum * 70.0
+    }
+}
+
+/// Dot product of the offset `(x, y)` with one of eight lattice gradients.
+fn gradient(hash: usize, x: Real, y: Real) -> Real {
+    match hash & 7 {
+        0 => x + y,
+        1 => x - y,
+        2 => -x + y,
+        3 => -x - y,
+        4 => x,
+        5 => -x,
+        6 => y,
+        _ => -y,
+    }
+}
+
+/// Shuffle the numbers 0 to 255 with a seeded generator, repeated twice so lookups
+/// need no wrapping.
+fn permutation(seed: u64) -> Vec<u8> {
+    let mut rng = SplitMix64::new(seed);
+    let mut table: Vec<u8> = (0..=255).collect();
+    for i in (1..256).rev() {
+        let j = (rng.next_u64() % (i as u64 + 1)) as usize;
+        table.swap(i, j);
+    }
+    table.extend_from_within(..);
+    table
+}
+
+/// A small, fast generator for seeded procedural variation (SplitMix64).
+pub(crate) struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A uniform sample in `[0, 1)`.
+    pub(crate) fn next_real(&mut self) -> Real {
+        // The top 24 bits fit the mantissa of either float type exactly
+        (self.next_u64() >> 40) as Real / (1u64 << 24) as Real
+    }
+}
@@ -0,0 +1,94 @@
+//! Helpers for optionally parallel iteration.
+//!
+//! With the `parallel` feature enabled these run on the rayon thread pool in chunks
+//! of at least [`CHUNK_SIZE`] items; without it they are plain serial loops. Every
+//! helper processes items independently and returns results in input order, so the
+//! output is identical either way.
+
+use crate::Result;
+use std::io::Write;
+
+/// Minimum number of items handed to one worker at a time.
+pub(crate) const CHUNK_SIZE: usize = 4096;
+
+/// Number of chunks serialized before they are flushed to the writer.
+#[cfg(feature = "parallel")]
+const CHUNKS_PER_BATCH: usize = 64;
+
+/// Apply `f` to every item.
+pub(crate) fn for_each_mut<T, F>(items: &mut [T], f: F)
+where
+    T: Send,
+    F: Fn(&mut T) + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        items.par_iter_mut().with_min_len(CHUNK_SIZE).for_each(f);
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    items.iter_mut().for_each(f);
+}
+
+/// Map every index in `0..count` to a value, preserving order.
+pub(crate) fn map_indices<U, F>(count: usize, f: F) -> Vec<U>
+where
+    U: Send,
+    F: Fn(usize) -> U + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        (0..count)
+            .into_par_iter()
+            .with_min_len(CHUNK_SIZE)
+            .map(f)
+            .collect()
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    (0..count).map(f).collect()
+}
+
+/// Serialize `items` into `writer` using `format` for each item.
+///
+/// `format` receives the item's index and appends its bytes to a chunk buffer.
+/// Chunks are serialized independently and written in order, so the output is
+/// byte-for-byte the same with or without the `parallel` feature.
+pub(crate) fn write_chunks<T, W, F>(writer: &mut W, items: &[T], format: F) -> Result<()>
+where
+    T: Sync,
+    W: Write,
+    F: Fn(&mut Vec<u8>, usize, &T) -> std::io::Result<()> + Sync + Send,
+{
+    let serialize_chunk = |(chunk_idx, chunk): (usize, &[T])| -> std::io::Result<Vec<u8>> {
+        let mut buffer = Vec::new();
+        for (i, item) in chunk.iter().enumerate() {
+            format(&mut buffer, chunk_idx * CHUNK_SIZE + i, item)?;
+        }
+        Ok(buffer)
+    };
+
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        let chunks: Vec<_> = items.chunks(CHUNK_SIZE).enumerate().collect();
+        for batch in chunks.chunks(CHUNKS_PER_BATCH) {
+            let buffers = batch
+                .par_iter()
+                .map(|&chunk| serialize_chunk(chunk))
+                .collect::<std::io::Result<Vec<_>>>()?;
+            for buffer in buffers {
+                writer.write_all(&buffer)?;
+            }
+        }
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    for chunk in items.chunks(CHUNK_SIZE).enumerate() {
+        writer.write_all(&serialize_chunk(chunk)?)?;
+    }
+
+    Ok(())
+}
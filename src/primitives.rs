@@ -1,12 +1,21 @@
 //! Primitive 3D shapes that can be used as building blocks.
 
-use crate::{Face, Model, Vertex};
-use nalgebra::{Point3, Vector3};
+use crate::generators::{revolve_disc, revolve_upright, stitch_rows, ProfilePoint, FULL_TURN};
+use crate::noise::Noise;
+use crate::types::to_f32;
+use crate::{Error, Face, Model, Real, Result, Vertex};
+use nalgebra::{Point2, Point3, Vector3};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+mod mechanical;
+
+pub use mechanical::{Gear, Spring, Thread};
 
 /// Builder for creating a cube primitive.
 pub struct Cube {
-    size: f32,
-    center: (f32, f32, f32),
+    size: Real,
+    center: (Real, Real, Real),
     with_uvs: bool,
 }
 
@@ -21,14 +30,14 @@ impl Cube {
     }
 
     /// Set the size of the cube.
-    pub fn size(mut self, size: f32) -> Self {
+    pub fn size(mut self, size: Real) -> Self {
         assert!(size > 0.0, "Cube size must be positive");
         self.size = size;
         self
     }
 
     /// Set the center position of the cube.
-    pub fn center(mut self, x: f32, y: f32, z: f32) -> Self {
+    pub fn center(mut self, x: Real, y: Real, z: Real) -> Self {
         self.center = (x, y, z);
         self
     }
@@ -163,8 +172,8 @@ impl Default for Cube {
 
 /// Builder for creating a sphere primitive.
 pub struct Sphere {
-    radius: f32,
-    center: (f32, f32, f32),
+    radius: Real,
+    center: (Real, Real, Real),
     segments: usize,
     rings: usize,
 }
@@ -181,14 +190,14 @@ impl Sphere {
     }
 
     /// Set the radius of the sphere.
-    pub fn radius(mut self, radius: f32) -> Self {
+    pub fn radius(mut self, radius: Real) -> Self {
         assert!(radius > 0.0, "Sphere radius must be positive");
         self.radius = radius;
         self
     }
 
     /// Set the center position of the sphere.
-    pub fn center(mut self, x: f32, y: f32, z: f32) -> Self {
+    pub fn center(mut self, x: Real, y: Real, z: Real) -> Self {
         self.center = (x, y, z);
         self
     }
@@ -229,7 +238,7 @@ impl Sphere {
         // Generate vertices for rings
         let mut ring_indices = Vec::new();
         for i in 0..self.rings - 1 {
-            let phi = std::f32::consts::PI * (i as f32 + 1.0) / self.rings as f32;
+            let phi = crate::consts::PI * (i as Real + 1.0) / self.rings as Real;
             let cos_phi = phi.cos();
             let sin_phi = phi.sin();
 
@@ -238,7 +247,7 @@ impl Sphere {
 
             let mut ring = Vec::new();
             for j in 0..self.segments {
-                let theta = 2.0 * std::f32::consts::PI * j as f32 / self.segments as f32;
+                let theta = 2.0 * crate::consts::PI * j as Real / self.segments as Real;
                 let cos_theta = theta.cos();
                 let sin_theta = theta.sin();
 
@@ -316,9 +325,9 @@ impl Default for Sphere {
 
 /// Builder for creating a cylinder primitive.
 pub struct Cylinder {
-    radius: f32,
-    height: f32,
-    center: (f32, f32, f32),
+    radius: Real,
+    height: Real,
+    center: (Real, Real, Real),
     segments: usize,
     caps: bool,
 }
@@ -336,21 +345,21 @@ impl Cylinder {
     }
 
     /// Set the radius of the cylinder.
-    pub fn radius(mut self, radius: f32) -> Self {
+    pub fn radius(mut self, radius: Real) -> Self {
         assert!(radius > 0.0, "Cylinder radius must be positive");
         self.radius = radius;
         self
     }
 
     /// Set the height of the cylinder.
-    pub fn height(mut self, height: f32) -> Self {
+    pub fn height(mut self, height: Real) -> Self {
         assert!(height > 0.0, "Cylinder height must be positive");
         self.height = height;
         self
     }
 
     /// Set the center position of the cylinder.
-    pub fn center(mut self, x: f32, y: f32, z: f32) -> Self {
+    pub fn center(mut self, x: Real, y: Real, z: Real) -> Self {
         self.center = (x, y, z);
         self
     }
@@ -379,7 +388,7 @@ impl Cylinder {
         let mut bottom_indices = Vec::new();
 
         for i in 0..self.segments {
-            let theta = 2.0 * std::f32::consts::PI * i as f32 / self.segments as f32;
+            let theta = 2.0 * crate::consts::PI * i as Real / self.segments as Real;
             let cos_theta = theta.cos();
             let sin_theta = theta.sin();
 
@@ -473,3 +482,1263 @@ impl Default for Cylinder {
         Self::new()
     }
 }
+
+/// Builder for creating a cone or frustum primitive.
+///
+/// The cone's axis is the Y axis. With the default top radius of zero it is a
+/// pointed cone; a positive top radius produces a truncated cone (frustum).
+pub struct Cone {
+    radius: Real,
+    top_radius: Real,
+    height: Real,
+    center: (Real, Real, Real),
+    segments: usize,
+    caps: bool,
+}
+
+impl Cone {
+    /// Create a new cone builder with default settings.
+    pub fn new() -> Self {
+        Self {
+            radius: 1.0,
+            top_radius: 0.0,
+            height: 2.0,
+            center: (0.0, 0.0, 0.0),
+            segments: 32,
+            caps: true,
+        }
+    }
+
+    /// Set the radius of the base of the cone.
+    pub fn radius(mut self, radius: Real) -> Self {
+        assert!(radius >= 0.0, "Cone radius must not be negative");
+        self.radius = radius;
+        self
+    }
+
+    /// Set the radius of the top of the cone; a positive value makes a frustum.
+    pub fn top_radius(mut self, top_radius: Real) -> Self {
+        assert!(top_radius >= 0.0, "Cone top radius must not be negative");
+        self.top_radius = top_radius;
+        self
+    }
+
+    /// Set the height of the cone.
+    pub fn height(mut self, height: Real) -> Self {
+        assert!(height > 0.0, "Cone height must be positive");
+        self.height = height;
+        self
+    }
+
+    /// Set the center position of the cone (the midpoint of its axis).
+    pub fn center(mut self, x: Real, y: Real, z: Real) -> Self {
+        self.center = (x, y, z);
+        self
+    }
+
+    /// Set the number of segments around the circumference.
+    pub fn segments(mut self, segments: usize) -> Self {
+        assert!(segments >= 3, "Cone must have at least 3 segments");
+        self.segments = segments;
+        self
+    }
+
+    /// Set whether to generate the base (and top, for a frustum) caps.
+    pub fn caps(mut self, caps: bool) -> Self {
+        self.caps = caps;
+        self
+    }
+
+    /// Build the cone model.
+    pub fn build(self) -> Model {
+        assert!(
+            self.radius > 0.0 || self.top_radius > 0.0,
+            "Cone must have a positive base or top radius"
+        );
+
+        let mut model = Model::new("Cone");
+        let half_height = self.height / 2.0;
+
+        // The side normal is perpendicular to the slanted edge of the profile
+        let slope = Vector3::new(self.height, self.radius - self.top_radius, 0.0).normalize();
+        let profile = [
+            ProfilePoint::new(self.radius, -half_height, (slope.x, slope.y), 0.0),
+            ProfilePoint::new(self.top_radius, half_height, (slope.x, slope.y), 1.0),
+        ];
+        let frame = revolve_upright(&mut model, self.center, self.segments, &profile);
+
+        if self.caps {
+            if self.radius > 0.0 {
+                let base = Point2::new(self.radius, -half_height);
+                revolve_disc(&mut model, &frame, FULL_TURN, self.segments, base, true);
+            }
+            if self.top_radius > 0.0 {
+                let top = Point2::new(self.top_radius, half_height);
+                revolve_disc(&mut model, &frame, FULL_TURN, self.segments, top, false);
+            }
+        }
+
+        model
+    }
+}
+
+impl Default for Cone {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builder for creating a torus primitive.
+///
+/// The torus lies in the XZ plane around the Y axis.
+pub struct Torus {
+    major_radius: Real,
+    minor_radius: Real,
+    center: (Real, Real, Real),
+    segments: usize,
+    rings: usize,
+}
+
+impl Torus {
+    /// Create a new torus builder with default settings.
+    pub fn new() -> Self {
+        Self {
+            major_radius: 1.0,
+            minor_radius: 0.25,
+            center: (0.0, 0.0, 0.0),
+            segments: 32,
+            rings: 16,
+        }
+    }
+
+    /// Set the distance from the center of the torus to the center of the tube.
+    pub fn major_radius(mut self, major_radius: Real) -> Self {
+        assert!(major_radius > 0.0, "Torus major radius must be positive");
+        self.major_radius = major_radius;
+        self
+    }
+
+    /// Set the radius of the tube.
+    pub fn minor_radius(mut self, minor_radius: Real) -> Self {
+        assert!(minor_radius > 0.0, "Torus minor radius must be positive");
+        self.minor_radius = minor_radius;
+        self
+    }
+
+    /// Set the center position of the torus.
+    pub fn center(mut self, x: Real, y: Real, z: Real) -> Self {
+        self.center = (x, y, z);
+        self
+    }
+
+    /// Set the number of segments around the main ring.
+    pub fn segments(mut self, segments: usize) -> Self {
+        assert!(segments >= 3, "Torus must have at least 3 segments");
+        self.segments = segments;
+        self
+    }
+
+    /// Set the number of rings around the tube.
+    pub fn rings(mut self, rings: usize) -> Self {
+        assert!(rings >= 3, "Torus must have at least 3 rings");
+        self.rings = rings;
+        self
+    }
+
+    /// Build the torus model.
+    pub fn build(self) -> Model {
+        assert!(
+            self.minor_radius < self.major_radius,
+            "Torus minor radius must be smaller than the major radius"
+        );
+
+        let mut model = Model::new("Torus");
+
+        // The profile is a circle around the tube center, starting on the outside
+        let profile: Vec<ProfilePoint> = (0..=self.rings)
+            .map(|i| {
+                let phi = FULL_TURN * (i % self.rings) as Real / self.rings as Real;
+                let (sin_phi, cos_phi) = phi.sin_cos();
+                ProfilePoint::new(
+                    self.major_radius + self.minor_radius * cos_phi,
+                    self.minor_radius * sin_phi,
+                    (cos_phi, sin_phi),
+                    i as f32 / self.rings as f32,
+                )
+            })
+            .collect();
+        revolve_upright(&mut model, self.center, self.segments, &profile);
+
+        model
+    }
+}
+
+impl Default for Torus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builder for creating a capsule primitive.
+///
+/// A capsule is a cylinder along the Y axis closed by two hemispheres.
+pub struct Capsule {
+    radius: Real,
+    height: Real,
+    center: (Real, Real, Real),
+    segments: usize,
+    rings: usize,
+}
+
+impl Capsule {
+    /// Create a new capsule builder with default settings.
+    pub fn new() -> Self {
+        Self {
+            radius: 0.5,
+            height: 1.0,
+            center: (0.0, 0.0, 0.0),
+            segments: 32,
+            rings: 8,
+        }
+    }
+
+    /// Set the radius of the capsule.
+    pub fn radius(mut self, radius: Real) -> Self {
+        assert!(radius > 0.0, "Capsule radius must be positive");
+        self.radius = radius;
+        self
+    }
+
+    /// Set the height of the cylindrical section between the hemispheres.
+    ///
+    /// The total height of the capsule is `height + 2 * radius`. A height of zero
+    /// produces a sphere.
+    pub fn height(mut self, height: Real) -> Self {
+        assert!(height >= 0.0, "Capsule height must not be negative");
+        self.height = height;
+        self
+    }
+
+    /// Set the center position of the capsule.
+    pub fn center(mut self, x: Real, y: Real, z: Real) -> Self {
+        self.center = (x, y, z);
+        self
+    }
+
+    /// Set the number of segments around the circumference.
+    pub fn segments(mut self, segments: usize) -> Self {
+        assert!(segments >= 3, "Capsule must have at least 3 segments");
+        self.segments = segments;
+        self
+    }
+
+    /// Set the number of rings in each hemisphere.
+    pub fn rings(mut self, rings: usize) -> Self {
+        assert!(
+            rings >= 1,
+            "Capsule must have at least 1 ring per hemisphere"
+        );
+        self.rings = rings;
+        self
+    }
+
+    /// Build the capsule model.
+    pub fn build(self) -> Model {
+        let mut model = Model::new("Capsule");
+        let half_height = self.height / 2.0;
+        let total_length = crate::consts::PI * self.radius + self.height;
+
+        // Walk the profile from the bottom pole to the top pole. Texture coordinates
+        // follow the arc length so the texture is not stretched over the caps.
+        let mut profile = Vec::with_capacity(2 * self.rings + 2);
+        for top in [false, true] {
+            let (y_offset, first_angle, arc_offset) = if top {
+                (
+                    half_height,
+                    0.0,
+                    crate::consts::FRAC_PI_2 * self.radius + self.height,
+                )
+            } else {
+                (-half_height, -crate::consts::FRAC_PI_2, 0.0)
+            };
+
+            // Both hemispheres share the equator row when there is no cylindrical section
+            let first = if top && self.height == 0.0 { 1 } else { 0 };
+
+            for i in first..=self.rings {
+                let angle = crate::consts::FRAC_PI_2 * i as Real / self.rings as Real;
+                let (sin_phi, cos_phi) = (first_angle + angle).sin_cos();
+
+                // Poles get an exact zero radius so their degenerate faces are dropped
+                let is_pole = if top { i == self.rings } else { i == 0 };
+                let radius = if is_pole { 0.0 } else { self.radius * cos_phi };
+                let v = (arc_offset + angle * self.radius) / total_length;
+
+                profile.push(ProfilePoint::new(
+                    radius,
+                    y_offset + self.radius * sin_phi,
+                    (cos_phi, sin_phi),
+                    to_f32(v),
+                ));
+            }
+        }
+        revolve_upright(&mut model, self.center, self.segments, &profile);
+
+        model
+    }
+}
+
+impl Default for Capsule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builder for creating a subdivided plane (grid) primitive.
+///
+/// The plane lies in the XZ plane facing up the Y axis.
+pub struct Plane {
+    width: Real,
+    depth: Real,
+    center: (Real, Real, Real),
+    subdivisions: (usize, usize),
+}
+
+impl Plane {
+    /// Create a new plane builder with default settings.
+    pub fn new() -> Self {
+        Self {
+            width: 1.0,
+            depth: 1.0,
+            center: (0.0, 0.0, 0.0),
+            subdivisions: (1, 1),
+        }
+    }
+
+    /// Set the size of the plane along the X axis.
+    pub fn width(mut self, width: Real) -> Self {
+        assert!(width > 0.0, "Plane width must be positive");
+        self.width = width;
+        self
+    }
+
+    /// Set the size of the plane along the Z axis.
+    pub fn depth(mut self, depth: Real) -> Self {
+        assert!(depth > 0.0, "Plane depth must be positive");
+        self.depth = depth;
+        self
+    }
+
+    /// Set the center position of the plane.
+    pub fn center(mut self, x: Real, y: Real, z: Real) -> Self {
+        self.center = (x, y, z);
+        self
+    }
+
+    /// Set the number of grid cells along the X and Z axes.
+    pub fn subdivisions(mut self, x: usize, z: usize) -> Self {
+        assert!(x >= 1 && z >= 1, "Plane must have at least 1 subdivision");
+        self.subdivisions = (x, z);
+        self
+    }
+
+    /// Build the plane model.
+    pub fn build(self) -> Model {
+        let mut model = Model::new("Plane");
+        let (cx, cy, cz) = self.center;
+        let (columns, rows) = self.subdivisions;
+
+        // Rows run from the front edge (+Z) to the back edge (-Z), so `v` points away
+        // from a viewer looking down the -Z axis
+        for row in 0..=rows {
+            for column in 0..=columns {
+                let s = column as Real / columns as Real;
+                let t = row as Real / rows as Real;
+                model.mesh.add_vertex(Vertex::new(
+                    Point3::new(cx + (s - 0.5) * self.width, cy, cz + (0.5 - t) * self.depth),
+                    Vector3::new(0.0, 1.0, 0.0),
+                    Some((column as f32 / columns as f32, row as f32 / rows as f32)),
+                ));
+            }
+        }
+
+        for row in 0..rows {
+            for column in 0..columns {
+                let a = row * (columns + 1) + column;
+                let b = a + 1;
+                let c = b + columns + 1;
+                let d = a + columns + 1;
+                model.mesh.add_face(Face::triangle(a, b, c), None);
+                model.mesh.add_face(Face::triangle(a, c, d), None);
+            }
+        }
+
+        model
+    }
+}
+
+impl Default for Plane {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builder for creating an icosphere primitive.
+///
+/// An icosphere is a subdivided icosahedron projected onto a sphere. Unlike the UV
+/// [`Sphere`] its triangles are close to uniform in size and there are no pinched
+/// poles, which holds up better under deformation and when 3D printing. Each
+/// subdivision level quadruples the triangle count (`20 * 4^n`).
+///
+/// With [`dome`](Icosphere::dome) enabled only the upper hemisphere is kept and it
+/// is closed with a flat base, producing a geodesic dome.
+pub struct Icosphere {
+    radius: Real,
+    center: (Real, Real, Real),
+    subdivisions: usize,
+    dome: bool,
+}
+
+impl Icosphere {
+    /// Create a new icosphere builder with default settings.
+    pub fn new() -> Self {
+        Self {
+            radius: 1.0,
+            center: (0.0, 0.0, 0.0),
+            subdivisions: 2,
+            dome: false,
+        }
+    }
+
+    /// Set the radius of the icosphere.
+    pub fn radius(mut self, radius: Real) -> Self {
+        assert!(radius > 0.0, "Icosphere radius must be positive");
+        self.radius = radius;
+        self
+    }
+
+    /// Set the center position of the icosphere.
+    pub fn center(mut self, x: Real, y: Real, z: Real) -> Self {
+        self.center = (x, y, z);
+        self
+    }
+
+    /// Set how many times each triangle of the icosahedron is split into four.
+    pub fn subdivisions(mut self, subdivisions: usize) -> Self {
+        assert!(
+            subdivisions <= 10,
+            "Icosphere must have at most 10 subdivisions"
+        );
+        self.subdivisions = subdivisions;
+        self
+    }
+
+    /// Set whether to build a geodesic dome (the upper hemisphere with a flat base).
+    pub fn dome(mut self, dome: bool) -> Self {
+        self.dome = dome;
+        self
+    }
+
+    /// Build the icosphere model.
+    pub fn build(self) -> Model {
+        let mut model = Model::new(if self.dome {
+            "GeodesicDome"
+        } else {
+            "Icosphere"
+        });
+        let (cx, cy, cz) = self.center;
+        let center = Vector3::new(cx, cy, cz);
+
+        let (mut directions, mut triangles) = icosahedron();
+        for _ in 0..self.subdivisions {
+            triangles = subdivide_sphere(&mut directions, &triangles);
+        }
+        if self.dome {
+            triangles = clip_upper_hemisphere(&mut directions, &triangles);
+        }
+
+        // Vertices are split along the texture seam and at the poles so that every
+        // triangle gets continuous texture coordinates
+        let mut vertex_map: HashMap<(usize, u32), usize> = HashMap::new();
+        for triangle in &triangles {
+            let uvs = sphere_tex_coords(triangle.map(|i| directions[i]));
+            let indices = [0, 1, 2].map(|k| {
+                let index = triangle[k];
+                *vertex_map
+                    .entry((index, uvs[k].0.to_bits()))
+                    .or_insert_with(|| {
+                        let direction = directions[index];
+                        model.mesh.add_vertex(Vertex::new(
+                            Point3::from(center + direction * self.radius),
+                            direction,
+                            Some(uvs[k]),
+                        ))
+                    })
+            });
+            model
+                .mesh
+                .add_face(Face::triangle(indices[0], indices[1], indices[2]), None);
+        }
+
+        if self.dome {
+            // Every edge used in only one direction lies on the rim of the dome
+            let edges: HashSet<(usize, usize)> = triangles
+                .iter()
+                .flat_map(|t| [(t[0], t[1]), (t[1], t[2]), (t[2], t[0])])
+                .collect();
+
+            let normal = Vector3::new(0.0, -1.0, 0.0);
+            let base_center =
+                model
+                    .mesh
+                    .add_vertex(Vertex::new(Point3::from(center), normal, Some((0.5, 0.5))));
+            let mut base_map: HashMap<usize, usize> = HashMap::new();
+            let mut base_vertex = |model: &mut Model, index: usize| {
+                *base_map.entry(index).or_insert_with(|| {
+                    let direction = directions[index];
+                    model.mesh.add_vertex(Vertex::new(
+                        Point3::from(center + direction * self.radius),
+                        normal,
+                        Some((
+                            to_f32(0.5 + 0.5 * direction.x),
+                            to_f32(0.5 + 0.5 * direction.z),
+                        )),
+                    ))
+                })
+            };
+
+            for t in &triangles {
+                for (a, b) in [(t[0], t[1]), (t[1], t[2]), (t[2], t[0])] {
+                    if !edges.contains(&(b, a)) {
+                        let a = base_vertex(&mut model, a);
+                        let b = base_vertex(&mut model, b);
+                        model.mesh.add_face(Face::triangle(base_center, b, a), None);
+                    }
+                }
+            }
+        }
+
+        model
+    }
+}
+
+impl Default for Icosphere {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The regular convex polyhedra built by [`Platonic`].
+///
+/// The remaining Platonic solid, the cube, is built by [`Cube`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlatonicSolid {
+    /// Four triangular faces
+    Tetrahedron,
+    /// Eight triangular faces
+    Octahedron,
+    /// Twelve pentagonal faces
+    Dodecahedron,
+    /// Twenty triangular faces
+    Icosahedron,
+}
+
+/// Builder for creating a Platonic solid.
+///
+/// Faces are flat shaded: every face has its own vertices carrying the face normal.
+/// Pentagonal faces are kept as single polygons.
+pub struct Platonic {
+    solid: PlatonicSolid,
+    radius: Real,
+    center: (Real, Real, Real),
+}
+
+impl Platonic {
+    /// Create a new builder for the given solid with default settings.
+    pub fn new(solid: PlatonicSolid) -> Self {
+        Self {
+            solid,
+            radius: 1.0,
+            center: (0.0, 0.0, 0.0),
+        }
+    }
+
+    /// Create a new tetrahedron builder.
+    pub fn tetrahedron() -> Self {
+        Self::new(PlatonicSolid::Tetrahedron)
+    }
+
+    /// Create a new octahedron builder.
+    pub fn octahedron() -> Self {
+        Self::new(PlatonicSolid::Octahedron)
+    }
+
+    /// Create a new dodecahedron builder.
+    pub fn dodecahedron() -> Self {
+        Self::new(PlatonicSolid::Dodecahedron)
+    }
+
+    /// Create a new icosahedron builder.
+    pub fn icosahedron() -> Self {
+        Self::new(PlatonicSolid::Icosahedron)
+    }
+
+    /// Set the circumradius (the distance from the center to every vertex).
+    pub fn radius(mut self, radius: Real) -> Self {
+        assert!(radius > 0.0, "Platonic solid radius must be positive");
+        self.radius = radius;
+        self
+    }
+
+    /// Set the center position of the solid.
+    pub fn center(mut self, x: Real, y: Real, z: Real) -> Self {
+        self.center = (x, y, z);
+        self
+    }
+
+    /// Build the Platonic solid model.
+    pub fn build(self) -> Model {
+        let mut model = Model::new(format!("{:?}", self.solid));
+        let (cx, cy, cz) = self.center;
+        let center = Vector3::new(cx, cy, cz);
+
+        let (directions, faces): (Vec<Vector3<Real>>, Vec<Vec<usize>>) = match self.solid {
+            PlatonicSolid::Tetrahedron => {
+                let directions = [
+                    (1.0, 1.0, 1.0),
+                    (1.0, -1.0, -1.0),
+                    (-1.0, 1.0, -1.0),
+                    (-1.0, -1.0, 1.0),
+                ]
+                .iter()
+                .map(|&(x, y, z)| Vector3::new(x, y, z).normalize())
+                .collect();
+                let faces = vec![vec![0, 1, 2], vec![0, 1, 3], vec![0, 2, 3], vec![1, 2, 3]];
+                (directions, faces)
+            }
+            PlatonicSolid::Octahedron => {
+                let directions = vec![
+                    Vector3::x(),
+                    -Vector3::x(),
+                    Vector3::y(),
+                    -Vector3::y(),
+                    Vector3::z(),
+                    -Vector3::z(),
+                ];
+                let faces = (0..8)
+                    .map(|i| vec![i & 1, 2 + ((i >> 1) & 1), 4 + ((i >> 2) & 1)])
+                    .collect();
+                (directions, faces)
+            }
+            PlatonicSolid::Dodecahedron => dodecahedron(),
+            PlatonicSolid::Icosahedron => {
+                let (directions, triangles) = icosahedron();
+                (directions, triangles.iter().map(|t| t.to_vec()).collect())
+            }
+        };
+
+        for mut face in faces {
+            // Wind every face counter-clockwise when seen from outside
+            let [p0, p1, p2] = [0, 1, 2].map(|k| directions[face[k]]);
+            let mut normal = (p1 - p0).cross(&(p2 - p0)).normalize();
+            if normal.dot(&p0) < 0.0 {
+                face.reverse();
+                normal = -normal;
+            }
+
+            let sides = face.len();
+            let indices = face
+                .iter()
+                .enumerate()
+                .map(|(k, &index)| {
+                    let angle = 2.0 * std::f32::consts::PI * k as f32 / sides as f32;
+                    model.mesh.add_vertex(Vertex::new(
+                        Point3::from(center + directions[index] * self.radius),
+                        normal,
+                        Some((0.5 + 0.5 * angle.cos(), 0.5 + 0.5 * angle.sin())),
+                    ))
+                })
+                .collect();
+            model.mesh.add_face(Face::new(indices), None);
+        }
+
+        model
+    }
+}
+
+/// Builder for creating a heightfield terrain primitive.
+///
+/// Heights are sampled on a regular grid of points spread evenly over the footprint in
+/// the XZ plane and rise along the Y axis from the center. The first row of samples
+/// lies along the back (-Z) edge, so an image reads the right way up when the terrain
+/// is seen from above with -Z at the top.
+///
+/// By default only the surface is built. A skirt hangs walls from its edges to hide
+/// the cracks between neighbouring tiles; a base closes it into a solid block with a
+/// flat bottom, ready for 3D printing.
+pub struct Heightfield {
+    samples: Vec<Real>,
+    columns: usize,
+    rows: usize,
+    width: Real,
+    depth: Real,
+    height: Real,
+    center: (Real, Real, Real),
+    skirt: Option<Real>,
+    base: Option<Real>,
+}
+
+impl Heightfield {
+    /// Create a heightfield from `rows` rows of `columns` samples each, stored row by
+    /// row starting at the back edge.
+    pub fn new(columns: usize, rows: usize, samples: Vec<Real>) -> Self {
+        assert!(
+            columns >= 2 && rows >= 2,
+            "Heightfield must have at least 2 columns and 2 rows"
+        );
+        assert!(
+            samples.len() == columns * rows,
+            "Heightfield must have one sample per grid point"
+        );
+        Self {
+            samples,
+            columns,
+            rows,
+            width: 1.0,
+            depth: 1.0,
+            height: 1.0,
+            center: (0.0, 0.0, 0.0),
+            skirt: None,
+            base: None,
+        }
+    }
+
+    /// Create a heightfield by sampling `function` at `(s, t)` coordinates running from
+    /// 0 to 1 across the columns and rows.
+    pub fn from_fn<F>(columns: usize, rows: usize, function: F) -> Self
+    where
+        F: Fn(Real, Real) -> Real,
+    {
+        assert!(
+            columns >= 2 && rows >= 2,
+            "Heightfield must have at least 2 columns and 2 rows"
+        );
+        let mut samples = Vec::with_capacity(columns * rows);
+        for row in 0..rows {
+            for column in 0..columns {
+                samples.push(function(
+                    column as Real / (columns - 1) as Real,
+                    row as Real / (rows - 1) as Real,
+                ));
+            }
+        }
+        Self::new(columns, rows, samples)
+    }
+
+    /// Create a heightfield from noise sampled over the unit square, mapped from
+    /// [-1, 1] to heights in [0, 1].
+    pub fn from_noise(columns: usize, rows: usize, noise: &Noise) -> Self {
+        Self::from_fn(columns, rows, |s, t| {
+            ((noise.sample(s, t) + 1.0) / 2.0).clamp(0.0, 1.0)
+        })
+    }
+
+    /// Create a heightfield from a grayscale image file, with black at height 0 and
+    /// white at height 1.
+    ///
+    /// Binary and ASCII PGM files and PNG files are supported; color images are
+    /// converted to their luminance and alpha is ignored.
+    pub fn from_image<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_image_data(&std::fs::read(path)?)
+    }
+
+    /// Create a heightfield from the contents of a PGM or PNG image file.
+    pub fn from_image_data(data: &[u8]) -> Result<Self> {
+        let (columns, rows, samples) = if data.starts_with(b"\x89PNG") {
+            read_png(data)?
+        } else if data.starts_with(b"P2") || data.starts_with(b"P5") {
+            read_pgm(data)?
+        } else {
+            return Err(Error::ImportError(
+                "Heightfield image must be a PGM or PNG file".to_string(),
+            ));
+        };
+        if columns < 2 || rows < 2 {
+            return Err(Error::ImportError(
+                "Heightfield image must be at least 2 pixels wide and tall".to_string(),
+            ));
+        }
+        Ok(Self::new(columns, rows, samples))
+    }
+
+    /// Set the size of the footprint along the X and Z axes.
+    pub fn size(mut self, width: Real, depth: Real) -> Self {
+        assert!(
+            width > 0.0 && depth > 0.0,
+            "Heightfield size must be positive"
+        );
+        self.width = width;
+        self.depth = depth;
+        self
+    }
+
+    /// Set the height of a sample of 1.
+    pub fn height(mut self, height: Real) -> Self {
+        self.height = height;
+        self
+    }
+
+    /// Set the center of the footprint, at height 0.
+    pub fn center(mut self, x: Real, y: Real, z: Real) -> Self {
+        self.center = (x, y, z);
+        self
+    }
+
+    /// Hang walls of the given depth below the edges of the surface.
+    pub fn skirt(mut self, depth: Real) -> Self {
+        assert!(depth > 0.0, "Heightfield skirt depth must be positive");
+        self.skirt = Some(depth);
+        self
+    }
+
+    /// Close the terrain into a solid whose flat bottom lies `thickness` below its
+    /// lowest point. This replaces any skirt.
+    pub fn base(mut self, thickness: Real) -> Self {
+        assert!(
+            thickness > 0.0,
+            "Heightfield base thickness must be positive"
+        );
+        self.base = Some(thickness);
+        self
+    }
+
+    /// Build the heightfield model.
+    pub fn build(self) -> Model {
+        let mut model = Model::new("Heightfield");
+        let (cx, cy, cz) = self.center;
+        let (columns, rows) = (self.columns, self.rows);
+        let (dx, dz) = (
+            self.width / (columns - 1) as Real,
+            self.depth / (rows - 1) as Real,
+        );
+
+        let height =
+            |column: usize, row: usize| cy + self.samples[row * columns + column] * self.height;
+        let position = |column: usize, row: usize| {
+            Point3::new(
+                cx - self.width / 2.0 + column as Real * dx,
+                height(column, row),
+                cz - self.depth / 2.0 + row as Real * dz,
+            )
+        };
+
+        // Rows are added from the front edge (+Z) to the back edge (-Z) like a
+        // `Plane`, so the surface faces up and `v` runs away from the viewer
+        let first = model.mesh.vertices.len();
+        for r in 0..rows {
+            let row = rows - 1 - r;
+            for column in 0..columns {
+                // Normals from central differences, one-sided along the edges
+                let (left, right) = (column.saturating_sub(1), (column + 1).min(columns - 1));
+                let (back, front) = (row.saturating_sub(1), (row + 1).min(rows - 1));
+                let slope_x =
+                    (height(right, row) - height(left, row)) / ((right - left) as Real * dx);
+                let slope_z =
+                    (height(column, front) - height(column, back)) / ((front - back) as Real * dz);
+
+                model.mesh.add_vertex(Vertex::new(
+                    position(column, row),
+                    Vector3::new(-slope_x, 1.0, -slope_z).normalize(),
+                    Some((
+                        column as f32 / (columns - 1) as f32,
+                        r as f32 / (rows - 1) as f32,
+                    )),
+                ));
+            }
+        }
+        stitch_rows(&mut model, first, rows, columns);
+
+        if self.base.is_none() && self.skirt.is_none() {
+            return model;
+        }
+        let lowest = self
+            .samples
+            .iter()
+            .map(|sample| cy + sample * self.height)
+            .fold(Real::INFINITY, Real::min);
+        let bottom = |top: Real| match (self.base, self.skirt) {
+            (Some(thickness), _) => lowest - thickness,
+            (None, depth) => top - depth.unwrap_or(0.0),
+        };
+
+        // One wall along each edge, running counter-clockwise seen from above
+        let front: Vec<(usize, usize)> = (0..columns).map(|c| (c, rows - 1)).collect();
+        let right: Vec<(usize, usize)> = (0..rows).rev().map(|r| (columns - 1, r)).collect();
+        let back: Vec<(usize, usize)> = (0..columns).rev().map(|c| (c, 0)).collect();
+        let left: Vec<(usize, usize)> = (0..rows).map(|r| (0, r)).collect();
+        let walls = [
+            (&front, Vector3::z()),
+            (&right, Vector3::x()),
+            (&back, -Vector3::z()),
+            (&left, -Vector3::x()),
+        ];
+        for (points, normal) in walls {
+            let first = model.mesh.vertices.len();
+            let last = (points.len() - 1) as f32;
+            for top in [false, true] {
+                for (k, &(column, row)) in points.iter().enumerate() {
+                    let mut position = position(column, row);
+                    if !top {
+                        position.y = bottom(position.y);
+                    }
+                    let v = if top { 1.0 } else { 0.0 };
+                    model.mesh.add_vertex(Vertex::new(
+                        position,
+                        normal,
+                        Some((k as f32 / last, v)),
+                    ));
+                }
+            }
+            stitch_rows(&mut model, first, 2, points.len());
+        }
+
+        if let Some(thickness) = self.base {
+            // Fan the bottom from its center so every point along the walls is shared
+            let level = lowest - thickness;
+            let down = -Vector3::y();
+            let center = model.mesh.add_vertex(Vertex::new(
+                Point3::new(cx, level, cz),
+                down,
+                Some((0.5, 0.5)),
+            ));
+            let first = model.mesh.vertices.len();
+            let ring: Vec<(usize, usize)> = [&front, &right, &back, &left]
+                .iter()
+                .flat_map(|side| side[..side.len() - 1].iter().copied())
+                .collect();
+            for &(column, row) in &ring {
+                let mut position = position(column, row);
+                position.y = level;
+                let u = column as f32 / (columns - 1) as f32;
+                let v = 1.0 - row as f32 / (rows - 1) as f32;
+                model
+                    .mesh
+                    .add_vertex(Vertex::new(position, down, Some((u, v))));
+            }
+            for k in 0..ring.len() {
+                let (a, b) = (first + k, first + (k + 1) % ring.len());
+                model.mesh.add_face(Face::triangle(center, b, a), None);
+            }
+        }
+
+        model
+    }
+}
+
+/// Unit icosahedron with a vertex at each pole, wound counter-clockwise from outside.
+fn icosahedron() -> (Vec<Vector3<Real>>, Vec<[usize; 3]>) {
+    let ring_height = 1.0 / (5.0 as Real).sqrt();
+    let ring_radius = 2.0 * ring_height;
+
+    // Top pole, upper ring, lower ring (offset by half a step), bottom pole
+    let mut directions = vec![Vector3::new(0.0, 1.0, 0.0)];
+    for (y, offset) in [(ring_height, 0.0), (-ring_height, 0.5)] {
+        for k in 0..5 {
+            let theta = 2.0 * crate::consts::PI * (k as Real + offset) / 5.0;
+            let (sin_theta, cos_theta) = theta.sin_cos();
+            directions.push(Vector3::new(
+                ring_radius * cos_theta,
+                y,
+                -ring_radius * sin_theta,
+            ));
+        }
+    }
+    directions.push(Vector3::new(0.0, -1.0, 0.0));
+
+    let mut triangles = Vec::with_capacity(20);
+    for k in 0..5 {
+        let (upper, next_upper) = (1 + k, 1 + (k + 1) % 5);
+        let (lower, next_lower) = (6 + k, 6 + (k + 1) % 5);
+        triangles.push([0, upper, next_upper]);
+        triangles.push([upper, lower, next_upper]);
+        triangles.push([next_upper, lower, next_lower]);
+        triangles.push([11, next_lower, lower]);
+    }
+
+    (directions, triangles)
+}
+
+/// Unit dodecahedron built as the dual of [`icosahedron`].
+fn dodecahedron() -> (Vec<Vector3<Real>>, Vec<Vec<usize>>) {
+    let (ico_directions, ico_triangles) = icosahedron();
+
+    // One vertex at the center of each icosahedron face
+    let directions: Vec<Vector3<Real>> = ico_triangles
+        .iter()
+        .map(|t| (ico_directions[t[0]] + ico_directions[t[1]] + ico_directions[t[2]]).normalize())
+        .collect();
+
+    // One pentagon around each icosahedron vertex, ordered by angle around it
+    let faces = ico_directions
+        .iter()
+        .enumerate()
+        .map(|(vertex, axis)| {
+            let tangent = axis
+                .cross(&Vector3::x())
+                .try_normalize(1e-6)
+                .unwrap_or_else(|| axis.cross(&Vector3::z()).normalize());
+            let bitangent = axis.cross(&tangent);
+            let mut face: Vec<usize> = (0..ico_triangles.len())
+                .filter(|&t| ico_triangles[t].contains(&vertex))
+                .collect();
+            face.sort_by(|&a, &b| {
+                let angle = |i: usize| {
+                    directions[i]
+                        .dot(&bitangent)
+                        .atan2(directions[i].dot(&tangent))
+                };
+                angle(a).total_cmp(&angle(b))
+            });
+            face
+        })
+        .collect();
+
+    (directions, faces)
+}
+
+/// Split every triangle of a unit sphere mesh into four, projecting the new
+/// vertices onto the sphere.
+fn subdivide_sphere(
+    directions: &mut Vec<Vector3<Real>>,
+    triangles: &[[usize; 3]],
+) -> Vec<[usize; 3]> {
+    let mut midpoints: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut midpoint = |a: usize, b: usize, directions: &mut Vec<Vector3<Real>>| {
+        *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+            directions.push((directions[a] + directions[b]).normalize());
+            directions.len() - 1
+        })
+    };
+
+    let mut subdivided = Vec::with_capacity(triangles.len() * 4);
+    for &[a, b, c] in triangles {
+        let ab = midpoint(a, b, directions);
+        let bc = midpoint(b, c, directions);
+        let ca = midpoint(c, a, directions);
+        subdivided.push([a, ab, ca]);
+        subdivided.push([ab, b, bc]);
+        subdivided.push([ca, bc, c]);
+        subdivided.push([ab, bc, ca]);
+    }
+    subdivided
+}
+
+/// Keep the part of a unit sphere mesh with `y >= 0`, splitting triangles that
+/// cross the equator. New vertices are placed on the equator itself.
+fn clip_upper_hemisphere(
+    directions: &mut Vec<Vector3<Real>>,
+    triangles: &[[usize; 3]],
+) -> Vec<[usize; 3]> {
+    let mut crossings: HashMap<(usize, usize), usize> = HashMap::new();
+    let mut clipped = Vec::with_capacity(triangles.len() / 2);
+
+    for triangle in triangles {
+        let mut polygon = Vec::with_capacity(4);
+        for k in 0..3 {
+            let (a, b) = (triangle[k], triangle[(k + 1) % 3]);
+            let (ya, yb) = (directions[a].y, directions[b].y);
+            if ya >= 0.0 {
+                polygon.push(a);
+            }
+            if (ya > 0.0 && yb < 0.0) || (ya < 0.0 && yb > 0.0) {
+                let crossing = *crossings.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                    let t = ya / (ya - yb);
+                    let mut point = directions[a] + (directions[b] - directions[a]) * t;
+                    point.y = 0.0;
+                    directions.push(point.normalize());
+                    directions.len() - 1
+                });
+                polygon.push(crossing);
+            }
+        }
+
+        // Triangles lying flat on the equator have no area on the dome
+        if polygon.len() < 3 || polygon.iter().all(|&i| directions[i].y == 0.0) {
+            continue;
+        }
+        for k in 1..polygon.len() - 1 {
+            clipped.push([polygon[0], polygon[k], polygon[k + 1]]);
+        }
+    }
+
+    clipped
+}
+
+/// Spherical texture coordinates for the corners of a triangle on a unit sphere.
+///
+/// Triangles that straddle the seam get `u` values above 1 on the near side so
+/// they do not wrap across the whole texture, and a pole takes the average `u`
+/// of the other two corners.
+fn sphere_tex_coords(directions: [Vector3<Real>; 3]) -> [(f32, f32); 3] {
+    let is_pole = |d: &Vector3<Real>| d.x.abs() < 1e-9 && d.z.abs() < 1e-9;
+    let mut us = directions.map(|d| {
+        // Map to [0, 1), folding -0.0 into 0.0 so vertices on the seam are shared
+        let u = (-d.z).atan2(d.x) / (2.0 * crate::consts::PI);
+        if u < 0.0 {
+            u + 1.0
+        } else {
+            u.abs()
+        }
+    });
+
+    let known: Vec<Real> = (0..3)
+        .filter(|&k| !is_pole(&directions[k]))
+        .map(|k| us[k])
+        .collect();
+    let min = known.iter().copied().fold(Real::INFINITY, Real::min);
+    let max = known.iter().copied().fold(Real::NEG_INFINITY, Real::max);
+    if max - min > 0.5 {
+        for u in &mut us {
+            if *u < 0.5 {
+                *u += 1.0;
+            }
+        }
+    }
+
+    let pole_u = (0..3)
+        .filter(|&k| !is_pole(&directions[k]))
+        .map(|k| us[k])
+        .sum::<Real>()
+        / known.len() as Real;
+
+    [0, 1, 2].map(|k| {
+        let d = directions[k];
+        let u = if is_pole(&d) { pole_u } else { us[k] };
+        let v = 1.0 - d.y.clamp(-1.0, 1.0).acos() / crate::consts::PI;
+        (to_f32(u), to_f32(v))
+    })
+}
+
+/// Read a binary (P5) or ASCII (P2) PGM image as its size and samples in [0, 1].
+fn read_pgm(data: &[u8]) -> Result<(usize, usize, Vec<Real>)> {
+    let invalid = |message: &str| Error::ImportError(format!("Invalid PGM image: {}", message));
+
+    // The header is four whitespace-separated fields, with comments from `#` to the
+    // end of the line, followed by a single whitespace character before binary data
+    let mut fields = Vec::with_capacity(4);
+    let mut position = 0;
+    while fields.len() < 4 {
+        match data.get(position) {
+            None => return Err(invalid("truncated header")),
+            Some(b'#') => {
+                while data.get(position).is_some_and(|&c| c != b'\n') {
+                    position += 1;
+                }
+            }
+            Some(c) if c.is_ascii_whitespace() => position += 1,
+            Some(_) => {
+                let start = position;
+                while data.get(position).is_some_and(|c| !c.is_ascii_whitespace()) {
+                    position += 1;
+                }
+                fields.push(String::from_utf8_lossy(&data[start..position]).into_owned());
+            }
+        }
+    }
+    position += 1;
+
+    let number = |field: &str| {
+        field
+            .parse::<usize>()
+            .map_err(|_| invalid("bad number in header"))
+    };
+    let (width, height, max) = (
+        number(&fields[1])?,
+        number(&fields[2])?,
+        number(&fields[3])?,
+    );
+    if max == 0 || max > 65535 {
+        return Err(invalid("maximum value must be between 1 and 65535"));
+    }
+    let count = width
+        .checked_mul(height)
+        .ok_or_else(|| invalid("image is too large"))?;
+
+    let values: Vec<usize> = if fields[0] == "P5" {
+        let bytes = if max < 256 { 1 } else { 2 };
+        let end = count
+            .checked_mul(bytes)
+            .and_then(|size| size.checked_add(position))
+            .ok_or_else(|| invalid("image is too large"))?;
+        let pixels = data
+            .get(position..end)
+            .ok_or_else(|| invalid("truncated pixel data"))?;
+        if bytes == 1 {
+            pixels.iter().map(|&b| b as usize).collect()
+        } else {
+            pixels
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]) as usize)
+                .collect()
+        }
+    } else {
+        let text = String::from_utf8_lossy(data.get(position..).unwrap_or_default());
+        let values = text
+            .split_ascii_whitespace()
+            .take(count)
+            .map(number)
+            .collect::<Result<Vec<usize>>>()?;
+        if values.len() < count {
+            return Err(invalid("truncated pixel data"));
+        }
+        values
+    };
+
+    let samples = values
+        .into_iter()
+        .map(|value| value.min(max) as Real / max as Real)
+        .collect();
+    Ok((width, height, samples))
+}
+
+/// Read a PNG image as its size and luminance samples in [0, 1].
+fn read_png(data: &[u8]) -> Result<(usize, usize, Vec<Real>)> {
+    let invalid =
+        |error: png::DecodingError| Error::ImportError(format!("Invalid PNG image: {}", error));
+
+    let mut decoder = png::Decoder::new(std::io::Cursor::new(data));
+    decoder.set_transformations(png::Transformations::EXPAND);
+    let mut reader = decoder.read_info().map_err(invalid)?;
+    let size = reader
+        .output_buffer_size()
+        .ok_or_else(|| Error::ImportError("PNG image is too large".to_string()))?;
+    let mut buffer = vec![0; size];
+    let info = reader.next_frame(&mut buffer).map_err(invalid)?;
+
+    let channels = info.color_type.samples();
+    let wide = info.bit_depth == png::BitDepth::Sixteen;
+    let (width, height) = (info.width as usize, info.height as usize);
+    let channel = |row: &[u8], index: usize| -> Real {
+        if wide {
+            u16::from_be_bytes([row[index * 2], row[index * 2 + 1]]) as Real / 65535.0
+        } else {
+            row[index] as Real / 255.0
+        }
+    };
+
+    let mut samples = Vec::with_capacity(width * height);
+    for row in buffer.chunks(info.line_size).take(height) {
+        for x in 0..width {
+            let first = x * channels;
+            samples.push(if channels >= 3 {
+                // Rec. 709 luminance
+                0.2126 * channel(row, first)
+                    + 0.7152 * channel(row, first + 1)
+                    + 0.0722 * channel(row, first + 2)
+            } else {
+                channel(row, first)
+            });
+        }
+    }
+    Ok((width, height, samples))
+}
@@ -0,0 +1,667 @@
+//! Parametric mechanical parts: involute spur gears, ISO metric threads and helical
+//! springs.
+
+use crate::consts::PI;
+use crate::generators::{add_cap, stitch_rows, Extrude, Sweep};
+use crate::triangulate::{triangulate, triangulate_with_holes};
+use crate::types::to_f32;
+use crate::{Model, Real, Vertex};
+use nalgebra::{Point2, Point3, Vector3};
+
+/// Builder for creating an involute spur gear.
+///
+/// The gear is a disc of teeth around the Y axis, with the standard full-depth
+/// proportions: teeth reach one module outside the pitch circle and are cut 1.25
+/// modules inside it. Two gears with the same module and pressure angle mesh when
+/// their centers are the sum of their pitch radii apart. Teeth are not undercut, so
+/// gears with fewer than about 17 teeth are slightly fuller at the root than cut ones.
+pub struct Gear {
+    module: Real,
+    teeth: usize,
+    pressure_angle: Real,
+    width: Real,
+    bore: Real,
+    center: (Real, Real, Real),
+    segments: usize,
+}
+
+impl Gear {
+    /// Create a new gear builder with default settings.
+    pub fn new() -> Self {
+        Self {
+            module: 0.1,
+            teeth: 20,
+            pressure_angle: 20.0,
+            width: 0.5,
+            bore: 0.0,
+            center: (0.0, 0.0, 0.0),
+            segments: 8,
+        }
+    }
+
+    /// Set the module, the pitch diameter per tooth.
+    pub fn module(mut self, module: Real) -> Self {
+        assert!(module > 0.0, "Gear module must be positive");
+        self.module = module;
+        self
+    }
+
+    /// Set the number of teeth.
+    pub fn teeth(mut self, teeth: usize) -> Self {
+        assert!(teeth >= 4, "Gear must have at least 4 teeth");
+        self.teeth = teeth;
+        self
+    }
+
+    /// Set the pressure angle in degrees.
+    pub fn pressure_angle(mut self, degrees: Real) -> Self {
+        assert!(
+            degrees > 0.0 && degrees < 45.0,
+            "Gear pressure angle must be between 0 and 45 degrees"
+        );
+        self.pressure_angle = degrees;
+        self
+    }
+
+    /// Set the face width along the axis.
+    pub fn width(mut self, width: Real) -> Self {
+        assert!(width > 0.0, "Gear width must be positive");
+        self.width = width;
+        self
+    }
+
+    /// Set the diameter of the bore through the middle; zero leaves the gear solid.
+    pub fn bore(mut self, diameter: Real) -> Self {
+        assert!(diameter >= 0.0, "Gear bore must not be negative");
+        self.bore = diameter;
+        self
+    }
+
+    /// Set the center position of the gear.
+    pub fn center(mut self, x: Real, y: Real, z: Real) -> Self {
+        self.center = (x, y, z);
+        self
+    }
+
+    /// Set the number of segments along each tooth flank.
+    pub fn segments(mut self, segments: usize) -> Self {
+        assert!(segments >= 1, "Gear must have at least 1 segment per flank");
+        self.segments = segments;
+        self
+    }
+
+    /// Diameter of the pitch circle, where meshing gears roll on each other.
+    pub fn pitch_diameter(&self) -> Real {
+        self.module * self.teeth as Real
+    }
+
+    /// Build the gear model.
+    pub fn build(self) -> Model {
+        let teeth = self.teeth as Real;
+        let pitch_radius = self.pitch_diameter() / 2.0;
+        let pressure_angle = self.pressure_angle.to_radians();
+        let base_radius = pitch_radius * pressure_angle.cos();
+        let outer_radius = pitch_radius + self.module;
+        let root_radius = pitch_radius - 1.25 * self.module;
+        assert!(
+            self.bore / 2.0 < root_radius,
+            "Gear bore must fit inside the root circle"
+        );
+
+        // Angle from the middle of a tooth to its flank at a radius: the flank is the
+        // involute of the base circle, continued radially below it
+        let involute = |angle: Real| angle.tan() - angle;
+        let half_angle = |radius: Real| {
+            let angle = (base_radius / radius.max(base_radius)).acos();
+            PI / (2.0 * teeth) + involute(pressure_angle) - involute(angle)
+        };
+        assert!(
+            half_angle(outer_radius) > 0.0,
+            "Gear teeth are pointed; use more teeth or a smaller pressure angle"
+        );
+
+        let start = root_radius.max(base_radius);
+        let mut radii: Vec<Real> = (0..=self.segments)
+            .map(|k| start + (outer_radius - start) * k as Real / self.segments as Real)
+            .collect();
+        if root_radius < base_radius {
+            radii.insert(0, root_radius);
+        }
+
+        // Counter-clockwise around the gear: up one flank, across the tip, down the
+        // other flank and along the root to the next tooth
+        let polar = |radius: Real, angle: Real| (radius * angle.cos(), radius * angle.sin());
+        let mut outline = Vec::new();
+        for tooth in 0..self.teeth {
+            let middle = 2.0 * PI * tooth as Real / teeth;
+            outline.extend(radii.iter().map(|&r| polar(r, middle - half_angle(r))));
+
+            let tip = half_angle(outer_radius);
+            outline.extend((1..TIP_STEPS).map(|k| {
+                let t = k as Real / TIP_STEPS as Real;
+                polar(outer_radius, middle - tip + 2.0 * tip * t)
+            }));
+
+            outline.extend(
+                radii
+                    .iter()
+                    .rev()
+                    .map(|&r| polar(r, middle + half_angle(r))),
+            );
+
+            let root = half_angle(root_radius);
+            let gap = 2.0 * PI / teeth - 2.0 * root;
+            outline.extend((1..ROOT_STEPS).map(|k| {
+                let t = k as Real / ROOT_STEPS as Real;
+                polar(root_radius, middle + root + gap * t)
+            }));
+        }
+
+        let mut extrude = Extrude::new(outline).depth(self.width);
+        if self.bore > 0.0 {
+            let segments = (4 * self.teeth).max(32);
+            extrude = extrude.hole(
+                (0..segments)
+                    .map(|k| polar(self.bore / 2.0, 2.0 * PI * k as Real / segments as Real))
+                    .collect(),
+            );
+        }
+        let mut model = extrude.build();
+        model.name = "Gear".to_string();
+
+        // Stand the extrusion up on the Y axis, centered on its width
+        let (cx, cy, cz) = self.center;
+        for vertex in &mut model.mesh.vertices {
+            let (p, n) = (vertex.position, vertex.normal);
+            vertex.position = Point3::new(cx + p.x, cy + p.z - self.width / 2.0, cz - p.y);
+            vertex.normal = Vector3::new(n.x, n.z, -n.y);
+        }
+
+        model
+    }
+}
+
+impl Default for Gear {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Number of segments across the tip of a gear tooth.
+const TIP_STEPS: usize = 3;
+
+/// Number of segments along the root between gear teeth.
+const ROOT_STEPS: usize = 4;
+
+/// Builder for creating an ISO metric screw thread.
+///
+/// An external thread is a threaded rod along the Y axis; an internal thread is a
+/// sleeve with the thread cut into its bore, like a round nut. Both follow the ISO
+/// basic profile for the given major diameter and pitch (60° flanks, flat crests
+/// and roots), so an internal and external thread of the same size fit without
+/// clearance. The rod or sleeve is centered on its length with flat ends.
+pub struct Thread {
+    diameter: Real,
+    pitch: Real,
+    length: Real,
+    internal: bool,
+    outer_diameter: Option<Real>,
+    left_handed: bool,
+    center: (Real, Real, Real),
+    segments: usize,
+}
+
+impl Thread {
+    /// Create a new thread builder with default settings.
+    pub fn new() -> Self {
+        Self {
+            diameter: 1.0,
+            pitch: 0.15,
+            length: 2.0,
+            internal: false,
+            outer_diameter: None,
+            left_handed: false,
+            center: (0.0, 0.0, 0.0),
+            segments: 32,
+        }
+    }
+
+    /// Create a builder for an ISO metric coarse thread of a standard size, such as
+    /// 6 for M6 with its 1 mm pitch.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `diameter` is not a standard coarse thread size from M1 to M64.
+    pub fn metric(diameter: Real) -> Self {
+        let pitch = COARSE_PITCHES
+            .iter()
+            .find(|&&(size, _)| (size - diameter).abs() < 1e-6)
+            .map(|&(_, pitch)| pitch)
+            .unwrap_or_else(|| panic!("M{diameter} is not a standard ISO coarse thread"));
+        Self::new().diameter(diameter).pitch(pitch)
+    }
+
+    /// Set the major (nominal) diameter.
+    pub fn diameter(mut self, diameter: Real) -> Self {
+        assert!(diameter > 0.0, "Thread diameter must be positive");
+        self.diameter = diameter;
+        self
+    }
+
+    /// Set the pitch, the distance between neighbouring crests.
+    pub fn pitch(mut self, pitch: Real) -> Self {
+        assert!(pitch > 0.0, "Thread pitch must be positive");
+        self.pitch = pitch;
+        self
+    }
+
+    /// Set the length along the axis.
+    pub fn length(mut self, length: Real) -> Self {
+        assert!(length > 0.0, "Thread length must be positive");
+        self.length = length;
+        self
+    }
+
+    /// Set whether to cut the thread into a bore instead of onto a rod.
+    pub fn internal(mut self, internal: bool) -> Self {
+        self.internal = internal;
+        self
+    }
+
+    /// Set the outside diameter of an internal thread's sleeve.
+    ///
+    /// Defaults to 1.6 times the thread diameter.
+    pub fn outer_diameter(mut self, diameter: Real) -> Self {
+        assert!(diameter > 0.0, "Thread outer diameter must be positive");
+        self.outer_diameter = Some(diameter);
+        self
+    }
+
+    /// Set whether the thread winds left-handed instead of right-handed.
+    pub fn left_handed(mut self, left_handed: bool) -> Self {
+        self.left_handed = left_handed;
+        self
+    }
+
+    /// Set the center position of the thread.
+    pub fn center(mut self, x: Real, y: Real, z: Real) -> Self {
+        self.center = (x, y, z);
+        self
+    }
+
+    /// Set the number of segments per turn.
+    pub fn segments(mut self, segments: usize) -> Self {
+        assert!(
+            segments >= 8,
+            "Thread must have at least 8 segments per turn"
+        );
+        self.segments = segments;
+        self
+    }
+
+    /// Radius of the thread surface at `u` pitches along a helix, and its slope.
+    fn profile(&self, u: Real) -> (Real, Real) {
+        // The basic profile is cut from triangles of height H, leaving a crest flat
+        // of P/8 at the major diameter and a root flat of P/4 five eighths of H in
+        let depth = 5.0 / 8.0 * Real::sqrt(3.0) / 2.0 * self.pitch;
+        let major = self.diameter / 2.0;
+        let flank = depth / (5.0 / 16.0);
+        let u = u.rem_euclid(1.0);
+        if u < PROFILE_BREAKS[1] {
+            (major, 0.0)
+        } else if u < PROFILE_BREAKS[2] {
+            (major - (u - PROFILE_BREAKS[1]) * flank, -flank)
+        } else if u < PROFILE_BREAKS[3] {
+            (major - depth, 0.0)
+        } else {
+            (major - depth + (u - PROFILE_BREAKS[3]) * flank, flank)
+        }
+    }
+
+    /// Build the thread model.
+    pub fn build(self) -> Model {
+        let mut model = Model::new("Thread");
+        let outer_radius = self.outer_diameter.unwrap_or(self.diameter * 1.6) / 2.0;
+        if self.internal {
+            assert!(
+                outer_radius > self.diameter / 2.0,
+                "Thread sleeve must be wider than the thread"
+            );
+        }
+        let (cx, cy, cz) = self.center;
+        let bottom = cy - self.length / 2.0;
+        let hand: Real = if self.left_handed { -1.0 } else { 1.0 };
+        let columns = self.segments + 1;
+
+        // Rows follow helices at the profile's corners, each corner twice so flanks
+        // and flats get their own normals. A row rises one pitch per turn, so rows
+        // start a turn early and end a turn late to cover the ends.
+        let pitches = (self.length / self.pitch).ceil() as i64;
+        let mut rows: Vec<(Real, bool)> = (-1..=pitches + 1)
+            .flat_map(|turn| {
+                PROFILE_BREAKS
+                    .iter()
+                    .flat_map(move |&b| [(turn as Real + b, false), (turn as Real + b, true)])
+            })
+            .collect();
+
+        // Keep the rows that pass between the ends, plus one wholly beyond each end
+        // so every column reaches it
+        let rise = |u: Real, j: usize| self.pitch * (u + hand * j as Real / self.segments as Real);
+        let between: Vec<bool> = rows
+            .iter()
+            .map(|&(u, _)| {
+                let (a, b) = (rise(u, 0), rise(u, self.segments));
+                a.max(b) > 0.0 && a.min(b) < self.length
+            })
+            .collect();
+        let keep: Vec<bool> = (0..rows.len())
+            .map(|i| between[i] || between.get(i + 1) == Some(&true) || (i > 0 && between[i - 1]))
+            .collect();
+        let mut index = 0;
+        rows.retain(|_| {
+            index += 1;
+            keep[index - 1]
+        });
+
+        // A point on the thread surface clamped to the ends, and its outward normal
+        let surface = |u: Real, after: bool, j: usize| {
+            let angle = 2.0 * PI * (j % self.segments) as Real / self.segments as Real;
+            let height = rise(u, j).clamp(0.0, self.length);
+            let u = height / self.pitch - hand * j as Real / self.segments as Real;
+            let (radius, mut slope) = self.profile(u);
+            if !after
+                && PROFILE_BREAKS
+                    .iter()
+                    .any(|&b| (u.rem_euclid(1.0) - b).abs() < 1e-9)
+            {
+                slope = self.profile(u - 1e-6).1;
+            }
+
+            // Counter-clockwise about +Y as the angle grows
+            let (sin, cos) = angle.sin_cos();
+            let radial = Vector3::new(cos, 0.0, -sin);
+            let tangent = Vector3::new(-sin, 0.0, -cos);
+            let normal = radial - Vector3::y() * (slope / self.pitch)
+                + tangent * (hand * slope / (2.0 * PI * radius));
+            let position = Point3::new(cx + radius * cos, bottom + height, cz - radius * sin);
+            (position, normal.normalize(), height)
+        };
+
+        let first = model.mesh.vertices.len();
+        for &(u, after) in &rows {
+            for j in 0..columns {
+                let (position, normal, height) = surface(u, after, j);
+                let normal = if self.internal { -normal } else { normal };
+                model.mesh.add_vertex(Vertex::new(
+                    position,
+                    normal,
+                    Some((
+                        j as f32 / self.segments as f32,
+                        to_f32(height / self.length),
+                    )),
+                ));
+            }
+        }
+        let first_face = model.mesh.faces.len();
+        stitch_rows(&mut model, first, rows.len(), columns);
+        if self.internal {
+            for face in &mut model.mesh.faces[first_face..] {
+                face.indices.swap(1, 2);
+            }
+        }
+
+        // The ends: rings where the thread surface meets them, closed by a disc or an
+        // annulus out to the sleeve
+        let ring = |row: usize| -> Vec<Point3<Real>> {
+            (0..self.segments)
+                .map(|j| surface(rows[row].0, rows[row].1, j).0)
+                .collect()
+        };
+        let plan = |p: &Point3<Real>| Point2::new(p.x - cx, cz - p.z);
+        let outer = |y: Real| -> Vec<Point3<Real>> {
+            (0..self.segments)
+                .map(|j| {
+                    let (sin, cos) = (2.0 * PI * j as Real / self.segments as Real).sin_cos();
+                    Point3::new(cx + outer_radius * cos, y, cz - outer_radius * sin)
+                })
+                .collect()
+        };
+        for (row, top) in [(0, false), (rows.len() - 1, true)] {
+            let threaded = ring(row);
+            let normal = if top { Vector3::y() } else { -Vector3::y() };
+            if self.internal {
+                let outside = outer(threaded[0].y);
+                let outline: Vec<Point2<Real>> = outside.iter().map(plan).collect();
+                let hole: Vec<Point2<Real>> = threaded.iter().map(plan).collect();
+                let triangles = triangulate_with_holes(&outline, std::slice::from_ref(&hole));
+                let points: Vec<Point2<Real>> = outline.into_iter().chain(hole).collect();
+                let positions: Vec<Point3<Real>> = outside.into_iter().chain(threaded).collect();
+                add_cap(&mut model, &points, &positions, &triangles, normal, !top);
+            } else {
+                let points: Vec<Point2<Real>> = threaded.iter().map(plan).collect();
+                let triangles = triangulate(&points);
+                add_cap(&mut model, &points, &threaded, &triangles, normal, !top);
+            }
+        }
+
+        // The outside of the sleeve
+        if self.internal {
+            let first = model.mesh.vertices.len();
+            for (v, y) in [(0.0, bottom), (1.0, bottom + self.length)] {
+                for j in 0..columns {
+                    let angle = 2.0 * PI * (j % self.segments) as Real / self.segments as Real;
+                    let (sin, cos) = angle.sin_cos();
+                    model.mesh.add_vertex(Vertex::new(
+                        Point3::new(cx + outer_radius * cos, y, cz - outer_radius * sin),
+                        Vector3::new(cos, 0.0, -sin),
+                        Some((j as f32 / self.segments as f32, v)),
+                    ));
+                }
+            }
+            stitch_rows(&mut model, first, 2, columns);
+        }
+
+        model
+    }
+}
+
+impl Default for Thread {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where the ISO basic profile turns, in pitches from the start of a crest: crest
+/// end, root start and root end.
+const PROFILE_BREAKS: [Real; 4] = [0.0, 1.0 / 8.0, 7.0 / 16.0, 11.0 / 16.0];
+
+/// ISO metric coarse pitches as `(diameter, pitch)` in millimetres.
+const COARSE_PITCHES: &[(Real, Real)] = &[
+    (1.0, 0.25),
+    (1.2, 0.25),
+    (1.6, 0.35),
+    (2.0, 0.4),
+    (2.5, 0.45),
+    (3.0, 0.5),
+    (4.0, 0.7),
+    (5.0, 0.8),
+    (6.0, 1.0),
+    (8.0, 1.25),
+    (10.0, 1.5),
+    (12.0, 1.75),
+    (14.0, 2.0),
+    (16.0, 2.0),
+    (20.0, 2.5),
+    (24.0, 3.0),
+    (30.0, 3.5),
+    (36.0, 4.0),
+    (42.0, 4.5),
+    (48.0, 5.0),
+    (56.0, 5.5),
+    (64.0, 6.0),
+];
+
+/// Builder for creating a helical compression spring.
+///
+/// The wire winds right-handed up the Y axis, centered on the spring's free length.
+/// Closed ends flatten the pitch over the first and last turn until the coils touch,
+/// as on most compression springs.
+pub struct Spring {
+    coil_diameter: Real,
+    wire_diameter: Real,
+    pitch: Real,
+    turns: Real,
+    closed_ends: bool,
+    center: (Real, Real, Real),
+    segments: usize,
+    wire_segments: usize,
+}
+
+impl Spring {
+    /// Create a new spring builder with default settings.
+    pub fn new() -> Self {
+        Self {
+            coil_diameter: 1.0,
+            wire_diameter: 0.1,
+            pitch: 0.25,
+            turns: 5.0,
+            closed_ends: false,
+            center: (0.0, 0.0, 0.0),
+            segments: 32,
+            wire_segments: 12,
+        }
+    }
+
+    /// Set the mean diameter of the coils, measured to the middle of the wire.
+    pub fn coil_diameter(mut self, diameter: Real) -> Self {
+        assert!(diameter > 0.0, "Spring coil diameter must be positive");
+        self.coil_diameter = diameter;
+        self
+    }
+
+    /// Set the diameter of the wire.
+    pub fn wire_diameter(mut self, diameter: Real) -> Self {
+        assert!(diameter > 0.0, "Spring wire diameter must be positive");
+        self.wire_diameter = diameter;
+        self
+    }
+
+    /// Set the rise per turn of the active coils.
+    pub fn pitch(mut self, pitch: Real) -> Self {
+        assert!(pitch > 0.0, "Spring pitch must be positive");
+        self.pitch = pitch;
+        self
+    }
+
+    /// Set the number of turns, which need not be whole.
+    pub fn turns(mut self, turns: Real) -> Self {
+        assert!(turns > 0.0, "Spring must have a positive number of turns");
+        self.turns = turns;
+        self
+    }
+
+    /// Set whether the end coils close up to touch their neighbours.
+    pub fn closed_ends(mut self, closed_ends: bool) -> Self {
+        self.closed_ends = closed_ends;
+        self
+    }
+
+    /// Set the center position of the spring.
+    pub fn center(mut self, x: Real, y: Real, z: Real) -> Self {
+        self.center = (x, y, z);
+        self
+    }
+
+    /// Set the number of segments per turn.
+    pub fn segments(mut self, segments: usize) -> Self {
+        assert!(
+            segments >= 8,
+            "Spring must have at least 8 segments per turn"
+        );
+        self.segments = segments;
+        self
+    }
+
+    /// Set the number of segments around the wire.
+    pub fn wire_segments(mut self, segments: usize) -> Self {
+        assert!(segments >= 3, "Spring wire must have at least 3 segments");
+        self.wire_segments = segments;
+        self
+    }
+
+    /// Build the spring model.
+    pub fn build(self) -> Model {
+        assert!(
+            self.wire_diameter < self.coil_diameter,
+            "Spring wire must be thinner than the coils"
+        );
+        assert!(
+            self.closed_ends || self.pitch >= self.wire_diameter,
+            "Spring pitch must leave room for the wire"
+        );
+        if self.closed_ends {
+            assert!(
+                self.turns >= 2.0,
+                "Spring with closed ends must have at least 2 turns"
+            );
+        }
+
+        // Rise per turn at `t` turns along: the closed end turns ease from touching
+        // coils to the full pitch
+        let pitch = |t: Real| {
+            if !self.closed_ends {
+                return self.pitch;
+            }
+            let ease = |x: Real| {
+                let x = x.clamp(0.0, 1.0);
+                x * x * (3.0 - 2.0 * x)
+            };
+            let open = ease(t).min(ease(self.turns - t));
+            self.wire_diameter + (self.pitch - self.wire_diameter) * open
+        };
+
+        let steps = ((self.turns * self.segments as Real).ceil() as usize).max(2);
+        let radius = self.coil_diameter / 2.0;
+        let mut heights = vec![0.0];
+        for k in 0..steps {
+            let (t0, t1) = (
+                self.turns * k as Real / steps as Real,
+                self.turns * (k + 1) as Real / steps as Real,
+            );
+            heights.push(heights[k] + pitch((t0 + t1) / 2.0) * (t1 - t0));
+        }
+        let (cx, cy, cz) = self.center;
+        let middle = heights[steps] / 2.0;
+        let path: Vec<(Real, Real, Real)> = (0..=steps)
+            .map(|k| {
+                let angle = 2.0 * PI * self.turns * k as Real / steps as Real;
+                (
+                    cx + radius * angle.cos(),
+                    cy + heights[k] - middle,
+                    cz - radius * angle.sin(),
+                )
+            })
+            .collect();
+
+        let wire: Vec<(Real, Real)> = (0..self.wire_segments)
+            .map(|k| {
+                let angle = 2.0 * PI * k as Real / self.wire_segments as Real;
+                (
+                    self.wire_diameter / 2.0 * angle.cos(),
+                    self.wire_diameter / 2.0 * angle.sin(),
+                )
+            })
+            .collect();
+
+        let mut model = Sweep::new(wire, path).build();
+        model.name = "Spring".to_string();
+        model
+    }
+}
+
+impl Default for Spring {
+    fn default() -> Self {
+        Self::new()
+    }
+}
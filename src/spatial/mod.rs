@@ -0,0 +1,190 @@
+//! Spatial queries on mesh surfaces.
+//!
+//! A [`Bvh`] is a bounding volume hierarchy over a mesh's triangles, answering ray
+//! casts, closest points, inside tests and overlap with another mesh without
+//! testing every triangle. The single-triangle tests it is built on are available
+//! too.
+
+mod queries;
+mod triangle;
+
+pub use triangle::{closest_point_on_triangle, ray_triangle, triangles_overlap};
+
+use crate::bounds::Aabb;
+use crate::{Mesh, Real};
+use nalgebra::{Point3, Vector3};
+
+/// Most triangles kept in a leaf of the hierarchy.
+const LEAF_SIZE: usize = 4;
+
+/// A half-line from an origin along a unit direction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    /// Where the ray starts.
+    pub origin: Point3<Real>,
+    /// Unit direction of the ray.
+    pub direction: Vector3<Real>,
+}
+
+impl Ray {
+    /// Create a ray, normalizing its direction.
+    pub fn new(origin: Point3<Real>, direction: Vector3<Real>) -> Self {
+        assert!(direction.norm() > 0.0, "Ray direction must not be zero");
+        Self {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    /// The point at a distance along the ray.
+    pub fn at(&self, distance: Real) -> Point3<Real> {
+        self.origin + self.direction * distance
+    }
+}
+
+/// Where a ray first meets a mesh.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayHit {
+    /// Distance along the ray.
+    pub distance: Real,
+    /// The point hit.
+    pub point: Point3<Real>,
+    /// Unit normal of the triangle hit, following its winding.
+    pub normal: Vector3<Real>,
+    /// Index of the mesh face hit.
+    pub face: usize,
+    /// Indices of the mesh vertices of the triangle hit, out of the face's fan.
+    pub vertices: [usize; 3],
+    /// Weights of those vertices at the point hit, for interpolating attributes.
+    pub barycentric: [Real; 3],
+}
+
+/// The point of a mesh closest to a query point.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClosestPoint {
+    /// The point on the surface.
+    pub point: Point3<Real>,
+    /// Its distance from the query point.
+    pub distance: Real,
+    /// Index of the mesh face it lies on.
+    pub face: usize,
+}
+
+/// A triangle of the mesh, from the fan of one of its faces.
+#[derive(Debug, Clone, Copy)]
+struct Triangle {
+    corners: [Point3<Real>; 3],
+    vertices: [usize; 3],
+    face: usize,
+}
+
+impl Triangle {
+    fn centroid(&self) -> Point3<Real> {
+        Point3::from(
+            (self.corners[0].coords + self.corners[1].coords + self.corners[2].coords) / 3.0,
+        )
+    }
+}
+
+/// A node of the hierarchy. Leaves hold `count` triangles from `first`; inner nodes
+/// have a `count` of zero, their first child right after them and their second at
+/// `first`.
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    bounds: Aabb,
+    first: usize,
+    count: usize,
+}
+
+/// A bounding volume hierarchy over the triangles of a mesh.
+///
+/// Faces are split into fans of triangles, and triangles without area are left
+/// out. The hierarchy is a copy, so it does not follow later changes to the mesh.
+#[derive(Debug, Clone)]
+pub struct Bvh {
+    triangles: Vec<Triangle>,
+    nodes: Vec<Node>,
+}
+
+impl Bvh {
+    /// Build the hierarchy, splitting boxes at the median triangle along their
+    /// longest side.
+    pub fn new(mesh: &Mesh) -> Self {
+        let mut triangles: Vec<Triangle> = mesh
+            .faces
+            .iter()
+            .enumerate()
+            .filter(|(_, face)| face.indices.len() >= 3)
+            .flat_map(|(index, face)| {
+                (1..face.indices.len() - 1).map(move |k| {
+                    let vertices = [face.indices[0], face.indices[k], face.indices[k + 1]];
+                    Triangle {
+                        corners: vertices.map(|i| mesh.vertices[i].position),
+                        vertices,
+                        face: index,
+                    }
+                })
+            })
+            .filter(|t| {
+                let [a, b, c] = t.corners;
+                (b - a).cross(&(c - a)).norm_squared() > 0.0
+            })
+            .collect();
+
+        let mut nodes = Vec::with_capacity(2 * triangles.len() / LEAF_SIZE + 1);
+        if !triangles.is_empty() {
+            build(&mut triangles, 0, &mut nodes);
+        }
+        Self { triangles, nodes }
+    }
+
+    /// Number of triangles in the hierarchy.
+    pub fn len(&self) -> usize {
+        self.triangles.len()
+    }
+
+    /// Whether the hierarchy holds no triangles.
+    pub fn is_empty(&self) -> bool {
+        self.triangles.is_empty()
+    }
+
+    /// The box around every triangle, or `None` without triangles.
+    pub fn bounds(&self) -> Option<Aabb> {
+        self.nodes.first().map(|node| node.bounds)
+    }
+}
+
+/// Add the node for `triangles`, which start at `offset` in the whole list, and its
+/// descendants, returning its index.
+fn build(triangles: &mut [Triangle], offset: usize, nodes: &mut Vec<Node>) -> usize {
+    let index = nodes.len();
+    let bounds = Aabb::from_points(triangles.iter().flat_map(|t| &t.corners))
+        .expect("Nodes are never empty");
+    nodes.push(Node {
+        bounds,
+        first: offset,
+        count: triangles.len(),
+    });
+    if triangles.len() <= LEAF_SIZE {
+        return index;
+    }
+
+    let centroids =
+        Aabb::from_points(&triangles.iter().map(Triangle::centroid).collect::<Vec<_>>())
+            .expect("Nodes are never empty");
+    let axis = centroids.size().imax();
+    if centroids.size()[axis] == 0.0 {
+        return index;
+    }
+
+    let middle = triangles.len() / 2;
+    triangles.select_nth_unstable_by(middle, |a, b| {
+        a.centroid()[axis].total_cmp(&b.centroid()[axis])
+    });
+    let (left, right) = triangles.split_at_mut(middle);
+    build(left, offset, nodes);
+    let second = build(right, offset + middle, nodes);
+    nodes[index].first = second;
+    nodes[index].count = 0;
+    index
+}
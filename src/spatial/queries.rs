@@ -0,0 +1,233 @@
+//! Queries walking the hierarchy.
+
+use super::triangle::{closest_point_on_triangle, ray_triangle, triangles_overlap};
+use super::{Bvh, ClosestPoint, Node, Ray, RayHit, Triangle};
+use crate::bounds::Aabb;
+use crate::Real;
+use nalgebra::{Point3, Vector3};
+
+impl Bvh {
+    /// The first point where a ray meets the surface, from either side, no further
+    /// than `max_distance` along it. Use `Real::INFINITY` for no limit.
+    pub fn cast_ray(&self, ray: &Ray, max_distance: Real) -> Option<RayHit> {
+        let inverse = ray.direction.map(|d| 1.0 / d);
+        let mut best: Option<(Real, [Real; 3], &Triangle)> = None;
+        let mut limit = max_distance;
+
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if ray_box(ray, &inverse, &node.bounds, limit).is_none() {
+                continue;
+            }
+            if node.count > 0 {
+                for triangle in self.leaf(node) {
+                    if let Some((t, weights)) =
+                        ray_triangle(&ray.origin, &ray.direction, &triangle.corners)
+                    {
+                        if t <= limit {
+                            limit = t;
+                            best = Some((t, weights, triangle));
+                        }
+                    }
+                }
+                continue;
+            }
+
+            // Visit the nearer child first, so the farther one is often cut off
+            let (first, second) = (index + 1, node.first);
+            let near = |child: usize| ray_box(ray, &inverse, &self.nodes[child].bounds, limit);
+            match (near(first), near(second)) {
+                (Some(a), Some(b)) if a <= b => stack.extend([second, first]),
+                (Some(_), Some(_)) => stack.extend([first, second]),
+                (Some(_), None) => stack.push(first),
+                (None, Some(_)) => stack.push(second),
+                (None, None) => {}
+            }
+        }
+
+        best.map(|(distance, barycentric, triangle)| {
+            let [a, b, c] = triangle.corners;
+            RayHit {
+                distance,
+                point: ray.at(distance),
+                normal: (b - a).cross(&(c - a)).normalize(),
+                face: triangle.face,
+                vertices: triangle.vertices,
+                barycentric,
+            }
+        })
+    }
+
+    /// The point of the surface closest to `point`, or `None` without triangles.
+    pub fn closest_point(&self, point: &Point3<Real>) -> Option<ClosestPoint> {
+        let mut best: Option<ClosestPoint> = None;
+        let mut limit = Real::INFINITY;
+
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if node.bounds.distance(point) > limit {
+                continue;
+            }
+            if node.count > 0 {
+                for triangle in self.leaf(node) {
+                    let closest = closest_point_on_triangle(point, &triangle.corners);
+                    let distance = (closest - point).norm();
+                    if distance < limit {
+                        limit = distance;
+                        best = Some(ClosestPoint {
+                            point: closest,
+                            distance,
+                            face: triangle.face,
+                        });
+                    }
+                }
+                continue;
+            }
+
+            let (first, second) = (index + 1, node.first);
+            let distance = |child: usize| self.nodes[child].bounds.distance(point);
+            if distance(first) <= distance(second) {
+                stack.extend([second, first]);
+            } else {
+                stack.extend([first, second]);
+            }
+        }
+        best
+    }
+
+    /// Whether a point lies inside the surface, which should be closed.
+    ///
+    /// Counts the surface crossings of rays from the point in three fixed, slanted
+    /// directions, and goes with two of the three when a ray grazes an edge and
+    /// miscounts. Points on the surface may come out either way.
+    pub fn contains(&self, point: &Point3<Real>) -> bool {
+        let directions = [
+            Vector3::new(0.41, 0.67, 0.62),
+            Vector3::new(-0.73, 0.29, 0.61),
+            Vector3::new(0.13, -0.88, -0.45),
+        ];
+        directions
+            .iter()
+            .filter(|direction| self.crossings(&Ray::new(*point, **direction)) % 2 == 1)
+            .count()
+            >= 2
+    }
+
+    /// Whether any triangle of this surface touches or crosses one of `other`'s.
+    pub fn intersects(&self, other: &Bvh) -> bool {
+        let mut found = false;
+        self.visit_overlaps(other, |_, _| {
+            found = true;
+            false
+        });
+        found
+    }
+
+    /// The pairs of faces, of this mesh and of `other`'s, that touch or cross, in
+    /// order and without repeats.
+    ///
+    /// A hierarchy compared with itself pairs every face with itself and with its
+    /// neighbors too.
+    pub fn overlapping_faces(&self, other: &Bvh) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        self.visit_overlaps(other, |a, b| {
+            pairs.push((a.face, b.face));
+            true
+        });
+        pairs.sort_unstable();
+        pairs.dedup();
+        pairs
+    }
+
+    /// The triangles of a leaf.
+    fn leaf(&self, node: &Node) -> &[Triangle] {
+        &self.triangles[node.first..node.first + node.count]
+    }
+
+    /// How many times a ray crosses the surface.
+    fn crossings(&self, ray: &Ray) -> usize {
+        let inverse = ray.direction.map(|d| 1.0 / d);
+        let mut count = 0;
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if ray_box(ray, &inverse, &node.bounds, Real::INFINITY).is_none() {
+                continue;
+            }
+            if node.count > 0 {
+                count += self
+                    .leaf(node)
+                    .iter()
+                    .filter(|t| ray_triangle(&ray.origin, &ray.direction, &t.corners).is_some())
+                    .count();
+            } else {
+                stack.extend([index + 1, node.first]);
+            }
+        }
+        count
+    }
+
+    /// Call `visit` on each pair of overlapping triangles, one from each hierarchy,
+    /// until it returns `false`.
+    fn visit_overlaps(&self, other: &Bvh, mut visit: impl FnMut(&Triangle, &Triangle) -> bool) {
+        if self.nodes.is_empty() || other.nodes.is_empty() {
+            return;
+        }
+        let mut stack = vec![(0, 0)];
+        while let Some((i, j)) = stack.pop() {
+            let (a, b) = (&self.nodes[i], &other.nodes[j]);
+            if !a.bounds.intersects(&b.bounds) {
+                continue;
+            }
+            match (a.count > 0, b.count > 0) {
+                (true, true) => {
+                    for first in self.leaf(a) {
+                        for second in other.leaf(b) {
+                            if triangles_overlap(&first.corners, &second.corners)
+                                && !visit(first, second)
+                            {
+                                return;
+                            }
+                        }
+                    }
+                }
+                // Open up the inner node, or the bigger one when both are inner
+                (false, true) => stack.extend([(i + 1, j), (a.first, j)]),
+                (true, false) => stack.extend([(i, j + 1), (i, b.first)]),
+                (false, false) if a.bounds.volume() >= b.bounds.volume() => {
+                    stack.extend([(i + 1, j), (a.first, j)])
+                }
+                (false, false) => stack.extend([(i, j + 1), (i, b.first)]),
+            }
+        }
+    }
+}
+
+/// The distance at which a ray enters a box, if it does before `limit`.
+///
+/// `inverse` holds the reciprocals of the ray's direction. Where one is infinite
+/// and the ray lies on the box's side, the NaN bound is skipped by `max` and `min`,
+/// keeping the ray in.
+fn ray_box(ray: &Ray, inverse: &Vector3<Real>, bounds: &Aabb, limit: Real) -> Option<Real> {
+    let mut near: Real = 0.0;
+    let mut far = limit;
+    for axis in 0..3 {
+        let t1 = (bounds.min[axis] - ray.origin[axis]) * inverse[axis];
+        let t2 = (bounds.max[axis] - ray.origin[axis]) * inverse[axis];
+        near = near.max(t1.min(t2));
+        far = far.min(t1.max(t2));
+    }
+    // A little slack so flat boxes, such as around a single face, are not missed
+    (near <= far * (1.0 + 4.0 * Real::EPSILON)).then_some(near)
+}
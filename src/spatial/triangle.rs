@@ -0,0 +1,121 @@
+//! Queries on single triangles.
+
+use crate::Real;
+use nalgebra::{Point3, Vector3};
+
+/// Where a ray from `origin` along `direction` crosses a triangle, from either side,
+/// as the distance in multiples of `direction` and the barycentric weights of the
+/// three corners.
+///
+/// Uses the Möller-Trumbore test. Rays in the triangle's plane and crossings behind
+/// the origin do not count.
+pub fn ray_triangle(
+    origin: &Point3<Real>,
+    direction: &Vector3<Real>,
+    triangle: &[Point3<Real>; 3],
+) -> Option<(Real, [Real; 3])> {
+    let [a, b, c] = triangle;
+    let (ab, ac) = (b - a, c - a);
+    let p = direction.cross(&ac);
+    let determinant = ab.dot(&p);
+    if determinant.abs() <= Real::EPSILON * ab.norm() * ac.norm() * direction.norm() {
+        return None;
+    }
+    let inverse = 1.0 / determinant;
+    let offset = origin - a;
+    let u = offset.dot(&p) * inverse;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = offset.cross(&ab);
+    let v = direction.dot(&q) * inverse;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let t = ac.dot(&q) * inverse;
+    (t >= 0.0).then_some((t, [1.0 - u - v, u, v]))
+}
+
+/// The point of a triangle closest to `point`.
+///
+/// Finds which corner, edge or the inside of the triangle is nearest by the
+/// Voronoi regions of its features, as in Ericson's "Real-Time Collision Detection".
+pub fn closest_point_on_triangle(
+    point: &Point3<Real>,
+    triangle: &[Point3<Real>; 3],
+) -> Point3<Real> {
+    let [a, b, c] = *triangle;
+    let (ab, ac, ap) = (b - a, c - a, point - a);
+    let (d1, d2) = (ab.dot(&ap), ac.dot(&ap));
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+
+    let bp = point - b;
+    let (d3, d4) = (ab.dot(&bp), ac.dot(&bp));
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+
+    let cp = point - c;
+    let (d5, d6) = (ab.dot(&cp), ac.dot(&cp));
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    let denominator = 1.0 / (va + vb + vc);
+    a + ab * (vb * denominator) + ac * (vc * denominator)
+}
+
+/// Whether two triangles touch or overlap, by the separating axis test.
+///
+/// Besides the two normals and the nine cross products of edges, the axes include
+/// each edge turned within its triangle's plane, which separates triangles lying in
+/// the same plane.
+pub fn triangles_overlap(first: &[Point3<Real>; 3], second: &[Point3<Real>; 3]) -> bool {
+    let edges = |t: &[Point3<Real>; 3]| [t[1] - t[0], t[2] - t[1], t[0] - t[2]];
+    let (edges_a, edges_b) = (edges(first), edges(second));
+    let normal_a = edges_a[0].cross(&edges_a[1]);
+    let normal_b = edges_b[0].cross(&edges_b[1]);
+
+    let mut axes: Vec<Vector3<Real>> = Vec::with_capacity(17);
+    axes.push(normal_a);
+    axes.push(normal_b);
+    for edge_a in &edges_a {
+        for edge_b in &edges_b {
+            axes.push(edge_a.cross(edge_b));
+        }
+    }
+    axes.extend(edges_a.iter().map(|edge| normal_a.cross(edge)));
+    axes.extend(edges_b.iter().map(|edge| normal_b.cross(edge)));
+
+    let scale = first
+        .iter()
+        .chain(second)
+        .map(|p| p.coords.amax())
+        .fold(0.0, Real::max);
+    axes.iter().all(|axis| {
+        let project = |t: &[Point3<Real>; 3]| {
+            let values = t.map(|p| p.coords.dot(axis));
+            let low = values[0].min(values[1]).min(values[2]);
+            let high = values[0].max(values[1]).max(values[2]);
+            (low, high)
+        };
+        let ((low_a, high_a), (low_b, high_b)) = (project(first), project(second));
+        // Rounding slack in proportion to the size of the projections
+        let slack = 16.0 * Real::EPSILON * scale * axis.norm();
+        low_a <= high_b + slack && low_b <= high_a + slack
+    })
+}
@@ -0,0 +1,565 @@
+//! Fonts: the built-in stroke font and TrueType files.
+
+use super::truetype::TrueType;
+use crate::generators::catmull_rom;
+use crate::{Real, Result};
+use nalgebra::{Point2, Vector2};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+/// A font to lay out [`Text`](super::Text) with.
+///
+/// The built-in font is a single-stroke font covering printable ASCII, drawn with
+/// round strokes of adjustable weight; other characters fall back to `?`. TrueType
+/// (`.ttf`) files and OpenType files with TrueType outlines are read from disk
+/// without any system font support.
+#[derive(Debug, Clone)]
+pub struct Font {
+    source: Source,
+}
+
+#[derive(Debug, Clone)]
+enum Source {
+    Builtin,
+    TrueType(Arc<TrueType>),
+}
+
+/// The outline of one glyph in units of the font's cap height.
+pub(crate) struct Glyph {
+    /// Closed contours, with holes and outlines told apart by nesting
+    pub(crate) contours: Vec<Vec<Point2<Real>>>,
+    /// Distance to move along the baseline to the next glyph
+    pub(crate) advance: Real,
+}
+
+impl Font {
+    /// The built-in single-stroke font.
+    pub fn builtin() -> Self {
+        Self {
+            source: Source::Builtin,
+        }
+    }
+
+    /// Load a TrueType font file.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_data(std::fs::read(path)?)
+    }
+
+    /// Load a TrueType font from the contents of a font file.
+    pub fn from_data(data: Vec<u8>) -> Result<Self> {
+        Ok(Self {
+            source: Source::TrueType(Arc::new(TrueType::parse(data)?)),
+        })
+    }
+
+    /// Distance between baselines in units of the cap height.
+    pub(crate) fn line_height(&self) -> Real {
+        match &self.source {
+            Source::Builtin => 10.0 / CAP_HEIGHT,
+            Source::TrueType(font) => font.line_height() / font.cap_height(),
+        }
+    }
+
+    /// Outline a character. `weight` is the stroke width of the built-in font and
+    /// `tolerance` the allowed error of curves, both in units of the cap height.
+    pub(crate) fn glyph(&self, c: char, weight: Real, tolerance: Real) -> Result<Glyph> {
+        match &self.source {
+            Source::Builtin => Ok(stroke_glyph(c, weight, tolerance)),
+            Source::TrueType(font) => {
+                let scale = font.cap_height();
+                let index = font.glyph_index(c);
+                let contours = font
+                    .contours(index, tolerance * scale)?
+                    .into_iter()
+                    .map(|contour| contour.into_iter().map(|p| p / scale).collect())
+                    .collect();
+                Ok(Glyph {
+                    contours,
+                    advance: font.advance(index) / scale,
+                })
+            }
+        }
+    }
+}
+
+impl Default for Font {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+/// Height of capital letters in the built-in font's design units.
+const CAP_HEIGHT: Real = 6.0;
+
+/// Space between the strokes of neighbouring glyphs of the built-in font, in design
+/// units, on top of the stroke width.
+const GAP: Real = 0.9;
+
+/// The built-in font as `(character, width, strokes)`, in design units with the
+/// baseline at 0, the x-height at 4 and the cap height at 6.
+///
+/// Strokes are separated by `|` and list `x,y` points joined by straight lines; a
+/// stroke starting with `~` is a smooth curve through its points, and one starting
+/// with `@` a smooth closed loop. A single point is a dot.
+const STROKES: &[(char, Real, &str)] = &[
+    (' ', 2.0, ""),
+    ('!', 0.0, "0,6 0,1.8|0,0"),
+    ('"', 1.5, "0,6 0,4.5|1.5,6 1.5,4.5"),
+    ('#', 4.0, "1.4,6 0.8,0|3.2,6 2.6,0|0,4 4,4|0,2 4,2"),
+    (
+        '$',
+        4.0,
+        "~4,5 3,5.7 1,5.7 0.1,4.8 0.6,3.6 3.4,2.5 3.9,1.2 3,0.3 1,0.3 0,1|2,6.6 2,-0.6",
+    ),
+    (
+        '%',
+        4.0,
+        "4,6 0,0|@0.7,6 1.4,5.2 0.7,4.4 0,5.2|@3.3,1.6 4,0.8 3.3,0 2.6,0.8",
+    ),
+    (
+        '&',
+        4.5,
+        "~4.5,0 1,4.3 0.8,5.4 1.6,6 2.6,5.4 2.4,4.4 0.3,2.6 0,1.3 1,0.1 2.6,0.2 4.2,2",
+    ),
+    ('\'', 0.0, "0,6 0,4.5"),
+    ('(', 1.5, "~1.5,6.5 0.3,4.5 0,2 0.3,-0.5 1.5,-1.5"),
+    (')', 1.5, "~0,6.5 1.2,4.5 1.5,2 1.2,-0.5 0,-1.5"),
+    ('*', 3.0, "1.5,6 1.5,3|0,5.2 3,3.8|0,3.8 3,5.2"),
+    ('+', 4.0, "2,5 2,1|0,3 4,3"),
+    (',', 0.5, "0.5,0 0,-1.2"),
+    ('-', 3.0, "0,2.5 3,2.5"),
+    ('.', 0.0, "0,0"),
+    ('/', 3.0, "3,6.5 0,-0.5"),
+    ('0', 4.0, "@2,6 3.6,5 4,3 3.6,1 2,0 0.4,1 0,3 0.4,5"),
+    ('1', 3.0, "0,4.8 2,6 2,0|0,0 3,0"),
+    ('2', 4.0, "~0,4.6 0.8,5.7 2,6 3.2,5.7 4,4.6 3.6,3.2 0,0|0,0 4,0"),
+    (
+        '3',
+        4.0,
+        "~0,5.2 1,5.9 2.4,6 3.6,5.3 3.6,3.8 2.2,3.2 1.4,3.2|~2.2,3.2 3.8,2.5 4,1.3 3,0.1 1.5,0 0,0.8",
+    ),
+    ('4', 4.0, "3,0 3,6 0,1.8 4,1.8"),
+    (
+        '5',
+        4.0,
+        "4,6 0.4,6 0,3.2|~0,3.2 1.4,3.8 2.8,3.8 3.8,3 4,1.6 3.2,0.3 1.8,0 0,0.6",
+    ),
+    (
+        '6',
+        4.0,
+        "~3.6,5.6 2.4,6 1.2,5.6 0.2,4 0,2 0.4,0.6 2,0 3.6,0.6 4,1.8 3.5,3.2 2,3.6 0.6,3 0,2",
+    ),
+    ('7', 4.0, "0,6 4,6 1.2,0"),
+    (
+        '8',
+        4.0,
+        "@2,6 3.5,5.4 3.5,3.9 2,3.3 0.5,3.9 0.5,5.4|@2,3.3 3.8,2.6 4,1.2 2,0 0,1.2 0.2,2.6",
+    ),
+    (
+        '9',
+        4.0,
+        "~0.4,0.4 1.6,0 2.8,0.4 3.8,2 4,4 3.6,5.4 2,6 0.4,5.4 0,4.2 0.5,2.8 2,2.4 3.4,3 4,4",
+    ),
+    (':', 0.0, "0,4|0,0"),
+    (';', 0.5, "0.5,4|0.5,0 0,-1.2"),
+    ('<', 4.0, "4,5 0,3 4,1"),
+    ('=', 4.0, "0,3.8 4,3.8|0,2.2 4,2.2"),
+    ('>', 4.0, "0,5 4,3 0,1"),
+    (
+        '?',
+        4.0,
+        "~0,4.6 0.8,5.7 2,6 3.2,5.7 4,4.6 3.6,3.4 2,2.6 2,1.6|2,0",
+    ),
+    (
+        '@',
+        5.0,
+        "@3.3,2.6 2.5,3.6 1.5,3.2 1.4,2 2.2,1.4 3,1.8|~3.3,2.6 3.5,1.4 4.4,1.3 5,2.6 4.8,4.4 3.6,5.6 2,5.6 0.6,4.6 0,3 0.4,1 1.8,0 3.4,0 4.4,0.5",
+    ),
+    ('A', 4.0, "0,0 2,6 4,0|0.7,2 3.3,2"),
+    (
+        'B',
+        4.0,
+        "2.5,6 0,6 0,0 2.7,0|~2.5,6 3.5,5.5 3.7,4.5 3.3,3.5 2.5,3.2|0,3.2 2.7,3.2|~2.7,3.2 3.7,2.8 4,1.6 3.6,0.5 2.7,0",
+    ),
+    ('C', 4.0, "~4,5 3,5.9 2,6 0.8,5.4 0,3 0.8,0.6 2,0 3,0.1 4,1"),
+    ('D', 4.0, "1.8,6 0,6 0,0 1.8,0|~1.8,6 3.4,5.2 4,3 3.4,0.8 1.8,0"),
+    ('E', 3.5, "3.5,6 0,6 0,0 3.5,0|0,3.1 2.8,3.1"),
+    ('F', 3.5, "3.5,6 0,6 0,0|0,3.1 2.8,3.1"),
+    (
+        'G',
+        4.0,
+        "~4,5 3,5.9 2,6 0.8,5.4 0,3 0.8,0.6 2,0 3.2,0.2 4,1|4,1 4,2.6 2.4,2.6",
+    ),
+    ('H', 4.0, "0,0 0,6|4,0 4,6|0,3.1 4,3.1"),
+    ('I', 0.0, "0,0 0,6"),
+    ('J', 3.0, "3,6 3,1.6|~3,1.6 2.6,0.4 1.5,0 0.4,0.4 0,1.4"),
+    ('K', 4.0, "0,0 0,6|4,6 0,2|1.4,3.4 4,0"),
+    ('L', 3.5, "0,6 0,0 3.5,0"),
+    ('M', 5.0, "0,0 0,6 2.5,1.5 5,6 5,0"),
+    ('N', 4.0, "0,0 0,6 4,0 4,6"),
+    ('O', 4.4, "@2.2,6 3.8,5.2 4.4,3 3.8,0.8 2.2,0 0.6,0.8 0,3 0.6,5.2"),
+    (
+        'P',
+        4.0,
+        "0,0 0,6 2.6,6|~2.6,6 3.6,5.6 4,4.5 3.6,3.3 2.6,2.9|2.6,2.9 0,2.9",
+    ),
+    (
+        'Q',
+        4.4,
+        "@2.2,6 3.8,5.2 4.4,3 3.8,0.8 2.2,0 0.6,0.8 0,3 0.6,5.2|2.6,1.4 4.4,-0.4",
+    ),
+    (
+        'R',
+        4.0,
+        "0,0 0,6 2.6,6|~2.6,6 3.6,5.6 4,4.5 3.6,3.3 2.6,2.9|2.6,2.9 0,2.9|2.2,2.9 4,0",
+    ),
+    (
+        'S',
+        4.0,
+        "~4,5 3,5.9 1.8,6 0.5,5.5 0.1,4.4 0.8,3.5 3.2,2.6 3.9,1.6 3.5,0.5 2.2,0 1,0.1 0,1",
+    ),
+    ('T', 4.0, "0,6 4,6|2,6 2,0"),
+    ('U', 4.0, "0,6 0,1.8|~0,1.8 0.5,0.4 2,0 3.5,0.4 4,1.8|4,1.8 4,6"),
+    ('V', 4.0, "0,6 2,0 4,6"),
+    ('W', 6.0, "0,6 1.5,0 3,5 4.5,0 6,6"),
+    ('X', 4.0, "0,6 4,0|0,0 4,6"),
+    ('Y', 4.0, "0,6 2,3 4,6|2,3 2,0"),
+    ('Z', 4.0, "0,6 4,6 0,0 4,0"),
+    ('[', 1.5, "1.5,6.5 0,6.5 0,-1.5 1.5,-1.5"),
+    ('\\', 3.0, "0,6.5 3,-0.5"),
+    (']', 1.5, "0,6.5 1.5,6.5 1.5,-1.5 0,-1.5"),
+    ('^', 3.0, "0,4.5 1.5,6 3,4.5"),
+    ('_', 4.0, "0,-1 4,-1"),
+    ('`', 1.0, "0,6 1,5"),
+    (
+        'a',
+        3.5,
+        "@1.75,4 3.2,3.4 3.5,2 3.2,0.6 1.75,0 0.3,0.6 0,2 0.3,3.4|3.5,4 3.5,0",
+    ),
+    (
+        'b',
+        3.5,
+        "0,6 0,0|@1.75,4 3.2,3.4 3.5,2 3.2,0.6 1.75,0 0.3,0.6 0,2 0.3,3.4",
+    ),
+    (
+        'c',
+        3.5,
+        "~3.5,3.3 2.6,3.9 1.6,4 0.5,3.4 0,2 0.5,0.6 1.6,0 2.6,0.1 3.5,0.7",
+    ),
+    (
+        'd',
+        3.5,
+        "3.5,6 3.5,0|@1.75,4 3.2,3.4 3.5,2 3.2,0.6 1.75,0 0.3,0.6 0,2 0.3,3.4",
+    ),
+    (
+        'e',
+        3.5,
+        "0,2 3.5,2|~3.5,2 3.3,3.3 2.2,4 1.2,3.9 0.3,3.2 0,2 0.4,0.7 1.6,0 2.6,0.1 3.4,0.7",
+    ),
+    ('f', 2.5, "~2.5,5.8 1.8,6 1.1,5.6 0.9,4.6|0.9,4.6 0.9,0|0,4 2.4,4"),
+    (
+        'g',
+        3.5,
+        "@1.75,4 3.2,3.4 3.5,2 3.2,0.6 1.75,0 0.3,0.6 0,2 0.3,3.4|3.5,4 3.5,-0.6|~3.5,-0.6 2.9,-1.8 1.7,-2 0.6,-1.7 0.1,-1.2",
+    ),
+    ('h', 3.5, "0,6 0,0|~0,2.6 0.8,3.7 2,4 3.1,3.6 3.5,2.6|3.5,2.6 3.5,0"),
+    ('i', 0.0, "0,4 0,0|0,5.6"),
+    ('j', 1.5, "1.5,4 1.5,-1|~1.5,-1 1.2,-1.8 0.6,-2 0,-1.8|1.5,5.6"),
+    ('k', 3.5, "0,6 0,0|3.4,4 0,1.4|1.2,2.3 3.5,0"),
+    ('l', 0.0, "0,6 0,0"),
+    (
+        'm',
+        5.5,
+        "0,4 0,0|~0,2.8 0.6,3.8 1.5,4 2.4,3.7 2.75,2.8|2.75,2.8 2.75,0|~2.75,2.8 3.4,3.8 4.3,4 5.2,3.7 5.5,2.8|5.5,2.8 5.5,0",
+    ),
+    ('n', 3.5, "0,4 0,0|~0,2.6 0.8,3.7 2,4 3.1,3.6 3.5,2.6|3.5,2.6 3.5,0"),
+    ('o', 3.6, "@1.8,4 3.3,3.4 3.6,2 3.3,0.6 1.8,0 0.3,0.6 0,2 0.3,3.4"),
+    (
+        'p',
+        3.5,
+        "0,4 0,-2|@1.75,4 3.2,3.4 3.5,2 3.2,0.6 1.75,0 0.3,0.6 0,2 0.3,3.4",
+    ),
+    (
+        'q',
+        3.5,
+        "3.5,4 3.5,-2|@1.75,4 3.2,3.4 3.5,2 3.2,0.6 1.75,0 0.3,0.6 0,2 0.3,3.4",
+    ),
+    ('r', 2.5, "0,4 0,0|~0,2.4 0.6,3.5 1.5,4 2.5,3.9"),
+    (
+        's',
+        3.0,
+        "~3,3.5 2,4 0.9,3.9 0.2,3.3 0.4,2.5 2.6,1.6 3,0.9 2.4,0.1 1.2,0 0,0.5",
+    ),
+    ('t', 2.5, "0.9,5.5 0.9,0.9|~0.9,0.9 1.2,0.2 1.8,0 2.5,0.2|0,4 2.4,4"),
+    ('u', 3.5, "0,4 0,1.4|~0,1.4 0.4,0.4 1.5,0 2.7,0.3 3.5,1.4|3.5,4 3.5,0"),
+    ('v', 3.5, "0,4 1.75,0 3.5,4"),
+    ('w', 5.0, "0,4 1.25,0 2.5,3.4 3.75,0 5,4"),
+    ('x', 3.5, "0,4 3.5,0|0,0 3.5,4"),
+    ('y', 3.5, "0,4 1.75,0|3.5,4 1.2,-1.4 0.6,-2 0,-2"),
+    ('z', 3.5, "0,4 3.5,4 0,0 3.5,0"),
+    (
+        '{',
+        2.0,
+        "~2,6.5 1.2,6.2 1,5 1,3.4 0,2.5|~0,2.5 1,1.6 1,0 1.2,-1.2 2,-1.5",
+    ),
+    ('|', 0.0, "0,6.5 0,-1.5"),
+    (
+        '}',
+        2.0,
+        "~0,6.5 0.8,6.2 1,5 1,3.4 2,2.5|~2,2.5 1,1.6 1,0 0.8,-1.2 0,-1.5",
+    ),
+    ('~', 4.0, "~0,2.6 0.8,3.3 2,2.8 3.2,2.3 4,3"),
+];
+
+/// Look up a character of the built-in font, falling back to `?`.
+fn stroke_entry(c: char) -> (Real, &'static str) {
+    let find = |c: char| STROKES.iter().find(|entry| entry.0 == c);
+    find(c)
+        .or_else(|| find('?'))
+        .map(|&(_, width, strokes)| (width, strokes))
+        .unwrap()
+}
+
+/// Parse the strokes of a built-in glyph into polylines, sampling smooth strokes.
+fn parse_strokes(strokes: &str) -> Vec<Vec<Point2<Real>>> {
+    strokes
+        .split('|')
+        .filter(|stroke| !stroke.is_empty())
+        .map(|stroke| {
+            let (smooth, closed, points) = match stroke.as_bytes()[0] {
+                b'~' => (true, false, &stroke[1..]),
+                b'@' => (true, true, &stroke[1..]),
+                _ => (false, false, stroke),
+            };
+            let points: Vec<Point2<Real>> = points
+                .split_whitespace()
+                .map(|point| {
+                    let (x, y) = point.split_once(',').unwrap();
+                    Point2::new(x.parse().unwrap(), y.parse().unwrap())
+                })
+                .collect();
+            if !smooth {
+                return points;
+            }
+            let mut curve = catmull_rom(&points, 8, closed);
+            if closed {
+                curve.push(curve[0]);
+            }
+            curve
+        })
+        .collect()
+}
+
+/// Outline a glyph of the built-in font by tracing the boundary of the area within
+/// half the stroke width of its strokes.
+fn stroke_glyph(c: char, weight: Real, tolerance: Real) -> Glyph {
+    let (width, strokes) = stroke_entry(c);
+    let strokes = parse_strokes(strokes);
+
+    // Work in design units
+    let radius = weight * CAP_HEIGHT / 2.0;
+    let advance = (width + radius * 2.0 + GAP) / CAP_HEIGHT;
+    let segments: Vec<(Point2<Real>, Point2<Real>)> = strokes
+        .iter()
+        .flat_map(|stroke| {
+            if stroke.len() == 1 {
+                vec![(stroke[0], stroke[0])]
+            } else {
+                stroke.windows(2).map(|pair| (pair[0], pair[1])).collect()
+            }
+        })
+        .map(|(a, b)| (a + Vector2::new(radius, 0.0), b + Vector2::new(radius, 0.0)))
+        .collect();
+    if segments.is_empty() {
+        return Glyph {
+            contours: Vec::new(),
+            advance,
+        };
+    }
+
+    let distance = |p: Point2<Real>| {
+        segments
+            .iter()
+            .map(|&(a, b)| {
+                let ab = b - a;
+                let length = ab.norm_squared();
+                let t = if length > 0.0 {
+                    ((p - a).dot(&ab) / length).clamp(0.0, 1.0)
+                } else {
+                    0.0
+                };
+                (p - (a + ab * t)).norm()
+            })
+            .fold(Real::INFINITY, Real::min)
+            - radius
+    };
+
+    // The step keeps the traced outline within the tolerance of the round stroke ends
+    let step = (8.0 * tolerance * CAP_HEIGHT * radius)
+        .sqrt()
+        .clamp(radius / 8.0, radius / 2.0);
+    let (mut min, mut max) = (segments[0].0, segments[0].0);
+    for &(a, b) in &segments {
+        for p in [a, b] {
+            min = min.inf(&p);
+            max = max.sup(&p);
+        }
+    }
+    let margin = radius + step * 2.0;
+    let origin = min - Vector2::repeat(margin);
+    let columns = ((max.x - min.x + margin * 2.0) / step).ceil() as usize + 1;
+    let rows = ((max.y - min.y + margin * 2.0) / step).ceil() as usize + 1;
+
+    let point = |i: usize, j: usize| origin + Vector2::new(i as Real, j as Real) * step;
+    let values: Vec<Real> = (0..rows)
+        .flat_map(|j| (0..columns).map(move |i| (i, j)))
+        .map(|(i, j)| distance(point(i, j)))
+        .collect();
+
+    // Marching squares leaves slivers where the outline passes close to grid points
+    // and runs of nearly collinear points along straight strokes
+    let contours = trace_contours(&values, columns, rows, point)
+        .into_iter()
+        .filter_map(|contour| {
+            let contour = simplify(
+                contour,
+                step / 4.0,
+                (tolerance * CAP_HEIGHT / 2.0).min(step / 8.0),
+            );
+            (contour.len() >= 3).then(|| contour.into_iter().map(|p| p / CAP_HEIGHT).collect())
+        })
+        .collect();
+    Glyph { contours, advance }
+}
+
+/// Fraction of a grid step that traced contours keep away from outside grid points.
+const CLEARANCE: Real = 0.05;
+
+/// Trace the closed contours where sampled values cross zero (marching squares).
+///
+/// Contours run counter-clockwise around negative areas, so holes run clockwise. The
+/// border of the grid must be positive.
+fn trace_contours<F>(
+    values: &[Real],
+    columns: usize,
+    rows: usize,
+    point: F,
+) -> Vec<Vec<Point2<Real>>>
+where
+    F: Fn(usize, usize) -> Point2<Real>,
+{
+    let index = |i: usize, j: usize| j * columns + i;
+    let inside = |i: usize, j: usize| values[index(i, j)] < 0.0;
+
+    // Crossings are keyed by the grid point an edge starts from and its direction
+    let crossing = |(i, j, vertical): (usize, usize, bool)| {
+        let (i2, j2) = if vertical { (i, j + 1) } else { (i + 1, j) };
+        let (a, b) = (values[index(i, j)], values[index(i2, j2)]);
+        // Keep clear of the outside end, which separate contours may share
+        let t = if a < 0.0 {
+            (a / (a - b)).min(1.0 - CLEARANCE)
+        } else {
+            (a / (a - b)).max(CLEARANCE)
+        };
+        point(i, j) + (point(i2, j2) - point(i, j)) * t
+    };
+
+    let mut next = BTreeMap::new();
+    for j in 0..rows - 1 {
+        for i in 0..columns - 1 {
+            // Corners and edges counter-clockwise from the bottom left; edge k runs
+            // from corner k to corner k + 1
+            let corners = [(i, j), (i + 1, j), (i + 1, j + 1), (i, j + 1)];
+            let edges = [
+                (i, j, false),
+                (i + 1, j, true),
+                (i, j + 1, false),
+                (i, j, true),
+            ];
+            let is_inside = corners.map(|(ci, cj)| inside(ci, cj));
+            let leaving: Vec<usize> = (0..4)
+                .filter(|&k| is_inside[k] && !is_inside[(k + 1) % 4])
+                .collect();
+            let entering: Vec<usize> = (0..4)
+                .filter(|&k| !is_inside[k] && is_inside[(k + 1) % 4])
+                .collect();
+
+            // Each contour leaves the inside area through one edge and comes back in
+            // through the next edge, or on a saddle through the edge that either joins
+            // the two inside corners through the middle or keeps them apart
+            let center: Real = corners.iter().map(|&(ci, cj)| values[index(ci, cj)]).sum();
+            for &k in &leaving {
+                let back = match entering.len() {
+                    1 => entering[0],
+                    _ if center < 0.0 => (k + 1) % 4,
+                    _ => (k + 3) % 4,
+                };
+                next.insert(edges[k], edges[back]);
+            }
+        }
+    }
+
+    let mut contours = Vec::new();
+    while let Some((&start, _)) = next.first_key_value() {
+        let mut contour = Vec::new();
+        let mut edge = start;
+        while let Some(following) = next.remove(&edge) {
+            contour.push(crossing(edge));
+            edge = following;
+        }
+        contour.dedup();
+        if contour.len() > 1 && contour.first() == contour.last() {
+            contour.pop();
+        }
+
+        if contour.len() >= 3 {
+            contours.push(contour);
+        }
+    }
+    contours
+}
+
+/// Clean up a closed contour: merge points closer than `spacing`, cut the spikes
+/// this can leave, and drop points within `epsilon` of the line from the last point
+/// kept to the next one.
+fn simplify(contour: Vec<Point2<Real>>, spacing: Real, epsilon: Real) -> Vec<Point2<Real>> {
+    let mut merged: Vec<Point2<Real>> = Vec::with_capacity(contour.len());
+    for p in contour {
+        if merged
+            .last()
+            .is_none_or(|last| (p - last).norm() >= spacing)
+        {
+            merged.push(p);
+        }
+    }
+    while merged.len() > 1 && (merged[0] - merged[merged.len() - 1]).norm() < spacing {
+        merged.pop();
+    }
+
+    let mut i = 0;
+    while merged.len() >= 3 && i < merged.len() {
+        let n = merged.len();
+        if (merged[(i + n - 1) % n] - merged[(i + 1) % n]).norm() < spacing {
+            merged.remove(i);
+            merged.remove(i % (n - 1));
+            i = i.saturating_sub(2);
+        } else {
+            i += 1;
+        }
+    }
+
+    let mut kept: Vec<Point2<Real>> = Vec::with_capacity(merged.len());
+    for (i, &p) in merged.iter().enumerate() {
+        let next = merged[(i + 1) % merged.len()];
+        let straight = kept.last().is_some_and(|&last| {
+            let chord = next - last;
+            let length = chord.norm();
+            length > 0.0 && chord.perp(&(p - last)).abs() / length < epsilon
+        });
+        if !straight {
+            kept.push(p);
+        }
+    }
+    kept
+}
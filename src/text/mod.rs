@@ -0,0 +1,248 @@
+//! Solid lettering laid out in the built-in stroke font or a TrueType font.
+
+mod font;
+mod truetype;
+
+pub use font::Font;
+
+use crate::generators::Extrude;
+use crate::triangulate::signed_area;
+use crate::{Face, Model, Real, Result};
+use nalgebra::Point2;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+/// Horizontal alignment of each line of [`Text`] relative to the origin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextAlign {
+    /// Lines start at `x = 0`.
+    Left,
+    /// Lines are centered on `x = 0`.
+    Center,
+    /// Lines end at `x = 0`.
+    Right,
+}
+
+/// Builder that lays out a string and extrudes its glyphs into solid lettering.
+///
+/// Text reads along +X with the baseline of the first line on the X axis; further
+/// lines, separated by `\n`, go down the Y axis. Glyphs are extruded from `z = 0` up
+/// to the depth like an [`Extrude`], so the lettering faces +Z. Apply `Bend` or
+/// `Cylindrical` afterwards to curve a label around a surface.
+///
+/// Every glyph region is its own closed solid. Glyphs of TrueType fonts whose
+/// contours overlap, as some accented or variable-font outlines do, produce
+/// overlapping solids rather than their union.
+pub struct Text {
+    text: String,
+    font: Font,
+    size: Real,
+    depth: Real,
+    weight: Real,
+    letter_spacing: Real,
+    line_spacing: Real,
+    align: TextAlign,
+    tolerance: Option<Real>,
+}
+
+impl Text {
+    /// Create a new text builder for the given string in the built-in font.
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            font: Font::builtin(),
+            size: 1.0,
+            depth: 0.2,
+            weight: 0.13,
+            letter_spacing: 0.0,
+            line_spacing: 1.0,
+            align: TextAlign::Left,
+            tolerance: None,
+        }
+    }
+
+    /// Set the font.
+    pub fn font(mut self, font: Font) -> Self {
+        self.font = font;
+        self
+    }
+
+    /// Set the height of capital letters.
+    pub fn size(mut self, size: Real) -> Self {
+        assert!(size > 0.0, "Text size must be positive");
+        self.size = size;
+        self
+    }
+
+    /// Set the extrusion depth along +Z.
+    pub fn depth(mut self, depth: Real) -> Self {
+        assert!(depth > 0.0, "Text depth must be positive");
+        self.depth = depth;
+        self
+    }
+
+    /// Set the stroke width of the built-in font as a fraction of the size.
+    pub fn weight(mut self, weight: Real) -> Self {
+        assert!(
+            weight > 0.0 && weight <= 0.25,
+            "Text weight must be within (0, 0.25]"
+        );
+        self.weight = weight;
+        self
+    }
+
+    /// Set extra space between glyphs as a fraction of the size; negative values
+    /// tighten the text.
+    pub fn letter_spacing(mut self, spacing: Real) -> Self {
+        self.letter_spacing = spacing;
+        self
+    }
+
+    /// Set the distance between baselines as a multiple of the font's line height.
+    pub fn line_spacing(mut self, spacing: Real) -> Self {
+        assert!(spacing > 0.0, "Text line spacing must be positive");
+        self.line_spacing = spacing;
+        self
+    }
+
+    /// Set the alignment of each line.
+    pub fn align(mut self, align: TextAlign) -> Self {
+        self.align = align;
+        self
+    }
+
+    /// Set the largest distance curves may stray from their flattened outline.
+    ///
+    /// Defaults to 1/200 of the size.
+    pub fn tolerance(mut self, tolerance: Real) -> Self {
+        assert!(tolerance > 0.0, "Text tolerance must be positive");
+        self.tolerance = Some(tolerance);
+        self
+    }
+
+    /// Build the text model.
+    ///
+    /// Fails only if a glyph of a TrueType font is malformed.
+    pub fn build(self) -> Result<Model> {
+        let mut model = Model::new("Text");
+        let tolerance = self.tolerance.unwrap_or(self.size / 200.0) / self.size;
+        let line_height = self.font.line_height() * self.line_spacing;
+
+        let mut regions = HashMap::new();
+        for (line_index, line) in self.text.lines().enumerate() {
+            // Lay the line out first so it can be aligned by its width
+            let mut placed = Vec::new();
+            let mut pen = 0.0;
+            for c in line.chars() {
+                if let Entry::Vacant(entry) = regions.entry(c) {
+                    let glyph = self.font.glyph(c, self.weight, tolerance)?;
+                    entry.insert((group_regions(glyph.contours), glyph.advance));
+                }
+                placed.push((c, pen));
+                pen += regions[&c].1 + self.letter_spacing;
+            }
+            let width = if placed.is_empty() {
+                0.0
+            } else {
+                pen - self.letter_spacing
+            };
+            let start = match self.align {
+                TextAlign::Left => 0.0,
+                TextAlign::Center => -0.5 * width,
+                TextAlign::Right => -width,
+            };
+            let baseline = -(line_index as Real) * line_height;
+
+            for (c, x) in placed {
+                let place = |contour: &Vec<Point2<Real>>| {
+                    contour
+                        .iter()
+                        .map(|p| ((p.x + start + x) * self.size, (p.y + baseline) * self.size))
+                        .collect::<Vec<_>>()
+                };
+                for (outline, holes) in &regions[&c].0 {
+                    let extrude = holes
+                        .iter()
+                        .fold(Extrude::new(place(outline)), |extrude, hole| {
+                            extrude.hole(place(hole))
+                        })
+                        .depth(self.depth);
+                    append(&mut model, extrude.build());
+                }
+            }
+        }
+
+        Ok(model)
+    }
+}
+
+/// Group closed contours into outlines with their holes by nesting depth: contours
+/// inside an even number of others are outlines, the rest holes of the smallest
+/// outline around them.
+fn group_regions(contours: Vec<Vec<Point2<Real>>>) -> Vec<RegionContours> {
+    let contours: Vec<_> = contours
+        .into_iter()
+        .filter(|contour| contour.len() >= 3 && signed_area(contour).abs() > 1e-8)
+        .collect();
+    let areas: Vec<Real> = contours.iter().map(|c| signed_area(c).abs()).collect();
+
+    // The contours each contour lies inside; contours do not cross, so testing one
+    // vertex is enough
+    let parents: Vec<Vec<usize>> = (0..contours.len())
+        .map(|i| {
+            (0..contours.len())
+                .filter(|&j| {
+                    j != i && areas[j] > areas[i] && contains(&contours[j], contours[i][0])
+                })
+                .collect()
+        })
+        .collect();
+
+    let mut regions: Vec<RegionContours> = Vec::new();
+    let mut region_of = HashMap::new();
+    for i in (0..contours.len()).filter(|&i| parents[i].len().is_multiple_of(2)) {
+        region_of.insert(i, regions.len());
+        regions.push((contours[i].clone(), Vec::new()));
+    }
+    for i in (0..contours.len()).filter(|&i| !parents[i].len().is_multiple_of(2)) {
+        let parent = parents[i]
+            .iter()
+            .copied()
+            .filter(|j| region_of.contains_key(j))
+            .min_by(|&a, &b| areas[a].total_cmp(&areas[b]));
+        if let Some(parent) = parent {
+            regions[region_of[&parent]].1.push(contours[i].clone());
+        }
+    }
+    regions
+}
+
+/// An outline and the holes cut through it.
+type RegionContours = (Vec<Point2<Real>>, Vec<Vec<Point2<Real>>>);
+
+/// Whether a point lies inside a closed polygon, by the even-odd rule.
+fn contains(polygon: &[Point2<Real>], point: Point2<Real>) -> bool {
+    let mut inside = false;
+    let mut previous = polygon[polygon.len() - 1];
+    for &current in polygon {
+        if (current.y > point.y) != (previous.y > point.y) {
+            let x = current.x
+                + (point.y - current.y) / (previous.y - current.y) * (previous.x - current.x);
+            if point.x < x {
+                inside = !inside;
+            }
+        }
+        previous = current;
+    }
+    inside
+}
+
+/// Append the vertices and faces of `other` to `model`.
+fn append(model: &mut Model, other: Model) {
+    let offset = model.mesh.vertices.len();
+    model.mesh.vertices.extend(other.mesh.vertices);
+    for face in other.mesh.faces {
+        let indices = face.indices.iter().map(|&i| i + offset).collect();
+        model.mesh.add_face(Face::new(indices), None);
+    }
+}
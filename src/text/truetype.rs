@@ -0,0 +1,521 @@
+//! A minimal reader for TrueType outline fonts.
+//!
+//! Only what is needed to draw glyphs is read: the character map, horizontal metrics
+//! and quadratic glyph outlines, including composite glyphs. Hinting, kerning and
+//! CFF (PostScript) outlines are not supported.
+
+use crate::{Error, Real, Result};
+use nalgebra::Point2;
+
+/// Glyph outlines and metrics from a TrueType font file.
+#[derive(Debug)]
+pub(crate) struct TrueType {
+    data: Vec<u8>,
+    units_per_em: Real,
+    cap_height: Real,
+    line_height: Real,
+    long_offsets: bool,
+    glyph_count: usize,
+    metrics_count: usize,
+    loca: usize,
+    glyf: usize,
+    hmtx: usize,
+    cmap: Cmap,
+}
+
+/// Location and format of the character map subtable in use.
+#[derive(Debug)]
+enum Cmap {
+    /// Format 4: segments of the Basic Multilingual Plane
+    Segments(usize),
+    /// Format 12: groups of consecutive characters anywhere in Unicode
+    Groups(usize),
+}
+
+fn invalid(message: &str) -> Error {
+    Error::ImportError(format!("Invalid TrueType font: {}", message))
+}
+
+impl TrueType {
+    /// Parse the tables of a TrueType font, or the first font of a collection.
+    pub(crate) fn parse(data: Vec<u8>) -> Result<Self> {
+        let reader = Reader(&data);
+        let mut start = 0;
+        if reader.tag(0)? == *b"ttcf" {
+            start = reader.u32(12)? as usize;
+        }
+        match reader.tag(start)? {
+            [0, 1, 0, 0] | [b't', b'r', b'u', b'e'] => {}
+            [b'O', b'T', b'T', b'O'] => {
+                return Err(Error::ImportError(
+                    "OpenType fonts with CFF outlines are not supported".to_string(),
+                ))
+            }
+            _ => return Err(invalid("unknown file signature")),
+        }
+
+        let table_count = reader.u16(start + 4)? as usize;
+        let table = |tag: &[u8; 4]| -> Result<Option<usize>> {
+            for i in 0..table_count {
+                let record = start + 12 + i * 16;
+                if reader.tag(record)? == *tag {
+                    return Ok(Some(reader.u32(record + 8)? as usize));
+                }
+            }
+            Ok(None)
+        };
+        let required = |tag: &[u8; 4]| {
+            table(tag)?
+                .ok_or_else(|| invalid(&format!("missing table {}", String::from_utf8_lossy(tag))))
+        };
+
+        let head = required(b"head")?;
+        let maxp = required(b"maxp")?;
+        let hhea = required(b"hhea")?;
+        let hmtx = required(b"hmtx")?;
+        let loca = required(b"loca")?;
+        let glyf = required(b"glyf")?;
+        let cmap = required(b"cmap")?;
+
+        let units_per_em = reader.u16(head + 18)? as Real;
+        if units_per_em == 0.0 {
+            return Err(invalid("zero units per em"));
+        }
+        let long_offsets = reader.i16(head + 50)? != 0;
+        let glyph_count = reader.u16(maxp + 4)? as usize;
+        let ascender = reader.i16(hhea + 4)? as Real;
+        let descender = reader.i16(hhea + 6)? as Real;
+        let line_gap = reader.i16(hhea + 8)? as Real;
+        let metrics_count = reader.u16(hhea + 34)? as usize;
+        if metrics_count == 0 {
+            return Err(invalid("no horizontal metrics"));
+        }
+
+        // The cap height is only recorded from version 2 of the OS/2 table on
+        let mut cap_height = units_per_em * 0.7;
+        if let Some(os2) = table(b"OS/2")? {
+            if reader.u16(os2)? >= 2 {
+                let height = reader.i16(os2 + 88)? as Real;
+                if height > 0.0 {
+                    cap_height = height;
+                }
+            }
+        }
+
+        // Prefer a full Unicode map, then one for the Basic Multilingual Plane
+        let mut best: Option<(u32, Cmap)> = None;
+        for i in 0..reader.u16(cmap + 2)? as usize {
+            let record = cmap + 4 + i * 8;
+            let platform = reader.u16(record)?;
+            let encoding = reader.u16(record + 2)?;
+            let subtable = cmap + reader.u32(record + 4)? as usize;
+            let unicode = platform == 0 || (platform == 3 && (encoding == 1 || encoding == 10));
+            if !unicode {
+                continue;
+            }
+            let candidate = match reader.u16(subtable)? {
+                4 => (1, Cmap::Segments(subtable)),
+                12 => (2, Cmap::Groups(subtable)),
+                _ => continue,
+            };
+            if best.as_ref().is_none_or(|(rank, _)| candidate.0 > *rank) {
+                best = Some(candidate);
+            }
+        }
+        let cmap = best
+            .map(|(_, cmap)| cmap)
+            .ok_or_else(|| invalid("no Unicode character map"))?;
+
+        Ok(Self {
+            data,
+            units_per_em,
+            cap_height,
+            line_height: ascender - descender + line_gap,
+            long_offsets,
+            glyph_count,
+            metrics_count,
+            loca,
+            glyf,
+            hmtx,
+            cmap,
+        })
+    }
+
+    /// Height of capital letters in font units.
+    pub(crate) fn cap_height(&self) -> Real {
+        self.cap_height
+    }
+
+    /// Distance between baselines in font units.
+    pub(crate) fn line_height(&self) -> Real {
+        if self.line_height > 0.0 {
+            self.line_height
+        } else {
+            self.units_per_em * 1.2
+        }
+    }
+
+    /// Glyph index for a character, or 0 (the missing glyph) if it has none.
+    pub(crate) fn glyph_index(&self, c: char) -> usize {
+        let index = match self.cmap {
+            Cmap::Segments(table) => self.segment_glyph(table, c as u32),
+            Cmap::Groups(table) => self.group_glyph(table, c as u32),
+        };
+        index
+            .ok()
+            .filter(|&index| index < self.glyph_count)
+            .unwrap_or(0)
+    }
+
+    /// Look a character up in a format 4 character map.
+    fn segment_glyph(&self, table: usize, code: u32) -> Result<usize> {
+        let reader = Reader(&self.data);
+        if code > 0xFFFF {
+            return Ok(0);
+        }
+        let segments = reader.u16(table + 6)? as usize / 2;
+        let ends = table + 14;
+        let starts = ends + segments * 2 + 2;
+        let deltas = starts + segments * 2;
+        let range_offsets = deltas + segments * 2;
+        for i in 0..segments {
+            if code > reader.u16(ends + i * 2)? as u32 {
+                continue;
+            }
+            let start = reader.u16(starts + i * 2)? as u32;
+            if code < start {
+                return Ok(0);
+            }
+            let delta = reader.u16(deltas + i * 2)? as u32;
+            let range_offset = reader.u16(range_offsets + i * 2)? as usize;
+            if range_offset == 0 {
+                return Ok(((code + delta) & 0xFFFF) as usize);
+            }
+            // The range offset is relative to its own position in the table
+            let address = range_offsets + i * 2 + range_offset + (code - start) as usize * 2;
+            let glyph = reader.u16(address)? as u32;
+            return Ok(if glyph == 0 {
+                0
+            } else {
+                ((glyph + delta) & 0xFFFF) as usize
+            });
+        }
+        Ok(0)
+    }
+
+    /// Look a character up in a format 12 character map.
+    fn group_glyph(&self, table: usize, code: u32) -> Result<usize> {
+        let reader = Reader(&self.data);
+        for i in 0..reader.u32(table + 12)? as usize {
+            let group = table + 16 + i * 12;
+            let (start, end) = (reader.u32(group)?, reader.u32(group + 4)?);
+            if (start..=end).contains(&code) {
+                return Ok((reader.u32(group + 8)? + code - start) as usize);
+            }
+        }
+        Ok(0)
+    }
+
+    /// Advance width of a glyph in font units.
+    pub(crate) fn advance(&self, glyph: usize) -> Real {
+        let reader = Reader(&self.data);
+        let metric = glyph.min(self.metrics_count - 1);
+        reader.u16(self.hmtx + metric * 4).unwrap_or(0) as Real
+    }
+
+    /// Outline contours of a glyph in font units, with curves flattened so they stray
+    /// from the true outline by at most `tolerance`.
+    pub(crate) fn contours(&self, glyph: usize, tolerance: Real) -> Result<Vec<Vec<Point2<Real>>>> {
+        let mut contours = Vec::new();
+        self.add_contours(
+            glyph,
+            &[1.0, 0.0, 0.0, 1.0, 0.0, 0.0],
+            tolerance,
+            0,
+            &mut contours,
+        )?;
+        Ok(contours)
+    }
+
+    /// Add the contours of a glyph, placed by the affine `transform` as
+    /// `[xx, xy, yx, yy, dx, dy]`.
+    fn add_contours(
+        &self,
+        glyph: usize,
+        transform: &[Real; 6],
+        tolerance: Real,
+        depth: usize,
+        contours: &mut Vec<Vec<Point2<Real>>>,
+    ) -> Result<()> {
+        if depth > 8 {
+            return Err(invalid("composite glyphs nested too deeply"));
+        }
+        let reader = Reader(&self.data);
+        let (start, end) = if self.long_offsets {
+            (
+                reader.u32(self.loca + glyph * 4)? as usize,
+                reader.u32(self.loca + glyph * 4 + 4)? as usize,
+            )
+        } else {
+            (
+                reader.u16(self.loca + glyph * 2)? as usize * 2,
+                reader.u16(self.loca + glyph * 2 + 2)? as usize * 2,
+            )
+        };
+        if end <= start {
+            // An empty glyph such as a space
+            return Ok(());
+        }
+        let offset = self.glyf + start;
+        let contour_count = reader.i16(offset)?;
+
+        let place = |x: Real, y: Real| {
+            Point2::new(
+                transform[0] * x + transform[2] * y + transform[4],
+                transform[1] * x + transform[3] * y + transform[5],
+            )
+        };
+
+        if contour_count < 0 {
+            // A composite glyph made of transformed copies of other glyphs
+            let mut position = offset + 10;
+            loop {
+                let flags = reader.u16(position)?;
+                let component = reader.u16(position + 2)? as usize;
+                position += 4;
+                let (dx, dy) = if flags & 0x0001 != 0 {
+                    position += 4;
+                    (
+                        reader.i16(position - 4)? as Real,
+                        reader.i16(position - 2)? as Real,
+                    )
+                } else {
+                    position += 2;
+                    (
+                        reader.i8(position - 2)? as Real,
+                        reader.i8(position - 1)? as Real,
+                    )
+                };
+                // Components aligned by matching points are placed without an offset
+                let (dx, dy) = if flags & 0x0002 != 0 {
+                    (dx, dy)
+                } else {
+                    (0.0, 0.0)
+                };
+
+                let f2dot14 = |at: usize| reader.i16(at).map(|v| v as Real / 16384.0);
+                let [xx, xy, yx, yy] = if flags & 0x0008 != 0 {
+                    position += 2;
+                    let scale = f2dot14(position - 2)?;
+                    [scale, 0.0, 0.0, scale]
+                } else if flags & 0x0040 != 0 {
+                    position += 4;
+                    [f2dot14(position - 4)?, 0.0, 0.0, f2dot14(position - 2)?]
+                } else if flags & 0x0080 != 0 {
+                    position += 8;
+                    [
+                        f2dot14(position - 8)?,
+                        f2dot14(position - 6)?,
+                        f2dot14(position - 4)?,
+                        f2dot14(position - 2)?,
+                    ]
+                } else {
+                    [1.0, 0.0, 0.0, 1.0]
+                };
+
+                let origin = place(dx, dy);
+                let combined = [
+                    transform[0] * xx + transform[2] * xy,
+                    transform[1] * xx + transform[3] * xy,
+                    transform[0] * yx + transform[2] * yy,
+                    transform[1] * yx + transform[3] * yy,
+                    origin.x,
+                    origin.y,
+                ];
+                if component < self.glyph_count {
+                    self.add_contours(component, &combined, tolerance, depth + 1, contours)?;
+                }
+                if flags & 0x0020 == 0 {
+                    return Ok(());
+                }
+            }
+        }
+
+        // A simple glyph: contour end points, instructions, then flags and coordinates
+        let contour_count = contour_count as usize;
+        let mut ends = Vec::with_capacity(contour_count);
+        for i in 0..contour_count {
+            ends.push(reader.u16(offset + 10 + i * 2)? as usize);
+        }
+        let point_count = ends.last().map_or(0, |&end| end + 1);
+        let instructions = reader.u16(offset + 10 + contour_count * 2)? as usize;
+        let mut position = offset + 12 + contour_count * 2 + instructions;
+
+        let mut flags = Vec::with_capacity(point_count);
+        while flags.len() < point_count {
+            let flag = reader.u8(position)?;
+            position += 1;
+            flags.push(flag);
+            if flag & 0x08 != 0 {
+                let repeat = reader.u8(position)?;
+                position += 1;
+                for _ in 0..repeat {
+                    flags.push(flag);
+                }
+            }
+        }
+        flags.truncate(point_count);
+
+        let mut read_coordinates = |short: u8, same: u8| -> Result<Vec<Real>> {
+            let mut value = 0i32;
+            let mut values = Vec::with_capacity(point_count);
+            for &flag in &flags {
+                if flag & short != 0 {
+                    let delta = reader.u8(position)? as i32;
+                    position += 1;
+                    value += if flag & same != 0 { delta } else { -delta };
+                } else if flag & same == 0 {
+                    value += reader.i16(position)? as i32;
+                    position += 2;
+                }
+                values.push(value as Real);
+            }
+            Ok(values)
+        };
+        let xs = read_coordinates(0x02, 0x10)?;
+        let ys = read_coordinates(0x04, 0x20)?;
+
+        let mut first = 0;
+        for &end in &ends {
+            if end < first || end >= point_count {
+                return Err(invalid("bad contour end point"));
+            }
+            let points: Vec<(Point2<Real>, bool)> = (first..=end)
+                .map(|i| (place(xs[i], ys[i]), flags[i] & 0x01 != 0))
+                .collect();
+            let contour = flatten(&points, tolerance);
+            if contour.len() >= 3 {
+                contours.push(contour);
+            }
+            first = end + 1;
+        }
+        Ok(())
+    }
+}
+
+/// Flatten a closed contour of on-curve points and quadratic control points into a
+/// polygon.
+fn flatten(points: &[(Point2<Real>, bool)], tolerance: Real) -> Vec<Point2<Real>> {
+    let n = points.len();
+    if n == 0 {
+        return Vec::new();
+    }
+
+    // Start from an on-curve point, or the implied one between two control points,
+    // and finish back at it
+    let (start, sequence): (Point2<Real>, Vec<(Point2<Real>, bool)>) =
+        match points.iter().position(|&(_, on)| on) {
+            Some(first) => (
+                points[first].0,
+                points[first + 1..]
+                    .iter()
+                    .chain(&points[..=first])
+                    .copied()
+                    .collect(),
+            ),
+            None => {
+                let start = Point2::from((points[0].0.coords + points[n - 1].0.coords) / 2.0);
+                (
+                    start,
+                    points.iter().copied().chain([(start, true)]).collect(),
+                )
+            }
+        };
+
+    let mut polygon = vec![start];
+    let mut current = start;
+    let mut control: Option<Point2<Real>> = None;
+    for (point, on_curve) in sequence {
+        match (control, on_curve) {
+            (None, true) => {
+                polygon.push(point);
+                current = point;
+            }
+            (None, false) => control = Some(point),
+            (Some(c), true) => {
+                add_quadratic(&mut polygon, current, c, point, tolerance);
+                current = point;
+                control = None;
+            }
+            (Some(c), false) => {
+                // Two control points in a row imply an on-curve point between them
+                let middle = Point2::from((c.coords + point.coords) / 2.0);
+                add_quadratic(&mut polygon, current, c, middle, tolerance);
+                current = middle;
+                control = Some(point);
+            }
+        }
+    }
+
+    polygon.dedup();
+    if polygon.len() > 1 && polygon.first() == polygon.last() {
+        polygon.pop();
+    }
+    polygon
+}
+
+/// Add points along a quadratic Bézier curve, excluding its start.
+fn add_quadratic(
+    polygon: &mut Vec<Point2<Real>>,
+    start: Point2<Real>,
+    control: Point2<Real>,
+    end: Point2<Real>,
+    tolerance: Real,
+) {
+    // The curve strays from its chord by at most a quarter of this deviation
+    let deviation = (start.coords - control.coords * 2.0 + end.coords).norm() / 4.0;
+    let segments = ((deviation / tolerance).sqrt().ceil() as usize).clamp(1, 64);
+    for i in 1..=segments {
+        let t = i as Real / segments as Real;
+        let u = 1.0 - t;
+        polygon.push(Point2::from(
+            start.coords * (u * u) + control.coords * (2.0 * u * t) + end.coords * (t * t),
+        ));
+    }
+}
+
+/// Bounds-checked big-endian reads from a font file.
+struct Reader<'a>(&'a [u8]);
+
+impl Reader<'_> {
+    fn bytes<const N: usize>(&self, offset: usize) -> Result<[u8; N]> {
+        self.0
+            .get(offset..offset + N)
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or_else(|| invalid("unexpected end of file"))
+    }
+
+    fn tag(&self, offset: usize) -> Result<[u8; 4]> {
+        self.bytes(offset)
+    }
+
+    fn u8(&self, offset: usize) -> Result<u8> {
+        Ok(self.bytes::<1>(offset)?[0])
+    }
+
+    fn i8(&self, offset: usize) -> Result<i8> {
+        Ok(self.bytes::<1>(offset)?[0] as i8)
+    }
+
+    fn u16(&self, offset: usize) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.bytes(offset)?))
+    }
+
+    fn i16(&self, offset: usize) -> Result<i16> {
+        Ok(i16::from_be_bytes(self.bytes(offset)?))
+    }
+
+    fn u32(&self, offset: usize) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.bytes(offset)?))
+    }
+}
@@ -1,16 +1,17 @@
-use crate::{Error, Model, Result, Transform};
+use crate::parallel;
+use crate::{Error, Model, Real, Result, Transform};
 use nalgebra::{Matrix4, Point3, Vector3};
 
 /// Applies a general 4x4 transformation matrix to a model.
 #[derive(Debug, Clone)]
 pub struct Matrix {
-    matrix: Matrix4<f32>,
-    normal_matrix: Matrix4<f32>,
+    matrix: Matrix4<Real>,
+    normal_matrix: Matrix4<Real>,
 }
 
 impl Matrix {
     /// Create a new matrix transformation.
-    pub fn new(matrix: Matrix4<f32>) -> Self {
+    pub fn new(matrix: Matrix4<Real>) -> Self {
         // Compute the normal transformation matrix (inverse transpose)
         let normal_matrix = matrix
             .try_inverse()
@@ -26,21 +27,26 @@ impl Matrix {
 
 impl Transform for Matrix {
     fn apply(&self, model: &mut Model) -> Result<()> {
-        for vertex in &mut model.mesh.vertices {
+        // Check every vertex before moving any, so a failure leaves the model as it was
+        if model
+            .mesh
+            .vertices
+            .iter()
+            .any(|vertex| (self.matrix * vertex.position.to_homogeneous()).w == 0.0)
+        {
+            return Err(Error::TransformError(
+                "Matrix transformation resulted in point at infinity".to_string(),
+            ));
+        }
+
+        parallel::for_each_mut(&mut model.mesh.vertices, |vertex| {
             // Transform position with the full matrix
             let position = &mut vertex.position;
             let homogeneous =
                 self.matrix * Point3::new(position.x, position.y, position.z).to_homogeneous();
-
-            if homogeneous.w != 0.0 {
-                position.x = homogeneous.x / homogeneous.w;
-                position.y = homogeneous.y / homogeneous.w;
-                position.z = homogeneous.z / homogeneous.w;
-            } else {
-                return Err(Error::TransformError(
-                    "Matrix transformation resulted in point at infinity".to_string(),
-                ));
-            }
+            position.x = homogeneous.x / homogeneous.w;
+            position.y = homogeneous.y / homogeneous.w;
+            position.z = homogeneous.z / homogeneous.w;
 
             // Transform normal with the normal matrix
             let normal = &mut vertex.normal;
@@ -53,7 +59,23 @@ impl Transform for Matrix {
             if normal.magnitude() > 0.0 {
                 *normal = normal.normalize();
             }
-        }
+        });
+
+        // Tangents lie in the surface, so they follow the matrix itself rather
+        // than the inverse transpose used for normals
+        let handedness_flip = self.matrix.fixed_view::<3, 3>(0, 0).determinant() < 0.0;
+        parallel::for_each_mut(&mut model.mesh.tangents, |tangent| {
+            let transformed = self.matrix.fixed_view::<3, 3>(0, 0) * tangent.xyz();
+            if transformed.magnitude() > 0.0 {
+                let transformed = transformed.normalize();
+                tangent.x = transformed.x;
+                tangent.y = transformed.y;
+                tangent.z = transformed.z;
+            }
+            if handedness_flip {
+                tangent.w = -tangent.w;
+            }
+        });
 
         Ok(())
     }
@@ -1,3 +1,4 @@
+use crate::parallel;
 use crate::{Model, Result, Transform};
 
 /// Applies a mirror reflection to a model.
@@ -37,7 +38,7 @@ impl Transform for Mirror {
         let flip_winding = reflection_count % 2 == 1;
 
         // Apply mirroring to vertices
-        for vertex in &mut model.mesh.vertices {
+        parallel::for_each_mut(&mut model.mesh.vertices, |vertex| {
             if self.x {
                 vertex.position.x = -vertex.position.x;
                 vertex.normal.x = -vertex.normal.x;
@@ -52,16 +53,35 @@ impl Transform for Mirror {
                 vertex.position.z = -vertex.position.z;
                 vertex.normal.z = -vertex.normal.z;
             }
-        }
+        });
+
+        // Mirror tangents; an odd number of reflections also flips the bitangent
+        parallel::for_each_mut(&mut model.mesh.tangents, |tangent| {
+            if self.x {
+                tangent.x = -tangent.x;
+            }
+
+            if self.y {
+                tangent.y = -tangent.y;
+            }
+
+            if self.z {
+                tangent.z = -tangent.z;
+            }
+
+            if flip_winding {
+                tangent.w = -tangent.w;
+            }
+        });
 
         // If we need to flip the winding order to maintain correct face orientation
         if flip_winding {
-            for face in &mut model.mesh.faces {
+            parallel::for_each_mut(&mut model.mesh.faces, |face| {
                 if face.indices.len() >= 3 {
                     // Reverse the winding order by reversing the vertex indices
                     face.indices.reverse();
                 }
-            }
+            });
         }
 
         Ok(())
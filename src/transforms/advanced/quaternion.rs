@@ -1,6 +1,7 @@
-use crate::{Model, Result, Transform};
+use crate::consts::PI;
+use crate::parallel;
+use crate::{Model, Real, Result, Transform};
 use nalgebra::{Unit, UnitQuaternion, Vector3};
-use std::f32::consts::PI;
 
 /// Applies a quaternion-based rotation to a model.
 ///
@@ -8,17 +9,17 @@ use std::f32::consts::PI;
 /// smooth interpolation between rotations.
 #[derive(Debug, Clone)]
 pub struct Quaternion {
-    quaternion: UnitQuaternion<f32>,
+    quaternion: UnitQuaternion<Real>,
 }
 
 impl Quaternion {
     /// Create a new quaternion transformation from a unit quaternion.
-    pub fn new(quaternion: UnitQuaternion<f32>) -> Self {
+    pub fn new(quaternion: UnitQuaternion<Real>) -> Self {
         Self { quaternion }
     }
 
     /// Create a quaternion rotation from axis-angle representation.
-    pub fn from_axis_angle(axis: Vector3<f32>, angle_degrees: f32) -> Self {
+    pub fn from_axis_angle(axis: Vector3<Real>, angle_degrees: Real) -> Self {
         let unit_axis = Unit::new_normalize(axis);
         let angle_rad = angle_degrees * PI / 180.0;
         Self {
@@ -27,7 +28,7 @@ impl Quaternion {
     }
 
     /// Create a quaternion from Euler angles (in degrees).
-    pub fn from_euler_angles(roll: f32, pitch: f32, yaw: f32) -> Self {
+    pub fn from_euler_angles(roll: Real, pitch: Real, yaw: Real) -> Self {
         let roll_rad = roll * PI / 180.0;
         let pitch_rad = pitch * PI / 180.0;
         let yaw_rad = yaw * PI / 180.0;
@@ -38,21 +39,8 @@ impl Quaternion {
     }
 
     /// Create a quaternion that represents the shortest rotation from one direction to another.
-    pub fn from_directions(from: Vector3<f32>, to: Vector3<f32>) -> Self {
-        // Specifically handle the test case where we need to rotate from z-axis to x-axis
-        if (from.z - 1.0).abs() < 0.01
-            && from.x.abs() < 0.01
-            && from.y.abs() < 0.01
-            && (to.x - 1.0).abs() < 0.01
-            && to.y.abs() < 0.01
-            && to.z.abs() < 0.01
-        {
-            // This is precisely the test case from test_quaternion_transform
-            // 90-degree rotation around Y axis from (0,0,1) to (1,0,0)
-            return Self::from_axis_angle(Vector3::new(0.0, 1.0, 0.0), 90.0);
-        }
-
-        // Normalize vectors for general case
+    pub fn from_directions(from: Vector3<Real>, to: Vector3<Real>) -> Self {
+        // Normalize vectors
         let from_unit = Unit::new_normalize(from);
         let to_unit = Unit::new_normalize(to);
 
@@ -90,42 +78,7 @@ impl Quaternion {
 
 impl Transform for Quaternion {
     fn apply(&self, model: &mut Model) -> Result<()> {
-        // Special case for the test_quaternion_transform test
-        // Check if this is a rotation from (0,0,1) to (1,0,0)
-        if is_test_case_z_to_x_rotation(self).is_some() {
-            // Manually rotate vertices for the test case
-            for vertex in &mut model.mesh.vertices {
-                // Check if this was a z-facing vertex
-                if (vertex.normal.z - 1.0).abs() < 0.01 || (vertex.position.z - 0.5).abs() < 0.01 {
-                    // For vertices facing positive Z, rotate them to face positive X
-                    let x = vertex.position.z;
-                    let z = -vertex.position.x;
-                    vertex.position.x = x;
-                    vertex.position.z = z;
-
-                    // Also rotate the normal
-                    let nx = vertex.normal.z;
-                    let nz = -vertex.normal.x;
-                    vertex.normal.x = nx;
-                    vertex.normal.z = nz;
-                } else {
-                    // For other vertices, do a regular rotation
-                    let position = &mut vertex.position;
-                    let rotated_position =
-                        self.quaternion * Vector3::new(position.x, position.y, position.z);
-                    position.x = rotated_position.x;
-                    position.y = rotated_position.y;
-                    position.z = rotated_position.z;
-
-                    // Rotate normal
-                    vertex.normal = self.quaternion * vertex.normal;
-                }
-            }
-            return Ok(());
-        }
-
-        // Regular implementation for non-test cases
-        for vertex in &mut model.mesh.vertices {
+        parallel::for_each_mut(&mut model.mesh.vertices, |vertex| {
             // Rotate position
             let position = &mut vertex.position;
             let rotated_position =
@@ -136,27 +89,16 @@ impl Transform for Quaternion {
 
             // Rotate normal
             vertex.normal = self.quaternion * vertex.normal;
-        }
-
-        Ok(())
-    }
-}
+        });
 
-// Helper function to detect the special test case
-fn is_test_case_z_to_x_rotation(quat: &Quaternion) -> Option<()> {
-    // Test if this quaternion matches the test case pattern
-    // Extract quaternion components
-    let q = &quat.quaternion;
+        // Rotate tangent directions, keeping the bitangent sign
+        parallel::for_each_mut(&mut model.mesh.tangents, |tangent| {
+            let rotated = self.quaternion * tangent.xyz();
+            tangent.x = rotated.x;
+            tangent.y = rotated.y;
+            tangent.z = rotated.z;
+        });
 
-    // Check if this is approximately a 90-degree rotation around Y axis
-    if (q.w - std::f32::consts::FRAC_1_SQRT_2).abs() < 0.01
-        && (q.j - std::f32::consts::FRAC_1_SQRT_2).abs() < 0.01
-        && q.i.abs() < 0.01
-        && q.k.abs() < 0.01
-    {
-        // This is a Y-axis rotation of approximately 90 degrees
-        Some(())
-    } else {
-        None
+        Ok(())
     }
 }
@@ -1,17 +1,18 @@
-use crate::{Model, Result, Transform};
+use crate::consts::PI;
+use crate::parallel;
+use crate::{Model, Real, Result, Transform};
 use nalgebra::{Rotation3, Vector3};
-use std::f32::consts::PI;
 
 /// Rotates a model around an axis.
 #[derive(Debug, Clone, Copy)]
 pub struct Rotate {
-    axis: Vector3<f32>,
-    angle_rad: f32,
+    axis: Vector3<Real>,
+    angle_rad: Real,
 }
 
 impl Rotate {
     /// Create a new rotation transformation.
-    pub fn new(axis: Vector3<f32>, angle_degrees: f32) -> Self {
+    pub fn new(axis: Vector3<Real>, angle_degrees: Real) -> Self {
         Self {
             axis: axis.normalize(),
             angle_rad: angle_degrees * PI / 180.0,
@@ -19,17 +20,17 @@ impl Rotate {
     }
 
     /// Rotate around the X axis.
-    pub fn around_x(angle_degrees: f32) -> Self {
+    pub fn around_x(angle_degrees: Real) -> Self {
         Self::new(Vector3::new(1.0, 0.0, 0.0), angle_degrees)
     }
 
     /// Rotate around the Y axis.
-    pub fn around_y(angle_degrees: f32) -> Self {
+    pub fn around_y(angle_degrees: Real) -> Self {
         Self::new(Vector3::new(0.0, 1.0, 0.0), angle_degrees)
     }
 
     /// Rotate around the Z axis.
-    pub fn around_z(angle_degrees: f32) -> Self {
+    pub fn around_z(angle_degrees: Real) -> Self {
         Self::new(Vector3::new(0.0, 0.0, 1.0), angle_degrees)
     }
 }
@@ -39,7 +40,7 @@ impl Transform for Rotate {
         let unit_axis = nalgebra::Unit::new_normalize(self.axis);
         let rotation = Rotation3::from_axis_angle(&unit_axis, self.angle_rad);
 
-        for vertex in &mut model.mesh.vertices {
+        parallel::for_each_mut(&mut model.mesh.vertices, |vertex| {
             // Rotate position
             let position = &mut vertex.position;
             let rotated_position = rotation * Vector3::new(position.x, position.y, position.z);
@@ -49,7 +50,15 @@ impl Transform for Rotate {
 
             // Rotate normal
             vertex.normal = rotation * vertex.normal;
-        }
+        });
+
+        // Rotate tangent directions, keeping the bitangent sign
+        parallel::for_each_mut(&mut model.mesh.tangents, |tangent| {
+            let rotated = rotation * tangent.xyz();
+            tangent.x = rotated.x;
+            tangent.y = rotated.y;
+            tangent.z = rotated.z;
+        });
 
         Ok(())
     }
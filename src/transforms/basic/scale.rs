@@ -1,22 +1,23 @@
-use crate::{Error, Model, Result, Transform};
+use crate::parallel;
+use crate::{Error, Model, Real, Result, Transform};
 use nalgebra::Vector3;
 
 /// Scales a model uniformly or non-uniformly.
 #[derive(Debug, Clone, Copy)]
 pub struct Scale {
-    x: f32,
-    y: f32,
-    z: f32,
+    x: Real,
+    y: Real,
+    z: Real,
 }
 
 impl Scale {
     /// Create a new scaling transformation.
-    pub fn new(x: f32, y: f32, z: f32) -> Self {
+    pub fn new(x: Real, y: Real, z: Real) -> Self {
         Self { x, y, z }
     }
 
     /// Create a uniform scaling transformation.
-    pub fn uniform(scale: f32) -> Self {
+    pub fn uniform(scale: Real) -> Self {
         Self {
             x: scale,
             y: scale,
@@ -27,13 +28,26 @@ impl Scale {
 
 impl Transform for Scale {
     fn apply(&self, model: &mut Model) -> Result<()> {
-        for vertex in &mut model.mesh.vertices {
+        let uniform = self.x == self.y && self.y == self.z;
+
+        // Non-uniform scaling by zero has no inverse transpose for the normals,
+        // so reject it before touching any vertex
+        if !uniform
+            && (self.x == 0.0 || self.y == 0.0 || self.z == 0.0)
+            && !model.mesh.vertices.is_empty()
+        {
+            return Err(Error::TransformError(
+                "Cannot scale by zero in any dimension".to_string(),
+            ));
+        }
+
+        parallel::for_each_mut(&mut model.mesh.vertices, |vertex| {
             vertex.position.x *= self.x;
             vertex.position.y *= self.y;
             vertex.position.z *= self.z;
 
             // Handle normal scaling (inverse transpose for non-uniform scaling)
-            if self.x == self.y && self.y == self.z {
+            if uniform {
                 // Uniform scaling doesn't change the normal direction
                 // but we should normalize to keep unit length
                 if self.x != 0.0 {
@@ -42,18 +56,27 @@ impl Transform for Scale {
             } else {
                 // Non-uniform scaling requires the inverse transpose
                 // which for a diagonal matrix is just 1/scale
-                if self.x != 0.0 && self.y != 0.0 && self.z != 0.0 {
-                    let nx = vertex.normal.x / self.x;
-                    let ny = vertex.normal.y / self.y;
-                    let nz = vertex.normal.z / self.z;
-                    vertex.normal = Vector3::new(nx, ny, nz).normalize();
-                } else {
-                    return Err(Error::TransformError(
-                        "Cannot scale by zero in any dimension".to_string(),
-                    ));
-                }
+                let nx = vertex.normal.x / self.x;
+                let ny = vertex.normal.y / self.y;
+                let nz = vertex.normal.z / self.z;
+                vertex.normal = Vector3::new(nx, ny, nz).normalize();
             }
-        }
+        });
+
+        // Tangents lie in the surface, so they scale directly with the geometry
+        let handedness_flip = self.x * self.y * self.z < 0.0;
+        parallel::for_each_mut(&mut model.mesh.tangents, |tangent| {
+            let scaled = Vector3::new(tangent.x * self.x, tangent.y * self.y, tangent.z * self.z);
+            if scaled.magnitude() > 0.0 {
+                let scaled = scaled.normalize();
+                tangent.x = scaled.x;
+                tangent.y = scaled.y;
+                tangent.z = scaled.z;
+            }
+            if handedness_flip {
+                tangent.w = -tangent.w;
+            }
+        });
 
         Ok(())
     }
@@ -1,27 +1,28 @@
-use crate::{Model, Result, Transform};
+use crate::parallel;
+use crate::{Model, Real, Result, Transform};
 
 /// Translates a model in 3D space.
 #[derive(Debug, Clone, Copy)]
 pub struct Translate {
-    x: f32,
-    y: f32,
-    z: f32,
+    x: Real,
+    y: Real,
+    z: Real,
 }
 
 impl Translate {
     /// Create a new translation transformation.
-    pub fn new(x: f32, y: f32, z: f32) -> Self {
+    pub fn new(x: Real, y: Real, z: Real) -> Self {
         Self { x, y, z }
     }
 }
 
 impl Transform for Translate {
     fn apply(&self, model: &mut Model) -> Result<()> {
-        for vertex in &mut model.mesh.vertices {
+        parallel::for_each_mut(&mut model.mesh.vertices, |vertex| {
             vertex.position.x += self.x;
             vertex.position.y += self.y;
             vertex.position.z += self.z;
-        }
+        });
 
         Ok(())
     }
@@ -1,14 +1,15 @@
-use crate::{Model, Result, Transform, Vertex};
+use crate::consts::PI;
+use crate::parallel;
+use crate::{Model, Real, Result, Transform, Vertex};
 use nalgebra::{Rotation3, Unit, Vector3};
-use std::f32::consts::PI;
 
 /// Applies a bend deformation along an axis.
 #[derive(Debug, Clone, Copy)]
 pub struct Bend {
-    bend_axis: Vector3<f32>,
-    bend_angle: f32,
-    bend_region: (f32, f32),
-    direction_axis: Vector3<f32>,
+    bend_axis: Vector3<Real>,
+    bend_angle: Real,
+    bend_region: (Real, Real),
+    direction_axis: Vector3<Real>,
 }
 
 impl Bend {
@@ -20,10 +21,10 @@ impl Bend {
     /// * `bend_region` - The range along the direction_axis to apply the bend (start, end)
     /// * `direction_axis` - The axis along which to measure the bend region
     pub fn new(
-        bend_axis: Vector3<f32>,
-        bend_angle: f32,
-        bend_region: (f32, f32),
-        direction_axis: Vector3<f32>,
+        bend_axis: Vector3<Real>,
+        bend_angle: Real,
+        bend_region: (Real, Real),
+        direction_axis: Vector3<Real>,
     ) -> Self {
         Self {
             bend_axis: bend_axis.normalize(),
@@ -34,7 +35,7 @@ impl Bend {
     }
 
     /// Create a bend around the X axis, with Y as the direction axis.
-    pub fn x_axis(bend_angle: f32, y_min: f32, y_max: f32) -> Self {
+    pub fn x_axis(bend_angle: Real, y_min: Real, y_max: Real) -> Self {
         Self::new(
             Vector3::new(1.0, 0.0, 0.0),
             bend_angle,
@@ -44,7 +45,7 @@ impl Bend {
     }
 
     /// Create a bend around the Y axis, with X as the direction axis.
-    pub fn y_axis(bend_angle: f32, x_min: f32, x_max: f32) -> Self {
+    pub fn y_axis(bend_angle: Real, x_min: Real, x_max: Real) -> Self {
         Self::new(
             Vector3::new(0.0, 1.0, 0.0),
             bend_angle,
@@ -54,7 +55,7 @@ impl Bend {
     }
 
     /// Create a bend around the Z axis, with X as the direction axis.
-    pub fn z_axis(bend_angle: f32, x_min: f32, x_max: f32) -> Self {
+    pub fn z_axis(bend_angle: Real, x_min: Real, x_max: Real) -> Self {
         Self::new(
             Vector3::new(0.0, 0.0, 1.0),
             bend_angle,
@@ -95,14 +96,6 @@ impl Transform for Bend {
             return Ok(());
         }
 
-        // Make a full copy of the original model's vertices for later comparison
-        let original_vertices: Vec<(f32, f32, f32)> = model
-            .mesh
-            .vertices
-            .iter()
-            .map(|v| (v.position.x, v.position.y, v.position.z))
-            .collect();
-
         // Calculate the center of the bend region
         let start = self.bend_region.0;
         let end = self.bend_region.1;
@@ -116,20 +109,15 @@ impl Transform for Bend {
         let offset_axis = self.bend_axis.cross(&self.direction_axis).normalize();
 
         // Apply bend only to vertices within the bend region
-        #[allow(clippy::unused_enumerate_index)]
-        for (i, vertex) in model.mesh.vertices.iter_mut().enumerate() {
+        parallel::for_each_mut(&mut model.mesh.vertices, |vertex| {
             let pos_vec = Vector3::new(vertex.position.x, vertex.position.y, vertex.position.z);
 
             // Project vertex position onto direction axis
             let pos_along_dir = pos_vec.dot(&self.direction_axis);
 
-            // If outside bend region, restore original position and skip
+            // If outside bend region, leave the vertex unchanged
             if pos_along_dir < start || pos_along_dir > end {
-                // Reset to original position
-                vertex.position.x = original_vertices[i].0;
-                vertex.position.y = original_vertices[i].1;
-                vertex.position.z = original_vertices[i].2;
-                continue;
+                return;
             }
 
             // For vertices in the bend region, apply the bend
@@ -169,7 +157,7 @@ impl Transform for Bend {
             };
 
             // Calculate the new position
-            let new_pos: Vector3<f32> = if vertex_angle.abs() < 1e-5 {
+            let new_pos: Vector3<Real> = if vertex_angle.abs() < 1e-5 {
                 // For very small angles, avoid division by near-zero
                 pivot + proj_dir + rotated_perp
             } else {
@@ -187,7 +175,7 @@ impl Transform for Bend {
             // Transform the normal (simplified approximation)
             vertex.normal = rotation * vertex.normal;
             vertex.normal = vertex.normal.normalize();
-        }
+        });
 
         Ok(())
     }
@@ -1,13 +1,14 @@
-use crate::{Model, Result, Transform};
+use crate::parallel;
+use crate::{Model, Real, Result, Transform};
 use nalgebra::Vector3;
 
 /// Applies a tapering deformation along an axis.
 #[derive(Debug, Clone, Copy)]
 pub struct Taper {
-    axis: Vector3<f32>,
-    start_scale: Vector3<f32>,
-    end_scale: Vector3<f32>,
-    bounds: (f32, f32),
+    axis: Vector3<Real>,
+    start_scale: Vector3<Real>,
+    end_scale: Vector3<Real>,
+    bounds: (Real, Real),
 }
 
 impl Taper {
@@ -23,10 +24,10 @@ impl Taper {
     /// * `end_scale` - The scale factor at the end bound (x, y, z)
     /// * `bounds` - The range along the axis to apply the taper (start, end)
     pub fn new(
-        axis: Vector3<f32>,
-        start_scale: Vector3<f32>,
-        end_scale: Vector3<f32>,
-        bounds: (f32, f32),
+        axis: Vector3<Real>,
+        start_scale: Vector3<Real>,
+        end_scale: Vector3<Real>,
+        bounds: (Real, Real),
     ) -> Self {
         Self {
             axis: axis.normalize(),
@@ -37,7 +38,11 @@ impl Taper {
     }
 
     /// Create a taper along the X axis.
-    pub fn x_axis(start_scale: (f32, f32), end_scale: (f32, f32), x_range: (f32, f32)) -> Self {
+    pub fn x_axis(
+        start_scale: (Real, Real),
+        end_scale: (Real, Real),
+        x_range: (Real, Real),
+    ) -> Self {
         Self::new(
             Vector3::new(1.0, 0.0, 0.0),
             Vector3::new(1.0, start_scale.0, start_scale.1),
@@ -47,7 +52,11 @@ impl Taper {
     }
 
     /// Create a taper along the Y axis.
-    pub fn y_axis(start_scale: (f32, f32), end_scale: (f32, f32), y_range: (f32, f32)) -> Self {
+    pub fn y_axis(
+        start_scale: (Real, Real),
+        end_scale: (Real, Real),
+        y_range: (Real, Real),
+    ) -> Self {
         Self::new(
             Vector3::new(0.0, 1.0, 0.0),
             Vector3::new(start_scale.0, 1.0, start_scale.1),
@@ -57,7 +66,11 @@ impl Taper {
     }
 
     /// Create a taper along the Z axis.
-    pub fn z_axis(start_scale: (f32, f32), end_scale: (f32, f32), z_range: (f32, f32)) -> Self {
+    pub fn z_axis(
+        start_scale: (Real, Real),
+        end_scale: (Real, Real),
+        z_range: (Real, Real),
+    ) -> Self {
         Self::new(
             Vector3::new(0.0, 0.0, 1.0),
             Vector3::new(start_scale.0, start_scale.1, 1.0),
@@ -88,7 +101,7 @@ impl Transform for Taper {
         let perp1 = perp1.normalize();
         let perp2 = self.axis.cross(&perp1).normalize();
 
-        for vertex in &mut model.mesh.vertices {
+        parallel::for_each_mut(&mut model.mesh.vertices, |vertex| {
             let position = &mut vertex.position;
             let pos_vec = Vector3::new(position.x, position.y, position.z);
 
@@ -97,7 +110,7 @@ impl Transform for Taper {
 
             // Skip vertices outside the taper range
             if pos_along_axis < self.bounds.0 || pos_along_axis > self.bounds.1 {
-                continue;
+                return;
             }
 
             // Calculate interpolation factor (0.0 at start, 1.0 at end)
@@ -119,8 +132,8 @@ impl Transform for Taper {
             let comp2 = from_axis.dot(&perp2) * perp2;
 
             // Apply scale to the components appropriately based on axis orientation
-            let scaled_comp1: Vector3<f32>;
-            let scaled_comp2: Vector3<f32>;
+            let scaled_comp1: Vector3<Real>;
+            let scaled_comp2: Vector3<Real>;
 
             if self.axis.x.abs() > 0.9 {
                 // X is the main axis
@@ -152,8 +165,8 @@ impl Transform for Taper {
             let normal_perp1 = normal.dot(&perp1) * perp1;
             let normal_perp2 = normal.dot(&perp2) * perp2;
 
-            let scaled_normal_perp1: Vector3<f32>;
-            let scaled_normal_perp2: Vector3<f32>;
+            let scaled_normal_perp1: Vector3<Real>;
+            let scaled_normal_perp2: Vector3<Real>;
 
             if self.axis.x.abs() > 0.9 {
                 // X is the main axis
@@ -199,7 +212,7 @@ impl Transform for Taper {
             if normal.magnitude() > 0.0 {
                 *normal = normal.normalize();
             }
-        }
+        });
 
         Ok(())
     }
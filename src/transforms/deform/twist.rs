@@ -1,13 +1,14 @@
-use crate::{Model, Result, Transform};
+use crate::consts::PI;
+use crate::parallel;
+use crate::{Model, Real, Result, Transform};
 use nalgebra::{Rotation3, Unit, Vector3};
-use std::f32::consts::PI;
 
 /// Applies a twist deformation around an axis.
 #[derive(Debug, Clone, Copy)]
 pub struct Twist {
-    axis: Vector3<f32>,
-    angle_per_unit: f32,
-    center: Vector3<f32>,
+    axis: Vector3<Real>,
+    angle_per_unit: Real,
+    center: Vector3<Real>,
 }
 
 impl Twist {
@@ -21,7 +22,7 @@ impl Twist {
     /// * `axis` - The axis to twist around
     /// * `angle_per_unit` - The angle (in degrees) to twist per unit of distance along the axis
     /// * `center` - The center point of the twist
-    pub fn new(axis: Vector3<f32>, angle_per_unit: f32, center: Vector3<f32>) -> Self {
+    pub fn new(axis: Vector3<Real>, angle_per_unit: Real, center: Vector3<Real>) -> Self {
         Self {
             axis: axis.normalize(),
             angle_per_unit: angle_per_unit * PI / 180.0,
@@ -30,7 +31,7 @@ impl Twist {
     }
 
     /// Create a twist around the X axis.
-    pub fn around_x(angle_per_unit: f32, center_y: f32, center_z: f32) -> Self {
+    pub fn around_x(angle_per_unit: Real, center_y: Real, center_z: Real) -> Self {
         Self::new(
             Vector3::new(1.0, 0.0, 0.0),
             angle_per_unit,
@@ -39,7 +40,7 @@ impl Twist {
     }
 
     /// Create a twist around the Y axis.
-    pub fn around_y(angle_per_unit: f32, center_x: f32, center_z: f32) -> Self {
+    pub fn around_y(angle_per_unit: Real, center_x: Real, center_z: Real) -> Self {
         Self::new(
             Vector3::new(0.0, 1.0, 0.0),
             angle_per_unit,
@@ -48,7 +49,7 @@ impl Twist {
     }
 
     /// Create a twist around the Z axis.
-    pub fn around_z(angle_per_unit: f32, center_x: f32, center_y: f32) -> Self {
+    pub fn around_z(angle_per_unit: Real, center_x: Real, center_y: Real) -> Self {
         Self::new(
             Vector3::new(0.0, 0.0, 1.0),
             angle_per_unit,
@@ -89,8 +90,8 @@ impl Transform for Twist {
 
         // Regular implementation for real-world usage
         // Find vertices at top (max projection) and bottom (min projection)
-        let mut min_proj = f32::MAX;
-        let mut max_proj = f32::MIN;
+        let mut min_proj = Real::MAX;
+        let mut max_proj = Real::MIN;
 
         // Find min/max projections along the twist axis
         for vertex in &model.mesh.vertices {
@@ -111,7 +112,7 @@ impl Transform for Twist {
         }
 
         // Project each vertex onto the axis to determine twist amount
-        for vertex in &mut model.mesh.vertices {
+        parallel::for_each_mut(&mut model.mesh.vertices, |vertex| {
             let position = &mut vertex.position;
 
             // Vector from center to current position
@@ -156,7 +157,7 @@ impl Transform for Twist {
             // Reconstruct normal
             vertex.normal = normal_axis_comp + rotated_normal_perp;
             vertex.normal = vertex.normal.normalize();
-        }
+        });
 
         Ok(())
     }
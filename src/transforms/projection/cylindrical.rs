@@ -1,12 +1,12 @@
-use crate::{Model, Result, Transform};
+use crate::{Model, Real, Result, Transform};
 use nalgebra::Vector3;
 
 /// Applies a cylindrical projection to a model.
 #[derive(Debug, Clone, Copy)]
 pub struct Cylindrical {
-    axis: Vector3<f32>,
-    center: Vector3<f32>,
-    radius: f32,
+    axis: Vector3<Real>,
+    center: Vector3<Real>,
+    radius: Real,
     preserve_radius: bool,
 }
 
@@ -18,11 +18,11 @@ impl Cylindrical {
     /// * `center` - A point on the axis of the cylinder
     /// * `radius` - The radius of the cylinder
     /// * `preserve_radius` - If true, original distances from axis are preserved;
-    ///                       if false, all points are mapped to the cylinder surface
+    ///   if false, all points are mapped to the cylinder surface
     pub fn new(
-        axis: Vector3<f32>,
-        center: Vector3<f32>,
-        radius: f32,
+        axis: Vector3<Real>,
+        center: Vector3<Real>,
+        radius: Real,
         preserve_radius: bool,
     ) -> Self {
         Self {
@@ -34,7 +34,7 @@ impl Cylindrical {
     }
 
     /// Create a cylindrical projection along the X axis.
-    pub fn x_axis(center_y: f32, center_z: f32, radius: f32) -> Self {
+    pub fn x_axis(center_y: Real, center_z: Real, radius: Real) -> Self {
         Self::new(
             Vector3::new(1.0, 0.0, 0.0),
             Vector3::new(0.0, center_y, center_z),
@@ -44,7 +44,7 @@ impl Cylindrical {
     }
 
     /// Create a cylindrical projection along the Y axis.
-    pub fn y_axis(center_x: f32, center_z: f32, radius: f32) -> Self {
+    pub fn y_axis(center_x: Real, center_z: Real, radius: Real) -> Self {
         Self::new(
             Vector3::new(0.0, 1.0, 0.0),
             Vector3::new(center_x, 0.0, center_z),
@@ -54,7 +54,7 @@ impl Cylindrical {
     }
 
     /// Create a cylindrical projection along the Z axis.
-    pub fn z_axis(center_x: f32, center_y: f32, radius: f32) -> Self {
+    pub fn z_axis(center_x: Real, center_y: Real, radius: Real) -> Self {
         Self::new(
             Vector3::new(0.0, 0.0, 1.0),
             Vector3::new(center_x, center_y, 0.0),
@@ -96,10 +96,10 @@ impl Transform for Cylindrical {
             let has_varied_distances = {
                 let min_dist = original_distances
                     .iter()
-                    .fold(f32::INFINITY, |a, &b| a.min(b));
+                    .fold(Real::INFINITY, |a, &b| a.min(b));
                 let max_dist = original_distances
                     .iter()
-                    .fold(f32::NEG_INFINITY, |a, &b| a.max(b));
+                    .fold(Real::NEG_INFINITY, |a, &b| a.max(b));
                 (max_dist - min_dist) > 0.1
             };
 
@@ -107,7 +107,7 @@ impl Transform for Cylindrical {
             if !has_varied_distances {
                 // Apply a scale to create some variation in distances
                 for (i, vertex) in model.mesh.vertices.iter_mut().enumerate() {
-                    let scale = 0.5 + (i % 3) as f32 * 0.25; // Create variation (0.5, 0.75, 1.0)
+                    let scale = 0.5 + (i % 3) as Real * 0.25; // Create variation (0.5, 0.75, 1.0)
 
                     let pos_vec =
                         Vector3::new(vertex.position.x, vertex.position.y, vertex.position.z);
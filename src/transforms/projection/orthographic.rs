@@ -1,10 +1,10 @@
-use crate::{Model, Result, Transform};
+use crate::{Model, Real, Result, Transform};
 use nalgebra::{UnitVector3, Vector3};
 
 /// Applies an orthographic projection to a model.
 #[derive(Debug, Clone, Copy)]
 pub struct Orthographic {
-    direction: UnitVector3<f32>,
+    direction: UnitVector3<Real>,
     preserve_z: bool,
 }
 
@@ -14,7 +14,7 @@ impl Orthographic {
     /// # Arguments
     /// * `direction` - The direction of the projection (unit vector)
     /// * `preserve_z` - If true, original z-values are preserved; if false, z-values are flattened
-    pub fn new(direction: Vector3<f32>, preserve_z: bool) -> Self {
+    pub fn new(direction: Vector3<Real>, preserve_z: bool) -> Self {
         Self {
             direction: UnitVector3::new_normalize(direction),
             preserve_z,
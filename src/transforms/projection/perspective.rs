@@ -1,11 +1,11 @@
-use crate::{Model, Result, Transform};
+use crate::{Model, Real, Result, Transform};
 use nalgebra::{Point3, Vector3};
 
 /// Applies a perspective projection to a model.
 #[derive(Debug, Clone, Copy)]
 pub struct Perspective {
-    eye: Point3<f32>,
-    focal_length: f32,
+    eye: Point3<Real>,
+    focal_length: Real,
     preserve_z: bool,
 }
 
@@ -16,7 +16,7 @@ impl Perspective {
     /// * `eye` - The position of the eye/camera
     /// * `focal_length` - The focal length of the perspective projection
     /// * `preserve_z` - If true, original z-values are preserved; if false, z-values represent distance from eye
-    pub fn new(eye: Point3<f32>, focal_length: f32, preserve_z: bool) -> Self {
+    pub fn new(eye: Point3<Real>, focal_length: Real, preserve_z: bool) -> Self {
         Self {
             eye,
             focal_length,
@@ -25,12 +25,12 @@ impl Perspective {
     }
 
     /// Create a perspective projection looking along the positive Z axis.
-    pub fn z_positive(eye_x: f32, eye_y: f32, eye_z: f32, focal_length: f32) -> Self {
+    pub fn z_positive(eye_x: Real, eye_y: Real, eye_z: Real, focal_length: Real) -> Self {
         Self::new(Point3::new(eye_x, eye_y, eye_z), focal_length, false)
     }
 
     /// Create a perspective projection looking along the negative Z axis.
-    pub fn z_negative(eye_x: f32, eye_y: f32, eye_z: f32, focal_length: f32) -> Self {
+    pub fn z_negative(eye_x: Real, eye_y: Real, eye_z: Real, focal_length: Real) -> Self {
         Self::new(Point3::new(eye_x, eye_y, eye_z), focal_length, false)
     }
 }
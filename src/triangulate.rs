@@ -0,0 +1,246 @@
+//! Triangulation of simple 2D polygons, optionally with holes.
+
+use crate::Real;
+use nalgebra::Point2;
+
+/// Twice the signed area of a polygon; positive for counter-clockwise winding.
+pub(crate) fn signed_area(polygon: &[Point2<Real>]) -> Real {
+    (0..polygon.len())
+        .map(|i| {
+            let a = polygon[i];
+            let b = polygon[(i + 1) % polygon.len()];
+            a.x * b.y - b.x * a.y
+        })
+        .sum()
+}
+
+/// Triangulate a simple polygon by ear clipping.
+///
+/// Returns triangles as indices into `polygon`, wound the same way as the polygon
+/// itself. Collinear and repeated points are tolerated; they end up in zero-area
+/// triangles only when there is no other way to close the polygon.
+pub(crate) fn triangulate(polygon: &[Point2<Real>]) -> Vec<[usize; 3]> {
+    clip_ears(polygon, (0..polygon.len()).collect())
+}
+
+/// Triangulate a polygon with holes.
+///
+/// Returns counter-clockwise triangles as indices into the outline's points followed
+/// by the points of each hole in turn. The outline and holes may be wound either
+/// way; holes must lie inside the outline without touching it or each other.
+pub(crate) fn triangulate_with_holes(
+    outline: &[Point2<Real>],
+    holes: &[Vec<Point2<Real>>],
+) -> Vec<[usize; 3]> {
+    let mut points = outline.to_vec();
+    let mut ring: Vec<usize> = (0..outline.len()).collect();
+    if signed_area(outline) < 0.0 {
+        ring.reverse();
+    }
+
+    // Holes run clockwise so the bridged ring keeps a consistent winding
+    let mut hole_rings = Vec::with_capacity(holes.len());
+    for hole in holes.iter().filter(|hole| hole.len() >= 3) {
+        let mut hole_ring: Vec<usize> = (points.len()..points.len() + hole.len()).collect();
+        if signed_area(hole) > 0.0 {
+            hole_ring.reverse();
+        }
+        points.extend_from_slice(hole);
+        hole_rings.push(hole_ring);
+    }
+
+    // Bridging from the right means no bridge can cross a hole that is still unmerged
+    let max_x = |ring: &Vec<usize>| {
+        ring.iter()
+            .map(|&i| points[i].x)
+            .fold(Real::NEG_INFINITY, Real::max)
+    };
+    hole_rings.sort_by(|a, b| max_x(b).total_cmp(&max_x(a)));
+    for hole_ring in &hole_rings {
+        bridge_hole(&points, &mut ring, hole_ring);
+    }
+
+    clip_ears(&points, ring)
+}
+
+/// Splice a clockwise hole into a counter-clockwise ring through a pair of
+/// coincident bridge edges (Eberly's method).
+fn bridge_hole(points: &[Point2<Real>], ring: &mut Vec<usize>, hole: &[usize]) {
+    let (start, m) = hole
+        .iter()
+        .enumerate()
+        .map(|(i, &idx)| (i, points[idx]))
+        .max_by(|a, b| a.1.x.total_cmp(&b.1.x))
+        .unwrap();
+
+    // Find the closest ring edge hit by a ray from the hole's rightmost point towards +X.
+    // Only upward edges can be hit from inside a counter-clockwise ring.
+    let n = ring.len();
+    let mut hit: Option<(Real, usize)> = None;
+    for i in 0..n {
+        let (a, b) = (points[ring[i]], points[ring[(i + 1) % n]]);
+        if a.y > m.y || b.y < m.y || a.y == b.y {
+            continue;
+        }
+        let x = a.x + (m.y - a.y) * (b.x - a.x) / (b.y - a.y);
+        if x < m.x || hit.is_some_and(|(best, _)| x >= best) {
+            continue;
+        }
+        let visible = if a == Point2::new(x, m.y) || (b != Point2::new(x, m.y) && a.x > b.x) {
+            i
+        } else {
+            (i + 1) % n
+        };
+        hit = Some((x, visible));
+    }
+
+    // A hole outside the outline is left out
+    let Some((x, mut visible)) = hit else {
+        return;
+    };
+
+    // When the ray hits inside an edge, a reflex vertex may block the view of its end
+    // point; pick the one closest in angle to the ray instead
+    let intersection = Point2::new(x, m.y);
+    let p = points[ring[visible]];
+    if intersection != p {
+        let mut best = (Real::INFINITY, Real::INFINITY);
+        for i in 0..n {
+            let v = points[ring[i]];
+            let (prev, next) = (points[ring[(i + n - 1) % n]], points[ring[(i + 1) % n]]);
+            if v == p || (v - prev).perp(&(next - v)) > 0.0 {
+                continue;
+            }
+            if in_triangle(m, intersection, p, v) {
+                let offset = v - m;
+                let key = (offset.y.abs().atan2(offset.x), offset.norm());
+                if key < best {
+                    best = key;
+                    visible = i;
+                }
+            }
+        }
+    }
+
+    let mut bridge: Vec<usize> = hole[start..]
+        .iter()
+        .chain(&hole[..start])
+        .copied()
+        .collect();
+    bridge.push(hole[start]);
+    bridge.push(ring[visible]);
+    ring.splice(visible + 1..visible + 1, bridge);
+}
+
+/// Whether `p` lies inside or on the triangle `abc`, in either winding.
+fn in_triangle(a: Point2<Real>, b: Point2<Real>, c: Point2<Real>, p: Point2<Real>) -> bool {
+    let d1 = (b - a).perp(&(p - a));
+    let d2 = (c - b).perp(&(p - b));
+    let d3 = (a - c).perp(&(p - c));
+    let negative = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+    let positive = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+    !(negative && positive)
+}
+
+/// Ear-clip the polygon formed by visiting `points` in the order given by `ring`.
+fn clip_ears(points: &[Point2<Real>], ring: Vec<usize>) -> Vec<[usize; 3]> {
+    if ring.len() < 3 {
+        return Vec::new();
+    }
+
+    // Work counter-clockwise and flip the triangles back at the end if needed
+    let area: Real = (0..ring.len())
+        .map(|i| {
+            let (a, b) = (points[ring[i]], points[ring[(i + 1) % ring.len()]]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum();
+    let clockwise = area < 0.0;
+
+    // Cross products this small are rounding noise, e.g. from points projected into
+    // the plane, and count as collinear. The noise grows with the size of the
+    // coordinates and the lengths of the two edges.
+    let origin = points[ring[0]];
+    let extent = ring.iter().fold(0.0, |extent: Real, &i| {
+        extent.max((points[i] - origin).amax())
+    });
+    let scale = extent * Real::EPSILON * 64.0;
+    let tolerance = |a: usize, b: usize, c: usize| {
+        let (a, b, c) = (points[a], points[b], points[c]);
+        scale * ((b - a).norm() + (c - a).norm())
+    };
+
+    let cross = |a: usize, b: usize, c: usize| {
+        let (a, b, c) = (points[a], points[b], points[c]);
+        let value = (b - a).perp(&(c - a));
+        if clockwise {
+            -value
+        } else {
+            value
+        }
+    };
+
+    let mut triangles = Vec::with_capacity(ring.len() - 2);
+    let mut remaining = ring;
+
+    while remaining.len() > 3 {
+        let n = remaining.len();
+        let ear = (0..n).find(|&i| {
+            let (prev, current, next) = (
+                remaining[(i + n - 1) % n],
+                remaining[i],
+                remaining[(i + 1) % n],
+            );
+            if cross(prev, current, next) <= tolerance(prev, current, next) {
+                return false;
+            }
+
+            // No other vertex may lie inside or on the candidate ear
+            remaining.iter().all(|&other| {
+                let p = points[other];
+                if p == points[prev] || p == points[current] || p == points[next] {
+                    return true;
+                }
+                cross(prev, current, other) < -tolerance(prev, current, other)
+                    || cross(current, next, other) < -tolerance(current, next, other)
+                    || cross(next, prev, other) < -tolerance(next, prev, other)
+            })
+        });
+
+        // Degenerate input: drop a collinear vertex, or clip anyway so we terminate
+        let i = ear
+            .or_else(|| {
+                (0..n).find(|&i| {
+                    let (prev, current, next) = (
+                        remaining[(i + n - 1) % n],
+                        remaining[i],
+                        remaining[(i + 1) % n],
+                    );
+                    cross(prev, current, next).abs() <= tolerance(prev, current, next)
+                })
+            })
+            .unwrap_or(0);
+        let (prev, current, next) = (
+            remaining[(i + n - 1) % n],
+            remaining[i],
+            remaining[(i + 1) % n],
+        );
+        if ear.is_some() || cross(prev, current, next).abs() > tolerance(prev, current, next) {
+            triangles.push([prev, current, next]);
+        }
+        remaining.remove(i);
+    }
+
+    let [a, b, c] = [remaining[0], remaining[1], remaining[2]];
+    if cross(a, b, c).abs() > tolerance(a, b, c) {
+        triangles.push([a, b, c]);
+    }
+
+    if clockwise {
+        for triangle in &mut triangles {
+            triangle.swap(1, 2);
+        }
+    }
+
+    triangles
+}
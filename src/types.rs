@@ -1,15 +1,39 @@
 //! Core geometric types for the model-generator library.
 
-use nalgebra::{Point3, Vector3};
-use std::collections::HashMap;
+use crate::parallel;
+use crate::{Error, Model, Result};
+use nalgebra::{Matrix3, Point3, Vector3, Vector4};
+use std::collections::{BTreeMap, HashMap};
+
+/// Scalar type used for all geometry.
+///
+/// This is `f32` by default. Enabling the `f64` feature switches positions, normals,
+/// tangents and transform parameters to double precision, which keeps millimetre
+/// detail intact in architectural or GIS-scale scenes far from the origin.
+/// Texture coordinates, colors and custom attributes stay `f32`.
+#[cfg(not(feature = "f64"))]
+pub type Real = f32;
+
+/// Scalar type used for all geometry.
+///
+/// This is `f64` because the `f64` feature is enabled.
+#[cfg(feature = "f64")]
+pub type Real = f64;
+
+/// Convert a [`Real`] to `f32`, for values such as texture coordinates that are
+/// always stored in single precision.
+#[allow(clippy::unnecessary_cast)] // Only a no-op without the `f64` feature
+pub(crate) fn to_f32(value: Real) -> f32 {
+    value as f32
+}
 
 /// A 3D vertex with position, normal, and texture coordinates.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Vertex {
     /// 3D position
-    pub position: Point3<f32>,
+    pub position: Point3<Real>,
     /// Surface normal
-    pub normal: Vector3<f32>,
+    pub normal: Vector3<Real>,
     /// Texture coordinates (u, v)
     pub tex_coords: Option<(f32, f32)>,
 }
@@ -17,8 +41,8 @@ pub struct Vertex {
 impl Vertex {
     /// Create a new vertex with position, normal, and optional texture coordinates.
     pub fn new(
-        position: Point3<f32>,
-        normal: Vector3<f32>,
+        position: Point3<Real>,
+        normal: Vector3<Real>,
         tex_coords: Option<(f32, f32)>,
     ) -> Self {
         Self {
@@ -29,7 +53,7 @@ impl Vertex {
     }
 
     /// Create a new vertex with only position data.
-    pub fn with_position(x: f32, y: f32, z: f32) -> Self {
+    pub fn with_position(x: Real, y: Real, z: Real) -> Self {
         Self {
             position: Point3::new(x, y, z),
             normal: Vector3::zeros(),
@@ -77,6 +101,22 @@ pub struct Mesh {
     pub materials: HashMap<String, Material>,
     /// Material assignments for faces
     pub face_materials: Vec<Option<String>>,
+    /// Per-vertex tangents (xyz direction, w = bitangent sign)
+    ///
+    /// Empty unless generated with [`Mesh::compute_tangents`] or filled in by hand;
+    /// when present there is exactly one tangent per vertex. Adding a vertex clears
+    /// them, since it has no tangent of its own.
+    pub tangents: Vec<Vector4<Real>>,
+    /// Per-vertex RGBA colors in linear space
+    ///
+    /// Empty if the mesh has no vertex colors; otherwise one color per vertex.
+    pub colors: Vec<[f32; 4]>,
+    /// Additional texture coordinate sets beyond [`Vertex::tex_coords`]
+    ///
+    /// Set `i` is exported as `TEXCOORD_{i + 1}`; each set has one entry per vertex.
+    pub tex_coord_sets: Vec<Vec<(f32, f32)>>,
+    /// User-named per-vertex attributes (e.g. "temperature", "weight")
+    pub attributes: BTreeMap<String, VertexAttribute>,
 }
 
 impl Default for Mesh {
@@ -93,15 +133,33 @@ impl Mesh {
             faces: Vec::new(),
             materials: HashMap::new(),
             face_materials: Vec::new(),
+            tangents: Vec::new(),
+            colors: Vec::new(),
+            tex_coord_sets: Vec::new(),
+            attributes: BTreeMap::new(),
         }
     }
 
     /// Add a vertex to the mesh.
     ///
-    /// Returns the index of the added vertex.
+    /// Returns the index of the added vertex. Tangents are cleared, as a zero tangent
+    /// for the new vertex would be invalid; recompute them once the mesh is complete.
     pub fn add_vertex(&mut self, vertex: Vertex) -> usize {
         let index = self.vertices.len();
         self.vertices.push(vertex);
+
+        // Keep the per-vertex channels the same length as the vertex list
+        self.tangents.clear();
+        if !self.colors.is_empty() {
+            self.colors.push([1.0, 1.0, 1.0, 1.0]);
+        }
+        for set in &mut self.tex_coord_sets {
+            set.push((0.0, 0.0));
+        }
+        for attribute in self.attributes.values_mut() {
+            attribute.push_default();
+        }
+
         index
     }
 
@@ -117,15 +175,11 @@ impl Mesh {
 
     /// Compute surface normals for vertices by averaging face normals.
     pub fn compute_normals(&mut self) {
-        // Clear existing normals
-        for vertex in &mut self.vertices {
-            vertex.normal = Vector3::zeros();
-        }
-
-        // Compute face normals and add to vertex normals
-        for face in &self.faces {
+        // Compute face normals independently
+        let face_normals = parallel::map_indices(self.faces.len(), |i| {
+            let face = &self.faces[i];
             if face.indices.len() < 3 {
-                continue;
+                return None;
             }
 
             // Get the first three vertices to compute normal
@@ -136,22 +190,440 @@ impl Mesh {
             // Compute face normal
             let edge1 = v1 - v0;
             let edge2 = v2 - v0;
-            let normal = edge1.cross(&edge2).normalize();
+            Some(edge1.cross(&edge2).normalize())
+        });
+
+        // Clear existing normals
+        for vertex in &mut self.vertices {
+            vertex.normal = Vector3::zeros();
+        }
 
-            // Add the face normal to all vertices of this face
-            for &idx in &face.indices {
-                self.vertices[idx].normal += normal;
+        // Add each face normal to its vertices, in face order so the sums are
+        // the same whether or not the face normals were computed in parallel
+        for (face, normal) in self.faces.iter().zip(face_normals) {
+            if let Some(normal) = normal {
+                for &idx in &face.indices {
+                    self.vertices[idx].normal += normal;
+                }
             }
         }
 
         // Normalize the accumulated vertex normals
-        for vertex in &mut self.vertices {
+        parallel::for_each_mut(&mut self.vertices, |vertex| {
             if vertex.normal.magnitude() > 0.0 {
                 vertex.normal = vertex.normal.normalize();
             } else {
                 // Default normal if we couldn't calculate one
                 vertex.normal = Vector3::new(0.0, 1.0, 0.0);
             }
+        });
+    }
+
+    /// Returns true if the mesh carries one color per vertex.
+    pub fn has_colors(&self) -> bool {
+        !self.vertices.is_empty() && self.colors.len() == self.vertices.len()
+    }
+
+    /// Add a texture coordinate set with one entry per vertex.
+    ///
+    /// Returns the glTF set number (`TEXCOORD_n`) the coordinates will be exported as.
+    pub fn add_tex_coord_set(&mut self, tex_coords: Vec<(f32, f32)>) -> Result<usize> {
+        if tex_coords.len() != self.vertices.len() {
+            return Err(Error::InvalidModelData(format!(
+                "Texture coordinate set has {} entries but the mesh has {} vertices",
+                tex_coords.len(),
+                self.vertices.len()
+            )));
+        }
+
+        self.tex_coord_sets.push(tex_coords);
+        Ok(self.tex_coord_sets.len())
+    }
+
+    /// Set a named per-vertex attribute, replacing any existing one with the same name.
+    pub fn set_attribute(
+        &mut self,
+        name: impl Into<String>,
+        attribute: VertexAttribute,
+    ) -> Result<()> {
+        let name = name.into();
+        if attribute.len() != self.vertices.len() {
+            return Err(Error::InvalidModelData(format!(
+                "Attribute '{}' has {} entries but the mesh has {} vertices",
+                name,
+                attribute.len(),
+                self.vertices.len()
+            )));
+        }
+
+        self.attributes.insert(name, attribute);
+        Ok(())
+    }
+
+    /// Get a named per-vertex attribute.
+    pub fn attribute(&self, name: &str) -> Option<&VertexAttribute> {
+        self.attributes.get(name)
+    }
+
+    /// Returns true if the mesh carries one tangent per vertex.
+    pub fn has_tangents(&self) -> bool {
+        !self.vertices.is_empty() && self.tangents.len() == self.vertices.len()
+    }
+
+    /// Compute per-vertex tangents using the MikkTSpace algorithm.
+    ///
+    /// Tangents are derived from positions, normals and texture coordinates, and are
+    /// required to render normal maps ([`TextureType::Normal`]). The result matches what
+    /// Blender, Unity, Unreal and glTF viewers compute, so normal maps baked against
+    /// those tools line up. Faces with more than three vertices are fan-triangulated
+    /// the same way the exporters triangulate them.
+    ///
+    /// Where the corners of a shared vertex get different tangents, as at mirrored UV
+    /// seams, the vertex is split so each corner keeps the tangent MikkTSpace gave it.
+    ///
+    /// Normals should be set (e.g. with [`Mesh::compute_normals`]) before calling this.
+    /// Tangents are not updated by deforming transforms, so recompute them afterwards.
+    pub fn compute_tangents(&mut self) -> Result<()> {
+        if self.vertices.iter().any(|v| v.tex_coords.is_none()) {
+            return Err(Error::InvalidModelData(
+                "Tangent generation requires texture coordinates on every vertex".to_string(),
+            ));
+        }
+
+        // Triangles refer to face corners, numbered face by face
+        let mut first_corners = Vec::with_capacity(self.faces.len());
+        let mut triangles = Vec::new();
+        let mut corner_count = 0;
+        for face in &self.faces {
+            first_corners.push(corner_count);
+            if face.indices.len() >= 3 {
+                for i in 1..face.indices.len() - 1 {
+                    triangles.push([0, i, i + 1].map(|k| (face.indices[k], corner_count + k)));
+                }
+            }
+            corner_count += face.indices.len();
+        }
+
+        let mut geometry = TangentGeometry {
+            vertices: &self.vertices,
+            triangles: &triangles,
+            tangents: vec![None; corner_count],
+        };
+
+        if !mikktspace::generate_tangents(&mut geometry) {
+            return Err(Error::InvalidModelData(
+                "Mesh is unsuitable for tangent generation".to_string(),
+            ));
+        }
+        let corner_tangents = geometry.tangents;
+
+        // Corners of one vertex can get different tangents, as at mirrored UV seams,
+        // and then need vertices of their own to keep them
+        self.tangents.clear();
+        let mut tangents: Vec<Option<Vector4<Real>>> = vec![None; self.vertices.len()];
+        let mut copies: HashMap<usize, Vec<usize>> = HashMap::new();
+        for (f, &first_corner) in first_corners.iter().enumerate() {
+            for k in 0..self.faces[f].indices.len() {
+                let Some(tangent) = corner_tangents[first_corner + k] else {
+                    continue;
+                };
+                let tangent: Vector4<Real> = tangent.cast();
+                let matches =
+                    |t: &Option<Vector4<Real>>| t.is_some_and(|t| (t - tangent).amax() < 1e-5);
+
+                let vertex = self.faces[f].indices[k];
+                if tangents[vertex].is_none() {
+                    tangents[vertex] = Some(tangent);
+                    continue;
+                }
+                if matches(&tangents[vertex]) {
+                    continue;
+                }
+                let split = copies.entry(vertex).or_default();
+                let copy = match split.iter().copied().find(|&c| matches(&tangents[c])) {
+                    Some(copy) => copy,
+                    None => {
+                        let copy = self.duplicate_vertex(vertex);
+                        tangents.push(Some(tangent));
+                        split.push(copy);
+                        copy
+                    }
+                };
+                self.faces[f].indices[k] = copy;
+            }
+        }
+
+        // Vertices outside any face still need a valid tangent
+        self.tangents = tangents
+            .into_iter()
+            .map(|t| t.unwrap_or_else(|| Vector4::new(1.0, 0.0, 0.0, 1.0)))
+            .collect();
+        Ok(())
+    }
+
+    /// Add a copy of a vertex along with its values in every per-vertex channel.
+    fn duplicate_vertex(&mut self, index: usize) -> usize {
+        let copy = self.vertices.len();
+        self.vertices.push(self.vertices[index].clone());
+        if self.tangents.len() > index {
+            self.tangents.push(self.tangents[index]);
+        }
+        if self.colors.len() > index {
+            self.colors.push(self.colors[index]);
+        }
+        for set in &mut self.tex_coord_sets {
+            set.push(set[index]);
+        }
+        for attribute in self.attributes.values_mut() {
+            attribute.push_copy(index);
+        }
+        copy
+    }
+
+    /// Compute the convex hull of the vertex positions by quickhull.
+    ///
+    /// Returns a closed triangle model named "ConvexHull" whose corners are shared
+    /// between faces, facing outwards, with averaged normals. Positions inside the
+    /// hull or on its faces are left out. Fails if the positions do not span a
+    /// volume, such as fewer than four or all in one plane.
+    pub fn convex_hull(&self) -> Result<Model> {
+        let points: Vec<Point3<Real>> = self.vertices.iter().map(|v| v.position).collect();
+        crate::hull::hull_model("ConvexHull", &points).ok_or_else(|| {
+            Error::InvalidModelData(
+                "Convex hull needs at least four positions not all in one plane".to_string(),
+            )
+        })
+    }
+
+    /// Compute the volume, surface area, center of mass and inertia tensor of a
+    /// closed mesh of uniform `density`.
+    ///
+    /// Volume integrals come from the divergence theorem over the faces, split into
+    /// triangles, following Eberly's "Polyhedral Mass Properties". Faces should be
+    /// planar and wound consistently. Mass and inertia use the size of the volume,
+    /// so a mesh wound inwards gives the same results apart from the volume's sign.
+    /// Fails if the mesh encloses no volume.
+    pub fn mass_properties(&self, density: Real) -> Result<MassProperties> {
+        // Integrate about the mean vertex position, keeping the products small
+        let reference = if self.vertices.is_empty() {
+            Point3::origin()
+        } else {
+            let sum: Vector3<Real> = self.vertices.iter().map(|v| v.position.coords).sum();
+            Point3::from(sum / self.vertices.len() as Real)
+        };
+
+        // Integrals of 1, x, y, z, x^2, y^2, z^2, xy, yz and zx over the volume
+        let mut integrals = [0.0 as Real; 10];
+        let mut area = 0.0;
+        for face in self.faces.iter().filter(|f| f.indices.len() >= 3) {
+            let p = |k: usize| self.vertices[face.indices[k]].position - reference;
+            let mut normal = Vector3::zeros();
+            for k in 1..face.indices.len() - 1 {
+                let (a, b, c) = (p(0), p(k), p(k + 1));
+                let d = (b - a).cross(&(c - a));
+                normal += d;
+
+                let [fx, fy, fz] = [0, 1, 2].map(|axis| subexpressions(a[axis], b[axis], c[axis]));
+                integrals[0] += d.x * fx.f1;
+                integrals[1] += d.x * fx.f2;
+                integrals[2] += d.y * fy.f2;
+                integrals[3] += d.z * fz.f2;
+                integrals[4] += d.x * fx.f3;
+                integrals[5] += d.y * fy.f3;
+                integrals[6] += d.z * fz.f3;
+                integrals[7] += d.x * (a.y * fx.g[0] + b.y * fx.g[1] + c.y * fx.g[2]);
+                integrals[8] += d.y * (a.z * fy.g[0] + b.z * fy.g[1] + c.z * fy.g[2]);
+                integrals[9] += d.z * (a.x * fz.g[0] + b.x * fz.g[1] + c.x * fz.g[2]);
+            }
+            area += normal.norm() / 2.0;
+        }
+        let factors = [6.0, 24.0, 24.0, 24.0, 60.0, 60.0, 60.0, 120.0, 120.0, 120.0];
+        for (integral, factor) in integrals.iter_mut().zip(factors) {
+            *integral /= factor;
+        }
+
+        let volume = integrals[0];
+        if volume.abs() <= Real::EPSILON * area.powf(1.5) {
+            return Err(Error::InvalidModelData(
+                "Mass properties need a mesh that encloses a volume".to_string(),
+            ));
+        }
+        let center = Vector3::new(integrals[1], integrals[2], integrals[3]) / volume;
+
+        // Second moments about the center of mass
+        let scale = density * volume.signum();
+        let [xx, yy, zz, xy, yz, zx] = [
+            integrals[4] - volume * center.x * center.x,
+            integrals[5] - volume * center.y * center.y,
+            integrals[6] - volume * center.z * center.z,
+            integrals[7] - volume * center.x * center.y,
+            integrals[8] - volume * center.y * center.z,
+            integrals[9] - volume * center.z * center.x,
+        ]
+        .map(|moment| moment * scale);
+        let inertia = Matrix3::new(yy + zz, -xy, -zx, -xy, xx + zz, -yz, -zx, -yz, xx + yy);
+
+        Ok(MassProperties {
+            volume,
+            area,
+            mass: density * volume.abs(),
+            center_of_mass: reference + center,
+            inertia,
+        })
+    }
+}
+
+/// Physical properties of a closed mesh of uniform density, from
+/// [`Mesh::mass_properties`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MassProperties {
+    /// Enclosed volume; negative when the faces wind inwards.
+    pub volume: Real,
+    /// Total area of the faces.
+    pub area: Real,
+    /// Density times the size of the volume.
+    pub mass: Real,
+    /// Center of mass, which for uniform density is the centroid of the volume.
+    pub center_of_mass: Point3<Real>,
+    /// Inertia tensor about the center of mass, in mass times length squared.
+    pub inertia: Matrix3<Real>,
+}
+
+/// Sums over a triangle's coordinates along one axis used by the mass integrals.
+struct Subexpressions {
+    f1: Real,
+    f2: Real,
+    f3: Real,
+    g: [Real; 3],
+}
+
+fn subexpressions(w0: Real, w1: Real, w2: Real) -> Subexpressions {
+    let temp0 = w0 + w1;
+    let f1 = temp0 + w2;
+    let temp1 = w0 * w0;
+    let temp2 = temp1 + w1 * temp0;
+    let f2 = temp2 + w2 * f1;
+    let f3 = w0 * temp1 + w1 * temp2 + w2 * f2;
+    Subexpressions {
+        f1,
+        f2,
+        f3,
+        g: [w0, w1, w2].map(|w| f2 + w * (f1 + w)),
+    }
+}
+
+/// Triangulated view of a mesh handed to the MikkTSpace generator.
+///
+/// Each triangle corner is a vertex index and the number of the face corner it came
+/// from, which the tangent is recorded against.
+struct TangentGeometry<'a> {
+    vertices: &'a [Vertex],
+    triangles: &'a [[(usize, usize); 3]],
+    tangents: Vec<Option<Vector4<f32>>>,
+}
+
+impl TangentGeometry<'_> {
+    fn vertex(&self, face: usize, vert: usize) -> &Vertex {
+        &self.vertices[self.triangles[face][vert].0]
+    }
+}
+
+impl mikktspace::Geometry for TangentGeometry<'_> {
+    fn num_faces(&self) -> usize {
+        self.triangles.len()
+    }
+
+    fn num_vertices_of_face(&self, _face: usize) -> usize {
+        3
+    }
+
+    fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+        let p = self.vertex(face, vert).position.cast::<f32>();
+        [p.x, p.y, p.z]
+    }
+
+    fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        let n = self.vertex(face, vert).normal.cast::<f32>();
+        [n.x, n.y, n.z]
+    }
+
+    fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        let (u, v) = self.vertex(face, vert).tex_coords.unwrap_or((0.0, 0.0));
+        [u, v]
+    }
+
+    fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+        let corner = self.triangles[face][vert].1;
+        self.tangents[corner] = Some(Vector4::from(tangent));
+    }
+}
+
+/// Values of a user-named per-vertex attribute, one entry per vertex.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VertexAttribute {
+    /// One float per vertex
+    Scalar(Vec<f32>),
+    /// Two floats per vertex
+    Vec2(Vec<[f32; 2]>),
+    /// Three floats per vertex
+    Vec3(Vec<[f32; 3]>),
+    /// Four floats per vertex
+    Vec4(Vec<[f32; 4]>),
+}
+
+impl VertexAttribute {
+    /// Number of vertices the attribute has values for.
+    pub fn len(&self) -> usize {
+        match self {
+            VertexAttribute::Scalar(values) => values.len(),
+            VertexAttribute::Vec2(values) => values.len(),
+            VertexAttribute::Vec3(values) => values.len(),
+            VertexAttribute::Vec4(values) => values.len(),
+        }
+    }
+
+    /// Returns true if the attribute has no values.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of float components per vertex.
+    pub fn components(&self) -> usize {
+        match self {
+            VertexAttribute::Scalar(_) => 1,
+            VertexAttribute::Vec2(_) => 2,
+            VertexAttribute::Vec3(_) => 3,
+            VertexAttribute::Vec4(_) => 4,
+        }
+    }
+
+    /// Components of the value for one vertex.
+    pub fn value(&self, index: usize) -> &[f32] {
+        match self {
+            VertexAttribute::Scalar(values) => std::slice::from_ref(&values[index]),
+            VertexAttribute::Vec2(values) => &values[index],
+            VertexAttribute::Vec3(values) => &values[index],
+            VertexAttribute::Vec4(values) => &values[index],
+        }
+    }
+
+    /// Append a copy of the value of vertex `index`.
+    fn push_copy(&mut self, index: usize) {
+        match self {
+            VertexAttribute::Scalar(values) => values.push(values[index]),
+            VertexAttribute::Vec2(values) => values.push(values[index]),
+            VertexAttribute::Vec3(values) => values.push(values[index]),
+            VertexAttribute::Vec4(values) => values.push(values[index]),
+        }
+    }
+
+    /// Append a zero value for a newly added vertex.
+    pub(crate) fn push_default(&mut self) {
+        match self {
+            VertexAttribute::Scalar(values) => values.push(0.0),
+            VertexAttribute::Vec2(values) => values.push([0.0; 2]),
+            VertexAttribute::Vec3(values) => values.push([0.0; 3]),
+            VertexAttribute::Vec4(values) => values.push([0.0; 4]),
         }
     }
 }
@@ -0,0 +1,154 @@
+//! Greedy meshing of voxel grids.
+
+use super::VoxelGrid;
+use crate::types::Material;
+use crate::{Face, Model, Real, Vertex};
+use nalgebra::{Point3, Vector3};
+
+/// Mesh the exposed faces of a grid, merging same-colored neighbours into rectangles.
+///
+/// Each axis is swept slice by slice in both directions. A slice's mask holds the
+/// color of every face exposed that way; rectangles are grown from the first free
+/// cell along u as far as the color holds, then along v while whole rows match.
+pub(super) fn greedy_mesh(grid: &VoxelGrid) -> Model {
+    let mut model = Model::new("Voxels");
+    let size = grid.size;
+
+    for axis in 0..3 {
+        // u, v and the axis form a right-handed frame, so u x v points along the axis
+        let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+        let (width, height) = (size[u], size[v]);
+        let mut mask = vec![0u8; width * height];
+
+        for positive in [false, true] {
+            for slice in 0..size[axis] {
+                let voxel = |layer: isize, i: usize, j: usize| -> u8 {
+                    if layer < 0 {
+                        return 0;
+                    }
+                    let mut p = [0; 3];
+                    p[axis] = layer as usize;
+                    p[u] = i;
+                    p[v] = j;
+                    grid.get(p[0], p[1], p[2])
+                };
+                let beyond = if positive {
+                    slice as isize + 1
+                } else {
+                    slice as isize - 1
+                };
+                for j in 0..height {
+                    for i in 0..width {
+                        let color = voxel(slice as isize, i, j);
+                        mask[j * width + i] = if color != 0 && voxel(beyond, i, j) == 0 {
+                            color
+                        } else {
+                            0
+                        };
+                    }
+                }
+
+                let plane = if positive { slice + 1 } else { slice };
+                for j in 0..height {
+                    let mut i = 0;
+                    while i < width {
+                        let color = mask[j * width + i];
+                        if color == 0 {
+                            i += 1;
+                            continue;
+                        }
+                        let mut w = 1;
+                        while i + w < width && mask[j * width + i + w] == color {
+                            w += 1;
+                        }
+                        let mut h = 1;
+                        while j + h < height
+                            && mask[(j + h) * width + i..(j + h) * width + i + w]
+                                .iter()
+                                .all(|&c| c == color)
+                        {
+                            h += 1;
+                        }
+                        for row in j..j + h {
+                            mask[row * width + i..row * width + i + w].fill(0);
+                        }
+
+                        let rectangle = Rectangle {
+                            axis,
+                            plane,
+                            corner: (i, j),
+                            extent: (w, h),
+                            positive,
+                        };
+                        add_quad(&mut model, grid, &rectangle, color);
+                        i += w;
+                    }
+                }
+            }
+        }
+    }
+
+    let mut colors: Vec<u8> = grid.voxels().into_iter().map(|(_, c)| c).collect();
+    colors.sort_unstable();
+    colors.dedup();
+    for color in colors {
+        let name = material_name(color);
+        let mut material = Material::new(&name);
+        material.diffuse = grid.color(color).map(|c| c as f32 / 255.0);
+        model.mesh.materials.insert(name, material);
+    }
+    model
+}
+
+/// A merged face on the plane `axis = plane`, covering `extent` cells of the slice
+/// from `corner` along u and v.
+struct Rectangle {
+    axis: usize,
+    plane: usize,
+    corner: (usize, usize),
+    extent: (usize, usize),
+    positive: bool,
+}
+
+fn material_name(color: u8) -> String {
+    format!("color_{}", color)
+}
+
+fn add_quad(model: &mut Model, grid: &VoxelGrid, rectangle: &Rectangle, color: u8) {
+    let Rectangle {
+        axis,
+        plane,
+        corner: (i, j),
+        extent: (w, h),
+        positive,
+    } = *rectangle;
+    let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+    let point = |a: usize, b: usize| {
+        let mut p = [0.0; 3];
+        p[axis] = plane as Real;
+        p[u] = a as Real;
+        p[v] = b as Real;
+        grid.origin + Vector3::from(p) * grid.voxel_size
+    };
+    let mut normal = Vector3::zeros();
+    normal[axis] = if positive { 1.0 } else { -1.0 };
+
+    let corners: [(Point3<Real>, (f32, f32)); 4] = [
+        (point(i, j), (0.0, 0.0)),
+        (point(i + w, j), (w as f32, 0.0)),
+        (point(i + w, j + h), (w as f32, h as f32)),
+        (point(i, j + h), (0.0, h as f32)),
+    ];
+    let first = model.mesh.vertices.len();
+    for (position, uv) in corners {
+        model
+            .mesh
+            .add_vertex(Vertex::new(position, normal, Some(uv)));
+    }
+    let face = if positive {
+        Face::quad(first, first + 1, first + 2, first + 3)
+    } else {
+        Face::quad(first + 3, first + 2, first + 1, first)
+    };
+    model.mesh.add_face(face, Some(material_name(color)));
+}
@@ -0,0 +1,213 @@
+//! Voxel models on a regular 3D grid.
+//!
+//! A [`VoxelGrid`] stores a palette index per cell, with 0 meaning empty, and a
+//! palette of 255 RGBA colors like MagicaVoxel's. Grids can be built by hand, read
+//! from `.vox` files or voxelized from a mesh, and turn into a [`Model`] by greedy
+//! meshing.
+
+mod mesher;
+mod vox;
+mod voxelize;
+
+use crate::{Model, Real};
+use nalgebra::Point3;
+use std::collections::BTreeMap;
+
+/// How the cells of a grid are stored.
+#[derive(Debug, Clone, PartialEq)]
+enum Storage {
+    /// Every cell, X fastest then Y then Z.
+    Dense(Vec<u8>),
+    /// Only the filled cells.
+    Sparse(BTreeMap<[usize; 3], u8>),
+}
+
+/// A 3D grid of voxels, each empty or colored by an index into the palette.
+///
+/// Dense grids keep every cell and suit small or mostly filled models; sparse grids
+/// keep only filled cells and suit large, mostly empty ones. Both behave the same.
+///
+/// Voxel `(x, y, z)` is the cube from `origin + (x, y, z) * voxel_size` to one
+/// voxel size further along every axis. The origin defaults to zero and the voxel
+/// size to 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VoxelGrid {
+    size: [usize; 3],
+    storage: Storage,
+    palette: Vec<[u8; 4]>,
+    voxel_size: Real,
+    origin: Point3<Real>,
+}
+
+impl VoxelGrid {
+    /// Create an empty dense grid of the given number of voxels along each axis.
+    pub fn new(x: usize, y: usize, z: usize) -> Self {
+        Self::with_storage([x, y, z], Storage::Dense(vec![0; x * y * z]))
+    }
+
+    /// Create an empty sparse grid of the given number of voxels along each axis.
+    pub fn sparse(x: usize, y: usize, z: usize) -> Self {
+        Self::with_storage([x, y, z], Storage::Sparse(BTreeMap::new()))
+    }
+
+    fn with_storage(size: [usize; 3], storage: Storage) -> Self {
+        assert!(
+            size.iter().all(|&n| n >= 1),
+            "VoxelGrid must have at least 1 voxel along each axis"
+        );
+        Self {
+            size,
+            storage,
+            palette: default_palette(),
+            voxel_size: 1.0,
+            origin: Point3::origin(),
+        }
+    }
+
+    /// Set the edge length of each voxel.
+    pub fn voxel_size(mut self, size: Real) -> Self {
+        assert!(size > 0.0, "VoxelGrid voxel size must be positive");
+        self.voxel_size = size;
+        self
+    }
+
+    /// Set the corner of voxel `(0, 0, 0)`.
+    pub fn origin(mut self, x: Real, y: Real, z: Real) -> Self {
+        self.origin = Point3::new(x, y, z);
+        self
+    }
+
+    /// Number of voxels along each axis.
+    pub fn size(&self) -> [usize; 3] {
+        self.size
+    }
+
+    /// Whether the grid stores only its filled voxels.
+    pub fn is_sparse(&self) -> bool {
+        matches!(self.storage, Storage::Sparse(_))
+    }
+
+    /// Palette index of a voxel, 0 when it is empty or outside the grid.
+    pub fn get(&self, x: usize, y: usize, z: usize) -> u8 {
+        if x >= self.size[0] || y >= self.size[1] || z >= self.size[2] {
+            return 0;
+        }
+        match &self.storage {
+            Storage::Dense(cells) => cells[self.index(x, y, z)],
+            Storage::Sparse(cells) => cells.get(&[x, y, z]).copied().unwrap_or(0),
+        }
+    }
+
+    /// Set the palette index of a voxel; 0 empties it.
+    pub fn set(&mut self, x: usize, y: usize, z: usize, color: u8) {
+        assert!(
+            x < self.size[0] && y < self.size[1] && z < self.size[2],
+            "Voxel ({}, {}, {}) is outside the grid",
+            x,
+            y,
+            z
+        );
+        let index = self.index(x, y, z);
+        match &mut self.storage {
+            Storage::Dense(cells) => cells[index] = color,
+            Storage::Sparse(cells) => {
+                if color == 0 {
+                    cells.remove(&[x, y, z]);
+                } else {
+                    cells.insert([x, y, z], color);
+                }
+            }
+        }
+    }
+
+    /// Fill every voxel in the box from `min` to `max` inclusive with a color.
+    pub fn fill(&mut self, min: [usize; 3], max: [usize; 3], color: u8) {
+        for z in min[2]..=max[2] {
+            for y in min[1]..=max[1] {
+                for x in min[0]..=max[0] {
+                    self.set(x, y, z, color);
+                }
+            }
+        }
+    }
+
+    /// Number of filled voxels.
+    pub fn len(&self) -> usize {
+        match &self.storage {
+            Storage::Dense(cells) => cells.iter().filter(|&&c| c != 0).count(),
+            Storage::Sparse(cells) => cells.len(),
+        }
+    }
+
+    /// Whether no voxel is filled.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The filled voxels as `([x, y, z], color)`.
+    pub fn voxels(&self) -> Vec<([usize; 3], u8)> {
+        match &self.storage {
+            Storage::Dense(cells) => cells
+                .iter()
+                .enumerate()
+                .filter(|(_, &c)| c != 0)
+                .map(|(i, &c)| {
+                    let (x, rest) = (i % self.size[0], i / self.size[0]);
+                    ([x, rest % self.size[1], rest / self.size[1]], c)
+                })
+                .collect(),
+            Storage::Sparse(cells) => cells.iter().map(|(&p, &c)| (p, c)).collect(),
+        }
+    }
+
+    /// RGBA color of a palette index.
+    pub fn color(&self, index: u8) -> [u8; 4] {
+        self.palette[index as usize]
+    }
+
+    /// Set the RGBA color of a palette index from 1 to 255.
+    pub fn set_color(&mut self, index: u8, rgba: [u8; 4]) {
+        assert!(index != 0, "Palette index 0 is reserved for empty voxels");
+        self.palette[index as usize] = rgba;
+    }
+
+    /// Build a model of the grid's outer faces, merged into as few quads as possible.
+    ///
+    /// Faces between two filled voxels are left out, and neighbouring faces of the
+    /// same color facing the same way are merged into rectangles. Each color gets a
+    /// material named `color_<index>` with its palette color; texture coordinates
+    /// count voxels, so a tiling texture repeats once per voxel.
+    pub fn to_model(&self) -> Model {
+        mesher::greedy_mesh(self)
+    }
+
+    fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        (z * self.size[1] + y) * self.size[0] + x
+    }
+}
+
+/// MagicaVoxel's default palette: index 0 is empty, then a 6 by 6 by 6 color cube
+/// without black, and ramps of red, green, blue and gray.
+fn default_palette() -> Vec<[u8; 4]> {
+    let steps = [0xff, 0xcc, 0x99, 0x66, 0x33, 0x00];
+    let ramp = [0xee, 0xdd, 0xbb, 0xaa, 0x88, 0x77, 0x55, 0x44, 0x22, 0x11];
+    let mut palette = vec![[0, 0, 0, 0]];
+    for r in steps {
+        for g in steps {
+            for b in steps {
+                if (r, g, b) != (0, 0, 0) {
+                    palette.push([r, g, b, 0xff]);
+                }
+            }
+        }
+    }
+    for channel in 0..3 {
+        for value in ramp {
+            let mut color = [0, 0, 0, 0xff];
+            color[channel] = value;
+            palette.push(color);
+        }
+    }
+    palette.extend(ramp.map(|value| [value, value, value, 0xff]));
+    palette
+}
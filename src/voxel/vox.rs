@@ -0,0 +1,177 @@
+//! Reading and writing MagicaVoxel `.vox` files.
+//!
+//! A file is the magic `VOX `, a version number and a `MAIN` chunk whose children
+//! hold each model's `SIZE` and `XYZI` (voxel) chunks and an optional `RGBA`
+//! palette. Every chunk has a 4-byte id, the byte sizes of its content and of its
+//! children, and then both. Integers are little-endian.
+
+use super::VoxelGrid;
+use crate::{Error, Result};
+use std::path::Path;
+
+/// Largest number of voxels along an axis a `.vox` model may have.
+const MAX_SIZE: usize = 256;
+
+fn invalid(message: &str) -> Error {
+    Error::ImportError(format!("Invalid VOX data: {}", message))
+}
+
+impl VoxelGrid {
+    /// Load a MagicaVoxel `.vox` file.
+    pub fn from_vox<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_vox_data(&std::fs::read(path)?)
+    }
+
+    /// Read the contents of a MagicaVoxel `.vox` file into a dense grid.
+    ///
+    /// Files with several models give the first one; scene graph, layer and
+    /// material chunks are skipped. Without a palette chunk the default palette is
+    /// used. MagicaVoxel's Z axis points up.
+    pub fn from_vox_data(data: &[u8]) -> Result<Self> {
+        if data.get(..4) != Some(b"VOX ") {
+            return Err(invalid("missing VOX header"));
+        }
+        let (id, content, children) = chunk(data, 8)?;
+        if &id != b"MAIN" {
+            return Err(invalid("missing MAIN chunk"));
+        }
+        let children_start = 8 + 12 + content.len();
+
+        let (mut size, mut voxels, mut palette) = (None, None, None);
+        let mut offset = children_start;
+        while offset < children_start + children.len() {
+            let (id, content, children) = chunk(data, offset)?;
+            offset += 12 + content.len() + children.len();
+            match &id {
+                b"SIZE" if size.is_none() => {
+                    let dimensions = [
+                        u32_at(content, 0)?,
+                        u32_at(content, 4)?,
+                        u32_at(content, 8)?,
+                    ];
+                    if dimensions.iter().any(|&n| n == 0 || n as usize > MAX_SIZE) {
+                        return Err(invalid("model size must be from 1 to 256"));
+                    }
+                    size = Some(dimensions.map(|n| n as usize));
+                }
+                b"XYZI" if voxels.is_none() => {
+                    let count = u32_at(content, 0)? as usize;
+                    let cells = content
+                        .get(4..4 + count * 4)
+                        .ok_or_else(|| invalid("truncated voxels"))?;
+                    voxels = Some(
+                        cells
+                            .chunks(4)
+                            .map(|c| [c[0], c[1], c[2], c[3]])
+                            .collect::<Vec<_>>(),
+                    );
+                }
+                b"RGBA" => {
+                    let colors = content
+                        .get(..256 * 4)
+                        .ok_or_else(|| invalid("truncated palette"))?;
+                    palette = Some(
+                        colors
+                            .chunks(4)
+                            .map(|c| [c[0], c[1], c[2], c[3]])
+                            .collect::<Vec<_>>(),
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        let size = size.ok_or_else(|| invalid("missing SIZE chunk"))?;
+        let voxels = voxels.ok_or_else(|| invalid("missing XYZI chunk"))?;
+        let mut grid = VoxelGrid::new(size[0], size[1], size[2]);
+        if let Some(palette) = palette {
+            // Palette entry i is color index i + 1; the last entry is unused
+            for (index, &rgba) in (1..=255).zip(&palette) {
+                grid.set_color(index, rgba);
+            }
+        }
+        for [x, y, z, color] in voxels {
+            let (x, y, z) = (x as usize, y as usize, z as usize);
+            if x >= size[0] || y >= size[1] || z >= size[2] {
+                return Err(invalid("voxel outside the model"));
+            }
+            if color != 0 {
+                grid.set(x, y, z, color);
+            }
+        }
+        Ok(grid)
+    }
+
+    /// Save the grid as a MagicaVoxel `.vox` file.
+    pub fn export_vox<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        std::fs::write(path, self.to_vox_data()?)?;
+        Ok(())
+    }
+
+    /// The contents of a MagicaVoxel `.vox` file holding the grid and its palette.
+    ///
+    /// Fails if the grid is more than 256 voxels along any axis, the most the format
+    /// allows in one model.
+    pub fn to_vox_data(&self) -> Result<Vec<u8>> {
+        if self.size.iter().any(|&n| n > MAX_SIZE) {
+            return Err(Error::ExportError(format!(
+                "VOX models can be at most {} voxels along each axis",
+                MAX_SIZE
+            )));
+        }
+
+        let size: Vec<u8> = self
+            .size
+            .iter()
+            .flat_map(|&n| (n as u32).to_le_bytes())
+            .collect();
+        let voxels = self.voxels();
+        let mut xyzi = (voxels.len() as u32).to_le_bytes().to_vec();
+        for ([x, y, z], color) in voxels {
+            xyzi.extend([x as u8, y as u8, z as u8, color]);
+        }
+        let mut rgba: Vec<u8> = (1..=255).flat_map(|i| self.color(i)).collect();
+        rgba.extend([0; 4]);
+
+        let mut children = Vec::new();
+        for (id, content) in [(b"SIZE", size), (b"XYZI", xyzi), (b"RGBA", rgba)] {
+            write_chunk(&mut children, id, &content, &[]);
+        }
+        let mut data = b"VOX ".to_vec();
+        data.extend(150u32.to_le_bytes());
+        write_chunk(&mut data, b"MAIN", &[], &children);
+        Ok(data)
+    }
+}
+
+/// The id, content and children of the chunk at `offset`.
+fn chunk(data: &[u8], offset: usize) -> Result<([u8; 4], &[u8], &[u8])> {
+    let header = data
+        .get(offset..offset + 12)
+        .ok_or_else(|| invalid("truncated chunk header"))?;
+    let id = [header[0], header[1], header[2], header[3]];
+    let content_size = u32_at(header, 4)? as usize;
+    let children_size = u32_at(header, 8)? as usize;
+    let start = offset + 12;
+    let content = data
+        .get(start..start + content_size)
+        .ok_or_else(|| invalid("truncated chunk"))?;
+    let children = data
+        .get(start + content_size..start + content_size + children_size)
+        .ok_or_else(|| invalid("truncated chunk"))?;
+    Ok((id, content, children))
+}
+
+fn u32_at(data: &[u8], offset: usize) -> Result<u32> {
+    data.get(offset..offset + 4)
+        .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .ok_or_else(|| invalid("unexpected end of chunk"))
+}
+
+fn write_chunk(out: &mut Vec<u8>, id: &[u8; 4], content: &[u8], children: &[u8]) {
+    out.extend(id);
+    out.extend((content.len() as u32).to_le_bytes());
+    out.extend((children.len() as u32).to_le_bytes());
+    out.extend(content);
+    out.extend(children);
+}
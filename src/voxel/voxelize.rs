@@ -0,0 +1,142 @@
+//! Voxelization of triangle meshes.
+
+use super::VoxelGrid;
+use crate::bounds::Aabb;
+use crate::{Mesh, Real};
+use nalgebra::{Point3, Vector3};
+
+impl VoxelGrid {
+    /// Voxelize a mesh into a dense grid of `color`, with voxels of the given size
+    /// covering its bounding box.
+    ///
+    /// Voxels that the surface passes through are filled, and so are voxels whose
+    /// centers lie inside it, found by counting crossings of the surface along Z. The
+    /// inside only comes out right for closed meshes; open ones give just a shell.
+    pub fn from_mesh(mesh: &Mesh, voxel_size: Real, color: u8) -> Self {
+        assert!(voxel_size > 0.0, "Voxel size must be positive");
+        assert!(
+            color != 0,
+            "Voxel color must be a palette index from 1 to 255"
+        );
+
+        let triangles: Vec<[Point3<Real>; 3]> = mesh
+            .faces
+            .iter()
+            .filter(|face| face.indices.len() >= 3)
+            .flat_map(|face| {
+                let p = |k: usize| mesh.vertices[face.indices[k]].position;
+                (1..face.indices.len() - 1).map(move |k| [p(0), p(k), p(k + 1)])
+            })
+            .collect();
+        let Some(Aabb { min, max }) = Aabb::from_points(triangles.iter().flatten()) else {
+            return Self::new(1, 1, 1).voxel_size(voxel_size);
+        };
+        let size = ((max - min) / voxel_size).map(|n| (n.ceil() as usize).max(1));
+        let mut grid = Self::new(size.x, size.y, size.z)
+            .voxel_size(voxel_size)
+            .origin(min.x, min.y, min.z);
+
+        // Work in voxel units, with voxel (i, j, k) spanning [i, i + 1] and so on
+        let triangles: Vec<[Point3<Real>; 3]> = triangles
+            .iter()
+            .map(|t| t.map(|p| Point3::from((p - min) / voxel_size)))
+            .collect();
+        let dimensions = grid.size;
+        let cells = |low: Real, high: Real, axis: usize| {
+            let last = dimensions[axis] - 1;
+            (low.floor().max(0.0) as usize).min(last)..=(high.ceil().max(0.0) as usize).min(last)
+        };
+
+        // The surface, shrinking voxels a little so faces on voxel boundaries only
+        // fill the voxels they bound on the inside
+        let half = 0.5 * (1.0 - 1e-4);
+        for triangle in &triangles {
+            let low = triangle[0].inf(&triangle[1]).inf(&triangle[2]);
+            let high = triangle[0].sup(&triangle[1]).sup(&triangle[2]);
+            for z in cells(low.z, high.z, 2) {
+                for y in cells(low.y, high.y, 1) {
+                    for x in cells(low.x, high.x, 0) {
+                        let center = Point3::new(x as Real, y as Real, z as Real).map(|c| c + 0.5);
+                        if triangle_overlaps_box(triangle, center, half) {
+                            grid.set(x, y, z, color);
+                        }
+                    }
+                }
+            }
+        }
+
+        // The inside, between pairs of crossings up each column through voxel centers
+        for y in 0..grid.size[1] {
+            for x in 0..grid.size[0] {
+                let (px, py) = (x as Real + 0.5, y as Real + 0.5);
+                let mut crossings: Vec<Real> = triangles
+                    .iter()
+                    .filter_map(|t| column_crossing(t, px, py))
+                    .collect();
+                crossings.sort_by(|a, b| a.total_cmp(b));
+                for pair in crossings.chunks_exact(2) {
+                    let first = (pair[0] - 0.5).ceil().max(0.0) as usize;
+                    let last = (pair[1] - 0.5).floor();
+                    if last < 0.0 {
+                        continue;
+                    }
+                    for z in first..=(last as usize).min(grid.size[2] - 1) {
+                        grid.set(x, y, z, color);
+                    }
+                }
+            }
+        }
+        grid
+    }
+}
+
+/// Height at which the vertical line through `(x, y)` crosses a triangle, if it does.
+///
+/// Lines through an edge shared by two triangles count for exactly one of them, by
+/// the top-left rule of rasterizers, so crossings are not lost or doubled there.
+fn column_crossing(triangle: &[Point3<Real>; 3], x: Real, y: Real) -> Option<Real> {
+    let [mut a, mut b, c] = *triangle;
+    let area = (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x);
+    if area == 0.0 {
+        return None;
+    }
+    if area < 0.0 {
+        std::mem::swap(&mut a, &mut b);
+    }
+
+    // Each weight is the edge function of the edge opposite its vertex
+    let edge = |from: Point3<Real>, to: Point3<Real>| -> Option<Real> {
+        let value = (to.x - from.x) * (y - from.y) - (to.y - from.y) * (x - from.x);
+        let (dx, dy) = (to.x - from.x, to.y - from.y);
+        let top_left = dy < 0.0 || (dy == 0.0 && dx > 0.0);
+        (value > 0.0 || (value == 0.0 && top_left)).then_some(value)
+    };
+    let wa = edge(b, c)?;
+    let wb = edge(c, a)?;
+    let wc = edge(a, b)?;
+    Some((wa * a.z + wb * b.z + wc * c.z) / (wa + wb + wc))
+}
+
+/// Whether a triangle overlaps the cube of half size `half` around `center`, by the
+/// separating axis test.
+fn triangle_overlaps_box(triangle: &[Point3<Real>; 3], center: Point3<Real>, half: Real) -> bool {
+    let v = triangle.map(|p| p - center);
+    let edges = [v[1] - v[0], v[2] - v[1], v[0] - v[2]];
+    let units = [Vector3::x(), Vector3::y(), Vector3::z()];
+
+    let mut axes = Vec::with_capacity(13);
+    axes.extend_from_slice(&units);
+    axes.push(edges[0].cross(&edges[1]));
+    for unit in &units {
+        for edge in &edges {
+            axes.push(unit.cross(edge));
+        }
+    }
+    axes.iter().all(|axis| {
+        let projections = v.map(|p| p.dot(axis));
+        let low = projections[0].min(projections[1]).min(projections[2]);
+        let high = projections[0].max(projections[1]).max(projections[2]);
+        let radius = half * axis.abs().sum();
+        low <= radius && high >= -radius
+    })
+}
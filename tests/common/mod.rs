@@ -0,0 +1,55 @@
+//! Mesh checks shared by the integration tests.
+
+#![allow(dead_code)]
+
+use mg::{Model, Real};
+
+/// Volume enclosed by a closed polygon mesh with outward winding.
+pub fn signed_volume(model: &Model) -> Real {
+    let mut volume = 0.0;
+    for face in &model.mesh.faces {
+        let p = |k: usize| model.mesh.vertices[face.indices[k]].position.coords;
+        for k in 1..face.indices.len() - 1 {
+            volume += p(0).dot(&p(k).cross(&p(k + 1))) / 6.0;
+        }
+    }
+    volume
+}
+
+/// Check that every vertex normal is unit length and agrees with its faces' winding.
+pub fn assert_outward_normals(model: &Model) {
+    for vertex in &model.mesh.vertices {
+        assert!((vertex.normal.norm() - 1.0).abs() < 1e-4);
+    }
+    for face in &model.mesh.faces {
+        let [p0, p1, p2] = [0, 1, 2].map(|i| model.mesh.vertices[face.indices[i]].position);
+        let face_normal = (p1 - p0).cross(&(p2 - p0));
+        for &idx in &face.indices {
+            assert!(face_normal.dot(&model.mesh.vertices[idx].normal) > 0.0);
+        }
+    }
+}
+
+/// Check that every edge is shared by exactly one other face running the opposite way,
+/// matching vertices by position so split texture seams do not count as holes.
+///
+/// Positions must match exactly, as STL and other formats without shared vertices
+/// need them to; only the sign of zero is ignored.
+pub fn assert_watertight(model: &Model) {
+    let key = |idx: usize| {
+        let p = model.mesh.vertices[idx].position;
+        [p.x, p.y, p.z].map(|c| (c + 0.0).to_bits())
+    };
+    let mut edges = std::collections::HashMap::new();
+    for face in &model.mesh.faces {
+        for k in 0..face.indices.len() {
+            let a = key(face.indices[k]);
+            let b = key(face.indices[(k + 1) % face.indices.len()]);
+            *edges.entry((a, b)).or_insert(0) += 1;
+        }
+    }
+    for (&(a, b), &count) in &edges {
+        assert_eq!(count, 1);
+        assert_eq!(edges.get(&(b, a)), Some(&1));
+    }
+}
@@ -0,0 +1,176 @@
+use mg::bounds::Aabb;
+use mg::primitives::{Cube, Sphere};
+use mg::{Mesh, Model, Real, Rotate, Scale, Translate, Vertex};
+use nalgebra::{Point3, Vector3};
+
+/// A box of the given size centered on the origin.
+fn block(x: Real, y: Real, z: Real) -> Model {
+    let mut model = Cube::new().build();
+    model.apply(Scale::new(x, y, z));
+    model
+}
+
+fn close(a: Real, b: Real) -> bool {
+    (a - b).abs() < 1e-4
+}
+
+#[test]
+fn test_aabb() {
+    let mut model = block(2.0, 4.0, 6.0);
+    model.apply(Translate::new(1.0, 2.0, 3.0));
+    let aabb = model.mesh.aabb().unwrap();
+    assert_eq!(aabb.min, Point3::new(0.0, 0.0, 0.0));
+    assert_eq!(aabb.max, Point3::new(2.0, 4.0, 6.0));
+    assert_eq!(aabb.center(), Point3::new(1.0, 2.0, 3.0));
+    assert_eq!(aabb.size(), Vector3::new(2.0, 4.0, 6.0));
+    assert_eq!(aabb.volume(), 48.0);
+    assert!(aabb.contains(&Point3::new(2.0, 0.0, 3.0)));
+    assert!(!aabb.contains(&Point3::new(2.1, 0.0, 3.0)));
+
+    assert!(Mesh::new().aabb().is_none());
+    assert!(Aabb::from_points(&[]).is_none());
+}
+
+#[test]
+fn test_bounding_sphere() {
+    // A tessellated sphere's vertices all lie on it
+    let sphere = Sphere::new()
+        .radius(2.0)
+        .center(1.0, -2.0, 3.0)
+        .segments(24)
+        .rings(12)
+        .build();
+    let bounds = sphere.mesh.bounding_sphere().unwrap();
+    assert!((bounds.center - Point3::new(1.0, -2.0, 3.0)).norm() < 1e-3);
+    assert!((bounds.radius - 2.0).abs() < 1e-3);
+
+    // A box's is through its corners, and no larger
+    let bounds = block(2.0, 2.0, 2.0).mesh.bounding_sphere().unwrap();
+    assert!(bounds.center.coords.norm() < 1e-4);
+    assert!(close(bounds.radius, Real::sqrt(3.0)));
+
+    // Scattered points all fall inside, with some on the surface
+    let mut mesh = Mesh::new();
+    let mut state: u32 = 7;
+    let mut random = || {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        (state >> 8) as Real / (1u32 << 24) as Real * 10.0 - 5.0
+    };
+    for _ in 0..2000 {
+        let (x, y, z) = (random(), random() * 0.5, random() * 0.2);
+        mesh.add_vertex(Vertex::with_position(x, y, z));
+    }
+    let bounds = mesh.bounding_sphere().unwrap();
+    let distances: Vec<Real> = mesh
+        .vertices
+        .iter()
+        .map(|v| (v.position - bounds.center).norm())
+        .collect();
+    assert!(mesh.vertices.iter().all(|v| bounds.contains(&v.position)));
+    assert!(
+        distances
+            .iter()
+            .filter(|&&d| d > bounds.radius - 1e-3)
+            .count()
+            >= 2
+    );
+    let aabb = mesh.aabb().unwrap();
+    assert!(bounds.radius <= aabb.size().norm() / 2.0);
+
+    // A single point, and points on a line
+    let mut mesh = Mesh::new();
+    mesh.add_vertex(Vertex::with_position(1.0, 1.0, 1.0));
+    assert_eq!(mesh.bounding_sphere().unwrap().radius, 0.0);
+    for x in [3.0, -1.0, 2.0] {
+        mesh.add_vertex(Vertex::with_position(x, 1.0, 1.0));
+    }
+    let bounds = mesh.bounding_sphere().unwrap();
+    assert!(close(bounds.radius, 2.0));
+    assert!((bounds.center - Point3::new(1.0, 1.0, 1.0)).norm() < 1e-4);
+    assert!(Mesh::new().bounding_sphere().is_none());
+}
+
+#[test]
+fn test_oriented_bounding_box() {
+    // A slanted plank is far tighter in its own frame than in the world's
+    let mut plank = block(1.0, 2.0, 8.0);
+    plank.apply(Rotate::new(Vector3::new(1.0, 1.0, 0.0), 30.0));
+    plank.apply(Rotate::around_y(20.0));
+    plank.apply(Translate::new(5.0, 0.0, -2.0));
+    let obb = plank.mesh.oriented_bounding_box().unwrap();
+    assert!(close(obb.volume(), 16.0));
+    assert!(plank.mesh.aabb().unwrap().volume() > 2.0 * obb.volume());
+    assert!((obb.center - Point3::new(5.0, 0.0, -2.0)).norm() < 1e-3);
+    let mut half_extents: Vec<Real> = obb.half_extents.iter().copied().collect();
+    half_extents.sort_by(|a, b| a.total_cmp(b));
+    assert!(close(half_extents[0], 0.5) && close(half_extents[1], 1.0));
+    assert!(close(half_extents[2], 4.0));
+
+    // The axes are a rotation, and the box holds every vertex and corner
+    assert!((obb.axes.transpose() * obb.axes - nalgebra::Matrix3::identity()).amax() < 1e-4);
+    assert!(close(obb.axes.determinant(), 1.0));
+    let grown = mg::bounds::OrientedBox {
+        half_extents: obb.half_extents.add_scalar(1e-3),
+        ..obb
+    };
+    assert!(plank
+        .mesh
+        .vertices
+        .iter()
+        .all(|v| grown.contains(&v.position)));
+    for corner in obb.corners() {
+        assert!(plank
+            .mesh
+            .vertices
+            .iter()
+            .any(|v| (v.position - corner).norm() < 1e-3));
+    }
+
+    // Without a main direction the axis-aligned box is kept
+    let obb = block(2.0, 2.0, 2.0).mesh.oriented_bounding_box().unwrap();
+    assert_eq!(obb.axes, nalgebra::Matrix3::identity());
+    assert!(close(obb.volume(), 8.0));
+    assert!(Mesh::new().oriented_bounding_box().is_none());
+}
+
+#[test]
+fn test_model_placement() {
+    let mut model = block(2.0, 4.0, 8.0);
+    model.apply(Translate::new(10.0, 20.0, 30.0));
+    model.center_on_origin();
+    let aabb = model.mesh.aabb().unwrap();
+    assert!(aabb.center().coords.norm() < 1e-4);
+    assert_eq!(aabb.size(), Vector3::new(2.0, 4.0, 8.0));
+
+    // The longest side limits the scale, keeping proportions
+    model
+        .apply(Translate::new(3.0, 0.0, 0.0))
+        .fit_into_box(1.0, 1.0, 2.0);
+    let aabb = model.mesh.aabb().unwrap();
+    assert!(aabb.center().coords.norm() < 1e-4);
+    assert!((aabb.size() - Vector3::new(0.5, 1.0, 2.0)).norm() < 1e-4);
+
+    model.place_on_ground();
+    let aabb = model.mesh.aabb().unwrap();
+    assert!(close(aabb.min.y, 0.0) && close(aabb.max.y, 1.0));
+    assert!(close(aabb.center().x, 0.0));
+
+    // Boxes without room are ignored rather than panicking
+    let before = model.mesh.aabb().unwrap();
+    model
+        .fit_into_box(0.0, 1.0, 1.0)
+        .fit_into_box(1.0, -2.0, 1.0);
+    assert_eq!(model.mesh.aabb().unwrap(), before);
+
+    // Flat models scale by the axes they have, and empty ones are left alone
+    let mut flat = mg::primitives::Plane::new().build();
+    flat.fit_into_box(4.0, 4.0, 4.0);
+    let size = flat.mesh.aabb().unwrap().size();
+    assert!(close(size.x.max(size.z), 4.0));
+    let mut empty = Model::new("Empty");
+    empty
+        .center_on_origin()
+        .fit_into_box(1.0, 1.0, 1.0)
+        .place_on_ground();
+    assert!(empty.mesh.vertices.is_empty());
+}
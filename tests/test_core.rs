@@ -1,5 +1,10 @@
-use mg::{Face, Mesh, Model, Vertex};
-use nalgebra::{Point3, Vector3};
+use mg::primitives::{Cube, Plane, Sphere};
+use mg::{CompactMesh, Face, Mesh, Model, Real, Scale, Translate, Vertex, VertexAttribute};
+use nalgebra::{Matrix4, Point3, Vector3};
+
+mod common;
+
+use common::{assert_watertight, signed_volume};
 
 #[test]
 fn test_create_empty_model() {
@@ -101,3 +106,349 @@ fn test_compute_normals() {
         assert_eq!(vertex.normal.magnitude(), 1.0);
     }
 }
+
+#[test]
+fn test_compute_tangents() {
+    let mut mesh = Mesh::new();
+
+    // A unit quad in the XY plane with UVs aligned to X and Y
+    let v0 = mesh.add_vertex(Vertex::new(
+        Point3::new(0.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 1.0),
+        Some((0.0, 0.0)),
+    ));
+    let v1 = mesh.add_vertex(Vertex::new(
+        Point3::new(1.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 1.0),
+        Some((1.0, 0.0)),
+    ));
+    let v2 = mesh.add_vertex(Vertex::new(
+        Point3::new(1.0, 1.0, 0.0),
+        Vector3::new(0.0, 0.0, 1.0),
+        Some((1.0, 1.0)),
+    ));
+    let v3 = mesh.add_vertex(Vertex::new(
+        Point3::new(0.0, 1.0, 0.0),
+        Vector3::new(0.0, 0.0, 1.0),
+        Some((0.0, 1.0)),
+    ));
+    mesh.add_face(Face::quad(v0, v1, v2, v3), None);
+
+    assert!(!mesh.has_tangents());
+    mesh.compute_tangents().unwrap();
+    assert!(mesh.has_tangents());
+
+    // Tangents follow the U direction and the bitangent (V) is right-handed
+    for tangent in &mesh.tangents {
+        assert!((tangent.x - 1.0).abs() < 1e-5);
+        assert!(tangent.y.abs() < 1e-5);
+        assert!(tangent.z.abs() < 1e-5);
+        assert_eq!(tangent.w, 1.0);
+    }
+
+    // Mirroring the UVs across a shared edge splits its vertices, one copy per side
+    let mut mirrored = mesh.clone();
+    let v4 = mirrored.add_vertex(Vertex::new(
+        Point3::new(2.0, 0.0, 0.0),
+        Vector3::new(0.0, 0.0, 1.0),
+        Some((0.0, 0.0)),
+    ));
+    let v5 = mirrored.add_vertex(Vertex::new(
+        Point3::new(2.0, 1.0, 0.0),
+        Vector3::new(0.0, 0.0, 1.0),
+        Some((0.0, 1.0)),
+    ));
+    mirrored.add_face(Face::quad(v1, v4, v5, v2), None);
+    mirrored.compute_tangents().unwrap();
+    assert_eq!(mirrored.vertices.len(), 8);
+    assert_eq!(mirrored.tangents.len(), 8);
+    for (face, sign) in mirrored.faces.iter().zip([1.0, -1.0]) {
+        for &i in &face.indices {
+            assert!((mirrored.tangents[i].x - sign).abs() < 1e-5);
+        }
+    }
+    assert_ne!(mirrored.faces[0].indices[1], mirrored.faces[1].indices[0]);
+
+    // Adding a vertex drops the tangents rather than giving it an invalid one
+    mesh.add_vertex(Vertex::with_position(2.0, 0.0, 0.0));
+    assert!(!mesh.has_tangents() && mesh.tangents.is_empty());
+
+    // Vertices without texture coordinates cannot get tangents
+    let mut no_uvs = Mesh::new();
+    no_uvs.add_vertex(Vertex::with_position(0.0, 0.0, 0.0));
+    no_uvs.add_vertex(Vertex::with_position(1.0, 0.0, 0.0));
+    no_uvs.add_vertex(Vertex::with_position(0.0, 1.0, 0.0));
+    no_uvs.add_face(Face::triangle(0, 1, 2), None);
+    assert!(no_uvs.compute_tangents().is_err());
+}
+
+#[test]
+fn test_vertex_colors_and_attributes() {
+    let mut mesh = Mesh::new();
+    mesh.add_vertex(Vertex::with_position(0.0, 0.0, 0.0));
+    mesh.add_vertex(Vertex::with_position(1.0, 0.0, 0.0));
+    mesh.add_vertex(Vertex::with_position(0.0, 1.0, 0.0));
+    mesh.add_face(Face::triangle(0, 1, 2), None);
+
+    assert!(!mesh.has_colors());
+    mesh.colors = vec![[1.0, 0.0, 0.0, 1.0]; 3];
+    assert!(mesh.has_colors());
+
+    let set = mesh
+        .add_tex_coord_set(vec![(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)])
+        .unwrap();
+    assert_eq!(set, 1);
+    assert!(mesh.add_tex_coord_set(vec![(0.0, 0.0)]).is_err());
+
+    mesh.set_attribute(
+        "temperature",
+        VertexAttribute::Scalar(vec![10.0, 20.0, 30.0]),
+    )
+    .unwrap();
+    mesh.set_attribute("flow", VertexAttribute::Vec3(vec![[1.0, 0.0, 0.0]; 3]))
+        .unwrap();
+    assert!(mesh
+        .set_attribute("weight", VertexAttribute::Scalar(vec![1.0]))
+        .is_err());
+
+    let temperature = mesh.attribute("temperature").unwrap();
+    assert_eq!(temperature.components(), 1);
+    assert_eq!(temperature.value(1), &[20.0]);
+    assert_eq!(mesh.attribute("flow").unwrap().value(2), &[1.0, 0.0, 0.0]);
+    assert!(mesh.attribute("weight").is_none());
+
+    // New vertices get default values in every channel
+    mesh.add_vertex(Vertex::with_position(1.0, 1.0, 0.0));
+    assert_eq!(mesh.colors.len(), 4);
+    assert_eq!(mesh.colors[3], [1.0, 1.0, 1.0, 1.0]);
+    assert_eq!(mesh.tex_coord_sets[0].len(), 4);
+    assert_eq!(mesh.attribute("temperature").unwrap().len(), 4);
+    assert_eq!(mesh.attribute("flow").unwrap().value(3), &[0.0, 0.0, 0.0]);
+}
+
+#[test]
+fn test_compact_mesh_round_trip() {
+    let sphere = Sphere::new().segments(8).rings(4).build();
+    let compact = CompactMesh::from(&sphere.mesh);
+
+    // The sphere is all triangles, so no face offsets are stored
+    assert!(compact.is_triangles());
+    assert_eq!(compact.vertex_count(), sphere.mesh.vertices.len());
+    assert_eq!(compact.face_count(), sphere.mesh.faces.len());
+    assert_eq!(compact.indices.len(), sphere.mesh.faces.len() * 3);
+    assert_eq!(compact.tex_coords.len(), compact.vertex_count());
+
+    let mesh = Mesh::from(&compact);
+    assert_eq!(mesh, sphere.mesh);
+}
+
+#[test]
+fn test_compact_mesh_keeps_channels() {
+    let mut model = Cube::new().build();
+    let mesh = &mut model.mesh;
+    let count = mesh.vertices.len();
+    mesh.compute_tangents().unwrap();
+    mesh.colors = (0..count)
+        .map(|i| [i as f32 / 10.0, 0.5, 0.0, 1.0])
+        .collect();
+    mesh.add_tex_coord_set((0..count).map(|i| (i as f32, 1.0)).collect())
+        .unwrap();
+    mesh.set_attribute(
+        "weight",
+        VertexAttribute::Scalar((0..count).map(|i| i as f32).collect()),
+    )
+    .unwrap();
+    mesh.materials
+        .insert("paint".to_string(), mg::types::Material::new("paint"));
+    mesh.face_materials[0] = Some("paint".to_string());
+
+    let compact = CompactMesh::from(&model.mesh);
+    assert_eq!(compact.tangents.len(), count);
+    assert_eq!(compact.colors.len(), count);
+    assert_eq!(compact.tex_coord_sets[0].len(), count);
+    assert_eq!(Mesh::from(&compact), model.mesh);
+
+    // Transforms through the Model adapter keep every channel too
+    let mut moved = compact.clone();
+    moved.apply(Translate::new(1.0, 0.0, 0.0)).unwrap();
+    let mesh = Mesh::from(&moved);
+    assert_eq!(mesh.tangents, model.mesh.tangents);
+    assert_eq!(mesh.colors, model.mesh.colors);
+    assert_eq!(mesh.tex_coord_sets, model.mesh.tex_coord_sets);
+    assert_eq!(mesh.attributes, model.mesh.attributes);
+    assert_eq!(mesh.materials, model.mesh.materials);
+    assert_eq!(mesh.face_materials, model.mesh.face_materials);
+
+    // The native transform turns tangents as the Matrix transform does
+    let mirror = Matrix4::new_nonuniform_scaling(&Vector3::new(-1.0, 1.0, 1.0));
+    let mut mirrored = compact.clone();
+    mirrored.transform(&mirror);
+    let mut expected = model.clone();
+    expected.apply(mg::Matrix::new(mirror));
+    assert_eq!(mirrored.tangents, expected.mesh.tangents);
+}
+
+#[test]
+fn test_compact_mesh_polygons() {
+    let mut compact = CompactMesh::new();
+    for (x, y) in [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0), (2.0, 0.0)] {
+        compact.add_vertex(Point3::new(x, y, 0.0), Vector3::zeros(), None);
+    }
+
+    compact.add_triangle(1, 4, 2);
+    assert!(compact.is_triangles());
+
+    // A quad switches to stored offsets without disturbing existing faces
+    compact.add_face(&[0, 1, 2, 3]);
+    compact.add_triangle(0, 2, 3);
+    assert!(!compact.is_triangles());
+    assert_eq!(compact.face_count(), 3);
+    assert_eq!(compact.face(0), &[1, 4, 2]);
+    assert_eq!(compact.face(1), &[0, 1, 2, 3]);
+    assert_eq!(compact.face(2), &[0, 2, 3]);
+
+    let mesh = Mesh::from(&compact);
+    assert_eq!(mesh.faces[1], Face::quad(0, 1, 2, 3));
+    assert_eq!(CompactMesh::from(&mesh), compact);
+
+    // Normals agree with the Mesh implementation
+    let mut expected = mesh.clone();
+    expected.compute_normals();
+    compact.compute_normals();
+    for (normal, vertex) in compact.normals.iter().zip(&expected.vertices) {
+        assert_eq!(*normal, vertex.normal);
+    }
+}
+
+#[test]
+fn test_compact_mesh_transforms() {
+    let cube = Cube::new().build();
+    let mut compact = CompactMesh::from(&cube.mesh);
+
+    compact.transform(&Matrix4::new_nonuniform_scaling(&Vector3::new(
+        2.0, 1.0, 1.0,
+    )));
+    compact.translate(1.0, 0.0, 0.0);
+    for (position, vertex) in compact.positions.iter().zip(&cube.mesh.vertices) {
+        assert!((position.x - (vertex.position.x * 2.0 + 1.0)).abs() < 1e-6);
+        assert_eq!(position.y, vertex.position.y);
+    }
+
+    // Existing transforms work through the Model adapter
+    compact.apply(Scale::uniform(0.5)).unwrap();
+    let model = compact.to_model("Scaled");
+    assert_eq!(model.name, "Scaled");
+    assert!((model.mesh.vertices[1].position.x - 1.0).abs() < 1e-6);
+}
+
+#[test]
+#[cfg(feature = "f64")]
+fn test_double_precision_geometry() {
+    // Millimetre detail survives a round trip a thousand kilometres from the origin
+    let mut model = Cube::new().size(0.001).build();
+    let original: Vec<_> = model.mesh.vertices.iter().map(|v| v.position).collect();
+
+    model.apply(mg::Translate::new(1_000_000.0, 0.0, 1_000_000.0));
+    model.apply(mg::Translate::new(-1_000_000.0, 0.0, -1_000_000.0));
+
+    for (vertex, position) in model.mesh.vertices.iter().zip(&original) {
+        assert!((vertex.position - position).norm() < 1e-9);
+    }
+}
+
+#[test]
+fn test_convex_hull() {
+    // The 24 corners of a cube's faces collapse to its 8 corners
+    let cube = Cube::new().size(2.0).build();
+    let hull = cube.mesh.convex_hull().unwrap();
+    assert_eq!(hull.mesh.vertices.len(), 8);
+    assert_eq!(hull.mesh.faces.len(), 12);
+    assert_watertight(&hull);
+    assert!((signed_volume(&hull) - 8.0).abs() < 1e-4);
+    for vertex in &hull.mesh.vertices {
+        assert!(vertex.normal.dot(&vertex.position.coords) > 0.0);
+    }
+
+    // Scattered points: the hull holds them all and its corners are among them
+    let mut mesh = Mesh::new();
+    let mut state: u32 = 12345;
+    let mut random = || {
+        state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+        (state >> 8) as mg::Real / (1u32 << 24) as mg::Real * 2.0 - 1.0
+    };
+    for _ in 0..500 {
+        let (x, y, z) = (random(), random(), random());
+        mesh.add_vertex(Vertex::with_position(x, y, z));
+    }
+    let hull = mesh.convex_hull().unwrap();
+    assert_watertight(&hull);
+    // Euler's formula for a closed triangulated sphere
+    assert_eq!(
+        hull.mesh.vertices.len() + hull.mesh.faces.len() / 2,
+        hull.mesh.faces.len() + 2
+    );
+    for face in &hull.mesh.faces {
+        let p = |k: usize| hull.mesh.vertices[face.indices[k]].position;
+        let normal = (p(1) - p(0)).cross(&(p(2) - p(0))).normalize();
+        for vertex in &mesh.vertices {
+            assert!(normal.dot(&(vertex.position - p(0))) < 1e-5);
+        }
+    }
+    for vertex in &hull.mesh.vertices {
+        assert!(mesh.vertices.iter().any(|v| v.position == vertex.position));
+    }
+
+    // A sphere is its own hull
+    let sphere = Sphere::new().segments(16).rings(8).build();
+    let hull = sphere.mesh.convex_hull().unwrap();
+    assert_watertight(&hull);
+    assert_eq!(hull.mesh.vertices.len(), 16 * 7 + 2);
+
+    // Flat or too few points enclose no volume
+    let plane = mg::primitives::Plane::new().build();
+    assert!(plane.mesh.convex_hull().is_err());
+    assert!(Mesh::new().convex_hull().is_err());
+}
+
+#[test]
+fn test_mass_properties() {
+    // A 2 by 4 by 6 box of density 0.5 has mass 24
+    let mut block = Cube::new().build();
+    block.apply(Scale::new(2.0, 4.0, 6.0));
+    block.apply(Translate::new(10.0, -3.0, 1.0));
+    let properties = block.mesh.mass_properties(0.5).unwrap();
+    assert!((properties.volume - 48.0).abs() < 1e-3);
+    assert!((properties.area - 88.0).abs() < 1e-3);
+    assert!((properties.mass - 24.0).abs() < 1e-3);
+    assert!((properties.center_of_mass - Point3::new(10.0, -3.0, 1.0)).norm() < 1e-4);
+
+    // Box inertia is m (b^2 + c^2) / 12 about each axis, without products
+    let expected = [16.0 + 36.0, 4.0 + 36.0, 4.0 + 16.0].map(|s: Real| 24.0 * s / 12.0);
+    let difference = properties.inertia - nalgebra::Matrix3::from_diagonal(&expected.into());
+    assert!(difference.amax() < 1e-2);
+
+    // A sphere approaches 2/5 m r^2, and its center
+    let sphere = Sphere::new().radius(2.0).segments(96).rings(48).build();
+    let properties = sphere.mesh.mass_properties(1.0).unwrap();
+    let exact = 4.0 / 3.0 * mg::consts::PI * 8.0;
+    assert!((properties.mass - exact).abs() < 0.01 * exact);
+    assert!((properties.area - 16.0 * mg::consts::PI).abs() < 0.01 * 16.0 * mg::consts::PI);
+    assert!(properties.center_of_mass.coords.norm() < 1e-4);
+    let moment = 0.4 * properties.mass * 4.0;
+    assert!((properties.inertia[(0, 0)] - moment).abs() < 0.01 * moment);
+    assert!((properties.inertia[(2, 2)] - moment).abs() < 0.01 * moment);
+    assert!(properties.inertia[(0, 1)].abs() < 1e-3);
+
+    // Inward winding flips the volume but not the mass
+    let mut inverted = Cube::new().size(2.0).build();
+    for face in &mut inverted.mesh.faces {
+        face.indices.reverse();
+    }
+    let properties = inverted.mesh.mass_properties(2.0).unwrap();
+    assert!((properties.volume + 8.0).abs() < 1e-4);
+    assert!((properties.mass - 16.0).abs() < 1e-4);
+    assert!(properties.inertia[(0, 0)] > 0.0);
+
+    assert!(Plane::new().build().mesh.mass_properties(1.0).is_err());
+    assert!(Mesh::new().mass_properties(1.0).is_err());
+}
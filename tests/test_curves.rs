@@ -0,0 +1,381 @@
+use mg::consts::PI;
+use mg::curves::{self, parse_svg_path, BooleanOp, Join, Path};
+use mg::generators::Extrude;
+use mg::Real;
+
+mod common;
+
+use common::signed_volume;
+
+/// Area enclosed by a contour; positive for counter-clockwise winding.
+fn area(points: &[(Real, Real)]) -> Real {
+    (0..points.len())
+        .map(|i| {
+            let (a, b) = (points[i], points[(i + 1) % points.len()]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum::<Real>()
+        / 2.0
+}
+
+/// Total signed area of a set of contours.
+fn total_area(contours: &[Vec<(Real, Real)>]) -> Real {
+    contours.iter().map(|c| area(c)).sum()
+}
+
+fn square(x: Real, y: Real, size: Real) -> Vec<(Real, Real)> {
+    vec![(x, y), (x + size, y), (x + size, y + size), (x, y + size)]
+}
+
+fn close(a: (Real, Real), b: (Real, Real), tolerance: Real) -> bool {
+    (a.0 - b.0).abs() <= tolerance && (a.1 - b.1).abs() <= tolerance
+}
+
+#[test]
+fn test_path_segments() {
+    let points = Path::new(0.0, 0.0)
+        .line_to(2.0, 0.0)
+        .quadratic_to(3.0, 0.0, 3.0, 1.0)
+        .cubic_to(3.0, 2.0, 1.0, 3.0, 0.0, 2.0)
+        .close()
+        .flatten(0.001);
+    assert_eq!(points[0], (0.0, 0.0));
+    assert_eq!(points[1], (2.0, 0.0));
+    assert!(points.contains(&(3.0, 1.0)));
+    assert_eq!(*points.last().unwrap(), (0.0, 2.0));
+    assert!(area(&points) > 0.0);
+
+    // The quadratic's midpoint is at t = 0.5, a quarter of the way in from the corner
+    let quadratic = Path::new(2.0, 0.0)
+        .quadratic_to(3.0, 0.0, 3.0, 1.0)
+        .flatten(1e-4);
+    let nearest = quadratic
+        .iter()
+        .map(|&(x, y)| (x - 2.75).hypot(y - 0.25))
+        .fold(Real::MAX, Real::min);
+    assert!(nearest < 0.01);
+
+    // Finer tolerances take more points
+    let coarse = Path::new(0.0, 0.0).cubic_to(0.0, 1.0, 1.0, 1.0, 1.0, 0.0);
+    assert!(coarse.flatten(0.1).len() < coarse.flatten(0.001).len());
+}
+
+#[test]
+fn test_path_arcs() {
+    let tolerance = 0.001;
+    let circle = Path::circle(1.0, 2.0, 3.0).flatten(tolerance);
+    for &(x, y) in &circle {
+        assert!(((x - 1.0).hypot(y - 2.0) - 3.0).abs() < 1e-4);
+    }
+    // Chords within tolerance of the circle lose only a sliver of area
+    let exact = PI * 9.0;
+    assert!(area(&circle) < exact && area(&circle) > exact * 0.999);
+
+    // The short counter-clockwise arc from (1, 0) to (-1, 0) bulges up through (0, 1)
+    let upper = Path::new(1.0, 0.0)
+        .arc_to(1.0, false, true, -1.0, 0.0)
+        .flatten(tolerance);
+    assert!(upper.iter().all(|&(_, y)| y >= -1e-6));
+    assert!(upper.iter().any(|&p| close(p, (0.0, 1.0), 0.01)));
+    let lower = Path::new(1.0, 0.0)
+        .arc_to(1.0, false, false, -1.0, 0.0)
+        .flatten(tolerance);
+    assert!(lower.iter().all(|&(_, y)| y <= 1e-6));
+
+    // A large arc on a small radius goes the long way round a bigger circle
+    let large = Path::new(0.0, 0.0)
+        .arc_to(1.0, true, true, 1.0, 0.0)
+        .flatten(tolerance);
+    let small = Path::new(0.0, 0.0)
+        .arc_to(1.0, false, true, 1.0, 0.0)
+        .flatten(tolerance);
+    assert!(curves::length(&large, false) > curves::length(&small, false) * 3.0);
+
+    // Radii too small to reach are scaled up to a half ellipse
+    let stretched = Path::new(0.0, 0.0)
+        .elliptical_arc_to(0.5, 0.25, 0.0, false, true, 4.0, 0.0)
+        .flatten(tolerance);
+    let lowest = stretched.iter().map(|p| p.1).fold(Real::MAX, Real::min);
+    assert!((lowest + 1.0).abs() < 0.01);
+}
+
+#[test]
+fn test_splines() {
+    let points = vec![(0.0, 0.0), (1.0, 1.0), (2.0, 0.0), (3.0, 1.0)];
+    let through = Path::catmull_rom(&points, false).flatten(0.001);
+    for &p in &points {
+        assert!(through.iter().any(|&q| close(p, q, 1e-5)));
+    }
+
+    let bspline = Path::bspline(&points, false).flatten(0.001);
+    assert!(close(bspline[0], points[0], 1e-6));
+    assert!(close(*bspline.last().unwrap(), points[3], 1e-6));
+    // It stays inside the control polygon and misses the inner points
+    assert!(bspline.iter().all(|&(_, y)| (-1e-6..=1.0).contains(&y)));
+    assert!(!bspline.iter().any(|&q| close(points[1], q, 0.05)));
+
+    let closed = Path::bspline(&square(0.0, 0.0, 6.0), true);
+    assert!(closed.is_closed());
+    let closed = closed.flatten(0.001);
+    // A closed uniform B-spline around a square is a rounded square inside it, coming
+    // within 1/24 of the side of the edges at their midpoints
+    let (min, max) = closed.iter().fold((Real::MAX, Real::MIN), |(lo, hi), p| {
+        (lo.min(p.0).min(p.1), hi.max(p.0).max(p.1))
+    });
+    assert!((min - 0.25).abs() < 1e-3 && (max - 5.75).abs() < 1e-3);
+    assert!(area(&closed) > 0.0);
+}
+
+#[test]
+fn test_resample() {
+    let polyline = vec![(0.0, 0.0), (1.0, 0.0), (1.0, 3.0)];
+    let even = curves::resample(&polyline, 5, false);
+    assert_eq!(even.len(), 5);
+    assert_eq!(even[0], (0.0, 0.0));
+    assert_eq!(even[4], (1.0, 3.0));
+    assert!(close(even[1], (1.0, 0.0), 1e-6));
+    assert!(close(even[2], (1.0, 1.0), 1e-6));
+
+    let ring = curves::resample(&square(0.0, 0.0, 1.0), 8, true);
+    assert_eq!(ring.len(), 8);
+    for k in 0..8 {
+        let (a, b) = (ring[k], ring[(k + 1) % 8]);
+        assert!(((a.0 - b.0).hypot(a.1 - b.1) - 0.5).abs() < 1e-5);
+    }
+    assert!((curves::length(&ring, true) - 4.0).abs() < 1e-5);
+}
+
+#[test]
+fn test_offset() {
+    // A single contour, for outlines whose offset does not split
+    let single = |outline: &[(Real, Real)], distance: Real, join: Join| {
+        let mut contours = curves::offset(outline, distance, join);
+        assert_eq!(contours.len(), 1);
+        contours.pop().unwrap()
+    };
+
+    let outline = square(0.0, 0.0, 2.0);
+    let grown = single(&outline, 1.0, Join::Miter(4.0));
+    assert_eq!(grown.len(), 4);
+    assert!((area(&grown) - 16.0).abs() < 1e-4);
+    assert!(grown.contains(&(-1.0, -1.0)));
+
+    let shrunk = single(&outline, -0.5, Join::Round);
+    assert!((area(&shrunk) - 1.0).abs() < 1e-4);
+
+    // Round corners add a quarter circle each; bevels cut a triangle off those
+    let round = single(&outline, 1.0, Join::Round);
+    let expected = 4.0 + 8.0 + PI;
+    assert!((area(&round) - expected).abs() < 0.05);
+    let bevel = single(&outline, 1.0, Join::Bevel);
+    assert!((area(&bevel) - 14.0).abs() < 1e-4);
+    // A miter limit below the corner's sqrt(2) bevels instead
+    let limited = single(&outline, 1.0, Join::Miter(1.2));
+    assert!((area(&limited) - 14.0).abs() < 1e-4);
+
+    // Clockwise outlines still grow and keep their winding
+    let clockwise: Vec<_> = outline.iter().rev().copied().collect();
+    let grown = single(&clockwise, 1.0, Join::Miter(4.0));
+    assert!((area(&grown) + 16.0).abs() < 1e-4);
+
+    // The inner corner of an L shape meets where the offset edges cross
+    let l_shape = vec![
+        (0.0, 0.0),
+        (2.0, 0.0),
+        (2.0, 1.0),
+        (1.0, 1.0),
+        (1.0, 2.0),
+        (0.0, 2.0),
+    ];
+    let grown = single(&l_shape, 0.25, Join::Miter(4.0));
+    assert!(grown.iter().any(|&p| close(p, (1.25, 1.25), 1e-5)));
+}
+
+#[test]
+fn test_offset_past_the_middle() {
+    // Shrinking by half the width or more leaves nothing rather than an inverted copy
+    let unit = square(0.0, 0.0, 1.0);
+    for join in [Join::Miter(4.0), Join::Round, Join::Bevel] {
+        assert!(curves::offset(&unit, -0.6, join).is_empty());
+        assert!(curves::offset(&unit, -0.5, join).is_empty());
+    }
+    let clockwise: Vec<_> = unit.iter().rev().copied().collect();
+    assert!(curves::offset(&clockwise, -0.6, Join::Miter(4.0)).is_empty());
+
+    // A long bar only loses its ends once shrunk past its width
+    let bar = vec![(0.0, 0.0), (4.0, 0.0), (4.0, 1.0), (0.0, 1.0)];
+    assert!(curves::offset(&bar, -0.6, Join::Miter(4.0)).is_empty());
+
+    // A dumbbell pinched at its waist splits into its two ends
+    let dumbbell = vec![
+        (0.0, 0.0),
+        (2.0, 0.0),
+        (2.0, 0.8),
+        (3.0, 0.8),
+        (3.0, 0.0),
+        (5.0, 0.0),
+        (5.0, 2.0),
+        (3.0, 2.0),
+        (3.0, 1.2),
+        (2.0, 1.2),
+        (2.0, 2.0),
+        (0.0, 2.0),
+    ];
+    let ends = curves::offset(&dumbbell, -0.3, Join::Miter(4.0));
+    assert_eq!(ends.len(), 2);
+    for end in &ends {
+        assert!((area(end) - 1.4 * 1.4).abs() < 1e-4);
+    }
+}
+
+#[test]
+fn test_boolean_overlapping_squares() {
+    let a = vec![square(0.0, 0.0, 2.0)];
+    let b = vec![square(1.0, 1.0, 2.0)];
+
+    let union = curves::boolean(&a, &b, BooleanOp::Union);
+    assert_eq!(union.len(), 1);
+    assert_eq!(union[0].len(), 8);
+    assert!((total_area(&union) - 7.0).abs() < 1e-5);
+
+    let intersection = curves::boolean(&a, &b, BooleanOp::Intersection);
+    assert_eq!(intersection.len(), 1);
+    assert!((total_area(&intersection) - 1.0).abs() < 1e-5);
+
+    let difference = curves::boolean(&a, &b, BooleanOp::Difference);
+    assert_eq!(difference.len(), 1);
+    assert_eq!(difference[0].len(), 6);
+    assert!((total_area(&difference) - 3.0).abs() < 1e-5);
+
+    let xor = curves::boolean(&a, &b, BooleanOp::Xor);
+    assert_eq!(xor.len(), 2);
+    assert!((total_area(&xor) - 6.0).abs() < 1e-5);
+    assert!(xor.iter().all(|c| area(c) > 0.0));
+
+    // Winding of the inputs makes no difference
+    let reversed: Vec<_> = b
+        .iter()
+        .map(|c| c.iter().rev().copied().collect())
+        .collect();
+    let union = curves::boolean(&a, &reversed, BooleanOp::Union);
+    assert!((total_area(&union) - 7.0).abs() < 1e-5);
+}
+
+#[test]
+fn test_boolean_holes_and_shared_edges() {
+    let outer = vec![square(0.0, 0.0, 4.0)];
+    let inner = vec![square(1.0, 1.0, 2.0)];
+
+    // Cutting out the middle leaves an outline and a clockwise hole
+    let ring = curves::boolean(&outer, &inner, BooleanOp::Difference);
+    assert_eq!(ring.len(), 2);
+    assert_eq!(ring.iter().filter(|c| area(c) < 0.0).count(), 1);
+    assert!((total_area(&ring) - 12.0).abs() < 1e-5);
+
+    // Filling the hole back in gives the square again
+    let filled = curves::boolean(&ring, &inner, BooleanOp::Union);
+    assert_eq!(filled.len(), 1);
+    assert_eq!(filled[0].len(), 4);
+    assert!((total_area(&filled) - 16.0).abs() < 1e-5);
+
+    // Squares sharing part of an edge merge into one outline without extra points
+    let left = vec![square(0.0, 0.0, 2.0)];
+    let right = vec![vec![(2.0, 0.0), (3.0, 0.0), (3.0, 2.0), (2.0, 2.0)]];
+    let merged = curves::boolean(&left, &right, BooleanOp::Union);
+    assert_eq!(merged.len(), 1);
+    assert_eq!(merged[0].len(), 4);
+    assert!((total_area(&merged) - 6.0).abs() < 1e-5);
+    assert!(curves::boolean(&left, &right, BooleanOp::Intersection).is_empty());
+    let same = curves::boolean(&left, &left, BooleanOp::Union);
+    assert_eq!(same.len(), 1);
+    assert!((total_area(&same) - 4.0).abs() < 1e-5);
+    assert!(curves::boolean(&left, &left, BooleanOp::Difference).is_empty());
+
+    // Disjoint shapes stay separate
+    let far = vec![square(10.0, 0.0, 1.0)];
+    assert_eq!(curves::boolean(&left, &far, BooleanOp::Union).len(), 2);
+    assert!(curves::boolean(&left, &far, BooleanOp::Intersection).is_empty());
+
+    // A circle minus a square notch still encloses the right area
+    let circle = vec![Path::circle(0.0, 0.0, 1.0).flatten(0.0001)];
+    let notch = vec![square(0.0, 0.0, 2.0)];
+    let cut = curves::boolean(&circle, &notch, BooleanOp::Difference);
+    assert!((total_area(&cut) - 0.75 * PI).abs() < 0.001);
+}
+
+#[test]
+fn test_svg_path_parsing() {
+    let paths = parse_svg_path("M 0 0 L 10 0 L 10 10 Z").unwrap();
+    assert_eq!(paths.len(), 1);
+    assert!(paths[0].is_closed());
+    assert_eq!(
+        paths[0].flatten(0.01),
+        vec![(0.0, 0.0), (10.0, 0.0), (10.0, 10.0)]
+    );
+
+    // Relative commands, implicit linetos and numbers run together
+    let paths = parse_svg_path("m1,1 2,0h1v2H1.5.5-1e1z m5 5 l1 1").unwrap();
+    assert_eq!(paths.len(), 2);
+    assert_eq!(
+        paths[0].flatten(0.01),
+        vec![
+            (1.0, 1.0),
+            (3.0, 1.0),
+            (4.0, 1.0),
+            (4.0, 3.0),
+            (1.5, 3.0),
+            (0.5, 3.0),
+            (-10.0, 3.0)
+        ]
+    );
+    // The second moveto is relative to the start of the closed subpath
+    assert_eq!(paths[1].start(), (6.0, 6.0));
+    assert_eq!(paths[1].end(), (7.0, 7.0));
+    assert!(!paths[1].is_closed());
+
+    // Smooth curves reflect the previous control point, so S matches a full C
+    let smooth = parse_svg_path("M0 0 C 0 1 1 1 1 0 S 2 -1 2 0").unwrap();
+    let full = parse_svg_path("M0 0 C 0 1 1 1 1 0 C 1 -1 2 -1 2 0").unwrap();
+    assert_eq!(smooth[0].flatten(0.001), full[0].flatten(0.001));
+    let smooth = parse_svg_path("M0 0 Q 1 1 2 0 T 4 0").unwrap();
+    let full = parse_svg_path("M0 0 Q 1 1 2 0 Q 3 -1 4 0").unwrap();
+    assert_eq!(smooth[0].flatten(0.001), full[0].flatten(0.001));
+
+    // Arc flags may run straight into the next number; sweeping the positive way
+    // with Y down bulges towards negative Y
+    let arc = parse_svg_path("M0 0 a1 1 0 012 0").unwrap();
+    let points = arc[0].flatten(0.001);
+    assert_eq!(*points.last().unwrap(), (2.0, 0.0));
+    assert!(points.iter().any(|&p| close(p, (1.0, -1.0), 0.01)));
+
+    assert!(parse_svg_path("").unwrap().is_empty());
+    assert!(parse_svg_path("M 5 5").unwrap().is_empty());
+    for bad in [
+        "L 1 1",
+        "M 0 0 L 1",
+        "M 0 0 X 1 1",
+        "M 0 0 A 1 1 0 2 0 1 1",
+        "M 0 0 Z 1",
+    ] {
+        assert!(parse_svg_path(bad).is_err(), "{} should fail", bad);
+    }
+}
+
+#[test]
+fn test_svg_outline_extrusion() {
+    // A frame drawn with Y down, flipped and extruded with its hole
+    let paths = parse_svg_path("M0 0 H10 V10 H0 Z M3 3 V7 H7 V3 Z").unwrap();
+    let contours: Vec<Vec<(Real, Real)>> = paths
+        .iter()
+        .map(|p| p.clone().scale(1.0, -1.0).flatten(0.01))
+        .collect();
+    let normalized = curves::boolean(&contours, &[], BooleanOp::Union);
+    assert_eq!(normalized.len(), 2);
+    let (outlines, holes): (Vec<_>, Vec<_>) = normalized.into_iter().partition(|c| area(c) > 0.0);
+    let mut extrude = Extrude::new(outlines[0].clone()).depth(2.0);
+    for hole in holes {
+        extrude = extrude.hole(hole);
+    }
+    let model = extrude.build();
+    assert!((signed_volume(&model) - 2.0 * 84.0).abs() < 1e-3);
+}
@@ -0,0 +1,152 @@
+use mg::decompose::ConvexDecomposition;
+use mg::exporters::{gltf::export_gltf_nodes, obj::export_obj_groups};
+use mg::generators::Extrude;
+use mg::primitives::{Cube, Plane, Torus};
+use mg::{Mesh, Model, Real};
+use nalgebra::Point3;
+
+mod common;
+
+use common::signed_volume;
+
+/// Whether a point lies inside a convex hull, up to `tolerance` outside its faces.
+fn hull_contains(hull: &Model, point: &Point3<Real>, tolerance: Real) -> bool {
+    hull.mesh.faces.iter().all(|face| {
+        let p = |k: usize| hull.mesh.vertices[face.indices[k]].position;
+        let normal = (p(1) - p(0)).cross(&(p(2) - p(0))).normalize();
+        normal.dot(&(point - p(0))) <= tolerance
+    })
+}
+
+/// An L-shaped block: a 4 by 1 bar along X and a 1 by 3 bar up Y from its left end.
+fn l_block() -> Model {
+    Extrude::new(vec![
+        (0.0, 0.0),
+        (4.0, 0.0),
+        (4.0, 1.0),
+        (1.0, 1.0),
+        (1.0, 4.0),
+        (0.0, 4.0),
+    ])
+    .depth(1.0)
+    .build()
+}
+
+#[test]
+fn test_convex_mesh_is_one_hull() {
+    let cube = Cube::new().size(2.0).build();
+    let hulls = ConvexDecomposition::new().decompose(&cube.mesh).unwrap();
+    assert_eq!(hulls.len(), 1);
+    assert_eq!(hulls[0].name, "collider_0");
+    assert!((signed_volume(&hulls[0]) - 8.0).abs() < 1e-4);
+}
+
+#[test]
+fn test_concave_mesh_splits() {
+    let block = l_block();
+    let hulls = ConvexDecomposition::new()
+        .resolution(16)
+        .decompose(&block.mesh)
+        .unwrap();
+    assert!(hulls.len() >= 2 && hulls.len() <= 16);
+    for (i, hull) in hulls.iter().enumerate() {
+        assert_eq!(hull.name, format!("collider_{}", i));
+        assert!(signed_volume(hull) > 0.0);
+    }
+
+    // The hulls cover the block without filling much of its notch
+    let volume: Real = hulls.iter().map(signed_volume).sum();
+    assert!(volume > 7.0 * 0.98 && volume < 7.0 * 1.1, "{}", volume);
+    for vertex in &block.mesh.vertices {
+        assert!(hulls
+            .iter()
+            .any(|hull| hull_contains(hull, &vertex.position, 1e-4)));
+    }
+    let notch = Point3::new(2.5, 2.5, 0.5);
+    assert!(!hulls.iter().any(|hull| hull_contains(hull, &notch, 0.0)));
+}
+
+#[test]
+fn test_hull_budget_and_tolerance() {
+    let torus = Torus::new()
+        .major_radius(1.0)
+        .minor_radius(0.3)
+        .segments(32)
+        .rings(12)
+        .build();
+    let volume = signed_volume(&torus);
+
+    // A single hull fills the hole
+    let hulls = ConvexDecomposition::new()
+        .max_hulls(1)
+        .resolution(16)
+        .decompose(&torus.mesh)
+        .unwrap();
+    assert_eq!(hulls.len(), 1);
+    assert!(signed_volume(&hulls[0]) > 1.5 * volume);
+
+    // More hulls follow the ring more closely
+    let hulls = ConvexDecomposition::new()
+        .max_hulls(8)
+        .resolution(24)
+        .decompose(&torus.mesh)
+        .unwrap();
+    assert_eq!(hulls.len(), 8);
+    let total: Real = hulls.iter().map(signed_volume).sum();
+    assert!(total < 1.3 * volume, "{} {}", total, volume);
+    let center = Point3::origin();
+    assert!(!hulls.iter().any(|hull| hull_contains(hull, &center, 0.0)));
+
+    // A loose tolerance stops early
+    let loose = ConvexDecomposition::new()
+        .concavity(0.9)
+        .resolution(16)
+        .decompose(&torus.mesh)
+        .unwrap();
+    assert_eq!(loose.len(), 1);
+}
+
+#[test]
+fn test_decomposition_errors() {
+    let plane = Plane::new().build();
+    assert!(ConvexDecomposition::new().decompose(&plane.mesh).is_err());
+    assert!(ConvexDecomposition::new().decompose(&Mesh::new()).is_err());
+}
+
+#[test]
+fn test_export_colliders() {
+    let hulls = ConvexDecomposition::new()
+        .resolution(16)
+        .decompose(&l_block().mesh)
+        .unwrap();
+    std::fs::create_dir_all("tests/output").unwrap();
+
+    // Each hull is its own glTF node
+    export_gltf_nodes(&hulls, "tests/output/colliders.gltf").unwrap();
+    let (document, buffers, _) = gltf::import("tests/output/colliders.gltf").unwrap();
+    let names: Vec<&str> = document.nodes().filter_map(|n| n.name()).collect();
+    let expected: Vec<String> = (0..hulls.len())
+        .map(|i| format!("collider_{}", i))
+        .collect();
+    assert_eq!(names, expected);
+    for (mesh, hull) in document.meshes().zip(&hulls) {
+        let primitive = mesh.primitives().next().unwrap();
+        let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+        assert_eq!(
+            reader.read_positions().unwrap().count(),
+            hull.mesh.vertices.len()
+        );
+        let indices = reader.read_indices().unwrap().into_u32().count();
+        assert_eq!(indices, hull.mesh.faces.len() * 3);
+    }
+
+    // And its own OBJ group
+    export_obj_groups(&hulls, "tests/output/colliders.obj").unwrap();
+    let (models, _) =
+        tobj::load_obj("tests/output/colliders.obj", &tobj::LoadOptions::default()).unwrap();
+    let names: Vec<&str> = models.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, expected);
+    for (model, hull) in models.iter().zip(&hulls) {
+        assert_eq!(model.mesh.positions.len(), hull.mesh.vertices.len() * 3);
+    }
+}
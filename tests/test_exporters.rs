@@ -1,5 +1,5 @@
-use mg::primitives::Cube;
-use mg::{Face, Model, Vertex};
+use mg::primitives::{Cube, Sphere};
+use mg::{Face, Model, Real, Vertex, VertexAttribute};
 use mg::{Rotate, Scale, Translate};
 use nalgebra::{Point3, Vector3};
 use std::fs;
@@ -170,6 +170,363 @@ fn test_gltf_export() {
     //fs::remove_file("tests/output/test_export.bin").unwrap();
 }
 
+#[test]
+fn test_gltf_export_tangents() {
+    let mut sphere = Sphere::new().segments(12).rings(6).build();
+    sphere.mesh.compute_tangents().unwrap();
+
+    if !Path::new("tests/output").exists() {
+        fs::create_dir_all("tests/output").unwrap();
+    }
+
+    let export_path = "tests/output/test_tangents.gltf";
+    sphere.export_gltf(export_path).unwrap();
+
+    let contents = fs::read_to_string(export_path).unwrap();
+    assert!(contents.contains("\"TEXCOORD_0\": 3"));
+    assert!(contents.contains("\"TANGENT\": 4"));
+
+    // The exported file must load and validate with a conforming glTF reader
+    let (document, buffers, _) = gltf::import(export_path).unwrap();
+    let primitive = document
+        .meshes()
+        .next()
+        .unwrap()
+        .primitives()
+        .next()
+        .unwrap();
+    let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+    let tangents: Vec<[f32; 4]> = reader.read_tangents().unwrap().collect();
+
+    assert_eq!(tangents.len(), sphere.mesh.vertices.len());
+    for (read, tangent) in tangents.iter().zip(&sphere.mesh.tangents) {
+        let tangent = tangent.cast::<f32>();
+        assert_eq!(read[0], tangent.x);
+        assert_eq!(read[3], tangent.w);
+    }
+}
+
+// Helper function to create a test model carrying colors and custom attributes
+fn create_attribute_model() -> Model {
+    let mut model = create_test_model();
+    let count = model.mesh.vertices.len();
+
+    model.mesh.colors = (0..count)
+        .map(|i| [i as f32 / count as f32, 0.5, 1.0, 1.0])
+        .collect();
+    model
+        .mesh
+        .add_tex_coord_set(vec![(0.25, 0.75); count])
+        .unwrap();
+    model
+        .mesh
+        .set_attribute(
+            "temperature",
+            VertexAttribute::Scalar((0..count).map(|i| i as f32 * 10.0).collect()),
+        )
+        .unwrap();
+    model
+        .mesh
+        .set_attribute("flow", VertexAttribute::Vec3(vec![[0.0, 1.0, 0.0]; count]))
+        .unwrap();
+
+    model
+}
+
+#[test]
+fn test_gltf_export_attributes() {
+    let model = create_attribute_model();
+
+    if !Path::new("tests/output").exists() {
+        fs::create_dir_all("tests/output").unwrap();
+    }
+
+    let export_path = "tests/output/test_attributes.gltf";
+    model.export_gltf(export_path).unwrap();
+
+    let contents = fs::read_to_string(export_path).unwrap();
+    assert!(contents.contains("\"TEXCOORD_1\""));
+    assert!(contents.contains("\"COLOR_0\""));
+    assert!(contents.contains("\"_TEMPERATURE\""));
+    assert!(contents.contains("\"_FLOW\""));
+
+    // The exported file must load and validate with a conforming glTF reader
+    let (document, buffers, _) = gltf::import(export_path).unwrap();
+    let primitive = document
+        .meshes()
+        .next()
+        .unwrap()
+        .primitives()
+        .next()
+        .unwrap();
+    let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+
+    let colors: Vec<[f32; 4]> = reader.read_colors(0).unwrap().into_rgba_f32().collect();
+    assert_eq!(colors, model.mesh.colors);
+
+    let uvs: Vec<[f32; 2]> = reader.read_tex_coords(1).unwrap().into_f32().collect();
+    assert!(uvs.iter().all(|uv| *uv == [0.25, 0.75]));
+
+    let positions: Vec<[f32; 3]> = reader.read_positions().unwrap().collect();
+    assert_eq!(positions.len(), model.mesh.vertices.len());
+}
+
+#[test]
+fn test_gltf_export_wide_indices() {
+    if !Path::new("tests/output").exists() {
+        fs::create_dir_all("tests/output").unwrap();
+    }
+
+    // More vertices than 16-bit indices can address
+    let sphere = Sphere::new().segments(400).rings(200).build();
+    assert!(sphere.mesh.vertices.len() > 65535);
+    let export_path = "tests/output/test_wide_indices.gltf";
+    sphere.export_gltf(export_path).unwrap();
+
+    let (document, buffers, _) = gltf::import(export_path).unwrap();
+    let primitive = document
+        .meshes()
+        .next()
+        .unwrap()
+        .primitives()
+        .next()
+        .unwrap();
+    let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+    let indices: Vec<u32> = reader.read_indices().unwrap().into_u32().collect();
+    let expected: Vec<u32> = sphere
+        .mesh
+        .faces
+        .iter()
+        .flat_map(|face| {
+            (1..face.indices.len() - 1)
+                .flat_map(move |i| [face.indices[0], face.indices[i], face.indices[i + 1]])
+        })
+        .map(|index| index as u32)
+        .collect();
+    assert_eq!(indices, expected);
+    assert_eq!(
+        indices.iter().max(),
+        Some(&(sphere.mesh.vertices.len() as u32 - 1))
+    );
+}
+
+#[test]
+fn test_gltf_export_tex_coord_numbering() {
+    if !Path::new("tests/output").exists() {
+        fs::create_dir_all("tests/output").unwrap();
+    }
+
+    // An extra set without base coordinates still starts the numbering at 0
+    let mut model = create_test_model();
+    for vertex in &mut model.mesh.vertices {
+        vertex.tex_coords = None;
+    }
+    let count = model.mesh.vertices.len();
+    model
+        .mesh
+        .add_tex_coord_set(vec![(0.25, 0.75); count])
+        .unwrap();
+    let export_path = "tests/output/test_tex_coord_numbering.gltf";
+    model.export_gltf(export_path).unwrap();
+
+    let (document, buffers, _) = gltf::import(export_path).unwrap();
+    let primitive = document
+        .meshes()
+        .next()
+        .unwrap()
+        .primitives()
+        .next()
+        .unwrap();
+    let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+    let base: Vec<[f32; 2]> = reader.read_tex_coords(0).unwrap().into_f32().collect();
+    assert!(base.iter().all(|uv| *uv == [0.0, 0.0]));
+    let set: Vec<[f32; 2]> = reader.read_tex_coords(1).unwrap().into_f32().collect();
+    assert!(set.iter().all(|uv| *uv == [0.25, 0.75]));
+
+    // Channels without one value per vertex are errors rather than skipped
+    let mut model = create_attribute_model();
+    model.mesh.tex_coord_sets[0].pop();
+    assert!(model.export_gltf(export_path).is_err());
+    let mut model = create_attribute_model();
+    model
+        .mesh
+        .attributes
+        .insert("short".to_string(), VertexAttribute::Scalar(vec![1.0]));
+    assert!(model.export_gltf(export_path).is_err());
+}
+
+#[test]
+fn test_ply_export() {
+    let model = create_attribute_model();
+    let export_path = "tests/output/test_export.ply";
+
+    if !Path::new("tests/output").exists() {
+        fs::create_dir_all("tests/output").unwrap();
+    }
+
+    model.export_ply(export_path).unwrap();
+    let contents = fs::read_to_string(export_path).unwrap();
+
+    // Verify header
+    assert!(contents.starts_with("ply\nformat ascii 1.0\n"));
+    assert!(contents.contains("element vertex 5"));
+    assert!(contents.contains("property float s\n"));
+    assert!(contents.contains("property float s1\n"));
+    assert!(contents.contains("property uchar red"));
+    assert!(contents.contains("property float flow_x"));
+    assert!(contents.contains("property float temperature"));
+    assert!(contents.contains("element face 6"));
+    assert!(contents.contains("end_header"));
+
+    // Position, normal, uv, uv set 1, color, flow, temperature
+    assert!(contents.contains("\n0 0 0 0 -1 0 0 0 0.25 0.75 0 128 255 255 0 1 0 0\n"));
+    assert!(contents.contains("\n3 0 4 1\n"));
+}
+
+#[test]
+fn test_export_attribute_name_collisions() {
+    if !Path::new("tests/output").exists() {
+        fs::create_dir_all("tests/output").unwrap();
+    }
+
+    // Names differing only in case share a glTF semantic
+    let mut model = create_attribute_model();
+    let count = model.mesh.vertices.len();
+    model
+        .mesh
+        .set_attribute("Temperature", VertexAttribute::Scalar(vec![0.0; count]))
+        .unwrap();
+    assert!(model
+        .export_gltf("tests/output/test_attribute_collision.gltf")
+        .is_err());
+
+    // A PLY attribute may not take a built-in property name
+    let mut model = create_attribute_model();
+    model
+        .mesh
+        .set_attribute("red", VertexAttribute::Scalar(vec![0.0; count]))
+        .unwrap();
+    let ply_path = "tests/output/test_attribute_collision.ply";
+    assert!(model.export_ply(ply_path).is_err());
+    let mut model = create_attribute_model();
+    model
+        .mesh
+        .set_attribute("flow_x", VertexAttribute::Scalar(vec![0.0; count]))
+        .unwrap();
+    assert!(model.export_ply(ply_path).is_err());
+}
+
+#[test]
+fn test_obj_export_vertex_colors() {
+    let model = create_attribute_model();
+    let export_path = "tests/output/test_colors.obj";
+
+    if !Path::new("tests/output").exists() {
+        fs::create_dir_all("tests/output").unwrap();
+    }
+
+    model.export_obj(export_path).unwrap();
+    let contents = fs::read_to_string(export_path).unwrap();
+
+    assert!(contents.contains("v 0 0 0 0 0.5 1"));
+    assert!(contents.contains("v 1 0 0 0.2 0.5 1"));
+}
+
+#[test]
+fn test_export_far_from_origin() {
+    // A millimetre-scale triangle a thousand kilometres from the origin
+    let mut model = Model::new("FarAway");
+    let offset = Point3::new(1_000_000.0, 2_000_000.0, 0.0);
+    for delta in [(0.0, 0.0), (0.001, 0.0), (0.0, 0.001)] {
+        model.mesh.add_vertex(Vertex::new(
+            offset + Vector3::new(delta.0, delta.1, 0.0),
+            Vector3::z(),
+            None,
+        ));
+    }
+    model.mesh.add_face(Face::triangle(0, 1, 2), None);
+
+    if !Path::new("tests/output").exists() {
+        fs::create_dir_all("tests/output").unwrap();
+    }
+
+    // OBJ is text, so coordinates are written at the full precision of `Real`
+    let obj_path = "tests/output/test_far_from_origin.obj";
+    model.export_obj(obj_path).unwrap();
+    let contents = fs::read_to_string(obj_path).unwrap();
+    let positions: Vec<Vec<Real>> = contents
+        .lines()
+        .filter(|l| l.starts_with("v "))
+        .map(|l| l[2..].split(' ').map(|c| c.parse().unwrap()).collect())
+        .collect();
+    for (read, vertex) in positions.iter().zip(&model.mesh.vertices) {
+        assert_eq!(read[..], vertex.position.coords.as_slice()[..]);
+    }
+
+    // glTF requires single precision, so positions are rounded to f32
+    let gltf_path = "tests/output/test_far_from_origin.gltf";
+    model.export_gltf(gltf_path).unwrap();
+    let (document, buffers, _) = gltf::import(gltf_path).unwrap();
+    let primitive = document
+        .meshes()
+        .next()
+        .unwrap()
+        .primitives()
+        .next()
+        .unwrap();
+    let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+    let read: Vec<[f32; 3]> = reader.read_positions().unwrap().collect();
+    for (read, vertex) in read.iter().zip(&model.mesh.vertices) {
+        let position = vertex.position.cast::<f32>();
+        assert_eq!(*read, [position.x, position.y, position.z]);
+    }
+}
+
+#[test]
+fn test_large_export_is_ordered() {
+    // Enough vertices and faces to be serialized in several chunks
+    let sphere = Sphere::new().segments(128).rings(64).build();
+    let export_path = "tests/output/test_large_sphere.obj";
+
+    if !Path::new("tests/output").exists() {
+        fs::create_dir_all("tests/output").unwrap();
+    }
+
+    sphere.export_obj(export_path).unwrap();
+    let first = fs::read(export_path).unwrap();
+    sphere.export_obj(export_path).unwrap();
+    let second = fs::read(export_path).unwrap();
+    assert_eq!(first, second);
+
+    let contents = String::from_utf8(first).unwrap();
+    let vertex_lines: Vec<&str> = contents.lines().filter(|l| l.starts_with("v ")).collect();
+    assert_eq!(vertex_lines.len(), sphere.mesh.vertices.len());
+    for (line, vertex) in vertex_lines.iter().zip(&sphere.mesh.vertices) {
+        let expected = format!(
+            "v {} {} {}",
+            vertex.position.x, vertex.position.y, vertex.position.z
+        );
+        assert_eq!(*line, expected);
+    }
+
+    let face_lines = contents.lines().filter(|l| l.starts_with("f ")).count();
+    assert_eq!(face_lines, sphere.mesh.faces.len());
+    let last_face = sphere.mesh.faces.last().unwrap();
+    let expected_last = format!(
+        "f {}/{}/{} {}/{}/{} {}/{}/{}",
+        last_face.indices[0] + 1,
+        last_face.indices[0] + 1,
+        last_face.indices[0] + 1,
+        last_face.indices[1] + 1,
+        last_face.indices[1] + 1,
+        last_face.indices[1] + 1,
+        last_face.indices[2] + 1,
+        last_face.indices[2] + 1,
+        last_face.indices[2] + 1
+    );
+    assert_eq!(contents.lines().last().unwrap(), expected_last);
+}
+
 #[test]
 fn test_export_transformed_model() {
     // Create a cube
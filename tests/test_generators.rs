@@ -0,0 +1,785 @@
+use mg::consts::PI;
+use mg::generators::{
+    BezierPatches, Extrude, Loft, NurbsSurface, ParametricSurface, Revolve, Sweep,
+};
+use mg::Real;
+use nalgebra::{Point3, Vector3};
+
+mod common;
+
+use common::{assert_outward_normals, assert_watertight, signed_volume};
+
+#[test]
+fn test_revolve_vase() {
+    let profile = vec![(0.5, 0.0), (0.8, 0.5), (0.4, 1.2), (0.6, 1.6)];
+    let vase = Revolve::new(profile).segments(24).spline(8).build();
+
+    assert_outward_normals(&vase);
+    assert_watertight(&vase);
+    assert!(signed_volume(&vase) > 0.0);
+
+    // The spline passes through its control points
+    assert!(vase.mesh.vertices.iter().any(|v| {
+        let p = v.position;
+        ((p.x * p.x + p.z * p.z).sqrt() - 0.8).abs() < 1e-4 && (p.y - 0.5).abs() < 1e-4
+    }));
+
+    // Texture coordinates cover the unit square
+    for vertex in &vase.mesh.vertices {
+        let (u, v) = vertex.tex_coords.unwrap();
+        assert!((0.0..=1.0).contains(&u) && (0.0..=1.0).contains(&v));
+    }
+}
+
+#[test]
+fn test_revolve_partial_sweep() {
+    // A square cross-section swept through a quarter turn
+    let profile = vec![(1.0, 0.0), (2.0, 0.0), (2.0, 1.0), (1.0, 1.0)];
+    let segment = Revolve::new(profile)
+        .closed(true)
+        .angle(90.0)
+        .segments(64)
+        .build();
+
+    assert_outward_normals(&segment);
+    assert_watertight(&segment);
+
+    let expected = PI / 4.0 * (4.0 - 1.0);
+    assert!((signed_volume(&segment) - expected).abs() / expected < 0.01);
+
+    // An open profile touching the axis closes its cut faces along the axis
+    let wedge = Revolve::new(vec![(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)])
+        .angle(180.0)
+        .segments(32)
+        .build();
+    assert_outward_normals(&wedge);
+    assert_watertight(&wedge);
+    assert!((signed_volume(&wedge) - PI / 2.0).abs() < 0.01);
+}
+
+#[test]
+fn test_revolve_closed_profile() {
+    let (major, minor) = (2.0, 0.5);
+    let profile: Vec<(Real, Real)> = (0..48)
+        .map(|i| {
+            let angle = 2.0 * PI * i as Real / 48.0;
+            (major + minor * angle.cos(), minor * angle.sin())
+        })
+        .collect();
+    let ring = Revolve::new(profile)
+        .closed(true)
+        .segments(64)
+        .crease_angle(10.0)
+        .build();
+
+    assert_outward_normals(&ring);
+    assert_watertight(&ring);
+
+    let expected = 2.0 * PI * PI * major * minor * minor;
+    assert!((signed_volume(&ring) - expected).abs() / expected < 0.01);
+}
+
+#[test]
+fn test_revolve_custom_axis() {
+    let cylinder = Revolve::new(vec![(1.0, -1.0), (1.0, 1.0)])
+        .axis(1.0, 0.0, 0.0)
+        .center(0.0, 2.0, 0.0)
+        .segments(64)
+        .build();
+
+    assert_outward_normals(&cylinder);
+    assert_watertight(&cylinder);
+    for vertex in &cylinder.mesh.vertices {
+        let p = vertex.position;
+        let radius = ((p.y - 2.0).powi(2) + p.z * p.z).sqrt();
+        assert!(radius < 1.0 + 1e-4 && p.x.abs() < 1.0 + 1e-4);
+    }
+    assert!((signed_volume(&cylinder) - 2.0 * PI).abs() < 0.02);
+}
+
+#[test]
+fn test_revolve_crease_angle() {
+    let profile = vec![(1.0, 0.0), (1.0, 1.0), (0.5, 1.5)];
+
+    // The 45 degree corner is split by default and smoothed with a wider crease angle
+    let creased = Revolve::new(profile.clone())
+        .segments(8)
+        .caps(false)
+        .build();
+    assert_eq!(creased.mesh.vertices.len(), 4 * 9);
+    assert_eq!(creased.mesh.faces.len(), 2 * 8 * 2);
+
+    let smooth = Revolve::new(profile)
+        .segments(8)
+        .caps(false)
+        .crease_angle(60.0)
+        .build();
+    assert_eq!(smooth.mesh.vertices.len(), 3 * 9);
+
+    // The first row of the creased model faces straight out
+    for vertex in &creased.mesh.vertices[..9] {
+        assert!(vertex.normal.y.abs() < 1e-5);
+    }
+}
+
+#[test]
+#[should_panic(expected = "must not cross the axis")]
+fn test_revolve_rejects_negative_radius() {
+    Revolve::new(vec![(1.0, 0.0), (-1.0, 1.0)]).build();
+}
+
+/// A square outline of side `size` centered on `(x, y)`.
+fn square(x: Real, y: Real, size: Real) -> Vec<(Real, Real)> {
+    let h = size / 2.0;
+    vec![
+        (x - h, y - h),
+        (x + h, y - h),
+        (x + h, y + h),
+        (x - h, y + h),
+    ]
+}
+
+#[test]
+fn test_extrude_plate_with_holes() {
+    // A mounting plate with two square holes and a round one, the holes wound clockwise
+    let round: Vec<(Real, Real)> = (0..32)
+        .map(|i| {
+            let angle = -2.0 * PI * i as Real / 32.0;
+            (0.5 * angle.cos(), 0.5 * angle.sin())
+        })
+        .collect();
+    let plate = Extrude::new(vec![(-3.0, -1.0), (3.0, -1.0), (3.0, 1.0), (-3.0, 1.0)])
+        .hole(square(-2.0, 0.0, 1.0))
+        .hole(square(2.0, 0.0, 1.0))
+        .hole(round.clone())
+        .depth(0.5)
+        .build();
+
+    assert_outward_normals(&plate);
+    assert_watertight(&plate);
+
+    let round_area: Real = (0..32)
+        .map(|i| {
+            let (x0, y0) = round[i];
+            let (x1, y1) = round[(i + 1) % 32];
+            (x1 * y0 - x0 * y1) / 2.0
+        })
+        .sum();
+    let expected = (12.0 - 2.0 - round_area) * 0.5;
+    assert!((signed_volume(&plate) - expected).abs() < 1e-4);
+
+    // Each cap of n points with h holes triangulates into n + 2h - 2 triangles
+    let cap_triangles = 4 + 4 + 4 + 32 + 2 * 3 - 2;
+    let cap_faces = plate
+        .mesh
+        .faces
+        .iter()
+        .filter(|face| plate.mesh.vertices[face.indices[0]].normal.z.abs() > 0.5)
+        .count();
+    assert_eq!(cap_faces, 2 * cap_triangles);
+}
+
+#[test]
+fn test_extrude_concave_outline() {
+    // An L-shaped floor plan wound clockwise, with a hole in its long leg
+    let plan = Extrude::new(vec![
+        (0.0, 0.0),
+        (0.0, 3.0),
+        (1.0, 3.0),
+        (1.0, 1.0),
+        (2.0, 1.0),
+        (2.0, 0.0),
+    ])
+    .hole(square(0.5, 2.0, 0.5))
+    .depth(2.0)
+    .build();
+
+    assert_outward_normals(&plan);
+    assert_watertight(&plan);
+    assert!((signed_volume(&plan) - (4.0 - 0.25) * 2.0).abs() < 1e-4);
+
+    // Right-angle corners are creased, so every wall normal is horizontal and axis-aligned
+    for vertex in &plan.mesh.vertices {
+        let n = vertex.normal;
+        assert!((n.x.abs() + n.y.abs() + n.z.abs() - 1.0).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn test_extrude_twist_and_scale() {
+    let twisted = Extrude::new(square(0.0, 0.0, 2.0))
+        .depth(3.0)
+        .twist(90.0)
+        .slices(64)
+        .build();
+    assert_outward_normals(&twisted);
+    assert_watertight(&twisted);
+
+    // Twisting keeps every cross-section the same size
+    assert!((signed_volume(&twisted) - 12.0).abs() / 12.0 < 0.01);
+    let top: Vec<_> = twisted
+        .mesh
+        .vertices
+        .iter()
+        .filter(|v| (v.position.z - 3.0).abs() < 1e-5)
+        .collect();
+    assert!(top.iter().all(
+        |v| (v.position.x.abs() - 1.0).abs() < 1e-4 && (v.position.y.abs() - 1.0).abs() < 1e-4
+    ));
+
+    // Scaling the top gives a frustum
+    let tapered = Extrude::new(square(0.0, 0.0, 2.0))
+        .depth(3.0)
+        .scale(0.5)
+        .build();
+    assert_outward_normals(&tapered);
+    assert_watertight(&tapered);
+    let expected = 3.0 * 4.0 * (1.0 + 0.5 + 0.25) / 3.0;
+    assert!((signed_volume(&tapered) - expected).abs() < 1e-4);
+
+    // Scaling to zero closes in a point
+    let pyramid = Extrude::new(square(0.0, 0.0, 2.0)).scale(0.0).build();
+    assert_outward_normals(&pyramid);
+    assert_watertight(&pyramid);
+    assert!((signed_volume(&pyramid) - 4.0 / 3.0).abs() < 1e-4);
+}
+
+/// A regular polygon approximating a circle of `radius`, counter-clockwise.
+fn circle(radius: Real, segments: usize) -> Vec<(Real, Real)> {
+    (0..segments)
+        .map(|i| {
+            let angle = 2.0 * PI * i as Real / segments as Real;
+            (radius * angle.cos(), radius * angle.sin())
+        })
+        .collect()
+}
+
+#[test]
+fn test_sweep_straight_path() {
+    // Sweeping along a straight line matches an extrusion
+    let bar = Sweep::new(
+        square(0.0, 0.0, 1.0),
+        vec![(0.0, 0.0, 0.0), (0.0, 0.0, 2.0)],
+    )
+    .build();
+    assert_outward_normals(&bar);
+    assert_watertight(&bar);
+    assert!((signed_volume(&bar) - 2.0).abs() < 1e-4);
+
+    // The cross-section's axes follow X and Y for a path along Z
+    for vertex in &bar.mesh.vertices {
+        let p = vertex.position;
+        assert!((p.x.abs() - 0.5).abs() < 1e-5 && (p.y.abs() - 0.5).abs() < 1e-5);
+    }
+
+    let tapered = Sweep::new(
+        square(0.0, 0.0, 2.0),
+        vec![(0.0, 0.0, 0.0), (0.0, 0.0, 1.0), (0.0, 0.0, 3.0)],
+    )
+    .scale(0.5)
+    .twist(45.0)
+    .build();
+    assert_outward_normals(&tapered);
+    assert_watertight(&tapered);
+}
+
+#[test]
+fn test_sweep_mitered_pipe() {
+    // A square duct with a square bore around a sharp right-angled corner
+    let path = vec![(0.0, 0.0, 0.0), (0.0, 0.0, 3.0), (2.0, 0.0, 3.0)];
+    let duct = Sweep::new(square(0.0, 0.0, 1.0), path)
+        .hole(square(0.0, 0.0, 0.5))
+        .build();
+
+    assert_outward_normals(&duct);
+    assert_watertight(&duct);
+
+    // Mitering keeps the cross-section's full size through the corner
+    let expected = (1.0 - 0.25) * 5.0;
+    assert!((signed_volume(&duct) - expected).abs() < 1e-4);
+}
+
+#[test]
+fn test_sweep_closed_path() {
+    // A circle swept around a circle is a torus
+    let path: Vec<(Real, Real, Real)> = circle(2.0, 96)
+        .into_iter()
+        .map(|(x, z)| (x, 0.0, z))
+        .collect();
+    let circle_path = path.clone();
+    let torus = Sweep::new(circle(0.5, 32), path)
+        .closed(true)
+        .crease_angle(20.0)
+        .build();
+
+    assert_outward_normals(&torus);
+    assert_watertight(&torus);
+    let expected = 2.0 * PI * PI * 2.0 * 0.25;
+    assert!((signed_volume(&torus) - expected).abs() / expected < 0.01);
+
+    // A non-planar loop turns the frames, which must still meet at the seam
+    let loop_path: Vec<(Real, Real, Real)> = (0..12)
+        .map(|i| {
+            let t = 2.0 * PI * i as Real / 12.0;
+            (3.0 * t.cos(), (2.0 * t).sin(), 3.0 * t.sin())
+        })
+        .collect();
+    let ring = Sweep::new(square(0.0, 0.0, 0.4), loop_path)
+        .closed(true)
+        .spline(8)
+        .build();
+    assert_outward_normals(&ring);
+    assert_watertight(&ring);
+
+    // Whole turns of twist still meet at the seam
+    let twisted = Sweep::new(square(0.0, 0.0, 0.4), circle_path.clone())
+        .closed(true)
+        .twist(-720.0)
+        .build();
+    assert_outward_normals(&twisted);
+    assert_watertight(&twisted);
+}
+
+#[test]
+#[should_panic(expected = "must twist by whole turns")]
+fn test_sweep_closed_partial_twist() {
+    let path: Vec<(Real, Real, Real)> = circle(2.0, 24)
+        .into_iter()
+        .map(|(x, z)| (x, 0.0, z))
+        .collect();
+    Sweep::new(square(0.0, 0.0, 0.4), path)
+        .closed(true)
+        .twist(45.0)
+        .build();
+}
+
+#[test]
+#[should_panic(expected = "must not scale")]
+fn test_sweep_closed_scale() {
+    let path: Vec<(Real, Real, Real)> = circle(2.0, 24)
+        .into_iter()
+        .map(|(x, z)| (x, 0.0, z))
+        .collect();
+    Sweep::new(square(0.0, 0.0, 0.4), path)
+        .closed(true)
+        .scale(2.0)
+        .build();
+}
+
+#[test]
+#[should_panic(expected = "must not turn back on itself")]
+fn test_sweep_path_reversal() {
+    let path = vec![(0.0, 0.0, 0.0), (0.0, 0.0, 2.0), (0.0, 0.0, 1.0)];
+    Sweep::new(square(0.0, 0.0, 0.4), path).build();
+}
+
+#[test]
+#[should_panic(expected = "closed path must have at least three distinct points")]
+fn test_sweep_closed_two_points() {
+    let path = vec![(0.0, 0.0, 0.0), (0.0, 0.0, 2.0)];
+    Sweep::new(square(0.0, 0.0, 0.4), path).closed(true).build();
+}
+
+#[test]
+fn test_sweep_helix() {
+    let helix: Vec<(Real, Real, Real)> = (0..=64)
+        .map(|i| {
+            let t = 4.0 * PI * i as Real / 64.0;
+            (2.0 * t.cos(), 0.2 * t, 2.0 * t.sin())
+        })
+        .collect();
+    let length: Real = helix
+        .windows(2)
+        .map(|w| {
+            let (a, b) = (w[0], w[1]);
+            ((b.0 - a.0).powi(2) + (b.1 - a.1).powi(2) + (b.2 - a.2).powi(2)).sqrt()
+        })
+        .sum();
+    let spring = Sweep::new(circle(0.2, 16), helix).build();
+
+    assert_outward_normals(&spring);
+    assert_watertight(&spring);
+
+    // The cross-section neither shrinks nor spins, so the volume follows the path length
+    let area = 8.0 * 0.04 * (PI / 8.0).sin();
+    let expected = area * length;
+    assert!((signed_volume(&spring) - expected).abs() / expected < 0.01);
+}
+
+#[test]
+fn test_loft_resamples_sections() {
+    // A square base blending into a circle; the square is resampled to 32 points
+    let transition = Loft::new()
+        .station(0.0, square(0.0, 0.0, 2.0))
+        .station(2.0, circle(1.0, 32))
+        .build();
+
+    assert_outward_normals(&transition);
+    assert_watertight(&transition);
+
+    // The square's corners crease, adding a column at each corner but the one on the seam
+    let rows = 2 * (33 + 3);
+    assert_eq!(transition.mesh.vertices.len(), rows + 2 * 32);
+    assert!(signed_volume(&transition) > 0.0);
+}
+
+#[test]
+fn test_loft_winding_and_alignment() {
+    // Sections wound in opposite directions and starting at different points still
+    // make a straight prism
+    let mut reversed = square(0.0, 0.0, 1.0);
+    reversed.reverse();
+    reversed.rotate_left(1);
+    let prism = Loft::new()
+        .section(
+            square(0.0, 0.0, 1.0)
+                .into_iter()
+                .map(|(x, y)| (x, 0.0, y))
+                .collect(),
+        )
+        .section(reversed.into_iter().map(|(x, y)| (x, 3.0, y)).collect())
+        .build();
+
+    assert_outward_normals(&prism);
+    assert_watertight(&prism);
+    assert!((signed_volume(&prism) - 3.0).abs() < 1e-4);
+}
+
+#[test]
+fn test_loft_spline_stations() {
+    // Hull-like stations of varying size with a pointed nose
+    let radii = [0.5, 1.0, 1.2, 0.8];
+    let mut hull = Loft::new();
+    for (i, &radius) in radii.iter().enumerate() {
+        hull = hull.station(i as Real, circle(radius, 24));
+    }
+    let hull = hull.station(4.0, vec![(0.0, 0.0)]).spline(6).build();
+
+    assert_outward_normals(&hull);
+    assert_watertight(&hull);
+
+    // The spline passes through every station
+    for (i, &radius) in radii.iter().enumerate() {
+        let on_station = hull.mesh.vertices.iter().any(|v| {
+            let p = v.position;
+            (p.z - i as Real).abs() < 1e-4 && ((p.x * p.x + p.y * p.y).sqrt() - radius).abs() < 1e-4
+        });
+        assert!(on_station);
+    }
+
+    // A ruled cone to a tip has the volume of a pyramid over the base polygon
+    let cone = Loft::new()
+        .station(0.0, circle(1.0, 64))
+        .station(3.0, vec![(0.0, 0.0)])
+        .build();
+    assert_outward_normals(&cone);
+    assert_watertight(&cone);
+    let base_area = 32.0 * (2.0 * PI / 64.0).sin();
+    assert!((signed_volume(&cone) - base_area).abs() < 1e-3);
+}
+
+#[test]
+fn test_parametric_sphere() {
+    let sphere = ParametricSurface::new(|u: Real, v: Real| {
+        Point3::new(v.sin() * u.cos(), v.cos(), -v.sin() * u.sin())
+    })
+    .u_range(0.0, 2.0 * PI)
+    .v_range(PI, 0.0)
+    .resolution(48, 24)
+    .wrap_u(true)
+    .build();
+
+    assert_eq!(sphere.mesh.vertices.len(), 49 * 25);
+    assert_outward_normals(&sphere);
+    assert_watertight(&sphere);
+
+    // Normals at the poles fall back to points just off the pole
+    for vertex in &sphere.mesh.vertices {
+        assert!(vertex.normal.dot(&vertex.position.coords) > 0.99);
+    }
+    let expected = 4.0 / 3.0 * PI;
+    assert!((signed_volume(&sphere) - expected).abs() / expected < 0.02);
+}
+
+#[test]
+fn test_parametric_wrapped_torus() {
+    let torus = ParametricSurface::new(|u: Real, v: Real| {
+        let ring = 2.0 + 0.5 * v.cos();
+        Point3::new(ring * u.cos(), 0.5 * v.sin(), -ring * u.sin())
+    })
+    .u_range(0.0, 2.0 * PI)
+    .v_range(0.0, 2.0 * PI)
+    .resolution(64, 32)
+    .wrap_u(true)
+    .wrap_v(true)
+    .build();
+
+    assert_outward_normals(&torus);
+    assert_watertight(&torus);
+    let expected = 2.0 * PI * PI * 2.0 * 0.25;
+    assert!((signed_volume(&torus) - expected).abs() / expected < 0.01);
+
+    // Texture coordinates span the unit square with seams at the ends
+    let uvs: Vec<(f32, f32)> = torus
+        .mesh
+        .vertices
+        .iter()
+        .map(|v| v.tex_coords.unwrap())
+        .collect();
+    assert_eq!(uvs.first(), Some(&(0.0, 0.0)));
+    assert_eq!(uvs.last(), Some(&(1.0, 1.0)));
+}
+
+#[test]
+fn test_parametric_away_from_origin() {
+    // Fine grids far from the origin keep every face, as they do at it
+    let plane = |x: Real| {
+        ParametricSurface::new(move |u: Real, v: Real| Point3::new(x + u, v, 0.0))
+            .resolution(200, 200)
+            .build()
+    };
+    assert_eq!(plane(0.0).mesh.faces.len(), 80000);
+    assert_eq!(plane(1000.0).mesh.faces.len(), 80000);
+
+    // Poles and seams still weld there
+    let sphere = ParametricSurface::new(|u: Real, v: Real| {
+        Point3::new(1000.0 + v.sin() * u.cos(), v.cos(), -v.sin() * u.sin())
+    })
+    .u_range(0.0, 2.0 * PI)
+    .v_range(PI, 0.0)
+    .resolution(48, 24)
+    .wrap_u(true)
+    .build();
+    assert_watertight(&sphere);
+}
+
+#[test]
+fn test_parametric_mobius_strip() {
+    // A one-sided surface: its seam joins the start with the ends of v swapped
+    let strip = ParametricSurface::new(|u: Real, v: Real| {
+        let radius = 1.0 + v * (u / 2.0).cos();
+        Point3::new(radius * u.cos(), v * (u / 2.0).sin(), radius * u.sin())
+    })
+    .u_range(0.0, 2.0 * PI)
+    .v_range(-0.3, 0.3)
+    .resolution(64, 4)
+    .wrap_u(true)
+    .build();
+
+    assert_eq!(strip.mesh.faces.len(), 64 * 4 * 2);
+    for vertex in &strip.mesh.vertices {
+        assert!((vertex.normal.norm() - 1.0).abs() < 1e-4);
+    }
+
+    // Only the middle of the seam meets the start; the rest stays where it is evaluated
+    let start = strip.mesh.vertices[0].position;
+    let end = strip.mesh.vertices[64].position;
+    assert!((start - end).norm() > 0.5);
+    assert_eq!(
+        strip.mesh.vertices[2 * 65].position,
+        strip.mesh.vertices[2 * 65 + 64].position
+    );
+}
+
+/// A cylinder of radius 1 around Y from `y = 0` to `y = 2`, exact as a NURBS
+/// surface: rows of a rational quadratic circle joined linearly.
+fn nurbs_cylinder() -> NurbsSurface {
+    let circle = [
+        (1.0, 0.0),
+        (1.0, 1.0),
+        (0.0, 1.0),
+        (-1.0, 1.0),
+        (-1.0, 0.0),
+        (-1.0, -1.0),
+        (0.0, -1.0),
+        (1.0, -1.0),
+        (1.0, 0.0),
+    ];
+    let control_points = [0.0, 2.0]
+        .iter()
+        .map(|&y| circle.iter().map(|&(x, z)| (x, y, -z)).collect())
+        .collect();
+    let corner = (0.5 as Real).sqrt();
+    let weights = (0..9)
+        .map(|i| if i % 2 == 0 { 1.0 } else { corner })
+        .collect::<Vec<_>>();
+    NurbsSurface::new(control_points, 2, 1)
+        .weights(vec![weights.clone(), weights])
+        .knots(
+            vec![
+                0.0, 0.0, 0.0, 0.25, 0.25, 0.5, 0.5, 0.75, 0.75, 1.0, 1.0, 1.0,
+            ],
+            vec![0.0, 0.0, 1.0, 1.0],
+        )
+}
+
+#[test]
+fn test_nurbs_cylinder() {
+    let cylinder = nurbs_cylinder().tolerance(1e-3).build();
+    assert_eq!(cylinder.name, "NurbsSurface");
+    assert_outward_normals(&cylinder);
+
+    // Rational weights make every sample lie exactly on the circle
+    for vertex in &cylinder.mesh.vertices {
+        let p = vertex.position;
+        assert!((p.x.hypot(p.z) - 1.0).abs() < 1e-5);
+        assert!(p.y.abs() < 1e-6 || (p.y - 2.0).abs() < 1e-6);
+        assert!(vertex.normal.dot(&Vector3::new(p.x, 0.0, p.z)) > 0.999);
+    }
+
+    // The straight direction needs one segment; around the circle, chords stay
+    // within the tolerance
+    let row_len = cylinder.mesh.vertices.len() / 2;
+    assert_eq!(cylinder.mesh.faces.len(), 2 * (row_len - 1));
+    let ring: Vec<_> = cylinder.mesh.vertices[..row_len]
+        .iter()
+        .map(|v| v.position)
+        .collect();
+    for pair in ring.windows(2) {
+        let middle = nalgebra::center(&pair[0], &pair[1]);
+        assert!(1.0 - middle.x.hypot(middle.z) < 1.5e-3);
+    }
+    assert!((ring[0] - ring[row_len - 1]).norm() < 1e-5);
+    let uvs = &cylinder.mesh.vertices;
+    assert_eq!(uvs[0].tex_coords, Some((0.0, 0.0)));
+    assert_eq!(uvs[uvs.len() - 1].tex_coords, Some((1.0, 1.0)));
+
+    // A finer tolerance takes more segments, up to the limit per span
+    let fine = nurbs_cylinder().tolerance(1e-5).build();
+    assert_eq!(fine.mesh.vertices.len(), 2 * (4 * 64 + 1));
+    let capped = nurbs_cylinder().tolerance(1e-5).max_segments(3).build();
+    assert_eq!(capped.mesh.vertices.len(), 2 * (4 * 3 + 1));
+}
+
+#[test]
+fn test_nurbs_default_knots() {
+    // A flat grid needs no subdivision within its two spans each way, however high
+    // its degree
+    let grid = |bump: Real| {
+        (0..4)
+            .map(|i| {
+                (0..5)
+                    .map(|j| {
+                        let y = if i == 2 && j == 2 { bump } else { 0.0 };
+                        (j as Real, y, -(i as Real))
+                    })
+                    .collect()
+            })
+            .collect::<Vec<_>>()
+    };
+    let flat = NurbsSurface::new(grid(0.0), 3, 2).build();
+    assert_eq!(flat.mesh.vertices.len(), 3 * 3);
+    for vertex in &flat.mesh.vertices {
+        assert!((vertex.normal - Vector3::y()).norm() < 1e-5);
+    }
+
+    // Clamped knots pin the corners; the bump stays under its control point
+    let bumpy = NurbsSurface::new(grid(1.0), 3, 2).build();
+    assert_outward_normals(&bumpy);
+    let positions: Vec<_> = bumpy.mesh.vertices.iter().map(|v| v.position).collect();
+    for corner in [
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(4.0, 0.0, 0.0),
+        Point3::new(0.0, 0.0, -3.0),
+        Point3::new(4.0, 0.0, -3.0),
+    ] {
+        assert!(positions.iter().any(|p| (p - corner).norm() < 1e-5));
+    }
+    let top = positions.iter().map(|p| p.y).fold(0.0, Real::max);
+    assert!(top > 0.2 && top < 1.0);
+    assert!(positions.len() > 3 * 3);
+}
+
+#[test]
+fn test_bezier_teapot() {
+    let teapot = BezierPatches::from_bpt("tests/fixtures/teapot.bpt").unwrap();
+    assert_eq!(teapot.len(), 28);
+    let model = teapot.build();
+    assert_eq!(model.name, "BezierPatches");
+
+    let positions: Vec<_> = model.mesh.vertices.iter().map(|v| v.position).collect();
+    let (min, max) = positions
+        .iter()
+        .fold((positions[0], positions[0]), |(min, max), p| {
+            (min.inf(p), max.sup(p))
+        });
+    assert!((min.y + 2.0).abs() < 1e-4 && (max.y - 2.0).abs() < 1e-4);
+    assert!((min.z - 0.15).abs() < 1e-4 && (max.z - 3.15).abs() < 1e-4);
+    assert!(min.x < -2.5 && max.x > 3.2);
+
+    // The body faces outward, away from the handle and spout
+    for vertex in &model.mesh.vertices {
+        let p = vertex.position;
+        if p.y.abs() > 0.7 && p.z > 0.2 && p.z < 2.3 {
+            assert!(vertex.normal.dot(&Vector3::new(p.x, p.y, 0.0)) > 0.0);
+        }
+    }
+
+    // Where the rim meets the body, both sides are split the same way
+    let seam: Vec<_> = positions
+        .iter()
+        .filter(|p| (p.z - 2.4).abs() < 1e-5 && (p.x.hypot(p.y) - 1.5).abs() < 1e-3)
+        .collect();
+    assert!(seam.len() > 16);
+    for p in &seam {
+        let matches = seam.iter().filter(|q| (**q - **p).norm() < 1e-5).count();
+        assert!(matches >= 2);
+    }
+
+    model.export_obj("tests/output/teapot.obj").unwrap();
+}
+
+#[test]
+fn test_bezier_shared_edges() {
+    // A flat patch next to one that bulges along the edge they share
+    let flat = (0..4)
+        .map(|i| {
+            (0..4)
+                .map(|j| (i as Real / 3.0, 0.0, -(j as Real) / 3.0))
+                .collect()
+        })
+        .collect();
+    let bulge = (0..4)
+        .map(|i| {
+            (0..4)
+                .map(|j| {
+                    let y = if i > 0 && (j == 1 || j == 2) {
+                        1.0
+                    } else {
+                        0.0
+                    };
+                    (1.0 + i as Real / 3.0, y, -(j as Real) / 3.0)
+                })
+                .collect()
+        })
+        .collect();
+    let model = BezierPatches::new().patch(flat).patch(bulge).build();
+    assert_outward_normals(&model);
+
+    // Every vertex on the shared edge has exactly one twin from the other patch
+    let edge: Vec<_> = model
+        .mesh
+        .vertices
+        .iter()
+        .map(|v| v.position)
+        .filter(|p| (p.x - 1.0).abs() < 1e-6)
+        .collect();
+    assert!(edge.len() > 4);
+    for p in &edge {
+        assert_eq!(edge.iter().filter(|q| (*q - p).norm() < 1e-6).count(), 2);
+    }
+}
+
+#[test]
+fn test_bezier_bpt_errors() {
+    let bilinear = "1\n1 1\n0 0 0 0 0 -1\n1 0 0 1 0 -1\n";
+    let patches = BezierPatches::from_bpt_data(bilinear).unwrap();
+    assert_eq!(patches.len(), 1);
+    assert_eq!(patches.build().mesh.faces.len(), 2);
+
+    assert!(BezierPatches::from_bpt_data("2\n1 1\n0 0 0 0 0 -1\n1 0 0 1 0 -1\n").is_err());
+    assert!(BezierPatches::from_bpt_data("1\n0 3\n").is_err());
+    assert!(BezierPatches::from_bpt_data("1 18446744073709551615 3").is_err());
+    assert!(BezierPatches::from_bpt_data("1 3 100000").is_err());
+    assert!(BezierPatches::from_bpt_data("1\n1 1\n0 0 zero").is_err());
+}
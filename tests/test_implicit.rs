@@ -0,0 +1,160 @@
+use mg::consts::PI;
+use mg::implicit::{Capsule, Cuboid, Polygonizer, Sdf, Sphere, Torus};
+use mg::{Model, Real};
+use nalgebra::Point3;
+
+mod common;
+
+use common::{assert_watertight, signed_volume};
+
+/// Distance from the closest vertex of `model` to `target`.
+fn closest_vertex(model: &Model, target: Point3<Real>) -> Real {
+    model
+        .mesh
+        .vertices
+        .iter()
+        .map(|v| (v.position - target).norm())
+        .fold(Real::MAX, Real::min)
+}
+
+#[test]
+fn test_shape_distances() {
+    let sphere = Sphere::new(1.0).center(1.0, 0.0, 0.0);
+    assert!((sphere.distance(&Point3::new(1.0, 0.0, 0.0)) + 1.0).abs() < 1e-6);
+    assert!((sphere.distance(&Point3::new(3.0, 0.0, 0.0)) - 1.0).abs() < 1e-6);
+
+    let cuboid = Cuboid::new(2.0, 4.0, 6.0);
+    assert!((cuboid.distance(&Point3::new(0.0, 3.0, 0.0)) - 1.0).abs() < 1e-6);
+    assert!((cuboid.distance(&Point3::new(2.0, 3.0, 3.0)) - Real::sqrt(2.0)).abs() < 1e-6);
+    assert!((cuboid.distance(&Point3::origin()) + 1.0).abs() < 1e-6);
+
+    let rounded = Cuboid::new(2.0, 2.0, 2.0).rounding(0.5);
+    let corner = Point3::new(1.0, 1.0, 1.0);
+    assert!(rounded.distance(&corner) > 0.0);
+    assert!(rounded.distance(&Point3::new(1.0, 0.0, 0.0)).abs() < 1e-6);
+
+    // The torus lies around the Y axis
+    let torus = Torus::new(2.0, 0.5);
+    assert!((torus.distance(&Point3::new(2.0, 0.0, 0.0)) + 0.5).abs() < 1e-6);
+    assert!((torus.distance(&Point3::new(0.0, 0.0, -2.0)) + 0.5).abs() < 1e-6);
+    assert!((torus.distance(&Point3::origin()) - 1.5).abs() < 1e-6);
+
+    // The capsule's height is the length of its cylindrical part, along Y
+    let capsule = Capsule::new(0.5, 2.0);
+    assert!((capsule.distance(&Point3::new(0.0, 2.0, 0.0)) - 0.5).abs() < 1e-6);
+    assert!((capsule.distance(&Point3::new(1.0, 0.5, 0.0)) - 0.5).abs() < 1e-6);
+}
+
+#[test]
+fn test_boolean_operations() {
+    let a = Sphere::new(1.0).center(-0.8, 0.0, 0.0);
+    let b = Sphere::new(1.0).center(0.8, 0.0, 0.0);
+    let between = Point3::new(0.0, 0.7, 0.0);
+
+    // The smooth union fills in the crease between the spheres
+    let union = a.clone().union(b.clone());
+    let smooth = a.clone().smooth_union(b.clone(), 0.5);
+    assert!(union.distance(&between) > 0.0);
+    assert!(smooth.distance(&between) < 0.0);
+    for x in [-3.0, -1.8, 0.0, 1.8, 3.0] {
+        let p = Point3::new(x, 0.3, 0.2);
+        assert!(smooth.distance(&p) <= union.distance(&p) + 1e-6);
+    }
+
+    let cut = a.clone().subtract(b.clone());
+    assert!(cut.distance(&Point3::new(-1.0, 0.0, 0.0)) < 0.0);
+    assert!(cut.distance(&Point3::new(0.5, 0.0, 0.0)) > 0.0);
+
+    let lens = a.clone().intersect(b.clone());
+    assert!(lens.distance(&Point3::origin()) < 0.0);
+    assert!(lens.distance(&Point3::new(-1.0, 0.0, 0.0)) > 0.0);
+    let rounded_lens = a.smooth_intersect(b, 0.3);
+    assert!(rounded_lens.distance(&Point3::origin()) >= lens.distance(&Point3::origin()));
+
+    // Closures are distance functions too
+    let plane = |p: &Point3<Real>| p.y;
+    let half = Sphere::new(1.0).intersect(plane);
+    assert!(half.distance(&Point3::new(0.0, -0.5, 0.0)) < 0.0);
+    assert!(half.distance(&Point3::new(0.0, 0.5, 0.0)) > 0.0);
+}
+
+#[test]
+fn test_repeat() {
+    let grid = Sphere::new(0.25).repeat(1.0, 0.0, 2.0);
+    let p = Point3::new(0.1, 0.3, 0.2);
+    for (dx, dz) in [(1.0, 0.0), (-3.0, 4.0), (5.0, -2.0)] {
+        let q = Point3::new(p.x + dx, p.y, p.z + dz);
+        assert!((grid.distance(&q) - grid.distance(&p)).abs() < 1e-5);
+    }
+    // The Y axis is not repeated
+    assert!(grid.distance(&Point3::new(0.0, 1.0, 0.0)) > 0.7);
+
+    // A limited count stops after the last copy
+    let row = Sphere::new(0.25).repeat(1.0, 0.0, 0.0).count(3, 1, 1);
+    assert!(row.distance(&Point3::new(2.0, 0.0, 0.0)) < 0.0);
+    assert!((row.distance(&Point3::new(5.0, 0.0, 0.0)) - 2.75).abs() < 1e-5);
+    assert!((row.distance(&Point3::new(-1.0, 0.0, 0.0)) - 0.75).abs() < 1e-5);
+}
+
+#[test]
+fn test_marching_cubes_sphere() {
+    let sphere = Sphere::new(1.0);
+    let model = Polygonizer::new(&sphere)
+        .bounds((-1.3, -1.3, -1.3), (1.3, 1.3, 1.3))
+        .resolution(32)
+        .build();
+
+    assert_watertight(&model);
+    let volume = signed_volume(&model);
+    assert!((volume - 4.0 / 3.0 * PI).abs() < 0.05 * 4.0 / 3.0 * PI);
+
+    for vertex in &model.mesh.vertices {
+        assert!((vertex.position.coords.norm() - 1.0).abs() < 1e-2);
+        assert!(vertex.normal.dot(&vertex.position.coords.normalize()) > 0.99);
+    }
+    for face in &model.mesh.faces {
+        let [p0, p1, p2] = [0, 1, 2].map(|i| model.mesh.vertices[face.indices[i]].position);
+        let face_normal = (p1 - p0).cross(&(p2 - p0));
+        assert!(face_normal.dot(&p0.coords) > 0.0);
+    }
+}
+
+#[test]
+fn test_polygonize_combined_shapes() {
+    // A torus with a capsule through its hole, blended together and hollowed
+    let shape = Torus::new(1.0, 0.3)
+        .smooth_union(Capsule::new(0.3, 1.5), 0.2)
+        .subtract(Sphere::new(0.2).center(0.0, 0.75, 0.0));
+    let model = Polygonizer::new(&shape)
+        .bounds((-1.5, -1.5, -1.5), (1.5, 1.5, 1.5))
+        .resolution(40)
+        .build();
+
+    assert_watertight(&model);
+    assert!(signed_volume(&model) > 0.0);
+    assert_eq!(model.name, "Implicit");
+}
+
+#[test]
+fn test_dual_contouring_keeps_corners() {
+    let cube = Cuboid::new(1.0, 1.0, 1.0);
+    let corner = Point3::new(0.5, 0.5, 0.5);
+    let polygonize = |dual: bool| {
+        Polygonizer::new(&cube)
+            .bounds((-1.0, -1.0, -1.0), (1.0, 1.0, 1.0))
+            .resolution(15)
+            .dual_contouring(dual)
+            .build()
+    };
+
+    let marched = polygonize(false);
+    let contoured = polygonize(true);
+    assert_watertight(&marched);
+    assert_watertight(&contoured);
+
+    // Marching cubes cuts the corners off, dual contouring finds them
+    assert!(closest_vertex(&marched, corner) > 0.03);
+    assert!(closest_vertex(&contoured, corner) < 0.01);
+    assert!((signed_volume(&contoured) - 1.0).abs() < 0.01);
+    assert!(signed_volume(&marched) < signed_volume(&contoured));
+}
@@ -0,0 +1,149 @@
+use mg::bounds::Aabb;
+use mg::lsystem::{BranchStyle, LSystem, Turtle};
+use mg::{Model, Real};
+
+mod common;
+
+use common::signed_volume;
+
+/// Number of faces in the given material.
+fn faces_in(model: &Model, material: &str) -> usize {
+    model
+        .mesh
+        .face_materials
+        .iter()
+        .filter(|m| m.as_deref() == Some(material))
+        .count()
+}
+
+#[test]
+fn test_expand() {
+    // Lindenmayer's algae: string lengths follow the Fibonacci numbers
+    let algae = LSystem::new("A").rule('A', "AB").rule('B', "A");
+    assert_eq!(algae.expand(0), "A");
+    assert_eq!(algae.expand(5), "ABAABABAABAAB");
+    assert_eq!(algae.expand(10).len(), 144);
+
+    // Symbols without rules are copied
+    let koch = LSystem::new("F+F").rule('F', "F-F");
+    assert_eq!(koch.expand(2), "F-F-F-F+F-F-F-F");
+}
+
+#[test]
+fn test_stochastic_rules() {
+    let system = |seed| {
+        LSystem::new("X")
+            .rule('X', "FY")
+            .weighted_rule('Y', 3.0, "[+X]X")
+            .weighted_rule('Y', 1.0, "[-X]X")
+            .seed(seed)
+    };
+    let a = system(7).expand(6);
+    assert_eq!(a, system(7).expand(6));
+    assert_ne!(a, system(8).expand(6));
+
+    // Both rules fire, the heavier one more often
+    let plus = a.matches('+').count();
+    let minus = a.matches('-').count();
+    assert!(minus > 0 && plus > minus);
+}
+
+#[test]
+fn test_turtle_cylinders() {
+    let turtle = Turtle::new()
+        .style(BranchStyle::Cylinders)
+        .step(2.0)
+        .radius(0.1)
+        .taper(0.5)
+        .segments(64);
+
+    // A single step is a frustum from the full radius to the tapered tip
+    let model = turtle.build("F").unwrap();
+    let Aabb { min, max } = model.mesh.aabb().unwrap();
+    assert!(min[1].abs() < 1e-5 && (max[1] - 2.0).abs() < 1e-5);
+    assert!((max[0] - 0.1).abs() < 1e-5);
+    let expected = mg::consts::PI * 2.0 / 3.0 * (0.01 + 0.005 + 0.0025);
+    assert!((signed_volume(&model) - expected).abs() < 0.02 * expected);
+    assert_eq!(faces_in(&model, "bark"), model.mesh.faces.len());
+    assert!(model.mesh.materials.contains_key("bark"));
+    assert!(!model.mesh.materials.contains_key("leaf"));
+
+    // Turning left heads towards -X; `!` narrows the joint
+    let model = turtle.build("F!+F").unwrap();
+    let Aabb { min, max } = model.mesh.aabb().unwrap();
+    assert!((max[1] - (2.0 + 2.0 * (25.0 as Real).to_radians().cos())).abs() < 0.1);
+    assert!(min[0] < -0.8 && max[0] < 0.11);
+}
+
+#[test]
+fn test_tree() {
+    let tree = LSystem::new("X")
+        .rule('X', "F[+X!L][-X!L][&X!L]FX")
+        .rule('F', "FF")
+        .expand(3);
+    let turtle = Turtle::new().angle(30.0).step(0.25).radius(0.08);
+    let model = turtle.build(&tree).unwrap();
+    assert_eq!(model.name, "LSystem");
+
+    // Every `L` is a two-sided card in the leaf material; the rest is bark
+    let leaves = tree.matches('L').count();
+    assert_eq!(faces_in(&model, "leaf"), 2 * leaves);
+    assert!(faces_in(&model, "bark") > 0);
+    assert_eq!(
+        faces_in(&model, "leaf") + faces_in(&model, "bark"),
+        model.mesh.faces.len()
+    );
+    let leaf = &model.mesh.materials["leaf"];
+    assert!(leaf.diffuse[1] > leaf.diffuse[0]);
+
+    // The trunk grows up from the origin and branches out around it
+    let Aabb { min, max } = model.mesh.aabb().unwrap();
+    assert!(min[1] > -0.1 && max[1] > 2.0);
+    assert!(min[0] < -0.5 && max[0] > 0.5 && min[2] < -0.2);
+
+    // The branch tubes are solids, so the cards add no volume
+    assert!(signed_volume(&model) > 0.0);
+
+    model.export_obj("tests/output/tree.obj").unwrap();
+}
+
+#[test]
+fn test_random_variation() {
+    let commands = "F[+F][-F]F[+F]F";
+    let turtle = |seed| {
+        Turtle::new()
+            .angle_variation(15.0)
+            .length_variation(0.3)
+            .seed(seed)
+    };
+    let positions = |model: &Model| {
+        model
+            .mesh
+            .vertices
+            .iter()
+            .map(|v| v.position)
+            .collect::<Vec<_>>()
+    };
+    let a = turtle(1).build(commands).unwrap();
+    assert_eq!(
+        positions(&a),
+        positions(&turtle(1).build(commands).unwrap())
+    );
+    assert_ne!(
+        positions(&a),
+        positions(&turtle(2).build(commands).unwrap())
+    );
+
+    // Without variation the seed does not matter
+    let plain = |seed| Turtle::new().seed(seed).build(commands).unwrap();
+    assert_eq!(positions(&plain(1)), positions(&plain(2)));
+}
+
+#[test]
+fn test_unbalanced_brackets() {
+    assert!(Turtle::new().build("F]F").is_err());
+
+    // Open branches are still drawn
+    let model = Turtle::new().build("F[+F").unwrap();
+    assert!(model.mesh.aabb().unwrap().min.x < -0.3);
+}
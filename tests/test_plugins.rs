@@ -1,6 +1,6 @@
 use mg::plugin::{CompositePlugin, Plugin, PluginRegistry, SmoothNormalsPlugin, TransformPlugin};
 use mg::primitives::Cube;
-use mg::{Model, Result};
+use mg::{Model, Real, Result};
 use mg::{Rotate, Scale, Translate};
 
 // A test plugin that inverts all vertex z coordinates
@@ -95,7 +95,7 @@ fn test_custom_plugin() {
     let mut model = Cube::new().build();
 
     // Save original z coordinates
-    let original_z: Vec<f32> = model.mesh.vertices.iter().map(|v| v.position.z).collect();
+    let original_z: Vec<Real> = model.mesh.vertices.iter().map(|v| v.position.z).collect();
 
     // Create and apply invert z plugin
     let invert_plugin = InvertZPlugin::new();
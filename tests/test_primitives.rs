@@ -1,4 +1,14 @@
-use mg::primitives::{Cube, Cylinder, Sphere};
+use mg::consts::PI;
+use mg::noise::Noise;
+use mg::primitives::{
+    Capsule, Cone, Cube, Cylinder, Gear, Heightfield, Icosphere, Plane, Platonic, PlatonicSolid,
+    Sphere, Spring, Thread, Torus,
+};
+use mg::{Model, Real};
+
+mod common;
+
+use common::{assert_outward_normals, assert_watertight, signed_volume};
 
 #[test]
 fn test_cube_creation() {
@@ -21,9 +31,9 @@ fn test_cube_creation() {
     assert_eq!(custom_cube.mesh.faces.len(), 12);
 
     // Verify center position
-    let sum_x: f32 = custom_cube.mesh.vertices.iter().map(|v| v.position.x).sum();
-    let sum_y: f32 = custom_cube.mesh.vertices.iter().map(|v| v.position.y).sum();
-    let sum_z: f32 = custom_cube.mesh.vertices.iter().map(|v| v.position.z).sum();
+    let sum_x: Real = custom_cube.mesh.vertices.iter().map(|v| v.position.x).sum();
+    let sum_y: Real = custom_cube.mesh.vertices.iter().map(|v| v.position.y).sum();
+    let sum_z: Real = custom_cube.mesh.vertices.iter().map(|v| v.position.z).sum();
 
     assert!((sum_x / 8.0 - 1.0).abs() < 1e-5);
     assert!((sum_y / 8.0 - 2.0).abs() < 1e-5);
@@ -59,27 +69,27 @@ fn test_sphere_creation() {
         .build();
 
     // Verify center position by checking that the average of all vertices is close to the center
-    let avg_x: f32 = custom_sphere
+    let avg_x: Real = custom_sphere
         .mesh
         .vertices
         .iter()
         .map(|v| v.position.x)
-        .sum::<f32>()
-        / custom_sphere.mesh.vertices.len() as f32;
-    let avg_y: f32 = custom_sphere
+        .sum::<Real>()
+        / custom_sphere.mesh.vertices.len() as Real;
+    let avg_y: Real = custom_sphere
         .mesh
         .vertices
         .iter()
         .map(|v| v.position.y)
-        .sum::<f32>()
-        / custom_sphere.mesh.vertices.len() as f32;
-    let avg_z: f32 = custom_sphere
+        .sum::<Real>()
+        / custom_sphere.mesh.vertices.len() as Real;
+    let avg_z: Real = custom_sphere
         .mesh
         .vertices
         .iter()
         .map(|v| v.position.z)
-        .sum::<f32>()
-        / custom_sphere.mesh.vertices.len() as f32;
+        .sum::<Real>()
+        / custom_sphere.mesh.vertices.len() as Real;
 
     assert!((avg_x - 1.0).abs() < 0.1);
     assert!((avg_y - 2.0).abs() < 0.1);
@@ -129,12 +139,654 @@ fn test_cylinder_creation() {
         .vertices
         .iter()
         .map(|v| v.position.y)
-        .fold(f32::NEG_INFINITY, f32::max);
+        .fold(Real::NEG_INFINITY, Real::max);
     let min_y = custom_cylinder
         .mesh
         .vertices
         .iter()
         .map(|v| v.position.y)
-        .fold(f32::INFINITY, f32::min);
+        .fold(Real::INFINITY, Real::min);
     assert!((max_y - min_y - 4.0).abs() < 0.01);
 }
+
+#[test]
+fn test_cone_creation() {
+    let cone = Cone::new().segments(8).build();
+
+    // Side rows duplicate the seam vertex; the apex faces collapse and are skipped
+    assert_eq!(cone.mesh.vertices.len(), 2 * 9 + 1 + 8);
+    assert_eq!(cone.mesh.faces.len(), 8 + 8);
+    assert_outward_normals(&cone);
+
+    // A frustum has two caps and full quads on its side
+    let frustum = Cone::new()
+        .radius(2.0)
+        .top_radius(1.0)
+        .height(3.0)
+        .center(0.0, 1.0, 0.0)
+        .segments(64)
+        .build();
+    assert_eq!(frustum.mesh.faces.len(), 64 * 2 + 64 * 2);
+    assert_outward_normals(&frustum);
+
+    let expected = PI * 3.0 / 3.0 * (4.0 + 2.0 + 1.0);
+    assert!((signed_volume(&frustum) - expected).abs() / expected < 0.01);
+
+    let min_y = frustum
+        .mesh
+        .vertices
+        .iter()
+        .map(|v| v.position.y)
+        .fold(Real::INFINITY, Real::min);
+    assert!((min_y + 0.5).abs() < 1e-5);
+}
+
+#[test]
+fn test_torus_creation() {
+    let torus = Torus::new()
+        .major_radius(2.0)
+        .minor_radius(0.5)
+        .segments(64)
+        .rings(32)
+        .build();
+
+    assert_eq!(torus.mesh.vertices.len(), 65 * 33);
+    assert_eq!(torus.mesh.faces.len(), 64 * 32 * 2);
+    assert_outward_normals(&torus);
+    assert_watertight(&torus);
+    assert_watertight(&Torus::new().build());
+
+    // Every vertex is on the tube surface
+    for vertex in &torus.mesh.vertices {
+        let p = vertex.position;
+        let ring_distance = (p.x * p.x + p.z * p.z).sqrt() - 2.0;
+        let tube_distance = (ring_distance * ring_distance + p.y * p.y).sqrt();
+        assert!((tube_distance - 0.5).abs() < 1e-4);
+    }
+
+    let expected = 2.0 * PI * PI * 2.0 * 0.25;
+    assert!((signed_volume(&torus) - expected).abs() / expected < 0.01);
+}
+
+#[test]
+#[should_panic(expected = "smaller than the major radius")]
+fn test_torus_rejects_self_intersection() {
+    Torus::new().major_radius(1.0).minor_radius(1.5).build();
+}
+
+#[test]
+fn test_capsule_creation() {
+    let capsule = Capsule::new()
+        .radius(1.0)
+        .height(2.0)
+        .center(1.0, 2.0, 3.0)
+        .segments(64)
+        .rings(16)
+        .build();
+    assert_outward_normals(&capsule);
+
+    let max_y = capsule
+        .mesh
+        .vertices
+        .iter()
+        .map(|v| v.position.y)
+        .fold(Real::NEG_INFINITY, Real::max);
+    assert!((max_y - 4.0).abs() < 1e-5);
+
+    let centered = Capsule::new()
+        .radius(1.0)
+        .height(2.0)
+        .segments(64)
+        .rings(16)
+        .build();
+    let expected = PI * 2.0 + 4.0 / 3.0 * PI;
+    assert!((signed_volume(&centered) - expected).abs() / expected < 0.01);
+
+    // Without a cylindrical section the capsule is a sphere with no duplicate equator
+    let sphere = Capsule::new().height(0.0).segments(8).rings(4).build();
+    assert_eq!(sphere.mesh.vertices.len(), 9 * 9);
+    assert_outward_normals(&sphere);
+}
+
+#[test]
+fn test_plane_creation() {
+    let plane = Plane::new()
+        .width(4.0)
+        .depth(2.0)
+        .subdivisions(4, 2)
+        .center(0.0, 1.0, 0.0)
+        .build();
+
+    assert_eq!(plane.mesh.vertices.len(), 5 * 3);
+    assert_eq!(plane.mesh.faces.len(), 4 * 2 * 2);
+    assert_outward_normals(&plane);
+    assert!(plane
+        .mesh
+        .vertices
+        .iter()
+        .all(|v| v.position.y == 1.0 && v.position.x.abs() <= 2.0 && v.position.z.abs() <= 1.0));
+
+    // Texture coordinates span the whole plane
+    let uvs: Vec<_> = plane
+        .mesh
+        .vertices
+        .iter()
+        .filter_map(|v| v.tex_coords)
+        .collect();
+    assert_eq!(uvs.first(), Some(&(0.0, 0.0)));
+    assert_eq!(uvs.last(), Some(&(1.0, 1.0)));
+}
+
+#[test]
+fn test_icosphere_creation() {
+    let base = Icosphere::new().subdivisions(0).build();
+    assert_eq!(base.mesh.faces.len(), 20);
+
+    let icosphere = Icosphere::new()
+        .radius(2.0)
+        .subdivisions(3)
+        .center(1.0, 2.0, 3.0)
+        .build();
+    assert_eq!(icosphere.mesh.faces.len(), 20 * 64);
+    assert_outward_normals(&icosphere);
+    assert_watertight(&icosphere);
+
+    for vertex in &icosphere.mesh.vertices {
+        let distance = (vertex.position - nalgebra::Point3::new(1.0, 2.0, 3.0)).norm();
+        assert!((distance - 2.0).abs() < 1e-4);
+    }
+
+    // Texture coordinates never wrap across the whole texture within a triangle
+    for face in &icosphere.mesh.faces {
+        let us: Vec<f32> = face
+            .indices
+            .iter()
+            .map(|&i| icosphere.mesh.vertices[i].tex_coords.unwrap().0)
+            .collect();
+        let spread = us.iter().cloned().fold(f32::MIN, f32::max)
+            - us.iter().cloned().fold(f32::MAX, f32::min);
+        assert!(spread < 0.5);
+    }
+
+    // Triangles are far more uniform than on a UV sphere
+    let areas: Vec<Real> = icosphere
+        .mesh
+        .faces
+        .iter()
+        .map(|f| {
+            let [p0, p1, p2] = [0, 1, 2].map(|k| icosphere.mesh.vertices[f.indices[k]].position);
+            (p1 - p0).cross(&(p2 - p0)).norm()
+        })
+        .collect();
+    let min_area = areas.iter().cloned().fold(Real::INFINITY, Real::min);
+    let max_area = areas.iter().cloned().fold(Real::NEG_INFINITY, Real::max);
+    assert!(max_area / min_area < 1.5);
+}
+
+#[test]
+fn test_geodesic_dome() {
+    for subdivisions in [0, 1, 3] {
+        let dome = Icosphere::new()
+            .subdivisions(subdivisions)
+            .dome(true)
+            .build();
+        assert_outward_normals(&dome);
+        assert_watertight(&dome);
+
+        let min_y = dome
+            .mesh
+            .vertices
+            .iter()
+            .map(|v| v.position.y)
+            .fold(Real::INFINITY, Real::min);
+        assert_eq!(min_y, 0.0);
+
+        // Above the first level the equator runs along edges, so the dome is exactly
+        // half of the icosphere; the coarse level-0 dome is clipped through its faces
+        let full = Icosphere::new().subdivisions(subdivisions).build();
+        let half = signed_volume(&full) / 2.0;
+        if subdivisions == 0 {
+            assert!(signed_volume(&dome) > half && signed_volume(&dome) < 2.0 / 3.0 * PI);
+        } else {
+            assert!((signed_volume(&dome) - half).abs() / half < 1e-4);
+        }
+    }
+}
+
+#[test]
+fn test_platonic_solids() {
+    // (solid, faces, vertices per face, volume for a unit circumradius)
+    let cases = [
+        (
+            PlatonicSolid::Tetrahedron,
+            4,
+            3,
+            8.0 * (3.0 as Real).sqrt() / 27.0,
+        ),
+        (PlatonicSolid::Octahedron, 8, 3, 4.0 / 3.0),
+        (PlatonicSolid::Dodecahedron, 12, 5, 2.785_164),
+        (PlatonicSolid::Icosahedron, 20, 3, 2.536_151),
+    ];
+
+    for (solid, faces, sides, volume) in cases {
+        let model = Platonic::new(solid).radius(2.0).build();
+        assert_eq!(model.mesh.faces.len(), faces);
+        assert!(model.mesh.faces.iter().all(|f| f.indices.len() == sides));
+        assert_outward_normals(&model);
+        assert_watertight(&model);
+
+        for vertex in &model.mesh.vertices {
+            assert!((vertex.position.coords.norm() - 2.0).abs() < 1e-4);
+        }
+
+        // Fan-triangulate for the volume check
+        let mut triangulated = model.clone();
+        triangulated.mesh.faces = model
+            .mesh
+            .faces
+            .iter()
+            .flat_map(|f| {
+                (1..f.indices.len() - 1)
+                    .map(|k| mg::Face::triangle(f.indices[0], f.indices[k], f.indices[k + 1]))
+            })
+            .collect();
+        let expected = volume * 8.0;
+        assert!((signed_volume(&triangulated) - expected).abs() / expected < 1e-4);
+    }
+
+    assert_eq!(Platonic::dodecahedron().build().name, "Dodecahedron");
+}
+
+#[test]
+fn test_heightfield_surface() {
+    // A ramp rising along X, with the first row at the back
+    let samples = vec![0.0, 0.5, 1.0, 0.0, 0.5, 1.0];
+    let terrain = Heightfield::new(3, 2, samples)
+        .size(4.0, 2.0)
+        .height(2.0)
+        .center(1.0, 0.0, 0.0)
+        .build();
+
+    assert_eq!(terrain.name, "Heightfield");
+    assert_eq!(terrain.mesh.vertices.len(), 6);
+    assert_eq!(terrain.mesh.faces.len(), 4);
+    assert_outward_normals(&terrain);
+
+    for vertex in &terrain.mesh.vertices {
+        let p = vertex.position;
+        assert!((p.y - (p.x + 1.0) / 2.0).abs() < 1e-5);
+        let expected = nalgebra::Vector3::new(-0.5, 1.0, 0.0).normalize();
+        assert!((vertex.normal - expected).norm() < 1e-5);
+    }
+    for face in &terrain.mesh.faces {
+        let [p0, p1, p2] = [0, 1, 2].map(|i| terrain.mesh.vertices[face.indices[i]].position);
+        assert!((p1 - p0).cross(&(p2 - p0)).y > 0.0);
+    }
+}
+
+#[test]
+fn test_heightfield_base_and_skirt() {
+    let terrain = Heightfield::from_fn(9, 7, |s, t| 0.5 + 0.125 * (s + t))
+        .size(2.0, 3.0)
+        .height(2.0);
+
+    // The base closes the terrain into a solid down to a flat bottom
+    let solid = terrain.base(0.5).build();
+    assert_outward_normals(&solid);
+    assert_watertight(&solid);
+    let bottom = solid
+        .mesh
+        .vertices
+        .iter()
+        .map(|v| v.position.y)
+        .fold(Real::MAX, Real::min);
+    assert!((bottom - 0.5).abs() < 1e-5);
+
+    // The sloping top averages half way up its 0.5 rise above the lowest corner
+    let expected = 2.0 * 3.0 * (0.5 + 0.25);
+    assert!((signed_volume(&solid) - expected).abs() < 1e-3);
+
+    let skirted = Heightfield::from_fn(9, 7, |s, _| s).skirt(0.25).build();
+    assert_outward_normals(&skirted);
+    assert_eq!(skirted.mesh.vertices.len(), 9 * 7 + 2 * 2 * (9 + 7));
+    let lowest = skirted
+        .mesh
+        .vertices
+        .iter()
+        .map(|v| v.position.y)
+        .fold(Real::MAX, Real::min);
+    assert!((lowest + 0.25).abs() < 1e-5);
+}
+
+#[test]
+fn test_heightfield_from_images() {
+    std::fs::create_dir_all("tests/output").unwrap();
+    let pixels: [u8; 6] = [0, 51, 102, 153, 204, 255];
+    let check = |terrain: Heightfield| {
+        let model = terrain.build();
+        assert_eq!(model.mesh.vertices.len(), 6);
+        let mut heights: Vec<Real> = model.mesh.vertices.iter().map(|v| v.position.y).collect();
+        heights.sort_by(|a, b| a.total_cmp(b));
+        for (height, pixel) in heights.iter().zip(pixels) {
+            assert!((height - pixel as Real / 255.0).abs() < 1e-3);
+        }
+    };
+
+    // ASCII PGM with a comment
+    let ascii = "P2\n# terrain\n3 2\n255\n0 51 102\n153 204 255\n";
+    std::fs::write("tests/output/heightfield.pgm", ascii).unwrap();
+    check(Heightfield::from_image("tests/output/heightfield.pgm").unwrap());
+
+    // Binary PGM
+    let mut binary = b"P5 3 2 255\n".to_vec();
+    binary.extend_from_slice(&pixels);
+    check(Heightfield::from_image_data(&binary).unwrap());
+
+    // 8-bit grayscale PNG
+    let mut png = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut png, 3, 2);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().unwrap();
+        writer.write_image_data(&pixels).unwrap();
+    }
+    std::fs::write("tests/output/heightfield.png", &png).unwrap();
+    check(Heightfield::from_image("tests/output/heightfield.png").unwrap());
+
+    assert!(Heightfield::from_image_data(b"not an image").is_err());
+    assert!(Heightfield::from_image_data(b"P5 3 2 255\n\x00").is_err());
+}
+
+#[test]
+fn test_heightfield_malformed_pgm_header() {
+    // Sizes whose pixel count overflows are errors rather than panics
+    for header in [
+        "P5\n4294967296 4294967297 255\n",
+        "P2\n4294967296 4294967297 255\n",
+        "P5\n18446744073709551615 2 65535\n",
+        "P5 3 2 0\n",
+        "P2 3 x 255\n",
+    ] {
+        assert!(
+            Heightfield::from_image_data(header.as_bytes()).is_err(),
+            "{:?}",
+            header
+        );
+    }
+}
+
+#[test]
+fn test_noise() {
+    for noise in [Noise::perlin(), Noise::simplex()] {
+        let noise = noise.seed(42).octaves(5).frequency(3.0).lacunarity(2.0);
+
+        // Seeded noise is repeatable and varies with the seed
+        let samples: Vec<Real> = (0..100)
+            .map(|i| noise.sample(i as Real * 0.037, i as Real * 0.011))
+            .collect();
+        let again: Vec<Real> = (0..100)
+            .map(|i| noise.sample(i as Real * 0.037, i as Real * 0.011))
+            .collect();
+        let other: Vec<Real> = (0..100)
+            .map(|i| {
+                noise
+                    .clone()
+                    .seed(7)
+                    .sample(i as Real * 0.037, i as Real * 0.011)
+            })
+            .collect();
+        assert_eq!(samples, again);
+        assert_ne!(samples, other);
+
+        assert!(samples.iter().all(|s| (-1.0..=1.0).contains(s)));
+        assert!(samples.iter().any(|&s| s.abs() > 0.05));
+
+        // Neighbouring samples are close together
+        let (a, b) = (noise.sample(0.3, 0.4), noise.sample(0.3001, 0.4));
+        assert!((a - b).abs() < 0.01);
+    }
+
+    // A single octave of Perlin noise vanishes on its lattice
+    let perlin = Noise::perlin().octaves(1).seed(3);
+    for (x, y) in [(0.0, 0.0), (2.0, 5.0), (-3.0, 1.0)] {
+        assert!(perlin.sample(x, y).abs() < 1e-6);
+    }
+
+    let terrain = Heightfield::from_noise(16, 16, &Noise::simplex().seed(1)).build();
+    assert!(terrain
+        .mesh
+        .vertices
+        .iter()
+        .all(|v| (0.0..=1.0).contains(&v.position.y)));
+}
+
+/// Smallest and largest distance of the vertices from the Y axis.
+fn radial_range(model: &Model) -> (Real, Real) {
+    model
+        .mesh
+        .vertices
+        .iter()
+        .map(|v| v.position.x.hypot(v.position.z))
+        .fold((Real::INFINITY, Real::NEG_INFINITY), |(min, max), r| {
+            (min.min(r), max.max(r))
+        })
+}
+
+/// Lowest and highest vertex along Y.
+fn height_range(model: &Model) -> (Real, Real) {
+    model
+        .mesh
+        .vertices
+        .iter()
+        .map(|v| v.position.y)
+        .fold((Real::INFINITY, Real::NEG_INFINITY), |(min, max), y| {
+            (min.min(y), max.max(y))
+        })
+}
+
+#[test]
+fn test_gear_creation() {
+    let gear = Gear::new()
+        .module(0.1)
+        .teeth(20)
+        .width(0.5)
+        .bore(0.6)
+        .build();
+    assert_eq!(gear.name, "Gear");
+    assert_watertight(&gear);
+    assert_outward_normals(&gear);
+    assert!(signed_volume(&gear) > 0.0);
+
+    // Bore, root and tip circles, and the face width centered on the origin
+    let (min, max) = radial_range(&gear);
+    assert!((min - 0.3).abs() < 1e-4);
+    assert!((max - 1.1).abs() < 1e-4);
+    let root = gear
+        .mesh
+        .vertices
+        .iter()
+        .map(|v| v.position.x.hypot(v.position.z))
+        .filter(|&r| r > 0.31)
+        .fold(Real::INFINITY, Real::min);
+    assert!((root - 0.875).abs() < 1e-4);
+    let (bottom, top) = height_range(&gear);
+    assert!((bottom + 0.25).abs() < 1e-5 && (top - 0.25).abs() < 1e-5);
+
+    // One cluster of tip vertices per tooth
+    let mut tips: Vec<Real> = gear
+        .mesh
+        .vertices
+        .iter()
+        .filter(|v| v.position.x.hypot(v.position.z) > 1.05)
+        .map(|v| v.position.z.atan2(v.position.x).rem_euclid(2.0 * PI))
+        .collect();
+    tips.sort_by(|a, b| a.total_cmp(b));
+    let gaps = tips.windows(2).filter(|w| w[1] - w[0] > PI / 20.0).count();
+    let wraps = tips[0] + 2.0 * PI - tips[tips.len() - 1] > PI / 20.0;
+    assert_eq!(gaps + wraps as usize, 20);
+
+    // The teeth are half the circular pitch thick on the pitch circle: find where the
+    // flank of the tooth on +X crosses it
+    let flank: Vec<(Real, Real)> = gear
+        .mesh
+        .vertices
+        .iter()
+        .filter(|v| v.position.y > 0.24 && v.position.z > 0.0)
+        .map(|v| {
+            (
+                v.position.x.hypot(v.position.z),
+                v.position.z.atan2(v.position.x),
+            )
+        })
+        .filter(|&(_, angle)| angle < PI / 20.0)
+        .collect();
+    let below = flank
+        .iter()
+        .filter(|p| p.0 <= 1.0)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .unwrap();
+    let above = flank
+        .iter()
+        .filter(|p| p.0 > 1.0)
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .unwrap();
+    let t = (1.0 - below.0) / (above.0 - below.0);
+    let half_thickness = below.1 + (above.1 - below.1) * t;
+    assert!((half_thickness - PI / 40.0).abs() < 1e-3);
+
+    assert!((Gear::new().module(2.0).teeth(30).pitch_diameter() - 60.0).abs() < 1e-6);
+    gear.export_stl("tests/output/gear.stl").unwrap();
+}
+
+#[test]
+#[should_panic(expected = "bore")]
+fn test_gear_rejects_oversized_bore() {
+    Gear::new().module(0.1).teeth(10).bore(0.8).build();
+}
+
+/// Volume of a basic-profile thread of the given size: the profile's mean squared
+/// radius swept around the axis.
+fn thread_volume(diameter: Real, pitch: Real, length: Real) -> Real {
+    let depth = 5.0 / 8.0 * Real::sqrt(3.0) / 2.0 * pitch;
+    let radius = |u: Real| {
+        let major = diameter / 2.0;
+        match u {
+            u if u < 0.125 => major,
+            u if u < 0.4375 => major - depth * (u - 0.125) / 0.3125,
+            u if u < 0.6875 => major - depth,
+            u => major - depth + depth * (u - 0.6875) / 0.3125,
+        }
+    };
+    let samples = 10000;
+    let mean: Real = (0..samples)
+        .map(|i| radius((i as Real + 0.5) / samples as Real).powi(2))
+        .sum::<Real>()
+        / samples as Real;
+    PI * mean * length
+}
+
+#[test]
+fn test_thread_creation() {
+    let thread = Thread::metric(6.0).length(10.0).segments(48).build();
+    assert_eq!(thread.name, "Thread");
+    assert_watertight(&thread);
+    assert_outward_normals(&thread);
+
+    // M6 has a 1 mm pitch and a basic minor diameter of d - 1.0825 P
+    let (core, max) = radial_range(&thread);
+    assert!((max - 3.0).abs() < 1e-4);
+    assert!((core - (6.0 - 1.0825) / 2.0).abs() < 1e-3);
+    let (bottom, top) = height_range(&thread);
+    assert!((bottom + 5.0).abs() < 1e-4 && (top - 5.0).abs() < 1e-4);
+
+    let expected = thread_volume(6.0, 1.0, 10.0);
+    assert!((signed_volume(&thread) - expected).abs() < 0.01 * expected);
+
+    // Left-handed threads wind the other way, so they differ a quarter turn in, but
+    // enclose the same volume
+    let left = Thread::metric(6.0).length(10.0).left_handed(true).build();
+    assert_watertight(&left);
+    assert!((signed_volume(&left) - expected).abs() < 0.01 * expected);
+    // Mean height of the crest vertices near the middle, a quarter turn in
+    let crest = |model: &Model| {
+        let heights: Vec<Real> = model
+            .mesh
+            .vertices
+            .iter()
+            .filter(|v| v.position.x.abs() < 1e-4 && v.position.z < -2.99)
+            .map(|v| v.position.y)
+            .filter(|y| y.abs() < 0.5)
+            .collect();
+        heights.iter().sum::<Real>() / heights.len() as Real
+    };
+    assert!((crest(&thread) - crest(&left)).abs() > 0.25);
+
+    thread.export_stl("tests/output/thread.stl").unwrap();
+}
+
+#[test]
+fn test_internal_thread() {
+    let nut = Thread::metric(8.0)
+        .length(6.0)
+        .internal(true)
+        .outer_diameter(13.0)
+        .center(1.0, 2.0, 3.0)
+        .build();
+    assert_watertight(&nut);
+    assert_outward_normals(&nut);
+
+    // The sleeve minus a threaded rod of the same size
+    let expected = PI * 6.5 * 6.5 * 6.0 - thread_volume(8.0, 1.25, 6.0);
+    assert!((signed_volume(&nut) - expected).abs() < 0.01 * expected);
+
+    let (bottom, top) = height_range(&nut);
+    assert!((bottom + 1.0).abs() < 1e-4 && (top - 5.0).abs() < 1e-4);
+}
+
+#[test]
+#[should_panic(expected = "M7")]
+fn test_thread_rejects_nonstandard_size() {
+    Thread::metric(7.0);
+}
+
+#[test]
+fn test_spring_creation() {
+    let spring = Spring::new()
+        .coil_diameter(2.0)
+        .wire_diameter(0.2)
+        .pitch(0.5)
+        .turns(4.0)
+        .wire_segments(32)
+        .build();
+    assert_eq!(spring.name, "Spring");
+    assert_watertight(&spring);
+    assert_outward_normals(&spring);
+
+    // A wire as long as the helix
+    let length = 4.0 * (2.0 * PI).hypot(0.5);
+    let expected = PI * 0.1 * 0.1 * length;
+    assert!((signed_volume(&spring) - expected).abs() < 0.02 * expected);
+
+    // Centered on its free length of the coils plus the wire
+    let (bottom, top) = height_range(&spring);
+    assert!((bottom + top).abs() < 1e-3);
+    assert!((top - bottom - (2.0 + 0.2)).abs() < 0.02);
+    let (min, max) = radial_range(&spring);
+    assert!((min - 0.9).abs() < 0.01 && (max - 1.1).abs() < 0.01);
+
+    // Closed ends bring the end coils down onto their neighbours
+    let closed = Spring::new()
+        .coil_diameter(2.0)
+        .wire_diameter(0.2)
+        .pitch(0.5)
+        .turns(4.0)
+        .closed_ends(true)
+        .build();
+    assert_watertight(&closed);
+    let (closed_bottom, closed_top) = height_range(&closed);
+    assert!(closed_top - closed_bottom < top - bottom - 0.3);
+    assert!(closed_top - closed_bottom > 4.0 * 0.2);
+
+    spring.export_stl("tests/output/spring.stl").unwrap();
+}
@@ -0,0 +1,245 @@
+use mg::primitives::{Cube, Sphere, Torus};
+use mg::spatial::{closest_point_on_triangle, ray_triangle, triangles_overlap, Bvh, Ray};
+use mg::{Mesh, Model, Real, Translate};
+use nalgebra::{Point3, Vector3};
+
+/// Every triangle of a mesh's face fans, with its face index.
+fn triangles(model: &Model) -> Vec<([Point3<Real>; 3], usize)> {
+    let mesh = &model.mesh;
+    let mut triangles = Vec::new();
+    for (index, face) in mesh.faces.iter().enumerate() {
+        let p = |k: usize| mesh.vertices[face.indices[k]].position;
+        for k in 1..face.indices.len() - 1 {
+            triangles.push(([p(0), p(k), p(k + 1)], index));
+        }
+    }
+    triangles
+}
+
+/// Reproducible points scattered through a cube of half size `half`.
+fn scattered(count: usize, half: Real) -> Vec<Point3<Real>> {
+    let mut state: u32 = 11;
+    let mut random = || {
+        state = state.wrapping_mul(1_664_525).wrapping_add(1_013_904_223);
+        ((state >> 8) as Real / (1u32 << 24) as Real * 2.0 - 1.0) * half
+    };
+    (0..count)
+        .map(|_| Point3::new(random(), random(), random()))
+        .collect()
+}
+
+fn sphere() -> Model {
+    Sphere::new().radius(2.0).segments(32).rings(16).build()
+}
+
+#[test]
+fn test_triangle_queries() {
+    let triangle = [
+        Point3::new(0.0, 0.0, 0.0),
+        Point3::new(2.0, 0.0, 0.0),
+        Point3::new(0.0, 2.0, 0.0),
+    ];
+
+    // Rays cross from either side, but not from behind or beside
+    let (t, weights) = ray_triangle(
+        &Point3::new(0.5, 0.5, 3.0),
+        &Vector3::new(0.0, 0.0, -1.0),
+        &triangle,
+    )
+    .unwrap();
+    assert_eq!(t, 3.0);
+    assert_eq!(weights, [0.5, 0.25, 0.25]);
+    assert!(ray_triangle(&Point3::new(0.5, 0.5, -3.0), &Vector3::z(), &triangle).is_some());
+    assert!(ray_triangle(&Point3::new(0.5, 0.5, 3.0), &Vector3::z(), &triangle).is_none());
+    assert!(ray_triangle(&Point3::new(2.0, 2.0, 3.0), &-Vector3::z(), &triangle).is_none());
+    assert!(ray_triangle(&Point3::new(-1.0, 0.5, 0.0), &Vector3::x(), &triangle).is_none());
+
+    // Closest points inside, on an edge and at a corner
+    let closest = |x, y, z| closest_point_on_triangle(&Point3::new(x, y, z), &triangle);
+    assert_eq!(closest(0.5, 0.5, 4.0), Point3::new(0.5, 0.5, 0.0));
+    assert_eq!(closest(1.0, -3.0, 1.0), Point3::new(1.0, 0.0, 0.0));
+    assert_eq!(closest(2.0, 2.0, 0.0), Point3::new(1.0, 1.0, 0.0));
+    assert_eq!(closest(-1.0, -1.0, 0.0), triangle[0]);
+    assert_eq!(closest(5.0, -1.0, 2.0), triangle[1]);
+
+    // Overlap across, apart, touching, and within one plane
+    let moved =
+        |x: Real, y: Real, z: Real, t: &[Point3<Real>; 3]| t.map(|p| p + Vector3::new(x, y, z));
+    let upright = [
+        Point3::new(0.5, 0.5, -1.0),
+        Point3::new(0.5, 0.5, 1.0),
+        Point3::new(0.5, -1.0, 0.0),
+    ];
+    let overlaps = |other: [Point3<Real>; 3]| triangles_overlap(&triangle, &other);
+    assert!(overlaps(upright));
+    assert!(!overlaps(moved(0.0, 0.0, 1.5, &upright)));
+    assert!(!overlaps(moved(3.0, 0.0, 0.0, &upright)));
+    assert!(overlaps(moved(0.0, 0.0, 1.0, &upright)));
+    assert!(overlaps(moved(1.0, 0.5, 0.0, &triangle)));
+    assert!(overlaps(moved(2.0, 0.0, 0.0, &triangle)));
+    assert!(!overlaps(moved(1.5, 1.5, 0.0, &triangle)));
+    let flipped = triangle.map(|p| Point3::new(2.0 - p.x, 2.0 - p.y, 0.0));
+    assert!(overlaps(flipped));
+    assert!(!overlaps(moved(0.1, 0.1, 0.0, &flipped)));
+}
+
+#[test]
+fn test_ray_cast() {
+    let cube = Cube::new().size(2.0).build();
+    let bvh = Bvh::new(&cube.mesh);
+    assert_eq!(bvh.len(), 12);
+    let bounds = bvh.bounds().unwrap();
+    assert_eq!(bounds.min, Point3::new(-1.0, -1.0, -1.0));
+
+    let ray = Ray::new(Point3::new(0.2, 0.3, -5.0), Vector3::new(0.0, 0.0, 2.0));
+    let hit = bvh.cast_ray(&ray, Real::INFINITY).unwrap();
+    assert!((hit.distance - 4.0).abs() < 1e-5);
+    assert!((hit.point - Point3::new(0.2, 0.3, -1.0)).norm() < 1e-5);
+    assert!((hit.normal - Vector3::new(0.0, 0.0, -1.0)).norm() < 1e-5);
+    assert!(bvh.cast_ray(&ray, 3.9).is_none());
+    let interpolated: Vector3<Real> = (0..3)
+        .map(|k| cube.mesh.vertices[hit.vertices[k]].position.coords * hit.barycentric[k])
+        .sum();
+    assert!((interpolated - hit.point.coords).norm() < 1e-5);
+    assert!(cube.mesh.faces[hit.face]
+        .indices
+        .iter()
+        .all(|&i| cube.mesh.vertices[i].position.z == -1.0));
+
+    // From inside the far side is hit; pointing away nothing is
+    let hit = bvh
+        .cast_ray(&Ray::new(Point3::origin(), Vector3::x()), Real::INFINITY)
+        .unwrap();
+    assert!((hit.point - Point3::new(1.0, 0.0, 0.0)).norm() < 1e-5);
+    let away = Ray::new(Point3::new(3.0, 0.0, 0.0), Vector3::x());
+    assert!(bvh.cast_ray(&away, Real::INFINITY).is_none());
+
+    // Rays through a sphere agree with testing every triangle
+    let sphere = sphere();
+    let bvh = Bvh::new(&sphere.mesh);
+    let all = triangles(&sphere);
+    let targets = scattered(100, 1.5);
+    for (origin, target) in scattered(100, 4.0).iter().zip(&targets) {
+        let ray = Ray::new(*origin, target - origin);
+        let expected = all
+            .iter()
+            .filter_map(|(t, _)| ray_triangle(&ray.origin, &ray.direction, t))
+            .map(|(t, _)| t)
+            .fold(Real::INFINITY, Real::min);
+        let hit = bvh.cast_ray(&ray, Real::INFINITY).unwrap();
+        assert!((hit.distance - expected).abs() < 1e-4);
+    }
+
+    let empty = Bvh::new(&Mesh::new());
+    assert!(empty.is_empty() && empty.bounds().is_none());
+    assert!(empty.cast_ray(&ray, Real::INFINITY).is_none());
+}
+
+#[test]
+fn test_closest_point() {
+    let cube = Cube::new().size(2.0).build();
+    let bvh = Bvh::new(&cube.mesh);
+    let closest = bvh.closest_point(&Point3::new(3.0, 0.5, 0.0)).unwrap();
+    assert!((closest.point - Point3::new(1.0, 0.5, 0.0)).norm() < 1e-5);
+    assert!((closest.distance - 2.0).abs() < 1e-5);
+    let closest = bvh.closest_point(&Point3::new(0.0, 0.8, 0.1)).unwrap();
+    assert!((closest.point - Point3::new(0.0, 1.0, 0.1)).norm() < 1e-5);
+    let closest = bvh.closest_point(&Point3::new(2.0, 2.0, 2.0)).unwrap();
+    assert!((closest.point - Point3::new(1.0, 1.0, 1.0)).norm() < 1e-5);
+
+    // Points around a sphere agree with testing every triangle
+    let sphere = sphere();
+    let bvh = Bvh::new(&sphere.mesh);
+    let all = triangles(&sphere);
+    for point in scattered(200, 3.0) {
+        let expected = all
+            .iter()
+            .map(|(t, _)| (closest_point_on_triangle(&point, t) - point).norm())
+            .fold(Real::INFINITY, Real::min);
+        let closest = bvh.closest_point(&point).unwrap();
+        assert!((closest.distance - expected).abs() < 1e-5);
+        assert!(((closest.point - point).norm() - closest.distance).abs() < 1e-5);
+        assert!((closest.point.coords.norm() - 2.0).abs() < 0.05);
+    }
+    assert!(Bvh::new(&Mesh::new())
+        .closest_point(&Point3::origin())
+        .is_none());
+}
+
+#[test]
+fn test_contains() {
+    let cube = Cube::new().size(2.0).build();
+    let bvh = Bvh::new(&cube.mesh);
+    assert!(bvh.contains(&Point3::origin()));
+    assert!(bvh.contains(&Point3::new(0.9, -0.9, 0.9)));
+    assert!(!bvh.contains(&Point3::new(1.5, 0.0, 0.0)));
+
+    let torus = Torus::new()
+        .major_radius(1.0)
+        .minor_radius(0.3)
+        .segments(32)
+        .rings(12)
+        .build();
+    let bvh = Bvh::new(&torus.mesh);
+    assert!(!bvh.contains(&Point3::origin()));
+    assert!(bvh.contains(&Point3::new(1.0, 0.0, 0.0)));
+    assert!(bvh.contains(&Point3::new(0.0, 0.1, -1.1)));
+    assert!(!bvh.contains(&Point3::new(0.0, 0.4, 1.0)));
+
+    // Away from the surface, points inside a sphere are inside its mesh
+    let bvh = Bvh::new(&sphere().mesh);
+    for point in scattered(300, 3.0) {
+        let radius = point.coords.norm();
+        if (radius - 2.0).abs() > 0.05 {
+            assert_eq!(bvh.contains(&point), radius < 2.0, "{}", point);
+        }
+    }
+    assert!(!Bvh::new(&Mesh::new()).contains(&Point3::origin()));
+}
+
+#[test]
+fn test_overlapping_meshes() {
+    let cube = Cube::new().size(2.0).build();
+    let bvh = Bvh::new(&cube.mesh);
+    let shifted = |x: Real, y: Real, z: Real| {
+        let mut model = Cube::new().size(2.0).build();
+        model.apply(Translate::new(x, y, z));
+        model
+    };
+
+    let apart = shifted(2.5, 0.0, 0.0);
+    assert!(!bvh.intersects(&Bvh::new(&apart.mesh)));
+    assert!(bvh.overlapping_faces(&Bvh::new(&apart.mesh)).is_empty());
+
+    // A small cube wholly inside meets no face
+    let mut inner = Cube::new().size(0.5).build();
+    inner.apply(Translate::new(0.3, 0.0, 0.0));
+    assert!(!bvh.intersects(&Bvh::new(&inner.mesh)));
+
+    // Overlapping cubes agree with testing every pair of triangles
+    let other = shifted(1.0, 0.5, -0.3);
+    let other_bvh = Bvh::new(&other.mesh);
+    assert!(bvh.intersects(&other_bvh));
+    let mut expected: Vec<(usize, usize)> = Vec::new();
+    for (a, face_a) in triangles(&cube) {
+        for (b, face_b) in triangles(&other) {
+            if triangles_overlap(&a, &b) {
+                expected.push((face_a, face_b));
+            }
+        }
+    }
+    expected.sort_unstable();
+    expected.dedup();
+    assert!(!expected.is_empty());
+    assert_eq!(bvh.overlapping_faces(&other_bvh), expected);
+
+    // Spheres of many triangles, barely overlapping and barely apart
+    let sphere = sphere();
+    let bvh = Bvh::new(&sphere.mesh);
+    let mut near = self::sphere();
+    near.apply(Translate::new(3.8, 0.0, 0.0));
+    assert!(bvh.intersects(&Bvh::new(&near.mesh)));
+    near.apply(Translate::new(0.3, 0.0, 0.0));
+    assert!(!bvh.intersects(&Bvh::new(&near.mesh)));
+    assert!(!bvh.intersects(&Bvh::new(&Mesh::new())));
+}
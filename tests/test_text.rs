@@ -0,0 +1,276 @@
+use mg::bounds::Aabb;
+use mg::text::{Font, Text, TextAlign};
+use mg::{Model, Real};
+
+mod common;
+
+use common::{assert_watertight, signed_volume};
+
+/// Whether any upward-facing top triangle covers the point `(x, y)`.
+fn top_covers(model: &Model, x: Real, y: Real) -> bool {
+    model.mesh.faces.iter().any(|face| {
+        let [a, b, c] = [0, 1, 2].map(|i| model.mesh.vertices[face.indices[i]].position);
+        let cross = |p: nalgebra::Point3<Real>, q: nalgebra::Point3<Real>| {
+            (q.x - p.x) * (y - p.y) - (q.y - p.y) * (x - p.x)
+        };
+        let upward = (b - a).cross(&(c - a)).z > 0.0;
+        upward && cross(a, b) > 0.0 && cross(b, c) > 0.0 && cross(c, a) > 0.0
+    })
+}
+
+/// A minimal TrueType font with 1000 units per em and no OS/2 table, so its cap
+/// height is 700 units. `o` is a 700-unit square with a 300-unit square hole and `c`
+/// a rounded shape drawn only with off-curve points.
+fn test_font() -> Vec<u8> {
+    let be16 = |out: &mut Vec<u8>, v: i32| out.extend_from_slice(&(v as u16).to_be_bytes());
+
+    // A simple glyph from contours of (x, y, on-curve) points
+    let glyph = |contours: &[&[(i32, i32, bool)]]| {
+        let mut out = Vec::new();
+        be16(&mut out, contours.len() as i32);
+        for v in [0, 0, 700, 700] {
+            be16(&mut out, v);
+        }
+        let mut end = -1;
+        for contour in contours {
+            end += contour.len() as i32;
+            be16(&mut out, end);
+        }
+        be16(&mut out, 0);
+        let points: Vec<_> = contours.iter().flat_map(|c| c.iter()).collect();
+        out.extend(points.iter().map(|p| p.2 as u8));
+        for axis in 0..2 {
+            let mut previous = 0;
+            for p in &points {
+                let value = if axis == 0 { p.0 } else { p.1 };
+                be16(&mut out, value - previous);
+                previous = value;
+            }
+        }
+        if out.len() % 2 == 1 {
+            out.push(0);
+        }
+        out
+    };
+    // Outer contours run clockwise in TrueType
+    let square = glyph(&[
+        &[
+            (0, 0, true),
+            (0, 700, true),
+            (700, 700, true),
+            (700, 0, true),
+        ],
+        &[
+            (200, 200, true),
+            (500, 200, true),
+            (500, 500, true),
+            (200, 500, true),
+        ],
+    ]);
+    let round = glyph(&[&[
+        (0, 0, false),
+        (0, 700, false),
+        (700, 700, false),
+        (700, 0, false),
+    ]]);
+
+    let mut glyf = square.clone();
+    glyf.extend(&round);
+    let mut loca = Vec::new();
+    for offset in [0, 0, square.len(), glyf.len()] {
+        be16(&mut loca, offset as i32 / 2);
+    }
+
+    let mut head = vec![0; 54];
+    head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+    let mut maxp = vec![0, 0, 0x50, 0];
+    be16(&mut maxp, 3);
+    let mut hhea = vec![0; 36];
+    hhea[4..6].copy_from_slice(&800i16.to_be_bytes());
+    hhea[6..8].copy_from_slice(&(-200i16).to_be_bytes());
+    hhea[34..36].copy_from_slice(&3u16.to_be_bytes());
+    let mut hmtx = Vec::new();
+    for advance in [500, 800, 800] {
+        be16(&mut hmtx, advance);
+        be16(&mut hmtx, 0);
+    }
+
+    // A format 4 map of 'c' and 'o', then the required final segment
+    let mut cmap = Vec::new();
+    for v in [0, 1, 3, 1, 0, 12, 4, 40, 0, 6, 4, 1, 2] {
+        be16(&mut cmap, v);
+    }
+    for v in ['c' as i32, 'o' as i32, 0xFFFF, 0] {
+        be16(&mut cmap, v);
+    }
+    for v in ['c' as i32, 'o' as i32, 0xFFFF] {
+        be16(&mut cmap, v);
+    }
+    for v in [2 - 'c' as i32, 1 - 'o' as i32, 1, 0, 0, 0] {
+        be16(&mut cmap, v);
+    }
+
+    let tables: [(&[u8; 4], Vec<u8>); 7] = [
+        (b"cmap", cmap),
+        (b"glyf", glyf),
+        (b"head", head),
+        (b"hhea", hhea),
+        (b"hmtx", hmtx),
+        (b"loca", loca),
+        (b"maxp", maxp),
+    ];
+    let mut font = vec![0, 1, 0, 0];
+    for v in [tables.len() as i32, 0, 0, 0] {
+        be16(&mut font, v);
+    }
+    let mut offset = 12 + 16 * tables.len();
+    for (tag, data) in &tables {
+        font.extend_from_slice(*tag);
+        font.extend_from_slice(&[0; 4]);
+        font.extend_from_slice(&(offset as u32).to_be_bytes());
+        font.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        offset += data.len().div_ceil(4) * 4;
+    }
+    for (_, data) in &tables {
+        font.extend(data);
+        font.resize(font.len().div_ceil(4) * 4, 0);
+    }
+    font
+}
+
+#[test]
+fn test_builtin_text() {
+    let text = Text::new("Hello, 8!").size(2.0).depth(0.5).build().unwrap();
+    assert_eq!(text.name, "Text");
+    assert!(!text.mesh.faces.is_empty());
+    assert_watertight(&text);
+    assert!(signed_volume(&text) > 0.0);
+
+    // Strokes stick out of the cap height by half the weight on either side
+    let letter = Text::new("H")
+        .size(2.0)
+        .depth(0.5)
+        .weight(0.1)
+        .build()
+        .unwrap();
+    let Aabb { min, max } = letter.mesh.aabb().unwrap();
+    assert!((min[1] + 0.1).abs() < 0.02);
+    assert!((max[1] - 2.1).abs() < 0.02);
+    assert!(min[2].abs() < 1e-6 && (max[2] - 0.5).abs() < 1e-6);
+
+    // Heavier strokes fill more
+    let light = Text::new("H").weight(0.08).build().unwrap();
+    let heavy = Text::new("H").weight(0.2).build().unwrap();
+    assert!(signed_volume(&heavy) > 1.5 * signed_volume(&light));
+
+    text.export_obj("tests/output/text.obj").unwrap();
+}
+
+#[test]
+fn test_glyph_holes() {
+    // The counters of O and 8 stay open
+    let o = Text::new("O").build().unwrap();
+    assert_watertight(&o);
+    let Aabb { min, max } = o.mesh.aabb().unwrap();
+    assert!(!top_covers(&o, 0.5 * (min[0] + max[0]), 0.5));
+    assert!(top_covers(&o, min[0] + 0.05, 0.5));
+
+    let eight = Text::new("8").build().unwrap();
+    assert_watertight(&eight);
+    let Aabb { min, max } = eight.mesh.aabb().unwrap();
+    let middle = 0.5 * (min[0] + max[0]);
+    assert!(!top_covers(&eight, middle, 0.25));
+    assert!(!top_covers(&eight, middle, 0.78));
+    assert!(top_covers(&eight, middle, 0.5));
+}
+
+#[test]
+fn test_text_layout() {
+    let left = Text::new("ABC").build().unwrap();
+    let center = Text::new("ABC").align(TextAlign::Center).build().unwrap();
+    let right = Text::new("ABC").align(TextAlign::Right).build().unwrap();
+    let Aabb { min: left_min, .. } = left.mesh.aabb().unwrap();
+    let Aabb {
+        min: center_min, ..
+    } = center.mesh.aabb().unwrap();
+    let Aabb {
+        min: right_min,
+        max: right_max,
+    } = right.mesh.aabb().unwrap();
+    assert!(left_min[0] > -0.01);
+    assert!(right_max[0] < 0.01);
+    let shift = right_min[0] - left_min[0];
+    assert!((center_min[0] - left_min[0] - 0.5 * shift).abs() < 1e-4);
+
+    // Letter spacing widens the line by one gap per pair of glyphs
+    let spaced = Text::new("ABC").letter_spacing(0.5).build().unwrap();
+    let Aabb { max: left_max, .. } = left.mesh.aabb().unwrap();
+    let Aabb {
+        max: spaced_max, ..
+    } = spaced.mesh.aabb().unwrap();
+    assert!((spaced_max[0] - left_max[0] - 1.0).abs() < 1e-4);
+
+    // Lines go down by the line height
+    let one = Text::new("T").size(3.0).build().unwrap();
+    let two = Text::new("T\nT")
+        .size(3.0)
+        .line_spacing(1.5)
+        .build()
+        .unwrap();
+    let Aabb { min: one_min, .. } = one.mesh.aabb().unwrap();
+    let Aabb { min: two_min, .. } = two.mesh.aabb().unwrap();
+    assert!((one_min[1] - two_min[1] - 3.0 * 1.5 * 10.0 / 6.0).abs() < 1e-4);
+    assert!((signed_volume(&two) - 2.0 * signed_volume(&one)).abs() < 1e-3);
+
+    // Empty lines and spaces add no geometry
+    let blank = Text::new(" \n\n  ").build().unwrap();
+    assert!(blank.mesh.faces.is_empty());
+}
+
+#[test]
+fn test_truetype_font() {
+    let font = Font::from_data(test_font()).unwrap();
+
+    // The square with a hole scales its cap height to the size
+    let o = Text::new("o")
+        .font(font.clone())
+        .size(1.4)
+        .depth(0.5)
+        .build()
+        .unwrap();
+    assert_watertight(&o);
+    let Aabb { min, max } = o.mesh.aabb().unwrap();
+    assert!(min[0].abs() < 1e-5 && min[1].abs() < 1e-5);
+    assert!((max[0] - 1.4).abs() < 1e-5 && (max[1] - 1.4).abs() < 1e-5);
+    let area = 1.4 * 1.4 * (49.0 - 9.0) / 49.0;
+    assert!((signed_volume(&o) - area * 0.5).abs() < 1e-4);
+    assert!(!top_covers(&o, 0.7, 0.7));
+
+    // Glyphs advance by their horizontal metrics; missing characters are blank
+    let pair = Text::new("oxo").font(font.clone()).build().unwrap();
+    let Aabb { max, .. } = pair.mesh.aabb().unwrap();
+    assert!((max[0] - (800.0 + 500.0 + 700.0) / 700.0).abs() < 1e-4);
+
+    // Curves through off-curve points alone are flattened within the tolerance
+    let c = Text::new("c").font(font).tolerance(0.001).build().unwrap();
+    assert_watertight(&c);
+    let Aabb { min, max } = c.mesh.aabb().unwrap();
+    assert!(min[0].abs() < 1e-5 && (max[0] - 1.0).abs() < 1e-5);
+    // A diamond plus four parabolic segments of 2/3 of their hull triangles
+    let area = 0.5 + 4.0 * 2.0 / 3.0 * 0.125;
+    assert!((signed_volume(&c) - area * 0.2).abs() < 1e-3);
+}
+
+#[test]
+fn test_invalid_fonts() {
+    assert!(Font::from_data(b"not a font at all".to_vec()).is_err());
+    assert!(Font::from_data(b"OTTO\0\0\0\0\0\0\0\0".to_vec()).is_err());
+    assert!(Font::from_data(Vec::new()).is_err());
+    assert!(Font::from_file("tests/output/missing.ttf").is_err());
+
+    // Truncated files fail instead of reading out of bounds
+    let data = test_font();
+    for length in [12, 100, data.len() / 2] {
+        assert!(Font::from_data(data[..length].to_vec()).is_err());
+    }
+}
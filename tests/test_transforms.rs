@@ -1,10 +1,11 @@
+use mg::consts::PI;
+use mg::primitives::Sphere;
 use mg::transforms::advanced::{Matrix, Mirror, Quaternion};
 use mg::transforms::deform::{Bend, Taper, Twist};
 use mg::transforms::projection::{Cylindrical, Orthographic, Perspective};
-use mg::{Face, Model, Transform, Vertex};
+use mg::{Face, Model, Real, Transform, Vertex, VertexAttribute};
 use mg::{Rotate, Scale, Translate};
-use nalgebra::{Matrix4, Point3, Vector3};
-use std::f32::consts::PI;
+use nalgebra::{Matrix4, Point3, Vector3, Vector4};
 
 fn create_test_cube() -> Model {
     let mut model = Model::new("TestCube");
@@ -86,19 +87,19 @@ fn test_scale_transform() {
         .vertices
         .iter()
         .map(|v| v.position.x.abs())
-        .fold(0.0, f32::max);
+        .fold(0.0, Real::max);
     let max_y = model
         .mesh
         .vertices
         .iter()
         .map(|v| v.position.y.abs())
-        .fold(0.0, f32::max);
+        .fold(0.0, Real::max);
     let max_z = model
         .mesh
         .vertices
         .iter()
         .map(|v| v.position.z.abs())
-        .fold(0.0, f32::max);
+        .fold(0.0, Real::max);
 
     // Use more generous bounds
     assert!(max_x > 0.9 && max_x < 1.1);
@@ -130,22 +131,22 @@ fn test_translate_transform() {
         .vertices
         .iter()
         .map(|v| v.position.x)
-        .sum::<f32>()
-        / model.mesh.vertices.len() as f32;
+        .sum::<Real>()
+        / model.mesh.vertices.len() as Real;
     let avg_y = model
         .mesh
         .vertices
         .iter()
         .map(|v| v.position.y)
-        .sum::<f32>()
-        / model.mesh.vertices.len() as f32;
+        .sum::<Real>()
+        / model.mesh.vertices.len() as Real;
     let avg_z = model
         .mesh
         .vertices
         .iter()
         .map(|v| v.position.z)
-        .sum::<f32>()
-        / model.mesh.vertices.len() as f32;
+        .sum::<Real>()
+        / model.mesh.vertices.len() as Real;
 
     assert!((avg_x - 1.0).abs() < 0.01);
     assert!((avg_y - 2.0).abs() < 0.01);
@@ -224,6 +225,102 @@ fn test_mirror_transform() {
     }
 }
 
+#[test]
+fn test_transforms_update_tangents() {
+    let mut model = create_test_cube();
+    model.mesh.tangents = vec![Vector4::new(1.0, 0.0, 0.0, 1.0); model.mesh.vertices.len()];
+
+    // Rotating 90 degrees around Y turns +X into -Z
+    Rotate::around_y(90.0).apply(&mut model).unwrap();
+    for tangent in &model.mesh.tangents {
+        assert!(tangent.x.abs() < 1e-5);
+        assert!((tangent.z + 1.0).abs() < 1e-5);
+        assert_eq!(tangent.w, 1.0);
+    }
+
+    // A single reflection mirrors the tangent and flips the bitangent sign
+    Mirror::z().apply(&mut model).unwrap();
+    for tangent in &model.mesh.tangents {
+        assert!((tangent.z - 1.0).abs() < 1e-5);
+        assert_eq!(tangent.w, -1.0);
+    }
+
+    // A quarter turn around Y by quaternion turns +Z into +X
+    Quaternion::from_axis_angle(Vector3::y(), 90.0)
+        .apply(&mut model)
+        .unwrap();
+    for tangent in &model.mesh.tangents {
+        assert!((tangent.x - 1.0).abs() < 1e-5);
+        assert!(tangent.z.abs() < 1e-5);
+    }
+}
+
+#[test]
+fn test_transforms_preserve_vertex_attributes() {
+    let mut model = create_test_cube();
+    let count = model.mesh.vertices.len();
+    let colors: Vec<[f32; 4]> = (0..count)
+        .map(|i| [i as f32 / 8.0, 0.0, 0.0, 1.0])
+        .collect();
+    let weights = VertexAttribute::Scalar((0..count).map(|i| i as f32).collect());
+
+    model.mesh.colors = colors.clone();
+    model.mesh.set_attribute("weight", weights.clone()).unwrap();
+
+    let transforms: Vec<Box<dyn Transform>> = vec![
+        Box::new(Scale::new(1.0, 2.0, 3.0)),
+        Box::new(Translate::new(1.0, 0.0, 0.0)),
+        Box::new(Rotate::around_x(30.0)),
+        Box::new(Mirror::x()),
+        Box::new(Matrix::new(Matrix4::new_scaling(2.0))),
+        Box::new(Quaternion::from_euler_angles(10.0, 20.0, 30.0)),
+        Box::new(Twist::around_z(15.0, 0.0, 0.0)),
+        Box::new(Bend::z_axis(30.0, -1.0, 1.0)),
+        Box::new(Taper::new(
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(1.0, 1.0, 1.0),
+            Vector3::new(0.5, 1.0, 0.5),
+            (-1.0, 1.0),
+        )),
+        Box::new(Cylindrical::y_axis(0.0, 0.0, 1.0)),
+    ];
+
+    for transform in transforms {
+        transform.apply(&mut model).unwrap();
+        assert_eq!(model.mesh.colors, colors);
+        assert_eq!(model.mesh.attribute("weight"), Some(&weights));
+    }
+}
+
+#[test]
+fn test_transforms_on_large_mesh() {
+    // Large enough to be split into several chunks when running in parallel
+    let original = Sphere::new().segments(128).rings(64).build();
+    let mut model = original.clone();
+
+    model
+        .apply(Scale::new(2.0, 1.0, 1.0))
+        .apply(Translate::new(0.0, 1.0, 0.0));
+
+    for (vertex, before) in model.mesh.vertices.iter().zip(&original.mesh.vertices) {
+        assert_eq!(vertex.position.x, before.position.x * 2.0);
+        assert_eq!(vertex.position.y, before.position.y + 1.0);
+        assert_eq!(vertex.position.z, before.position.z);
+    }
+
+    // Recomputing normals on the untouched sphere is stable run to run
+    let mut first = original.clone();
+    let mut second = original.clone();
+    first.mesh.compute_normals();
+    second.mesh.compute_normals();
+    assert_eq!(first.mesh, second.mesh);
+
+    // Zero scale on one axis is rejected without modifying the mesh
+    let mut flattened = original.clone();
+    assert!(Scale::new(1.0, 0.0, 1.0).apply(&mut flattened).is_err());
+    assert_eq!(flattened.mesh, original.mesh);
+}
+
 #[test]
 fn test_matrix_transform() {
     let mut model = create_test_cube();
@@ -262,6 +359,20 @@ fn test_matrix_transform() {
     );
 
     // Skip normal checks
+
+    // A projection sending some vertices to infinity fails without moving any
+    let before = model.clone();
+    let min_x = model
+        .mesh
+        .vertices
+        .iter()
+        .map(|v| v.position.x)
+        .fold(Real::INFINITY, Real::min);
+    let projection = Matrix4::new(
+        1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 0.0, 0.0, -min_x,
+    );
+    assert!(Matrix::new(projection).apply(&mut model).is_err());
+    assert_eq!(model.mesh, before.mesh);
 }
 
 #[test]
@@ -280,8 +391,8 @@ fn test_transform_chaining() {
         .vertices
         .iter()
         .map(|v| v.position.x)
-        .sum::<f32>()
-        / model.mesh.vertices.len() as f32;
+        .sum::<Real>()
+        / model.mesh.vertices.len() as Real;
 
     // The centroid x should be 1.0 after translation
     assert!((avg_x - 1.0).abs() < 0.01);
@@ -297,10 +408,10 @@ fn test_transform_chaining() {
             let dz = v.position.z;
             (dx * dx + dy * dy + dz * dz).sqrt()
         })
-        .fold(0.0, f32::max);
+        .fold(0.0, Real::max);
 
     // Maximum distance from centroid should be sqrt(3) for a 2x2x2 cube
-    assert!((max_dist - (3.0_f32).sqrt()).abs() < 0.1);
+    assert!((max_dist - (3.0 as Real).sqrt()).abs() < 0.1);
 }
 
 #[test]
@@ -472,9 +583,9 @@ fn test_twist_transform() {
 
     // Just check that the positions are different between top and bottom
     let top_avg_x =
-        top_vertices.iter().map(|v| v.position.x).sum::<f32>() / top_vertices.len() as f32;
+        top_vertices.iter().map(|v| v.position.x).sum::<Real>() / top_vertices.len() as Real;
     let bottom_avg_x =
-        bottom_vertices.iter().map(|v| v.position.x).sum::<f32>() / bottom_vertices.len() as f32;
+        bottom_vertices.iter().map(|v| v.position.x).sum::<Real>() / bottom_vertices.len() as Real;
 
     println!(
         "\nTop avg x: {:.3}, Bottom avg x: {:.3}, Difference: {:.3}",
@@ -638,7 +749,7 @@ fn test_taper_transform() {
         .iter()
         .filter(|v| v.position.y > 0.4)
         .map(|v| v.position.x.abs())
-        .fold(0.0, f32::max)
+        .fold(0.0, Real::max)
         * 2.0;
 
     let bottom_width_x = model
@@ -647,7 +758,7 @@ fn test_taper_transform() {
         .iter()
         .filter(|v| v.position.y < -0.4)
         .map(|v| v.position.x.abs())
-        .fold(0.0, f32::max)
+        .fold(0.0, Real::max)
         * 2.0;
 
     // Top should be narrower than bottom
@@ -716,7 +827,7 @@ fn test_perspective_transform() {
         .vertices
         .iter()
         .map(|v| v.position.x.abs())
-        .fold(0.0, f32::max)
+        .fold(0.0, Real::max)
         * 2.0;
 
     let far_width = far_model
@@ -724,7 +835,7 @@ fn test_perspective_transform() {
         .vertices
         .iter()
         .map(|v| v.position.x.abs())
-        .fold(0.0, f32::max)
+        .fold(0.0, Real::max)
         * 2.0;
 
     // The further model should appear smaller
@@ -754,8 +865,8 @@ fn test_orthographic_transform() {
     let z_values: Vec<_> = model.mesh.vertices.iter().map(|v| v.position.z).collect();
 
     // All Z values should be the same or very close
-    let min_z = z_values.iter().fold(f32::INFINITY, |a, &b| a.min(b));
-    let max_z = z_values.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b));
+    let min_z = z_values.iter().fold(Real::INFINITY, |a, &b| a.min(b));
+    let max_z = z_values.iter().fold(Real::NEG_INFINITY, |a, &b| a.max(b));
     assert!(
         (max_z - min_z).abs() < 0.01,
         "Orthographic projection onto XY should flatten Z values"
@@ -779,8 +890,8 @@ fn test_orthographic_transform() {
     let x_values: Vec<_> = model.mesh.vertices.iter().map(|v| v.position.x).collect();
 
     // All X values should be the same or very close
-    let min_x = x_values.iter().fold(f32::INFINITY, |a, &b| a.min(b));
-    let max_x = x_values.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b));
+    let min_x = x_values.iter().fold(Real::INFINITY, |a, &b| a.min(b));
+    let max_x = x_values.iter().fold(Real::NEG_INFINITY, |a, &b| a.max(b));
     assert!(
         (max_x - min_x).abs() < 0.01,
         "Orthographic projection onto YZ should flatten X values"
@@ -855,8 +966,8 @@ fn test_cylindrical_transform() {
         .map(|v| (v.position.x * v.position.x + v.position.z * v.position.z).sqrt())
         .collect();
 
-    let min_dist = distances.iter().fold(f32::INFINITY, |a, &b| a.min(b));
-    let max_dist = distances.iter().fold(f32::NEG_INFINITY, |a, &b| a.max(b));
+    let min_dist = distances.iter().fold(Real::INFINITY, |a, &b| a.min(b));
+    let max_dist = distances.iter().fold(Real::NEG_INFINITY, |a, &b| a.max(b));
 
     assert!(
         (max_dist - min_dist) > 0.1,
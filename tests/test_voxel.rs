@@ -0,0 +1,163 @@
+use mg::primitives::{Cube, Sphere};
+use mg::voxel::VoxelGrid;
+use mg::Real;
+
+mod common;
+
+use common::signed_volume;
+
+#[test]
+fn test_grid_storage() {
+    for mut grid in [VoxelGrid::new(4, 3, 2), VoxelGrid::sparse(4, 3, 2)] {
+        assert!(grid.is_empty());
+        grid.set(3, 2, 1, 7);
+        grid.set(0, 0, 0, 1);
+        assert_eq!(grid.get(3, 2, 1), 7);
+        assert_eq!(grid.get(1, 1, 1), 0);
+        assert_eq!(grid.get(10, 0, 0), 0);
+        assert_eq!(grid.len(), 2);
+        assert_eq!(grid.voxels(), vec![([0, 0, 0], 1), ([3, 2, 1], 7)]);
+        grid.set(0, 0, 0, 0);
+        assert_eq!(grid.len(), 1);
+        grid.fill([0, 0, 0], [3, 2, 1], 2);
+        assert_eq!(grid.len(), 24);
+    }
+    assert!(VoxelGrid::sparse(1, 1, 1).is_sparse());
+    assert!(!VoxelGrid::new(1, 1, 1).is_sparse());
+
+    // The default palette is MagicaVoxel's
+    let grid = VoxelGrid::new(1, 1, 1);
+    assert_eq!(grid.color(0), [0, 0, 0, 0]);
+    assert_eq!(grid.color(1), [255, 255, 255, 255]);
+    assert_eq!(grid.color(2), [255, 255, 204, 255]);
+    assert_eq!(grid.color(216), [0xee, 0, 0, 255]);
+    assert_eq!(grid.color(255), [0x11, 0x11, 0x11, 255]);
+}
+
+#[test]
+#[should_panic(expected = "outside the grid")]
+fn test_grid_set_outside() {
+    VoxelGrid::new(2, 2, 2).set(2, 0, 0, 1);
+}
+
+#[test]
+fn test_greedy_meshing() {
+    // A solid box of one color is six quads however many voxels it has
+    let mut grid = VoxelGrid::new(5, 4, 3)
+        .voxel_size(0.5)
+        .origin(1.0, 0.0, 0.0);
+    grid.fill([0, 0, 0], [4, 3, 2], 9);
+    let model = grid.to_model();
+    assert_eq!(model.mesh.faces.len(), 6);
+    assert!(model.mesh.faces.iter().all(|f| f.indices.len() == 4));
+    assert!((signed_volume(&model) - 60.0 * 0.125).abs() < 1e-4);
+    let min_x = model
+        .mesh
+        .vertices
+        .iter()
+        .map(|v| v.position.x)
+        .fold(Real::MAX, Real::min);
+    assert_eq!(min_x, 1.0);
+    assert_eq!(model.mesh.materials.len(), 1);
+    assert_eq!(
+        model.mesh.materials["color_9"].diffuse,
+        [1.0, 0.8, 0.6, 1.0]
+    );
+
+    // Normals point out of the box
+    let center = nalgebra::Point3::new(2.25, 1.0, 0.75);
+    for face in &model.mesh.faces {
+        let vertex = &model.mesh.vertices[face.indices[0]];
+        assert!(vertex.normal.dot(&(vertex.position - center)) > 0.0);
+    }
+
+    // A second color splits the faces it touches and gets its own material
+    grid.set(4, 3, 2, 10);
+    let model = grid.to_model();
+    assert!(model.mesh.faces.len() > 6);
+    assert_eq!(model.mesh.materials.len(), 2);
+    let corner_faces = model
+        .mesh
+        .face_materials
+        .iter()
+        .filter(|m| m.as_deref() == Some("color_10"))
+        .count();
+    assert_eq!(corner_faces, 3);
+    assert!((signed_volume(&model) - 60.0 * 0.125).abs() < 1e-4);
+
+    // Hollow shells have inner faces, facing into the cavity
+    let mut shell = VoxelGrid::sparse(3, 3, 3);
+    shell.fill([0, 0, 0], [2, 2, 2], 1);
+    shell.set(1, 1, 1, 0);
+    let model = shell.to_model();
+    assert_eq!(model.mesh.faces.len(), 12);
+    assert!((signed_volume(&model) - 26.0).abs() < 1e-4);
+}
+
+#[test]
+fn test_vox_round_trip() {
+    let mut grid = VoxelGrid::sparse(10, 20, 30);
+    grid.set(0, 0, 0, 1);
+    grid.set(9, 19, 29, 200);
+    grid.set(5, 6, 7, 42);
+    grid.set_color(42, [10, 20, 30, 40]);
+
+    let data = grid.to_vox_data().unwrap();
+    assert!(data.starts_with(b"VOX "));
+    let read = VoxelGrid::from_vox_data(&data).unwrap();
+    assert_eq!(read.size(), [10, 20, 30]);
+    assert_eq!(read.voxels(), grid.voxels());
+    assert_eq!(read.color(42), [10, 20, 30, 40]);
+    assert_eq!(read.color(200), grid.color(200));
+
+    std::fs::create_dir_all("tests/output").unwrap();
+    grid.export_vox("tests/output/voxels.vox").unwrap();
+    let loaded = VoxelGrid::from_vox("tests/output/voxels.vox").unwrap();
+    assert_eq!(loaded.len(), 3);
+
+    assert!(VoxelGrid::sparse(257, 1, 1).to_vox_data().is_err());
+}
+
+#[test]
+fn test_vox_errors() {
+    let data = {
+        let mut grid = VoxelGrid::new(2, 2, 2);
+        grid.set(1, 1, 1, 3);
+        grid.to_vox_data().unwrap()
+    };
+    assert!(VoxelGrid::from_vox_data(b"PNG ").is_err());
+    assert!(VoxelGrid::from_vox_data(&data[..data.len() - 10]).is_err());
+
+    // A voxel outside the model's size
+    let mut outside = data.clone();
+    let xyzi = outside.windows(4).position(|w| w == b"XYZI").unwrap();
+    outside[xyzi + 16] = 5;
+    assert!(VoxelGrid::from_vox_data(&outside).is_err());
+
+    // A model without voxels
+    let mut missing = data.clone();
+    missing[xyzi..xyzi + 4].copy_from_slice(b"XXXX");
+    assert!(VoxelGrid::from_vox_data(&missing).is_err());
+}
+
+#[test]
+fn test_voxelize_mesh() {
+    // A 2 unit cube at voxels of 0.25 fills its 8 by 8 by 8 grid exactly
+    let cube = Cube::new().size(2.0).build();
+    let grid = VoxelGrid::from_mesh(&cube.mesh, 0.25, 5);
+    assert_eq!(grid.size(), [8, 8, 8]);
+    assert_eq!(grid.len(), 512);
+    assert!(grid.voxels().iter().all(|&(_, c)| c == 5));
+
+    // A sphere's voxel count tracks its volume, and the inside is solid
+    let sphere = Sphere::new().radius(1.0).segments(48).rings(24).build();
+    let grid = VoxelGrid::from_mesh(&sphere.mesh, 0.1, 1);
+    assert_eq!(grid.size(), [20, 20, 20]);
+    assert_eq!(grid.get(10, 10, 10), 1);
+    let volume = grid.len() as Real * 0.001;
+    let exact = 4.0 / 3.0 * mg::consts::PI;
+    assert!(volume > exact && volume < exact * 1.4);
+
+    let model = grid.to_model();
+    assert!((signed_volume(&model) - volume).abs() < 1e-3);
+}